@@ -0,0 +1,65 @@
+/*
+ * C API for the Yaesu FTX-1 through ftx1-mm's CAT protocol code.
+ *
+ * Build the shared library with:
+ *     cargo build --release --lib --no-default-features --features ffi
+ * and link against target/release/libftx1_mm.so (.dylib, .dll).
+ *
+ * Every function returns FTX1_OK or a negative FTX1_ERR_* code, except
+ * that reading an empty channel returns FTX1_EMPTY.
+ */
+#ifndef FTX1_H
+#define FTX1_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define FTX1_OK              0
+#define FTX1_EMPTY           1
+#define FTX1_ERR_ARG        (-1)
+#define FTX1_ERR_IO         (-2)
+#define FTX1_ERR_PROTOCOL   (-3)
+#define FTX1_ERR_NOT_FTX1   (-4)
+
+/* An open radio. */
+typedef struct ftx1_radio ftx1_radio;
+
+/* One memory channel. Enumerations are their CAT codes. */
+typedef struct ftx1_memory {
+    uint16_t channel;              /* 1-999 */
+    uint32_t frequency_hz;
+    int16_t  clarifier_offset_hz;  /* -9990..9990 in 10 Hz steps */
+    uint8_t  rx_clarifier;
+    uint8_t  tx_clarifier;
+    char     mode;                 /* MD mode character: '1' LSB, '2' USB, '4' FM, 'B' FM-N, ... */
+    uint8_t  sql_type;             /* 0 off, 1 tone squelch, 2 tone, 3 DCS, 4 PR FREQ, 5 REV TONE */
+    uint8_t  shift;                /* 0 simplex, 1 plus, 2 minus, 3 ARS */
+    uint8_t  ctcss_code;           /* index into the CTCSS tone table */
+    uint8_t  dcs_code;             /* index into the DCS code table */
+    uint32_t tx_frequency_hz;      /* split TX frequency, 0 when split is off */
+    char     tag[13];              /* NUL-terminated, up to 12 characters */
+} ftx1_memory;
+
+/* Opens the radio on a serial port ("/dev/ttyUSB0", "COM3"). NULL on failure. */
+ftx1_radio *ftx1_open(const char *port, uint32_t baud);
+
+/* Closes a radio ftx1_open returned. NULL is ignored. */
+void ftx1_close(ftx1_radio *radio);
+
+/* Reads the radio ID into id (may be NULL) and checks it's an FTX-1. */
+int ftx1_identify(ftx1_radio *radio, uint16_t *id);
+
+/* Reads memory channel 1-999 into out; FTX1_EMPTY if it isn't programmed. */
+int ftx1_read_memory(ftx1_radio *radio, uint16_t channel, ftx1_memory *out);
+
+/* Programs the channel memory describes, as --write-radio would. */
+int ftx1_write_memory(ftx1_radio *radio, const ftx1_memory *memory);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* FTX1_H */
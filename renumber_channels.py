@@ -1,8 +1,10 @@
 #!/usr/bin/env python3
 """Renumber the channel-number column of an FTX-1 memory CSV.
 
-Header row, blank lines, and lines starting with '#' pass through unchanged.
-Data rows get sequential 5-digit channel numbers starting at 00001.
+The header row (the first line that isn't blank or a '#' comment, such as
+the format stamp), blank lines, and lines starting with '#' pass through
+unchanged. Data rows get sequential 5-digit channel numbers starting at
+00001.
 
 Usage:
     python renumber_channels.py input.csv > output.csv
@@ -16,8 +18,13 @@ def main() -> None:
 
     with open(sys.argv[1]) as f:
         n = 0
-        for i, line in enumerate(f):
-            if i == 0 or not line.strip() or line.lstrip().startswith("#"):
+        seen_header = False
+        for line in f:
+            if not line.strip() or line.lstrip().startswith("#"):
+                sys.stdout.write(line)
+                continue
+            if not seen_header:
+                seen_header = True
                 sys.stdout.write(line)
                 continue
             n += 1
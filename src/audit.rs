@@ -0,0 +1,137 @@
+// The audit log for `--audit-log FILE`: one JSON line appended for every
+// memory channel a command writes, so a shared radio's programming can be
+// traced to who changed it, when and why:
+//
+//   {"time":"2026-03-01T18:04:05Z","operator":"kd2abc","operation":"write",
+//    "channel":"00012","old":{...},"new":{...},"reason":"ARES net freq change"}
+//
+// `old` and `new` are the channel as a memory-file record (the same fields
+// --format json prints); `old` is null when the slot was empty or can't be
+// read back (the non-numbered channels). The operator is --operator, else
+// the login name; the reason is --reason. Every command that writes
+// channels is logged: --write-radio, --undo (operation "restore"),
+// --plan-moves --apply ("move", "retag"), --sync and the REST API's PUT
+// ("api"). CAT can't clear a channel, so nothing is logged as a clear.
+//
+// The file is opened before anything is written and each entry is synced
+// to disk once its channel is written; a command stops at the first entry
+// it can't log rather than make changes the log doesn't show.
+
+use core::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::memfile::CsvRecord;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operation {
+    Write,
+    Restore,
+    Move,
+    Retag,
+    Sync,
+    Api,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Operation::Write => "write",
+            Operation::Restore => "restore",
+            Operation::Move => "move",
+            Operation::Retag => "retag",
+            Operation::Sync => "sync",
+            Operation::Api => "api",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Entry<'a> {
+    time: &'a str,
+    operator: &'a str,
+    operation: String,
+    channel: &'a str,
+    old: Option<&'a CsvRecord>,
+    new: &'a CsvRecord,
+    reason: &'a str,
+}
+
+/// The operator when --operator isn't given: the login name.
+pub fn default_operator() -> String {
+    ["USER", "USERNAME", "LOGNAME"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok().filter(|v| !v.is_empty()))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// One audit log line, without the newline.
+fn entry_line(time: &str, operator: &str, reason: &str, operation: Operation, old: Option<&CsvRecord>, new: &CsvRecord) -> String {
+    let entry = Entry { time, operator, operation: operation.to_string(), channel: &new.channel, old, new, reason };
+    serde_json::to_string(&entry).unwrap_or_default()
+}
+
+pub struct Log {
+    file: File,
+    operator: String,
+    reason: String,
+}
+
+impl Log {
+    /// Opens `path` for appending, creating it if needed.
+    pub fn open(path: &str, operator: String, reason: String) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Log { file, operator, reason })
+    }
+
+    /// Appends the entry for `new` replacing `old`.
+    pub fn append(&mut self, operation: Operation, old: Option<&CsvRecord>, new: &CsvRecord) -> io::Result<()> {
+        let time = jiff::Timestamp::now().strftime("%Y-%m-%dT%H:%M:%SZ").to_string();
+        let line = entry_line(&time, &self.operator, &self.reason, operation, old, new);
+        writeln!(self.file, "{}", line)?;
+        self.file.sync_data()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_line() {
+        let old = CsvRecord::test_channel("00012", 145_500_000, "CALL");
+        let new = CsvRecord::test_channel("00012", 145_550_000, "NET");
+        let line = entry_line("2026-03-01T18:04:05Z", "kd2abc", "net moved", Operation::Write, Some(&old), &new);
+        let entry: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(entry["time"], "2026-03-01T18:04:05Z");
+        assert_eq!(entry["operator"], "kd2abc");
+        assert_eq!(entry["operation"], "write");
+        assert_eq!(entry["channel"], "00012");
+        assert_eq!(entry["old"]["Frequency (Hz)"], 145_500_000);
+        assert_eq!(entry["new"]["Memory Tag"], "NET");
+        assert_eq!(entry["reason"], "net moved");
+        let line = entry_line("2026-03-01T18:04:05Z", "kd2abc", "", Operation::Restore, None, &new);
+        let entry: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert!(entry["old"].is_null());
+        assert_eq!(entry["operation"], "restore");
+        assert!(!line.contains('\n'));
+    }
+
+    #[test]
+    fn test_log_appends() {
+        let path = std::env::temp_dir().join(format!("ftx1_audit_test_{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let path = path.to_str().unwrap();
+        for freq in [145_500_000, 145_550_000] {
+            let mut log = Log::open(path, "op".to_string(), "test".to_string()).unwrap();
+            log.append(Operation::Api, None, &CsvRecord::test_channel("00012", freq, "X")).unwrap();
+        }
+        let text = std::fs::read_to_string(path).unwrap();
+        assert_eq!(text.lines().count(), 2);
+        assert!(text.lines().nth(1).unwrap().contains("145550000"));
+        std::fs::remove_file(path).unwrap();
+    }
+}
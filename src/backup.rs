@@ -0,0 +1,253 @@
+// Scheduled backups for `--backup [DIR]`. Each backup is a directory
+// under DIR (ftx1_backups by default) named for when it was taken,
+//
+//   ftx1_backups/ftx1_backup_20260301_030000/memory.csv
+//                                            settings.csv
+//
+// holding the same memory file --read-radio writes and the same settings
+// file --read-settings writes. `--restore` writes both back; either also
+// goes back alone with --write-radio or --write-settings. A backup is
+// written under a ".partial" name and only renamed into place once both
+// files are complete, so an interrupted run never passes for a good backup
+// or gets counted by --keep.
+//
+// With --passphrase-file the two files are encrypted (see crypt.rs) and
+// saved as memory.csv.enc and settings.csv.enc instead; the plaintext is
+// only held in memory, never written to the backup directory. The
+// manifest is not encrypted, so a backup can still be checked without its
+// passphrase.
+//
+// Each backup also holds a manifest.txt: when and by what it was taken,
+// and the size and SHA-256 of both files, in lines sha256sum -c reads,
+//
+//   # ftx1-mm backup manifest
+//   # created: 2026-03-01T03:00:00Z
+//   # tool: ftx1-mm 0.2.0
+//   # firmware: MAIN 1.08
+//   # size: memory.csv 41529
+//   # size: settings.csv 512
+//   9f86d081...  memory.csv
+//   60303ae2...  settings.csv
+//   # checksum: 2c26b46b...
+//
+// The last line is the SHA-256 of the lines above it, so an edited
+// manifest is caught along with an edited file. `--restore` checks it all
+// before anything is written and refuses a backup that doesn't match. It's
+// a checksum, not a signature: it catches damage, not someone who rewrites
+// the backup and its manifest together.
+//
+// `--every 24h` repeats until Ctrl-C; without it the backup runs once and
+// fails when the radio can't be reached, for cron or a systemd timer.
+// `--keep N` then deletes all but the newest N backups.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use sha2::{Digest, Sha256};
+
+pub const DEFAULT_DIR: &str = "ftx1_backups";
+pub const PREFIX: &str = "ftx1_backup";
+pub const MEMORY_FILE: &str = "memory.csv";
+pub const SETTINGS_FILE: &str = "settings.csv";
+pub const PARTIAL: &str = ".partial";
+pub const MANIFEST_FILE: &str = "manifest.txt";
+/// Suffix of a backup's files when --passphrase-file encrypts them.
+pub const ENCRYPTED: &str = ".enc";
+const MANIFEST_HEADER: &str = "# ftx1-mm backup manifest";
+const CHECKSUM: &str = "# checksum: ";
+
+/// `--every` values: a number and a unit, "90s", "30m", "24h", "7d".
+pub fn parse_every(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    let unit = text.chars().last()?;
+    let count: u64 = text[..text.len() - unit.len_utf8()].parse().ok().filter(|n| *n > 0)?;
+    let secs = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 3600,
+        'd' => 86_400,
+        _ => return None,
+    };
+    Some(Duration::from_secs(count.checked_mul(secs)?))
+}
+
+/// The finished backups in `dir`, oldest first. The timestamped names
+/// sort in the order they were taken.
+pub fn backups(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut found = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if path.is_dir() && name.starts_with(PREFIX) && !name.ends_with(PARTIAL) {
+            found.push(path);
+        }
+    }
+    found.sort();
+    Ok(found)
+}
+
+/// Deletes all but the newest `keep` backups in `dir`; returns the ones
+/// deleted.
+pub fn prune(dir: &Path, keep: usize) -> io::Result<Vec<PathBuf>> {
+    let mut found = backups(dir)?;
+    let excess = found.len().saturating_sub(keep);
+    found.truncate(excess);
+    for path in &found {
+        fs::remove_dir_all(path)?;
+    }
+    Ok(found)
+}
+
+/// The SHA-256 of `data` as lowercase hex, as sha256sum prints it.
+fn hex(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+/// The files a backup holds, besides the manifest.
+pub fn files(encrypted: bool) -> [String; 2] {
+    let suffix = if encrypted { ENCRYPTED } else { "" };
+    [MEMORY_FILE, SETTINGS_FILE].map(|name| format!("{}{}", name, suffix))
+}
+
+/// The manifest for `files` (name, contents), with `metadata` (key,
+/// value) lines at the top.
+pub fn manifest(metadata: &[(&str, String)], files: &[(&str, &[u8])]) -> String {
+    let mut text = format!("{}\n", MANIFEST_HEADER);
+    for (key, value) in metadata {
+        text += &format!("# {}: {}\n", key, value);
+    }
+    for (name, data) in files {
+        text += &format!("# size: {} {}\n", name, data.len());
+    }
+    for (name, data) in files {
+        text += &format!("{}  {}\n", hex(data), name);
+    }
+    let checksum = hex(text.as_bytes());
+    text + CHECKSUM + &checksum + "\n"
+}
+
+/// Checks a manifest against the files `read` returns by name. Ok gives
+/// the metadata lines; Err every problem found.
+pub fn verify(text: &str, read: impl Fn(&str) -> io::Result<Vec<u8>>) -> Result<Vec<(String, String)>, Vec<String>> {
+    let Some(at) = text.rfind(CHECKSUM) else { return Err(vec!["the manifest has no checksum line; it's cut short".to_string()]) };
+    let (body, checksum) = text.split_at(at);
+    if !body.starts_with(MANIFEST_HEADER) {
+        return Err(vec!["this isn't a backup manifest".to_string()]);
+    }
+    if hex(body.as_bytes()) != checksum[CHECKSUM.len()..].trim() {
+        return Err(vec!["the manifest doesn't match its own checksum; it's been changed or damaged".to_string()]);
+    }
+    let mut metadata = Vec::new();
+    let mut sizes = Vec::new();
+    let mut problems = Vec::new();
+    let mut checked = 0;
+    for line in body.lines().skip(1) {
+        if let Some(size) = line.strip_prefix("# size: ") {
+            if let Some((name, bytes)) = size.rsplit_once(' ') {
+                sizes.push((name.to_string(), bytes.parse::<usize>().unwrap_or(0)));
+            }
+        } else if let Some((key, value)) = line.strip_prefix("# ").and_then(|l| l.split_once(": ")) {
+            metadata.push((key.to_string(), value.to_string()));
+        } else if let Some((hash, name)) = line.split_once("  ") {
+            checked += 1;
+            let expected = sizes.iter().find(|(n, _)| n == name).map(|(_, b)| *b);
+            match read(name) {
+                Err(e) => problems.push(format!("{}: can't be read ({})", name, e)),
+                Ok(data) if expected.is_some_and(|b| data.len() < b) => {
+                    problems.push(format!("{}: truncated, {} of {} bytes", name, data.len(), expected.unwrap_or(0)))
+                }
+                Ok(data) if hex(&data) != hash => problems.push(format!("{}: checksum doesn't match; the file is damaged or changed", name)),
+                Ok(_) => {}
+            }
+        }
+    }
+    if checked == 0 {
+        problems.push("the manifest lists no files".to_string());
+    }
+    if problems.is_empty() { Ok(metadata) } else { Err(problems) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_every() {
+        assert_eq!(parse_every("24h"), Some(Duration::from_secs(86_400)));
+        assert_eq!(parse_every("30m"), Some(Duration::from_secs(1800)));
+        assert_eq!(parse_every("90s"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_every("7d"), Some(Duration::from_secs(604_800)));
+        assert_eq!(parse_every("0h"), None);
+        assert_eq!(parse_every("24"), None);
+        assert_eq!(parse_every("h"), None);
+        assert_eq!(parse_every("1w"), None);
+        assert_eq!(parse_every(""), None);
+    }
+
+    #[test]
+    fn test_manifest() {
+        let files: [(&str, &[u8]); 2] = [(MEMORY_FILE, b"Channel Number\n00001\n"), (SETTINGS_FILE, b"Setting,Value\n")];
+        let text = manifest(&[("created", "2026-03-01T03:00:00Z".to_string()), ("tool", "ftx1-mm 0.2.0".to_string())], &files);
+        assert!(text.starts_with("# ftx1-mm backup manifest\n# created: 2026-03-01T03:00:00Z\n# tool: ftx1-mm 0.2.0\n# size: memory.csv 21\n"));
+        assert!(text.contains(&format!("{}  memory.csv\n", hex(files[0].1))));
+        // What sha256sum prints.
+        assert_eq!(hex(b"abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        let read = |files: [(&'static str, &'static [u8]); 2]| {
+            move |name: &str| -> io::Result<Vec<u8>> {
+                files.iter().find(|(n, _)| *n == name).map(|(_, d)| d.to_vec()).ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+            }
+        };
+        let metadata = verify(&text, read(files)).unwrap();
+        assert_eq!(metadata[0], ("created".to_string(), "2026-03-01T03:00:00Z".to_string()));
+        assert_eq!(metadata.len(), 2);
+
+        // A truncated file, a changed one, a missing one.
+        let damaged: [(&str, &[u8]); 2] = [(MEMORY_FILE, b"Channel Number\n"), (SETTINGS_FILE, b"Setting,Valve\n")];
+        let problems = verify(&text, read(damaged)).unwrap_err();
+        assert_eq!(problems, ["memory.csv: truncated, 15 of 21 bytes", "settings.csv: checksum doesn't match; the file is damaged or changed"]);
+        let missing: [(&str, &[u8]); 2] = [(MEMORY_FILE, files[0].1), ("other", b"")];
+        assert!(verify(&text, read(missing)).unwrap_err()[0].starts_with("settings.csv: can't be read"));
+
+        // An edited or cut-short manifest.
+        let edited = text.replace("ftx1-mm 0.2.0", "ftx1-mm 0.3.0");
+        assert!(verify(&edited, read(files)).unwrap_err()[0].contains("its own checksum"));
+        let cut = &text[..text.len() / 2];
+        assert!(verify(cut, read(files)).unwrap_err()[0].contains("cut short"));
+    }
+
+    #[test]
+    fn test_backups_and_prune() {
+        let dir = std::env::temp_dir().join(format!("ftx1_backup_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        assert!(backups(&dir).unwrap().is_empty());
+        for name in [
+            "ftx1_backup_20260103_030000",
+            "ftx1_backup_20260101_030000",
+            "ftx1_backup_20260102_030000",
+            "ftx1_backup_20260104_030000.partial",
+            "other",
+        ] {
+            fs::create_dir_all(dir.join(name)).unwrap();
+        }
+        fs::write(dir.join("ftx1_backup_notes.txt"), "").unwrap();
+        let names = |paths: Vec<PathBuf>| -> Vec<String> {
+            paths.iter().map(|p| p.file_name().unwrap().to_string_lossy().into_owned()).collect()
+        };
+        assert_eq!(
+            names(backups(&dir).unwrap()),
+            ["ftx1_backup_20260101_030000", "ftx1_backup_20260102_030000", "ftx1_backup_20260103_030000"]
+        );
+        assert_eq!(names(prune(&dir, 2).unwrap()), ["ftx1_backup_20260101_030000"]);
+        assert!(prune(&dir, 2).unwrap().is_empty());
+        assert_eq!(names(backups(&dir).unwrap()), ["ftx1_backup_20260102_030000", "ftx1_backup_20260103_030000"]);
+        assert!(dir.join("other").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,351 @@
+// Regional band plans for `--check-data --band-plan`. Each plan is a list
+// of segments with the emissions it allows; a channel inside a plan's
+// amateur bands is checked against the segment it falls in. Frequencies
+// outside every plan's amateur bands (broadcast, airband, ...) are not
+// checked — those are receive-only channels, see Region.
+//
+// The tables follow the published plans closely enough to catch a wrong
+// mode or a channel in another region's allocation; they don't model
+// beacon or satellite sub-bands. Segments are half-open [start, end) in Hz.
+//
+// Each plan also lists its FM repeater output sub-bands with the usual
+// input offset, which `--import-list --auto-shift` uses to guess the shift
+// of repeaters a list gives only the output frequency for. Local
+// coordination varies; these are the common pairs, not every one.
+
+use crate::ftx1::Mode;
+use core::fmt;
+
+const CW: u8 = 0x01;
+const DATA: u8 = 0x02;
+const PHONE: u8 = 0x04;
+const FM: u8 = 0x08;
+const ALL: u8 = CW | DATA | PHONE | FM;
+
+type Segment = (u32, u32, u8);
+
+const R1: &[Segment] = &[
+    (1_810_000, 1_838_000, CW),
+    (1_838_000, 1_843_000, CW | DATA),
+    (1_843_000, 2_000_000, CW | DATA | PHONE),
+    (3_500_000, 3_570_000, CW),
+    (3_570_000, 3_600_000, CW | DATA),
+    (3_600_000, 3_800_000, CW | DATA | PHONE),
+    (5_351_500, 5_366_500, CW | DATA | PHONE),
+    (7_000_000, 7_040_000, CW),
+    (7_040_000, 7_060_000, CW | DATA),
+    (7_060_000, 7_200_000, CW | DATA | PHONE),
+    (10_100_000, 10_150_000, CW | DATA),
+    (14_000_000, 14_070_000, CW),
+    (14_070_000, 14_101_000, CW | DATA),
+    (14_101_000, 14_350_000, CW | DATA | PHONE),
+    (18_068_000, 18_095_000, CW),
+    (18_095_000, 18_111_000, CW | DATA),
+    (18_111_000, 18_168_000, CW | DATA | PHONE),
+    (21_000_000, 21_070_000, CW),
+    (21_070_000, 21_151_000, CW | DATA),
+    (21_151_000, 21_450_000, CW | DATA | PHONE),
+    (24_890_000, 24_915_000, CW),
+    (24_915_000, 24_931_000, CW | DATA),
+    (24_931_000, 24_990_000, CW | DATA | PHONE),
+    (28_000_000, 28_070_000, CW),
+    (28_070_000, 28_300_000, CW | DATA),
+    (28_300_000, 29_510_000, CW | DATA | PHONE),
+    (29_510_000, 29_700_000, ALL),
+    (50_000_000, 50_100_000, CW),
+    (50_100_000, 50_500_000, CW | DATA | PHONE),
+    (50_500_000, 52_000_000, ALL),
+    (144_000_000, 144_150_000, CW),
+    (144_150_000, 144_400_000, CW | DATA | PHONE),
+    (144_400_000, 144_500_000, CW | DATA),
+    (144_500_000, 146_000_000, ALL),
+    (430_000_000, 432_000_000, ALL),
+    (432_000_000, 432_100_000, CW),
+    (432_100_000, 432_400_000, CW | DATA | PHONE),
+    (432_400_000, 440_000_000, ALL),
+];
+
+const R2: &[Segment] = &[
+    (1_800_000, 1_840_000, CW | DATA),
+    (1_840_000, 2_000_000, CW | DATA | PHONE),
+    (3_500_000, 3_600_000, CW | DATA),
+    (3_600_000, 4_000_000, CW | DATA | PHONE),
+    (7_000_000, 7_125_000, CW | DATA),
+    (7_125_000, 7_300_000, CW | PHONE),
+    (10_100_000, 10_150_000, CW | DATA),
+    (14_000_000, 14_150_000, CW | DATA),
+    (14_150_000, 14_350_000, CW | PHONE),
+    (18_068_000, 18_110_000, CW | DATA),
+    (18_110_000, 18_168_000, CW | PHONE),
+    (21_000_000, 21_200_000, CW | DATA),
+    (21_200_000, 21_450_000, CW | PHONE),
+    (24_890_000, 24_930_000, CW | DATA),
+    (24_930_000, 24_990_000, CW | PHONE),
+    (28_000_000, 28_300_000, CW | DATA),
+    (28_300_000, 29_500_000, CW | PHONE),
+    (29_500_000, 29_700_000, ALL),
+    (50_000_000, 50_100_000, CW),
+    (50_100_000, 54_000_000, ALL),
+    (144_000_000, 144_100_000, CW),
+    (144_100_000, 148_000_000, ALL),
+    (430_000_000, 440_000_000, ALL),
+];
+
+// US: the FCC sub-bands (General class and up), plus the five 60 m channels.
+const US: &[Segment] = &[
+    (1_800_000, 2_000_000, CW | DATA | PHONE),
+    (3_500_000, 3_600_000, CW | DATA),
+    (3_600_000, 4_000_000, CW | PHONE),
+    (5_330_500, 5_406_500, CW | DATA | PHONE),
+    (7_000_000, 7_125_000, CW | DATA),
+    (7_125_000, 7_300_000, CW | PHONE),
+    (10_100_000, 10_150_000, CW | DATA),
+    (14_000_000, 14_150_000, CW | DATA),
+    (14_150_000, 14_350_000, CW | PHONE),
+    (18_068_000, 18_110_000, CW | DATA),
+    (18_110_000, 18_168_000, CW | PHONE),
+    (21_000_000, 21_200_000, CW | DATA),
+    (21_200_000, 21_450_000, CW | PHONE),
+    (24_890_000, 24_930_000, CW | DATA),
+    (24_930_000, 24_990_000, CW | PHONE),
+    (28_000_000, 28_300_000, CW | DATA),
+    (28_300_000, 29_500_000, CW | PHONE),
+    (29_500_000, 29_700_000, ALL),
+    (50_000_000, 50_100_000, CW),
+    (50_100_000, 54_000_000, ALL),
+    (144_000_000, 144_100_000, CW),
+    (144_100_000, 148_000_000, ALL),
+    (420_000_000, 450_000_000, ALL),
+];
+
+const R3: &[Segment] = &[
+    (1_800_000, 2_000_000, CW | DATA | PHONE),
+    (3_500_000, 3_535_000, CW),
+    (3_535_000, 3_900_000, CW | DATA | PHONE),
+    (7_000_000, 7_025_000, CW),
+    (7_025_000, 7_040_000, CW | DATA),
+    (7_040_000, 7_300_000, CW | DATA | PHONE),
+    (10_100_000, 10_150_000, CW | DATA),
+    (14_000_000, 14_070_000, CW),
+    (14_070_000, 14_112_000, CW | DATA),
+    (14_112_000, 14_350_000, CW | PHONE),
+    (18_068_000, 18_100_000, CW),
+    (18_100_000, 18_110_000, CW | DATA),
+    (18_110_000, 18_168_000, CW | PHONE),
+    (21_000_000, 21_070_000, CW),
+    (21_070_000, 21_125_000, CW | DATA),
+    (21_125_000, 21_450_000, CW | PHONE),
+    (24_890_000, 24_920_000, CW),
+    (24_920_000, 24_930_000, CW | DATA),
+    (24_930_000, 24_990_000, CW | PHONE),
+    (28_000_000, 28_050_000, CW),
+    (28_050_000, 28_150_000, CW | DATA),
+    (28_150_000, 29_500_000, CW | PHONE),
+    (29_500_000, 29_700_000, ALL),
+    (50_000_000, 50_100_000, CW),
+    (50_100_000, 54_000_000, ALL),
+    (144_000_000, 144_035_000, CW),
+    (144_035_000, 148_000_000, ALL),
+    (430_000_000, 440_000_000, ALL),
+];
+
+const JA: &[Segment] = &[
+    (1_810_000, 1_825_000, CW),
+    (1_907_500, 1_912_500, CW | DATA),
+    (3_500_000, 3_520_000, CW),
+    (3_520_000, 3_575_000, CW | DATA | PHONE),
+    (3_599_000, 3_612_000, CW | DATA | PHONE),
+    (3_680_000, 3_687_000, CW | DATA | PHONE),
+    (3_702_000, 3_716_000, CW | DATA | PHONE),
+    (3_745_000, 3_770_000, CW | DATA | PHONE),
+    (3_791_000, 3_805_000, CW | DATA | PHONE),
+    (7_000_000, 7_030_000, CW),
+    (7_030_000, 7_200_000, CW | DATA | PHONE),
+    (10_100_000, 10_150_000, CW | DATA),
+    (14_000_000, 14_070_000, CW),
+    (14_070_000, 14_100_000, CW | DATA),
+    (14_100_000, 14_350_000, CW | DATA | PHONE),
+    (18_068_000, 18_110_000, CW | DATA),
+    (18_110_000, 18_168_000, CW | DATA | PHONE),
+    (21_000_000, 21_070_000, CW),
+    (21_070_000, 21_150_000, CW | DATA),
+    (21_150_000, 21_450_000, CW | DATA | PHONE),
+    (24_890_000, 24_930_000, CW | DATA),
+    (24_930_000, 24_990_000, CW | DATA | PHONE),
+    (28_000_000, 28_070_000, CW),
+    (28_070_000, 28_200_000, CW | DATA),
+    (28_200_000, 29_000_000, CW | DATA | PHONE),
+    (29_000_000, 29_700_000, ALL),
+    (50_000_000, 50_100_000, CW),
+    (50_100_000, 54_000_000, ALL),
+    (144_000_000, 144_100_000, CW),
+    (144_100_000, 146_000_000, ALL),
+    (430_000_000, 440_000_000, ALL),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BandPlan {
+    R1,
+    R2,
+    R3,
+    Us,
+    /// European national plans follow IARU Region 1.
+    Eu,
+    Ja,
+}
+
+pub const BAND_PLANS: [BandPlan; 6] = [BandPlan::R1, BandPlan::R2, BandPlan::R3, BandPlan::Us, BandPlan::Eu, BandPlan::Ja];
+
+/// Outcome of checking one channel against a plan.
+#[derive(Debug, PartialEq, Eq)]
+pub enum PlanCheck {
+    /// Inside the plan and the mode is allowed.
+    Ok,
+    /// Not an amateur frequency in any plan; nothing to check.
+    NotAmateur,
+    /// An amateur frequency elsewhere, but outside this plan's bands.
+    OutsidePlan,
+    /// Inside the plan, but the segment doesn't allow the mode.
+    ModeNotPermitted,
+}
+
+/// Repeater output sub-band and TX offset in Hz, half-open like Segment.
+type RepeaterSegment = (u32, u32, i32);
+
+const R1_REPEATERS: &[RepeaterSegment] = &[
+    (29_620_000, 29_700_000, -100_000),
+    (51_810_000, 52_000_000, -600_000),
+    (145_600_000, 145_800_000, -600_000),
+    (438_650_000, 439_425_000, -7_600_000),
+];
+
+const US_REPEATERS: &[RepeaterSegment] = &[
+    (29_620_000, 29_700_000, -100_000),
+    (53_000_000, 54_000_000, -1_000_000),
+    (145_200_000, 145_500_000, -600_000),
+    (146_610_000, 147_000_000, -600_000),
+    (147_000_000, 147_400_000, 600_000),
+    (442_000_000, 445_000_000, 5_000_000),
+    (447_000_000, 450_000_000, -5_000_000),
+];
+
+const JA_REPEATERS: &[RepeaterSegment] = &[
+    (29_610_000, 29_700_000, -100_000),
+    (439_000_000, 440_000_000, -5_000_000),
+];
+
+fn emission(mode: Mode) -> u8 {
+    match mode {
+        Mode::CwU | Mode::CwL => CW,
+        Mode::RttyL | Mode::RttyU | Mode::DataL | Mode::DataU | Mode::DataFm | Mode::DataFmN | Mode::Psk => DATA,
+        Mode::Lsb | Mode::Usb | Mode::Am | Mode::AmN => PHONE,
+        Mode::Fm | Mode::FmN | Mode::Wfm | Mode::C4fmDn | Mode::C4fmVw => FM,
+    }
+}
+
+fn segment(segments: &[Segment], hz: u32) -> Option<u8> {
+    segments.iter().find(|(lo, hi, _)| (*lo..*hi).contains(&hz)).map(|(_, _, allowed)| *allowed)
+}
+
+impl BandPlan {
+    fn segments(self) -> &'static [Segment] {
+        match self {
+            BandPlan::R1 | BandPlan::Eu => R1,
+            BandPlan::R2 => R2,
+            BandPlan::R3 => R3,
+            BandPlan::Us => US,
+            BandPlan::Ja => JA,
+        }
+    }
+
+    fn repeaters(self) -> &'static [RepeaterSegment] {
+        match self {
+            BandPlan::R1 | BandPlan::Eu => R1_REPEATERS,
+            BandPlan::R2 | BandPlan::Us => US_REPEATERS,
+            BandPlan::R3 => &[],
+            BandPlan::Ja => JA_REPEATERS,
+        }
+    }
+
+    /// The TX offset of a repeater with output `hz`, if `hz` is in one of
+    /// the plan's repeater output sub-bands.
+    pub fn repeater_offset(self, hz: u32) -> Option<i32> {
+        self.repeaters().iter().find(|(lo, hi, _)| (*lo..*hi).contains(&hz)).map(|(_, _, offset)| *offset)
+    }
+
+    pub fn check(self, hz: u32, mode: Mode) -> PlanCheck {
+        match segment(self.segments(), hz) {
+            Some(allowed) if allowed & emission(mode) != 0 => PlanCheck::Ok,
+            Some(_) => PlanCheck::ModeNotPermitted,
+            None if BAND_PLANS.iter().any(|p| segment(p.segments(), hz).is_some()) => PlanCheck::OutsidePlan,
+            None => PlanCheck::NotAmateur,
+        }
+    }
+}
+
+impl TryFrom<&str> for BandPlan {
+    type Error = ();
+
+    fn try_from(item: &str) -> Result<Self, Self::Error> {
+        BAND_PLANS.into_iter().find(|p| p.to_string().eq_ignore_ascii_case(item)).ok_or(())
+    }
+}
+
+impl fmt::Display for BandPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            BandPlan::R1 => "R1",
+            BandPlan::R2 => "R2",
+            BandPlan::R3 => "R3",
+            BandPlan::Us => "US",
+            BandPlan::Eu => "EU",
+            BandPlan::Ja => "JA",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segments_are_sorted_and_disjoint() {
+        for plan in BAND_PLANS {
+            for pair in plan.segments().windows(2) {
+                assert!(pair[0].0 < pair[0].1 && pair[0].1 <= pair[1].0, "{} overlaps at {}", plan, pair[1].0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_repeater_offset() {
+        assert_eq!(BandPlan::Us.repeater_offset(146_940_000), Some(-600_000));
+        assert_eq!(BandPlan::Us.repeater_offset(147_000_000), Some(600_000));
+        assert_eq!(BandPlan::Us.repeater_offset(146_520_000), None);
+        assert_eq!(BandPlan::R2.repeater_offset(444_100_000), Some(5_000_000));
+        assert_eq!(BandPlan::Eu.repeater_offset(439_000_000), Some(-7_600_000));
+        assert_eq!(BandPlan::R3.repeater_offset(146_940_000), None);
+        for plan in BAND_PLANS {
+            for pair in plan.repeaters().windows(2) {
+                assert!(pair[0].1 <= pair[1].0, "{} repeater bands overlap at {}", plan, pair[1].0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        assert_eq!(BandPlan::R2.check(146_520_000, Mode::Fm), PlanCheck::Ok);
+        assert_eq!(BandPlan::R1.check(7_150_000, Mode::Fm), PlanCheck::ModeNotPermitted);
+        assert_eq!(BandPlan::R1.check(7_100_000, Mode::Lsb), PlanCheck::Ok);
+        // FT8 sits above the narrow-band segments on 40 m.
+        assert_eq!(BandPlan::R1.check(7_074_000, Mode::DataU), PlanCheck::Ok);
+        assert_eq!(BandPlan::R3.check(7_074_000, Mode::DataU), PlanCheck::Ok);
+        assert_eq!(BandPlan::Us.check(7_074_000, Mode::DataU), PlanCheck::Ok);
+        assert_eq!(BandPlan::R1.check(28_500_000, Mode::DataU), PlanCheck::Ok);
+        assert_eq!(BandPlan::Us.check(14_050_000, Mode::Usb), PlanCheck::ModeNotPermitted);
+        assert_eq!(BandPlan::R1.check(146_520_000, Mode::Fm), PlanCheck::OutsidePlan);
+        assert_eq!(BandPlan::R1.check(118_100_000, Mode::Am), PlanCheck::NotAmateur);
+        assert_eq!(BandPlan::try_from("eu"), Ok(BandPlan::Eu));
+    }
+}
@@ -0,0 +1,130 @@
+// Band activity scans for `--bandscan LO-HI`: the --side VFO is stepped
+// across the range (MHz) every --bandscan-step kHz, the S-meter is read at
+// each step after --bandscan-dwell ms, and the whole range is swept
+// --bandscan-passes times. That's a slow panadapter over CAT, for finding
+// a clear simplex frequency or seeing where a band is busy:
+//
+//   FrequencyHz,Peak,Mean,BusyPercent
+//   146520000,142,96,66
+//   146535000,12,9,0
+//
+// Peak and Mean are raw S-meter readings (0-255) over the passes;
+// BusyPercent is the share of passes a step read at or over the busy
+// level. The level is --busy-level when given, else the noise floor (the
+// median of the peaks, as most of a band is quiet most of the time) plus
+// AUTO_MARGIN. The radio's mode, squelch and filter are left alone, so set
+// them up for what's being looked for first; the VFO is put back after.
+
+/// Raw S-meter counts over the noise floor that count as busy when
+/// --busy-level isn't given; about two S-units.
+pub const AUTO_MARGIN: u8 = 20;
+/// More steps than this is a mistyped --bandscan-step.
+pub const MAX_STEPS: usize = 2000;
+
+pub const CSV_HEADER: &str = "FrequencyHz,Peak,Mean,BusyPercent";
+
+/// A step's readings, one per pass that got an answer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub freq: u32,
+    pub readings: Vec<u8>,
+}
+
+impl Step {
+    pub fn peak(&self) -> u8 {
+        self.readings.iter().copied().max().unwrap_or(0)
+    }
+
+    pub fn mean(&self) -> u8 {
+        let sum: u32 = self.readings.iter().map(|r| u32::from(*r)).sum();
+        sum.checked_div(self.readings.len() as u32).unwrap_or(0) as u8
+    }
+
+    /// Percent of readings at or over `level`.
+    pub fn busy_percent(&self, level: u8) -> u8 {
+        let busy = self.readings.iter().filter(|r| **r >= level).count();
+        (busy * 100).checked_div(self.readings.len()).unwrap_or(0) as u8
+    }
+
+    /// One CSV line, without the newline.
+    pub fn row(&self, level: u8) -> String {
+        format!("{},{},{},{}", self.freq, self.peak(), self.mean(), self.busy_percent(level))
+    }
+}
+
+/// The busy level without --busy-level: the median peak plus AUTO_MARGIN.
+pub fn auto_level(steps: &[Step]) -> u8 {
+    let mut peaks: Vec<u8> = steps.iter().filter(|s| !s.readings.is_empty()).map(Step::peak).collect();
+    peaks.sort_unstable();
+    peaks.get(peaks.len() / 2).copied().unwrap_or(0).saturating_add(AUTO_MARGIN)
+}
+
+/// Steps that were ever at or over `level`, busiest first.
+pub fn busy(steps: &[Step], level: u8) -> Vec<&Step> {
+    let mut busy: Vec<&Step> = steps.iter().filter(|s| s.peak() >= level).collect();
+    busy.sort_by_key(|s| (std::cmp::Reverse(s.busy_percent(level)), std::cmp::Reverse(s.peak()), s.freq));
+    busy
+}
+
+/// Up to `n` of the quietest steps that were never busy and whose
+/// neighbours weren't either, quietest first: a clear frequency next to a
+/// busy one still hears it.
+pub fn clearest(steps: &[Step], level: u8, n: usize) -> Vec<&Step> {
+    let quiet = |i: usize| steps.get(i).is_none_or(|s| s.readings.is_empty() || s.peak() < level);
+    let mut clear: Vec<&Step> = steps
+        .iter()
+        .enumerate()
+        .filter(|(i, s)| !s.readings.is_empty() && quiet(*i) && quiet(i + 1) && (*i == 0 || quiet(i - 1)))
+        .map(|(_, s)| s)
+        .collect();
+    clear.sort_by_key(|s| (s.peak(), s.mean(), s.freq));
+    clear.truncate(n);
+    clear
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(freq: u32, readings: &[u8]) -> Step {
+        Step { freq, readings: readings.to_vec() }
+    }
+
+    #[test]
+    fn test_step() {
+        let s = step(146_520_000, &[140, 10, 138]);
+        assert_eq!(s.peak(), 140);
+        assert_eq!(s.mean(), 96);
+        assert_eq!(s.busy_percent(30), 66);
+        assert_eq!(s.row(30), "146520000,140,96,66");
+        let empty = step(146_535_000, &[]);
+        assert_eq!((empty.peak(), empty.mean(), empty.busy_percent(30)), (0, 0, 0));
+    }
+
+    #[test]
+    fn test_auto_level() {
+        let steps = [step(1, &[10]), step(2, &[12]), step(3, &[150]), step(4, &[11]), step(5, &[])];
+        assert_eq!(auto_level(&steps), 12 + AUTO_MARGIN);
+        assert_eq!(auto_level(&[]), AUTO_MARGIN);
+        assert_eq!(auto_level(&[step(1, &[250])]), 255);
+    }
+
+    #[test]
+    fn test_busy_and_clearest() {
+        let steps = [
+            step(146_505_000, &[9, 9]),
+            step(146_520_000, &[140, 10]),
+            step(146_535_000, &[8, 8]),
+            step(146_550_000, &[12, 11]),
+            step(146_565_000, &[10, 7]),
+            step(146_580_000, &[90, 95]),
+            step(146_595_000, &[]),
+        ];
+        let freqs = |found: Vec<&Step>| found.iter().map(|s| s.freq).collect::<Vec<_>>();
+        assert_eq!(freqs(busy(&steps, 30)), [146_580_000, 146_520_000]);
+        // 146.535 and 146.565 sit next to busy steps; an unread step is
+        // neither clear nor in the way.
+        assert_eq!(freqs(clearest(&steps, 30, 5)), [146_550_000]);
+        assert_eq!(freqs(clearest(&steps, 100, 2)), [146_565_000, 146_550_000]);
+    }
+}
@@ -0,0 +1,201 @@
+// Checkpoints for long transfers over slow or flaky links, kept in
+// FILE.checkpoint next to the memory file and removed once the transfer
+// completes. If a run is cut short, the same command with --resume picks
+// up from it.
+//
+// --read-radio records the last channel read after each channel; the rows
+// read so far are already in FILE, and a resumed read appends to it. The
+// options that shape the rows are recorded too, and a resumed read must
+// use the same ones, or the file would mix two formats.
+//
+//   # ftx1-mm read checkpoint
+//   options delimiter=comma freq-unit=hz decimal-comma=no group=all
+//   last 00042
+//
+// --write-radio records each channel once it's been written and read back
+// the same, with a fingerprint of the row it wrote. A resumed write skips
+// those channels, unless the row has been edited since.
+//
+//   # ftx1-mm write checkpoint
+//   done 00001 3b5d0f2c9a1e4d77
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use sha2::{Digest, Sha256};
+
+use crate::memfile::CsvRecord;
+
+const READ_HEADER: &str = "# ftx1-mm read checkpoint";
+const WRITE_HEADER: &str = "# ftx1-mm write checkpoint";
+
+/// The checkpoint kept next to memory file `file`.
+pub fn path(file: &str) -> PathBuf {
+    PathBuf::from(format!("{}.checkpoint", file))
+}
+
+/// Where an interrupted read got to, and the options it read with.
+#[derive(Debug, PartialEq)]
+pub struct ReadCheckpoint {
+    pub last: u16,
+    pub options: String,
+}
+
+/// The checkpoint text for a read with `options` that got through
+/// channel `last`.
+pub fn read_text(last: u16, options: &str) -> String {
+    format!("{}\noptions {}\nlast {:05}\n", READ_HEADER, options, last)
+}
+
+/// The read checkpoint in `text`; None if it isn't one. One without an
+/// options line gives empty options, which match no read.
+pub fn read_checkpoint(text: &str) -> Option<ReadCheckpoint> {
+    let mut lines = text.lines();
+    if lines.next()? != READ_HEADER {
+        return None;
+    }
+    let mut options = String::new();
+    for line in lines {
+        if let Some(o) = line.strip_prefix("options ") {
+            options = o.trim().to_string();
+        } else if let Some(last) = line.strip_prefix("last ") {
+            return Some(ReadCheckpoint { last: last.trim().parse().ok()?, options });
+        }
+    }
+    None
+}
+
+/// Records that the read into `file` with `options` got through channel
+/// `last`. The checkpoint is replaced whole, so a crash mid-save leaves the
+/// old one.
+pub fn save_read(file: &str, last: u16, options: &str) -> io::Result<()> {
+    let path = path(file);
+    let mut temp = path.clone().into_os_string();
+    temp.push(".tmp");
+    fs::write(&temp, read_text(last, options))?;
+    fs::rename(&temp, &path)
+}
+
+/// Where the interrupted read into `file` got to; None when there's no
+/// checkpoint to resume from.
+pub fn load_read(file: &str) -> io::Result<Option<ReadCheckpoint>> {
+    match fs::read_to_string(path(file)) {
+        Ok(text) => read_checkpoint(&text)
+            .map(Some)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "not a read checkpoint")),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Identifies what a row puts in its slot (see CsvRecord::contents), so an
+/// edited row isn't taken as already written.
+pub fn fingerprint(record: &CsvRecord) -> String {
+    format!("{:x}", Sha256::digest(format!("{:?}", record.contents())))[..16].to_string()
+}
+
+/// The channels a write checkpoint lists as done, with their rows'
+/// fingerprints; None if `text` isn't one.
+pub fn written(text: &str) -> Option<HashMap<String, String>> {
+    let mut lines = text.lines();
+    if lines.next()? != WRITE_HEADER {
+        return None;
+    }
+    // A line cut off by a crash is ignored; its channel is written again.
+    Some(
+        lines
+            .filter_map(|l| match l.strip_prefix("done ")?.split_whitespace().collect::<Vec<_>>()[..] {
+                [channel, print] if print.len() == 16 => Some((channel.to_string(), print.to_string())),
+                _ => None,
+            })
+            .collect(),
+    )
+}
+
+/// Starts an empty write checkpoint for `file`, replacing any old one.
+pub fn start_write(file: &str) -> io::Result<()> {
+    fs::write(path(file), format!("{}\n", WRITE_HEADER))
+}
+
+/// Adds `record` to the write checkpoint of `file` as written and verified.
+pub fn save_written(file: &str, record: &CsvRecord) -> io::Result<()> {
+    let mut out = fs::OpenOptions::new().append(true).open(path(file))?;
+    writeln!(out, "done {} {}", record.channel, fingerprint(record))?;
+    out.sync_data()
+}
+
+/// The channels the interrupted write from `file` got done; None when
+/// there's no checkpoint to resume from.
+pub fn load_written(file: &str) -> io::Result<Option<HashMap<String, String>>> {
+    match fs::read_to_string(path(file)) {
+        Ok(text) => written(&text)
+            .map(Some)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "not a write checkpoint")),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Removes `file`'s checkpoint, if it has one.
+pub fn remove(file: &str) -> io::Result<()> {
+    match fs::remove_file(path(file)) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPTIONS: &str = "delimiter=comma freq-unit=hz decimal-comma=no group=all";
+
+    #[test]
+    fn test_read_checkpoint() {
+        let read = |last, options: &str| Some(ReadCheckpoint { last, options: options.to_string() });
+        assert_eq!(read_checkpoint(&read_text(42, OPTIONS)), read(42, OPTIONS));
+        assert_eq!(read_checkpoint("# ftx1-mm read checkpoint\nlast 00999\n"), read(999, ""));
+        assert_eq!(read_checkpoint("last 00042\n"), None);
+        assert_eq!(read_checkpoint("# ftx1-mm read checkpoint\n"), None);
+        assert_eq!(read_checkpoint(""), None);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let file = std::env::temp_dir().join(format!("ftx1_checkpoint_test_{}.csv", std::process::id()));
+        let file = file.to_str().unwrap();
+        assert_eq!(load_read(file).unwrap(), None);
+        save_read(file, 7, OPTIONS).unwrap();
+        save_read(file, 8, "delimiter=tab freq-unit=mhz decimal-comma=yes group=M-VHF").unwrap();
+        let loaded = load_read(file).unwrap().unwrap();
+        assert_eq!(loaded.last, 8);
+        assert_eq!(loaded.options, "delimiter=tab freq-unit=mhz decimal-comma=yes group=M-VHF");
+        remove(file).unwrap();
+        assert_eq!(load_read(file).unwrap(), None);
+        remove(file).unwrap();
+    }
+
+    #[test]
+    fn test_written() {
+        let file = std::env::temp_dir().join(format!("ftx1_checkpoint_write_test_{}.csv", std::process::id()));
+        let file = file.to_str().unwrap();
+        let (one, two) = (CsvRecord::test_channel("00001", 145_500_000, "RPT"), CsvRecord::test_channel("00002", 145_525_000, "RPT"));
+        start_write(file).unwrap();
+        save_written(file, &one).unwrap();
+        save_written(file, &two).unwrap();
+        let done = load_written(file).unwrap().unwrap();
+        assert_eq!(done.get("00001"), Some(&fingerprint(&one)));
+        assert_eq!(done.len(), 2);
+        // An edited row no longer matches what was written.
+        assert_ne!(fingerprint(&CsvRecord::test_channel("00001", 145_550_000, "RPT")), fingerprint(&one));
+        // Neither kind of checkpoint passes for the other.
+        assert!(load_read(file).is_err());
+        save_read(file, 3, OPTIONS).unwrap();
+        assert!(load_written(file).is_err());
+        remove(file).unwrap();
+        assert_eq!(load_written(file).unwrap(), None);
+        assert_eq!(written("# ftx1-mm write checkpoint\ndone 00001 3b5d\n"), Some(HashMap::new()));
+    }
+}
@@ -0,0 +1,116 @@
+// The configuration file: named radio profiles for `--radio NAME`, for
+// running more than one transceiver off the same machine.
+//
+//   # ~/.config/ftx1-mm/config.toml
+//   [radio.base]
+//   port = "/dev/ttyUSB0"
+//   speed = 38400
+//   model = "usa"
+//   file = "/home/me/radios/base.csv"
+//
+//   [radio.field]
+//   port = "tcp://pi.local:4532"
+//   model = "exp"
+//
+// A profile fills in --port, --speed, --region (model: the radio's regional
+// version), --file and --audit-log for whatever the command line leaves
+// out; every key is optional. The file lives in $XDG_CONFIG_HOME/ftx1-mm
+// (else ~/.config/ftx1-mm), or %APPDATA%\ftx1-mm on Windows, unless
+// --config names another.
+//
+// The file is read with the toml crate. An unknown table or key, or a
+// value of the wrong type, is an error with its line number rather than a
+// setting silently ignored.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+pub const FILE_NAME: &str = "config.toml";
+const APP_DIR: &str = "ftx1-mm";
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    pub port: Option<String>,
+    pub speed: Option<u32>,
+    pub model: Option<String>,
+    pub file: Option<String>,
+    pub audit_log: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Profiles by name.
+    #[serde(rename = "radio", default)]
+    pub radios: BTreeMap<String, Profile>,
+}
+
+/// Reads the configuration file's text. The error is toml's, which shows
+/// the line and points at what's wrong.
+pub fn parse(text: &str) -> Result<Config, String> {
+    toml::from_str(text).map_err(|e| e.to_string().trim_end().to_string())
+}
+
+/// Where the configuration file is looked for without --config.
+pub fn default_path() -> Option<PathBuf> {
+    let var = |name: &str| std::env::var_os(name).filter(|v| !v.is_empty()).map(PathBuf::from);
+    let dir = if cfg!(windows) {
+        var("APPDATA")?
+    } else {
+        var("XDG_CONFIG_HOME").or_else(|| var("HOME").map(|home| home.join(".config")))?
+    };
+    Some(dir.join(APP_DIR).join(FILE_NAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let config = parse(
+            "# Two radios\n\
+             [radio.base]\n\
+             port = \"/dev/ttyUSB0\"\n\
+             speed = 38_400   # the default\n\
+             model = \"usa\"\n\
+             file = \"C:\\\\radios\\\\base.csv\"\n\
+             \n\
+             [radio.field]  # portable\n\
+             port = \"tcp://pi.local:4532\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.radios.len(), 2);
+        assert_eq!(
+            config.radios["base"],
+            Profile {
+                port: Some("/dev/ttyUSB0".to_string()),
+                speed: Some(38_400),
+                model: Some("usa".to_string()),
+                file: Some("C:\\radios\\base.csv".to_string()),
+                audit_log: None,
+            }
+        );
+        assert_eq!(config.radios["field"], Profile { port: Some("tcp://pi.local:4532".to_string()), ..Profile::default() });
+        assert_eq!(parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |text: &str| parse(text).unwrap_err();
+        assert!(error("port = \"x\"").contains("unknown field `port`"));
+        assert!(error("[radios]").contains("unknown field `radios`"));
+        assert!(error("[radio.a]\n[radio.a]").contains("line 2"));
+        let baud = error("[radio.a]\nbaud = 9600");
+        assert!(baud.contains("line 2") && baud.contains("unknown field `baud`"), "{}", baud);
+        assert!(baud.contains("expected one of `port`, `speed`, `model`, `file`, `audit_log`"), "{}", baud);
+        assert!(error("[radio.a]\nspeed = \"fast\"").contains("invalid type: string \"fast\", expected u32"));
+        assert!(error("[radio.a]\nport = 1").contains("invalid type: integer `1`, expected a string"));
+        assert!(error("[radio.a]\nport = \"x").contains("line 2"));
+        assert!(error("[radio.a]\nport = /dev/ttyUSB0").contains("line 2"));
+        assert!(error("[radio.a]\nport").contains("line 2"));
+    }
+}
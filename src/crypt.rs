@@ -0,0 +1,146 @@
+// Passphrase encryption for backups (`--passphrase-file`): channel plans
+// for emcomm nets or events can be kept off a shared drive in plaintext.
+// An encrypted file is
+//
+//   "FTX1ENC2" | rounds (u32, big-endian) | salt (16) | nonce (12)
+//   | ciphertext | tag (16)
+//
+// PBKDF2-HMAC-SHA256 over the passphrase and the salt gives the key for
+// ChaCha20-Poly1305 (RFC 8439, from the RustCrypto crates), which also
+// authenticates the header. The tag is checked before anything is
+// decrypted, so a wrong passphrase and a damaged file both fail the same
+// way, and nothing half-decrypted comes out. Salt and nonce are fresh for
+// every file.
+
+use core::fmt;
+use std::io;
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::ChaCha20Poly1305;
+use sha2::Sha256;
+
+const MAGIC: &[u8; 8] = b"FTX1ENC2";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+const HEADER_LEN: usize = MAGIC.len() + 4 + SALT_LEN + NONCE_LEN;
+/// PBKDF2 rounds for new files: OWASP's figure for PBKDF2-HMAC-SHA256.
+pub const ROUNDS: u32 = 600_000;
+/// More than this in a header is taken as damage, not as a setting.
+const MAX_ROUNDS: u32 = 10_000_000;
+
+#[derive(Debug, PartialEq)]
+pub enum DecryptError {
+    NotEncrypted,
+    Truncated,
+    /// The passphrase is wrong, or the file was changed or damaged.
+    Rejected,
+}
+
+impl fmt::Display for DecryptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecryptError::NotEncrypted => write!(f, "not an encrypted ftx1-mm file"),
+            DecryptError::Truncated => write!(f, "the file is cut short"),
+            DecryptError::Rejected => write!(f, "wrong passphrase, or the file is damaged"),
+        }
+    }
+}
+
+/// The ChaCha20-Poly1305 key for `passphrase`.
+fn cipher(passphrase: &[u8], salt: &[u8], rounds: u32) -> ChaCha20Poly1305 {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(passphrase, salt, rounds, &mut key);
+    ChaCha20Poly1305::new(&key.into())
+}
+
+fn encrypt_with(passphrase: &[u8], plaintext: &[u8], rounds: u32, salt: [u8; SALT_LEN], nonce: [u8; NONCE_LEN]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(HEADER_LEN + plaintext.len() + TAG_LEN);
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&rounds.to_be_bytes());
+    out.extend_from_slice(&salt);
+    out.extend_from_slice(&nonce);
+    let sealed = cipher(passphrase, &salt, rounds)
+        .encrypt(&nonce.into(), Payload { msg: plaintext, aad: &out })
+        .expect("a backup is far below ChaCha20-Poly1305's limit");
+    out.extend_from_slice(&sealed);
+    out
+}
+
+fn encrypt_rounds(passphrase: &[u8], plaintext: &[u8], rounds: u32) -> io::Result<Vec<u8>> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    getrandom::fill(&mut salt).and_then(|_| getrandom::fill(&mut nonce)).map_err(io::Error::other)?;
+    Ok(encrypt_with(passphrase, plaintext, rounds, salt, nonce))
+}
+
+/// `plaintext` encrypted under `passphrase`, with a fresh salt and nonce.
+pub fn encrypt(passphrase: &[u8], plaintext: &[u8]) -> io::Result<Vec<u8>> {
+    encrypt_rounds(passphrase, plaintext, ROUNDS)
+}
+
+/// Whether `data` starts like an encrypted file.
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+pub fn decrypt(passphrase: &[u8], data: &[u8]) -> Result<Vec<u8>, DecryptError> {
+    if !is_encrypted(data) {
+        return Err(DecryptError::NotEncrypted);
+    }
+    if data.len() < HEADER_LEN + TAG_LEN {
+        return Err(DecryptError::Truncated);
+    }
+    let rounds = u32::from_be_bytes([data[8], data[9], data[10], data[11]]);
+    if rounds == 0 || rounds > MAX_ROUNDS {
+        return Err(DecryptError::Rejected);
+    }
+    let salt = &data[12..12 + SALT_LEN];
+    let nonce = &data[12 + SALT_LEN..HEADER_LEN];
+    let (header, sealed) = data.split_at(HEADER_LEN);
+    cipher(passphrase, salt, rounds)
+        .decrypt(nonce.into(), Payload { msg: sealed, aad: header })
+        .map_err(|_| DecryptError::Rejected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let plaintext = b"Channel Number,Frequency (Hz)\n00001,145500000\n";
+        let sealed = encrypt_with(b"club secret", plaintext, 10, [7; SALT_LEN], [9; NONCE_LEN]);
+        assert!(is_encrypted(&sealed));
+        assert_eq!(sealed.len(), HEADER_LEN + plaintext.len() + TAG_LEN);
+        assert!(!sealed.windows(7).any(|w| w == b"Channel"));
+        assert_eq!(decrypt(b"club secret", &sealed).unwrap(), plaintext);
+        // Fresh salt and nonce: the same file twice encrypts differently.
+        assert_ne!(sealed, encrypt_with(b"club secret", plaintext, 10, [8; SALT_LEN], [9; NONCE_LEN]));
+    }
+
+    #[test]
+    fn test_decrypt_rejects() {
+        let sealed = encrypt_with(b"club secret", b"00001,145500000\n", 10, [7; SALT_LEN], [9; NONCE_LEN]);
+        assert_eq!(decrypt(b"club secreT", &sealed), Err(DecryptError::Rejected));
+        let mut flipped = sealed.clone();
+        flipped[HEADER_LEN] ^= 1;
+        assert_eq!(decrypt(b"club secret", &flipped), Err(DecryptError::Rejected));
+        // The header is authenticated too.
+        let mut salted = sealed.clone();
+        salted[12] ^= 1;
+        assert_eq!(decrypt(b"club secret", &salted), Err(DecryptError::Rejected));
+        assert_eq!(decrypt(b"club secret", &sealed[..sealed.len() - 1]), Err(DecryptError::Rejected));
+        assert_eq!(decrypt(b"club secret", &sealed[..HEADER_LEN]), Err(DecryptError::Truncated));
+        assert_eq!(decrypt(b"club secret", b"Channel Number\n"), Err(DecryptError::NotEncrypted));
+        assert_eq!(DecryptError::Rejected.to_string(), "wrong passphrase, or the file is damaged");
+    }
+
+    #[test]
+    fn test_encrypt() {
+        let a = encrypt_rounds(b"pw", b"x", 10).unwrap();
+        let b = encrypt_rounds(b"pw", b"x", 10).unwrap();
+        assert_ne!(a[12..HEADER_LEN], b[12..HEADER_LEN]);
+        assert_eq!(decrypt(b"pw", &a).unwrap(), b"x");
+    }
+}
@@ -0,0 +1,282 @@
+// REST daemon (`--daemon`): a small HTTP/1.1 server so dashboards and
+// home automation can manage the radio without running the CLI:
+//
+//   GET /status        VFOs, mode, split and status flags
+//   GET /channels      every programmed memory channel (slow: reads all 999)
+//   GET /channels/42   one channel; 404 when the slot is empty
+//   PUT /channels/42   program one channel from a JSON object
+//   GET /metrics       CAT counters and live meters for Prometheus
+//
+// Channels are JSON objects keyed by the memory CSV's column names
+// ("Frequency (Hz)", "Mode", ...), so a row of the CSV and a channel here
+// carry the same fields; a PUT goes through the same checks as
+// --check-data. Each connection is served on a thread of its own, and
+// their CAT frames go through the command queue (queue.rs): a GET
+// /channels gives way to other requests between channels instead of
+// holding them up until it's done.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use crate::mqtt::Telemetry;
+
+// Larger bodies aren't channels; refuse them rather than buffer them.
+const MAX_BODY: usize = 16 * 1024;
+
+/// What the daemon needs from the radio.
+pub trait Radio {
+    fn status(&mut self) -> Result<Value, ()>;
+    fn channels(&mut self) -> Result<Value, ()>;
+    /// None when the slot is empty.
+    fn channel(&mut self, ch: u16) -> Result<Option<Value>, ()>;
+    fn put_channel(&mut self, ch: u16, channel: Value) -> Result<(), PutError>;
+    /// Readings for --mqtt, taken between requests.
+    fn telemetry(&mut self) -> Telemetry;
+    /// Live gauges for /metrics: (metric name, value).
+    fn meters(&mut self) -> Vec<(&'static str, f64)>;
+}
+
+pub enum PutError {
+    /// The channel was refused before anything was sent to the radio.
+    Invalid(Vec<String>),
+    Radio,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Body {
+    Json(Value),
+    Text(String),
+}
+
+pub struct Response {
+    pub code: u16,
+    pub body: Body,
+}
+
+impl Response {
+    fn ok(body: Value) -> Self {
+        Response { code: 200, body: Body::Json(body) }
+    }
+}
+
+fn error(code: u16, message: &str) -> Response {
+    Response { code, body: Body::Json(json!({ "error": message })) }
+}
+
+/// The CAT counters (see stats.rs) followed by the radio's meters.
+fn metrics(radio: &mut dyn Radio) -> String {
+    let mut text = crate::stats::prometheus();
+    for (name, value) in radio.meters() {
+        text += &format!("# TYPE {} gauge\n{} {}\n", name, name, value);
+    }
+    text
+}
+
+fn radio_error() -> Response {
+    error(502, "The radio didn't answer as expected.")
+}
+
+/// Dispatches one request.
+pub fn route(radio: &mut dyn Radio, method: &str, path: &str, body: &str) -> Response {
+    let path = path.split('?').next().unwrap_or(path).trim_end_matches('/');
+    let segments: Vec<&str> = path.split('/').skip(1).collect();
+    match (method, segments.as_slice()) {
+        ("GET", ["status"]) => radio.status().map_or_else(|_| radio_error(), Response::ok),
+        ("GET", ["channels"]) => radio.channels().map_or_else(|_| radio_error(), Response::ok),
+        ("GET", ["metrics"]) => Response { code: 200, body: Body::Text(metrics(radio)) },
+        (_, ["channels", ch]) => {
+            let Some(ch) = ch.parse::<u16>().ok().filter(|n| (1..=999).contains(n)) else {
+                return error(404, "Channels are numbered 1-999.");
+            };
+            match method {
+                "GET" => match radio.channel(ch) {
+                    Ok(Some(body)) => Response::ok(body),
+                    Ok(None) => error(404, &format!("Channel {} is empty.", ch)),
+                    Err(_) => radio_error(),
+                },
+                "PUT" => {
+                    let Ok(channel) = serde_json::from_str::<Value>(body) else {
+                        return error(400, "The body isn't valid JSON.");
+                    };
+                    match radio.put_channel(ch, channel) {
+                        Ok(()) => Response::ok(json!({ "written": ch })),
+                        Err(PutError::Invalid(errors)) => Response { code: 400, body: Body::Json(json!({ "errors": errors })) },
+                        Err(PutError::Radio) => radio_error(),
+                    }
+                }
+                _ => error(405, "Use GET or PUT."),
+            }
+        }
+        ("GET", _) | ("PUT", _) => error(404, "No such endpoint."),
+        _ => error(405, "Use GET or PUT."),
+    }
+}
+
+fn reason(code: u16) -> &'static str {
+    match code {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        502 => "Bad Gateway",
+        _ => "Error",
+    }
+}
+
+/// Serves requests until `stop` is set, each connection on its own thread
+/// with a Radio from `radios`, calling `idle` between connections. Returns
+/// once the connections in progress are done.
+pub fn run(
+    listener: &TcpListener,
+    stop: &AtomicBool,
+    radios: &(dyn Fn() -> Box<dyn Radio> + Sync),
+    idle: &mut (dyn FnMut(&mut dyn Radio) + Send),
+    quiet: bool,
+) -> io::Result<()> {
+    listener.set_nonblocking(true)?;
+    let mut idle_radio = radios();
+    std::thread::scope(|s| {
+        while !stop.load(Ordering::SeqCst) {
+            idle(&mut *idle_radio);
+            let stream = match listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    std::thread::sleep(Duration::from_millis(100));
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            s.spawn(move || {
+                if let Err(e) = handle_connection(stream, &mut *radios(), quiet)
+                    && !quiet
+                {
+                    println!("Request failed: {}", e);
+                }
+            });
+        }
+        Ok(())
+    })
+}
+
+fn handle_connection(stream: TcpStream, radio: &mut dyn Radio, quiet: bool) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or(""), parts.next().unwrap_or("/"));
+    let mut length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.trim().eq_ignore_ascii_case("content-length")
+        {
+            length = value.trim().parse().unwrap_or(0);
+        }
+    }
+
+    let response = if length > MAX_BODY {
+        error(413, "Request body too large.")
+    } else {
+        let mut body = vec![0u8; length];
+        reader.read_exact(&mut body)?;
+        route(radio, method, path, &String::from_utf8_lossy(&body))
+    };
+    if !quiet { println!("{} {} -> {}", method, path, response.code); }
+    let (content_type, body) = match response.body {
+        Body::Json(value) => ("application/json", value.to_string()),
+        Body::Text(text) => ("text/plain; version=0.0.4", text),
+    };
+    write!(
+        writer,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.code,
+        reason(response.code),
+        content_type,
+        body.len(),
+        body
+    )?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Channel 1 is programmed, everything else empty.
+    struct FakeRadio {
+        written: Vec<(u16, Value)>,
+    }
+
+    impl Radio for FakeRadio {
+        fn status(&mut self) -> Result<Value, ()> {
+            Ok(json!({ "vfo_a": 14074000 }))
+        }
+
+        fn channels(&mut self) -> Result<Value, ()> {
+            Ok(json!([{ "Channel Number": "00001" }]))
+        }
+
+        fn channel(&mut self, ch: u16) -> Result<Option<Value>, ()> {
+            Ok((ch == 1).then(|| json!({ "Channel Number": "00001" })))
+        }
+
+        fn put_channel(&mut self, ch: u16, channel: Value) -> Result<(), PutError> {
+            if channel.get("Mode").is_none() {
+                return Err(PutError::Invalid(vec!["Mode is missing.".to_string()]));
+            }
+            self.written.push((ch, channel));
+            Ok(())
+        }
+
+        fn telemetry(&mut self) -> Telemetry {
+            Telemetry::default()
+        }
+
+        fn meters(&mut self) -> Vec<(&'static str, f64)> {
+            vec![("ftx1_s_meter_raw", 87.0)]
+        }
+    }
+
+    #[test]
+    fn test_route() {
+        let mut radio = FakeRadio { written: Vec::new() };
+        assert_eq!(route(&mut radio, "GET", "/status", "").body, Body::Json(json!({ "vfo_a": 14074000 })));
+        assert_eq!(route(&mut radio, "GET", "/channels/", "").code, 200);
+        assert_eq!(route(&mut radio, "GET", "/channels/1", "").code, 200);
+        assert_eq!(route(&mut radio, "GET", "/channels/2", "").code, 404);
+        assert_eq!(route(&mut radio, "GET", "/channels/1000", "").code, 404);
+        assert_eq!(route(&mut radio, "DELETE", "/channels/1", "").code, 405);
+        assert_eq!(route(&mut radio, "GET", "/nope", "").code, 404);
+    }
+
+    #[test]
+    fn test_metrics() {
+        let mut radio = FakeRadio { written: Vec::new() };
+        let Body::Text(text) = route(&mut radio, "GET", "/metrics", "").body else { panic!("metrics should be text") };
+        assert!(text.contains("# TYPE ftx1_cat_bytes_sent_total counter\n"), "{}", text);
+        assert!(text.ends_with("# TYPE ftx1_s_meter_raw gauge\nftx1_s_meter_raw 87\n"), "{}", text);
+    }
+
+    #[test]
+    fn test_put_channel() {
+        let mut radio = FakeRadio { written: Vec::new() };
+        assert_eq!(route(&mut radio, "PUT", "/channels/42", "{not json").code, 400);
+        let refused = route(&mut radio, "PUT", "/channels/42", "{}");
+        assert_eq!(refused.code, 400);
+        assert_eq!(refused.body, Body::Json(json!({ "errors": ["Mode is missing."] })));
+        assert_eq!(route(&mut radio, "PUT", "/channels/42", r#"{"Mode":"FM"}"#).code, 200);
+        assert_eq!(radio.written, [(42, json!({ "Mode": "FM" }))]);
+    }
+}
@@ -0,0 +1,245 @@
+// Memory-file CSV dialects. Spreadsheets set to a European locale save
+// "CSV" with ';' between fields and a decimal comma ("88,5"), and choke
+// on files that use ',' for both. Reading takes either: the delimiter is
+// sniffed from the header line and decimal commas in number cells are
+// accepted (see normalize_record). Writing follows --delimiter and
+// --decimal-comma, so a file can go back to the same spreadsheet.
+//
+// Frequencies are written in Hz unless --freq-unit mhz asks for MHz
+// ("145.52500", headed "Frequency (MHz)"). Reading takes either unit in
+// either column: a value with a decimal point or comma, or below 1000, is
+// MHz, anything else Hz. MHz values are converted digit by digit, so
+// every Hz survives the round trip; MHz is written with 5 decimals, or 6
+// where the last Hz digit isn't 0.
+
+use serde::{Deserialize, Deserializer, Serializer};
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::migrate;
+
+/// The memory-file columns holding frequencies.
+const FREQ_COLUMNS: [&str; 2] = ["Frequency (Hz)", "Split TX (Hz)"];
+
+// Set once from the command line, before any file is written; serde's
+// serialize_with can't take arguments.
+static WRITE_MHZ: AtomicBool = AtomicBool::new(false);
+static WRITE_DECIMAL_COMMA: AtomicBool = AtomicBool::new(false);
+
+/// How memory files are written from now on.
+pub fn set_write_format(mhz: bool, decimal_comma: bool) {
+    WRITE_MHZ.store(mhz, Ordering::Relaxed);
+    WRITE_DECIMAL_COMMA.store(decimal_comma, Ordering::Relaxed);
+}
+
+/// A column's header as written: "Frequency (Hz)" becomes
+/// "Frequency (MHz)" with --freq-unit mhz.
+pub fn column_header(column: &str) -> String {
+    if WRITE_MHZ.load(Ordering::Relaxed) && FREQ_COLUMNS.contains(&column) {
+        column.replace(" (Hz)", " (MHz)")
+    } else {
+        column.to_string()
+    }
+}
+
+/// The CSV_COLUMNS name a header stands for: the MHz spelling of a
+/// frequency column is the Hz one.
+pub fn canonical_column(header: &str) -> &str {
+    FREQ_COLUMNS.into_iter().find(|c| c.replace(" (Hz)", " (MHz)") == header).unwrap_or(header)
+}
+
+/// `--delimiter` values: the character itself, or its name.
+pub fn parse_delimiter(name: &str) -> Option<u8> {
+    match name {
+        "," | "comma" => Some(b','),
+        ";" | "semicolon" => Some(b';'),
+        "\t" | "tab" => Some(b'\t'),
+        _ => None,
+    }
+}
+
+/// The delimiter of a memory file, judged by its header line: whichever
+/// of ',', ';' and tab it has most of.
+pub fn sniff_delimiter(text: &str) -> u8 {
+    let header = text.lines().find(|l| !l.trim().is_empty() && !l.starts_with('#')).unwrap_or("");
+    [b',', b';', b'\t']
+        .into_iter()
+        .max_by_key(|d| (header.bytes().filter(|b| b == d).count(), *d == b','))
+        .unwrap_or(b',')
+}
+
+/// The delimiter of the memory file at `path`; ',' if it can't be read.
+pub fn file_delimiter(path: &str) -> u8 {
+    std::fs::read_to_string(path).map_or(b',', |text| sniff_delimiter(&text))
+}
+
+/// A reader for a memory file in whichever dialect it's in, brought up
+/// to the current format (see migrate.rs).
+pub fn reader(path: &str) -> csv::Result<csv::Reader<io::Cursor<Vec<u8>>>> {
+    text_reader(std::fs::read_to_string(path)?)
+}
+
+/// reader for a memory file already in memory.
+pub fn text_reader(text: String) -> csv::Result<csv::Reader<io::Cursor<Vec<u8>>>> {
+    let text = migrate::migrate(text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let delimiter = sniff_delimiter(&text);
+    Ok(csv::ReaderBuilder::new().comment(Some(b'#')).delimiter(delimiter).from_reader(io::Cursor::new(text.into_bytes())))
+}
+
+/// A writer that leaves the header row to the caller, so frequency
+/// columns can be named in the unit written (see column_header).
+pub fn writer<W: io::Write>(out: W, delimiter: u8) -> csv::Writer<W> {
+    csv::WriterBuilder::new().delimiter(delimiter).has_headers(false).from_writer(out)
+}
+
+/// "88,5" -> "88.5"; anything that isn't a decimal-comma number is left alone.
+pub fn from_decimal_comma(value: &str) -> String {
+    let swapped = value.replacen(',', ".", 1);
+    if value.contains(',') && swapped.trim().parse::<f64>().is_ok() { swapped } else { value.to_string() }
+}
+
+/// "88.5" -> "88,5", for numbers only.
+pub fn to_decimal_comma(value: &str) -> String {
+    let swapped = value.replacen('.', ",", 1);
+    if value.contains('.') && value.trim().parse::<f64>().is_ok() { swapped } else { value.to_string() }
+}
+
+/// A frequency cell in Hz or MHz (see the top of this file) as Hz.
+pub fn parse_hz(value: &str) -> Option<u32> {
+    let value = value.trim().replace(',', ".");
+    // Digits only: no signs for u64::from_str to take, and frac is sliced
+    // by byte below.
+    let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    let hz: u64 = match value.split_once('.') {
+        Some((mhz, frac)) => {
+            if !digits(mhz) || !digits(frac) {
+                return None;
+            }
+            if frac.len() > 6 && frac[6..].bytes().any(|b| b != b'0') {
+                return None; // finer than 1 Hz
+            }
+            let digits = format!("{:0<6}", &frac[..frac.len().min(6)]);
+            let mhz = if mhz.is_empty() { 0 } else { mhz.parse::<u64>().ok()? };
+            mhz.checked_mul(1_000_000)? + digits.parse::<u64>().ok()?
+        }
+        None if !digits(&value) => return None,
+        None => match value.parse::<u64>().ok()? {
+            mhz @ 0..1000 => mhz * 1_000_000,
+            hz => hz,
+        },
+    };
+    u32::try_from(hz).ok()
+}
+
+/// Hz as MHz with 5 decimals, or 6 when the last Hz digit isn't 0.
+pub fn format_mhz(hz: u32) -> String {
+    let frac = format!("{:06}", hz % 1_000_000);
+    let frac = if frac.ends_with('0') { &frac[..5] } else { &frac[..] };
+    format!("{}.{}", hz / 1_000_000, frac)
+}
+
+// Cells arrive as numbers from JSON (the daemon) and from csv when they
+// look like one, as text otherwise.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Cell {
+    Int(u64),
+    Float(f64),
+    Text(String),
+}
+
+fn cell_hz<E: serde::de::Error>(cell: Cell) -> Result<u32, E> {
+    let text = match cell {
+        Cell::Int(n) => n.to_string(),
+        // Formatting the float back to text keeps "145.525" exact.
+        Cell::Float(f) => f.to_string(),
+        Cell::Text(t) => t,
+    };
+    parse_hz(&text).ok_or_else(|| E::custom(format!("'{}' is not a frequency in Hz or MHz", text)))
+}
+
+pub fn deserialize_hz<'de, D: Deserializer<'de>>(d: D) -> Result<u32, D::Error> {
+    cell_hz(Cell::deserialize(d)?)
+}
+
+pub fn deserialize_opt_hz<'de, D: Deserializer<'de>>(d: D) -> Result<Option<u32>, D::Error> {
+    Option::<Cell>::deserialize(d)?.map(cell_hz).transpose()
+}
+
+pub fn serialize_hz<S: Serializer>(hz: &u32, s: S) -> Result<S::Ok, S::Error> {
+    if !WRITE_MHZ.load(Ordering::Relaxed) {
+        return s.serialize_u32(*hz);
+    }
+    let mhz = format_mhz(*hz);
+    if WRITE_DECIMAL_COMMA.load(Ordering::Relaxed) { s.serialize_str(&to_decimal_comma(&mhz)) } else { s.serialize_str(&mhz) }
+}
+
+pub fn serialize_opt_hz<S: Serializer>(hz: &Option<u32>, s: S) -> Result<S::Ok, S::Error> {
+    match hz {
+        Some(hz) => serialize_hz(hz, s),
+        None => s.serialize_none(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff_delimiter() {
+        assert_eq!(sniff_delimiter("Channel Number,Frequency (Hz),Memory Tag\n00001,145000000,A;B\n"), b',');
+        assert_eq!(sniff_delimiter("# exported\nChannel Number;Frequency (Hz);Memory Tag\n"), b';');
+        assert_eq!(sniff_delimiter("Channel Number\tFrequency (Hz)\n"), b'\t');
+        assert_eq!(sniff_delimiter(""), b',');
+    }
+
+    #[test]
+    fn test_decimal_comma() {
+        assert_eq!(from_decimal_comma("88,5"), "88.5");
+        assert_eq!(from_decimal_comma("88.5"), "88.5");
+        assert_eq!(from_decimal_comma("A,B"), "A,B");
+        assert_eq!(to_decimal_comma("88.5"), "88,5");
+        assert_eq!(to_decimal_comma("23"), "23");
+        assert_eq!(to_decimal_comma("N.Y."), "N.Y.");
+    }
+
+    #[test]
+    fn test_parse_hz() {
+        assert_eq!(parse_hz("145525000"), Some(145_525_000));
+        assert_eq!(parse_hz("145.52500"), Some(145_525_000));
+        assert_eq!(parse_hz("145,525"), Some(145_525_000));
+        assert_eq!(parse_hz("7.074123"), Some(7_074_123));
+        assert_eq!(parse_hz("0.5"), Some(500_000));
+        assert_eq!(parse_hz("146"), Some(146_000_000));
+        assert_eq!(parse_hz("7.0741234"), None);
+        assert_eq!(parse_hz("7.0741230"), Some(7_074_123));
+        assert_eq!(parse_hz("5000.000000"), None);
+        assert_eq!(parse_hz("MHz"), None);
+        // Non-ASCII after the point mustn't be sliced through.
+        assert_eq!(parse_hz("145.12345é"), None);
+        assert_eq!(parse_hz("145.1234é"), None);
+        assert_eq!(parse_hz("14é.5"), None);
+        assert_eq!(parse_hz("145.+5"), None);
+        assert_eq!(parse_hz("+145.5"), None);
+        assert_eq!(parse_hz("-7.074"), None);
+        assert_eq!(parse_hz("+145525000"), None);
+    }
+
+    #[test]
+    fn test_format_mhz_round_trips() {
+        assert_eq!(format_mhz(145_525_000), "145.52500");
+        assert_eq!(format_mhz(7_074_123), "7.074123");
+        assert_eq!(format_mhz(30_000), "0.03000");
+        for hz in [30_000, 1_840_001, 145_525_000, 470_000_000, 433_512_345] {
+            assert_eq!(parse_hz(&format_mhz(hz)), Some(hz));
+        }
+    }
+
+    #[test]
+    fn test_canonical_column() {
+        assert_eq!(canonical_column("Frequency (MHz)"), "Frequency (Hz)");
+        assert_eq!(canonical_column("Split TX (MHz)"), "Split TX (Hz)");
+        assert_eq!(canonical_column("Clarifier Offset (MHz)"), "Clarifier Offset (MHz)");
+        assert_eq!(canonical_column("Mode"), "Mode");
+    }
+}
@@ -0,0 +1,85 @@
+// Radio-state events for `--monitor --udp-events ADDR`: every change of
+// VFO-A frequency, main-side mode or memory channel goes out as one JSON
+// datagram, e.g.
+//
+//   {"time":"2026-01-02T03:04:05Z","event":"frequency","value":14074000}
+//   {"time":"2026-01-02T03:04:05Z","event":"mode","value":"USB"}
+//   {"time":"2026-01-02T03:04:05Z","event":"channel","value":"00012"}
+//
+// so antenna switchers and band decoders on the LAN can follow the radio.
+// Datagrams are fire-and-forget; a listener that isn't there is ignored.
+
+use std::io;
+use std::net::UdpSocket;
+
+use crate::ftx1::{MemoryChannel, Mode};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    Frequency(u32),
+    Mode(Mode),
+    Channel(MemoryChannel),
+}
+
+impl Event {
+    pub fn to_json(self, time: &str) -> String {
+        let (event, value) = match self {
+            Event::Frequency(hz) => ("frequency", hz.to_string()),
+            Event::Mode(mode) => ("mode", format!("\"{}\"", mode)),
+            Event::Channel(ch) => {
+                let chars = ch.to_chars().map(|c| c.iter().collect::<String>()).unwrap_or_default();
+                ("channel", format!("\"{}\"", chars))
+            }
+        };
+        format!("{{\"time\":\"{}\",\"event\":\"{}\",\"value\":{}}}", time, event, value)
+    }
+}
+
+pub struct UdpEvents {
+    socket: UdpSocket,
+    target: String,
+}
+
+impl UdpEvents {
+    pub fn open(target: &str) -> io::Result<Self> {
+        let socket = UdpSocket::bind("0.0.0.0:0")?;
+        // Lets ADDR be a broadcast address such as 192.168.1.255.
+        socket.set_broadcast(true)?;
+        Ok(UdpEvents { socket, target: target.to_string() })
+    }
+
+    pub fn send(&self, event: Event) {
+        let time = jiff::Timestamp::now().strftime("%Y-%m-%dT%H:%M:%SZ").to_string();
+        let _ = self.socket.send_to(event.to_json(&time).as_bytes(), &self.target);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_json() {
+        let time = "2026-01-02T03:04:05Z";
+        assert_eq!(
+            Event::Frequency(14_074_000).to_json(time),
+            r#"{"time":"2026-01-02T03:04:05Z","event":"frequency","value":14074000}"#
+        );
+        assert_eq!(Event::Mode(Mode::Usb).to_json(time), r#"{"time":"2026-01-02T03:04:05Z","event":"mode","value":"USB"}"#);
+        assert_eq!(
+            Event::Channel(MemoryChannel::Mem(12)).to_json(time),
+            r#"{"time":"2026-01-02T03:04:05Z","event":"channel","value":"00012"}"#
+        );
+    }
+
+    #[test]
+    fn test_udp_events_sent() {
+        let listener = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let events = UdpEvents::open(&listener.local_addr().unwrap().to_string()).unwrap();
+        events.send(Event::Frequency(7_074_000));
+        let mut buf = [0u8; 256];
+        let n = listener.recv(&mut buf).unwrap();
+        let datagram = String::from_utf8_lossy(&buf[..n]);
+        assert!(datagram.ends_with(r#""event":"frequency","value":7074000}"#), "{}", datagram);
+    }
+}
@@ -0,0 +1,318 @@
+// C API for driving the radio from C/C++ programs (loggers and the like)
+// through this crate's protocol code: open a serial port, identify the
+// radio, read and write memory channels. Built into the cdylib with the
+// `ffi` feature; the declarations are in include/ftx1.h.
+//
+// Every function returns FTX1_OK or a negative FTX1_ERR_* code, except
+// that reading an empty channel returns FTX1_EMPTY. Channels are written
+// with the same frames as --write-radio (write_channel_frames).
+
+use std::ffi::{c_char, c_int, CStr};
+use std::io::{Read, Write};
+use std::time::Duration;
+
+use crate::ftx1::*;
+
+pub const FTX1_OK: c_int = 0;
+pub const FTX1_EMPTY: c_int = 1;
+pub const FTX1_ERR_ARG: c_int = -1;
+pub const FTX1_ERR_IO: c_int = -2;
+pub const FTX1_ERR_PROTOCOL: c_int = -3;
+pub const FTX1_ERR_NOT_FTX1: c_int = -4;
+
+const TIMEOUT: Duration = Duration::from_millis(200);
+const RX_BUFFER_SIZE: usize = 255;
+
+/// An open radio, opaque to C.
+pub struct Ftx1Radio {
+    port: Box<dyn serialport::SerialPort>,
+}
+
+/// One memory channel, as C sees it. Enumerations are their CAT codes.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ftx1Memory {
+    /// 1-999.
+    pub channel: u16,
+    pub frequency_hz: u32,
+    /// -9990..9990 in 10 Hz steps.
+    pub clarifier_offset_hz: i16,
+    pub rx_clarifier: u8,
+    pub tx_clarifier: u8,
+    /// The MD mode character: '1' LSB, '2' USB, '4' FM, 'B' FM-N, ...
+    pub mode: c_char,
+    /// 0 off, 1 tone squelch, 2 tone, 3 DCS, 4 PR FREQ, 5 REV TONE.
+    pub sql_type: u8,
+    /// 0 simplex, 1 plus, 2 minus, 3 ARS.
+    pub shift: u8,
+    /// Index into the radio's CTCSS tone and DCS code tables.
+    pub ctcss_code: u8,
+    pub dcs_code: u8,
+    /// Split TX frequency, 0 when split is off.
+    pub tx_frequency_hz: u32,
+    /// NUL-terminated, up to 12 characters.
+    pub tag: [c_char; 13],
+}
+
+impl Ftx1Radio {
+    /// Sends a frame and collects the reply, like cat_send: set commands
+    /// get none and end on the timeout.
+    fn exchange(&mut self, frame: &[u8]) -> Result<Vec<u8>, c_int> {
+        self.port.write_all(frame).map_err(|_| FTX1_ERR_IO)?;
+        let mut buffer = Vec::with_capacity(RX_BUFFER_SIZE);
+        let mut chunk = [0u8; RX_BUFFER_SIZE];
+        while !reply_complete(frame, &buffer) && buffer.len() < RX_BUFFER_SIZE {
+            match self.port.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(n) => buffer.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == std::io::ErrorKind::TimedOut => break,
+                Err(_) => return Err(FTX1_ERR_IO),
+            }
+        }
+        Ok(find_reply(frame, &buffer).map(<[u8]>::to_vec).unwrap_or_default())
+    }
+
+    fn identify(&mut self) -> Result<u16, c_int> {
+        let id = CMD_ID.decode(&self.exchange(&CMD_ID.read())?).map_err(|_| FTX1_ERR_PROTOCOL)?;
+        CMD_ID.validate(id).map_err(|_| FTX1_ERR_NOT_FTX1)?;
+        Ok(id)
+    }
+
+    fn read_memory(&mut self, channel: u16) -> Result<Option<Ftx1Memory>, c_int> {
+        let ch = MemoryChannel::Mem(channel);
+        let rx = self.exchange(&CMD_MR.read(ch))?;
+        if is_error_reply(&rx) {
+            return Ok(None);
+        }
+        let mem = CMD_MR.decode(&rx).map_err(|_| FTX1_ERR_PROTOCOL)?;
+        let tag = self.exchange(&CMD_MT.read(ch))?;
+        let tag = CMD_MT.decode(&tag).map(str::to_string).unwrap_or_default();
+        // Tones are only readable from the VFO: select the channel on SUB.
+        self.exchange(&CMD_MC.set(Side::Sub, ch))?;
+        let mut tones = [0; 2];
+        for (code, tone_type) in tones.iter_mut().zip([ToneType::Ctcss, ToneType::Dcs]) {
+            let rx = self.exchange(&CMD_CN.read(Side::Sub, tone_type))?;
+            *code = CMD_CN.decode(&rx).map_err(|_| FTX1_ERR_PROTOCOL)?.tone_code;
+        }
+        let split = CMD_MZ.decode(&self.exchange(&CMD_MZ.read(ch))?).ok().filter(|mz| mz.split_on);
+        Ok(Some(to_c(&mem, tones, split.map(|mz| mz.tx_frequency_hz), &tag)))
+    }
+
+    fn write_memory(&mut self, memory: &Ftx1Memory) -> Result<(), c_int> {
+        let (mem, tones, tx, tag) = from_c(memory).map_err(|_| FTX1_ERR_ARG)?;
+        for frame in write_channel_frames(&mem, tones, Some(tag), tx).map_err(|_| FTX1_ERR_ARG)? {
+            self.exchange(&frame)?;
+        }
+        Ok(())
+    }
+}
+
+fn to_c(mem: &MemoryReadWrite, tones: [ToneCode; 2], tx: Option<FrequencyHz>, tag: &str) -> Ftx1Memory {
+    let mut c_tag = [0 as c_char; 13];
+    for (dst, src) in c_tag.iter_mut().zip(tag.trim_end().bytes().take(12)) {
+        *dst = src as c_char;
+    }
+    Ftx1Memory {
+        channel: match mem.channel {
+            MemoryChannel::Mem(ch) => ch,
+            _ => 0,
+        },
+        frequency_hz: mem.frequency_hz.to_u32(),
+        clarifier_offset_hz: mem.clarifier_offset_hz.to_i16(),
+        rx_clarifier: mem.rx_clarifier_enabled as u8,
+        tx_clarifier: mem.tx_clarifier_enabled as u8,
+        mode: char::from(mem.mode) as c_char,
+        sql_type: mem.sql_type as u8,
+        shift: mem.shift as u8,
+        ctcss_code: tones[0],
+        dcs_code: tones[1],
+        tx_frequency_hz: tx.map_or(0, |f| f.to_u32()),
+        tag: c_tag,
+    }
+}
+
+type Channel = (MemoryReadWrite, (ToneCode, ToneCode), Option<FrequencyHz>, String);
+
+fn from_c(m: &Ftx1Memory) -> Result<Channel, ()> {
+    if !(1..=999).contains(&m.channel) {
+        return Err(());
+    }
+    let digit = |v: u8| char::from_digit(v as u32, 10).ok_or(());
+    let mem = MemoryReadWrite {
+        channel: MemoryChannel::try_from(format!("{:05}", m.channel))?,
+        frequency_hz: FrequencyHz::try_from(m.frequency_hz)?,
+        clarifier_offset_hz: ClarifierOffsetHz::try_from(m.clarifier_offset_hz)?,
+        rx_clarifier_enabled: RxClarifierOnOff::try_from(digit(m.rx_clarifier)?)?,
+        tx_clarifier_enabled: TxClarifierOnOff::try_from(digit(m.tx_clarifier)?)?,
+        mode: Mode::try_from(m.mode as u8)?,
+        ch_type: ChType::MemoryChannel,
+        sql_type: SqlType::try_from(digit(m.sql_type)?)?,
+        shift: Shift::try_from(digit(m.shift)?)?,
+    };
+    // Out-of-range codes are refused by the tone tables' own check.
+    CmdCn::tone_code_to_string(ToneType::Ctcss, m.ctcss_code)?;
+    CmdCn::tone_code_to_string(ToneType::Dcs, m.dcs_code)?;
+    let tx = match m.tx_frequency_hz {
+        0 => None,
+        hz => Some(FrequencyHz::try_from(hz)?),
+    };
+    let len = m.tag.iter().position(|&c| c == 0).ok_or(())?;
+    let tag: String = m.tag[..len].iter().map(|&c| c as u8 as char).collect();
+    Ok((mem, (m.ctcss_code, m.dcs_code), tx, tag))
+}
+
+/// Runs `f` on the radio behind a pointer C handed back.
+fn with_radio(radio: *mut Ftx1Radio, f: impl FnOnce(&mut Ftx1Radio) -> c_int) -> c_int {
+    // SAFETY: C only holds pointers ftx1_open returned and ftx1_close hasn't freed.
+    match unsafe { radio.as_mut() } {
+        Some(radio) => f(radio),
+        None => FTX1_ERR_ARG,
+    }
+}
+
+/// Opens the radio on a serial port ("/dev/ttyUSB0", "COM3") at `baud`.
+/// Returns NULL when the port can't be opened.
+///
+/// # Safety
+/// `port` must be a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ftx1_open(port: *const c_char, baud: u32) -> *mut Ftx1Radio {
+    if port.is_null() {
+        return std::ptr::null_mut();
+    }
+    // SAFETY: the caller passes a NUL-terminated string.
+    let Ok(name) = unsafe { CStr::from_ptr(port) }.to_str() else { return std::ptr::null_mut() };
+    match serialport::new(name, baud).timeout(TIMEOUT).open() {
+        Ok(port) => Box::into_raw(Box::new(Ftx1Radio { port })),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Closes a radio ftx1_open returned. NULL is ignored.
+///
+/// # Safety
+/// `radio` must come from ftx1_open and not be used again.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ftx1_close(radio: *mut Ftx1Radio) {
+    if !radio.is_null() {
+        // SAFETY: ftx1_open boxed it; the caller gives up the pointer.
+        drop(unsafe { Box::from_raw(radio) });
+    }
+}
+
+/// Reads the radio ID into `id` (may be NULL) and checks it's an FTX-1.
+///
+/// # Safety
+/// `radio` must come from ftx1_open; `id` must be NULL or writable.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ftx1_identify(radio: *mut Ftx1Radio, id: *mut u16) -> c_int {
+    with_radio(radio, |radio| match radio.identify() {
+        Ok(value) => {
+            // SAFETY: the caller passes NULL or a writable u16.
+            if let Some(id) = unsafe { id.as_mut() } {
+                *id = value;
+            }
+            FTX1_OK
+        }
+        Err(code) => code,
+    })
+}
+
+/// Reads memory channel `channel` (1-999) into `out`. FTX1_EMPTY when
+/// the channel isn't programmed; `out` is left alone then.
+///
+/// # Safety
+/// `radio` must come from ftx1_open; `out` must be writable.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ftx1_read_memory(radio: *mut Ftx1Radio, channel: u16, out: *mut Ftx1Memory) -> c_int {
+    // SAFETY: the caller passes a writable Ftx1Memory.
+    let Some(out) = (unsafe { out.as_mut() }) else { return FTX1_ERR_ARG };
+    if !(1..=999).contains(&channel) {
+        return FTX1_ERR_ARG;
+    }
+    with_radio(radio, |radio| match radio.read_memory(channel) {
+        Ok(Some(memory)) => {
+            *out = memory;
+            FTX1_OK
+        }
+        Ok(None) => FTX1_EMPTY,
+        Err(code) => code,
+    })
+}
+
+/// Programs the channel `memory` describes. Values the radio can't take
+/// are refused with FTX1_ERR_ARG before anything is sent.
+///
+/// # Safety
+/// `radio` must come from ftx1_open; `memory` must point to an Ftx1Memory.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ftx1_write_memory(radio: *mut Ftx1Radio, memory: *const Ftx1Memory) -> c_int {
+    // SAFETY: the caller passes a readable Ftx1Memory.
+    let Some(memory) = (unsafe { memory.as_ref() }) else { return FTX1_ERR_ARG };
+    with_radio(radio, |radio| match radio.write_memory(memory) {
+        Ok(()) => FTX1_OK,
+        Err(code) => code,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory() -> Ftx1Memory {
+        let mut tag = [0 as c_char; 13];
+        for (dst, src) in tag.iter_mut().zip(b"REPEATER") {
+            *dst = *src as c_char;
+        }
+        Ftx1Memory {
+            channel: 12,
+            frequency_hz: 145_600_000,
+            clarifier_offset_hz: 0,
+            rx_clarifier: 0,
+            tx_clarifier: 0,
+            mode: b'4' as c_char,
+            sql_type: 2,
+            shift: 2,
+            ctcss_code: 8,
+            dcs_code: 0,
+            tx_frequency_hz: 0,
+            tag,
+        }
+    }
+
+    #[test]
+    fn test_memory_round_trip() {
+        let (mem, tones, tx, tag) = from_c(&memory()).unwrap();
+        assert_eq!((mem.channel, mem.mode, mem.sql_type, mem.shift), (MemoryChannel::Mem(12), Mode::Fm, SqlType::CtcssEnc, Shift::MinusShift));
+        assert_eq!((tones, tx, tag.as_str()), ((8, 0), None, "REPEATER"));
+        assert_eq!(to_c(&mem, [tones.0, tones.1], tx, &tag), memory());
+    }
+
+    #[test]
+    fn test_memory_rejects_bad_values() {
+        for bad in [
+            Ftx1Memory { channel: 0, ..memory() },
+            Ftx1Memory { channel: 1000, ..memory() },
+            Ftx1Memory { clarifier_offset_hz: 15, ..memory() },
+            Ftx1Memory { mode: b'Z' as c_char, ..memory() },
+            Ftx1Memory { shift: 7, ..memory() },
+            Ftx1Memory { ctcss_code: 200, ..memory() },
+            Ftx1Memory { tag: [b'A' as c_char; 13], ..memory() },
+        ] {
+            assert!(from_c(&bad).is_err(), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn test_null_pointers() {
+        let mut out = memory();
+        // SAFETY: NULL radios and ports are what's being tested.
+        unsafe {
+            assert!(ftx1_open(std::ptr::null(), 38400).is_null());
+            assert_eq!(ftx1_identify(std::ptr::null_mut(), std::ptr::null_mut()), FTX1_ERR_ARG);
+            assert_eq!(ftx1_read_memory(std::ptr::null_mut(), 1, &mut out), FTX1_ERR_ARG);
+            assert_eq!(ftx1_write_memory(std::ptr::null_mut(), &out), FTX1_ERR_ARG);
+            ftx1_close(std::ptr::null_mut());
+        }
+    }
+}
@@ -0,0 +1,145 @@
+// Channel queries for `--filter QUERY`: space-separated conditions, all of
+// which a channel must meet, e.g.
+//
+//   band=2m mode=FM tag~REPEATER
+//
+//   band=NAME     amateur band by the --band names (160m ... 2m, 70cm)
+//                 or AIR, MW
+//   freq=LO-HI    frequency range in MHz, inclusive ("144-146.5")
+//   mode=MODE     mode, in any spelling --import-list knows ("usb", "NFM")
+//   group=GROUP   memory group (hf, 50, air, vhf, uhf; see groups.rs)
+//   tag=TEXT      tag is TEXT; tag~TEXT: tag contains TEXT (both ignore case)
+//   split=yes|no  has a split TX frequency or not
+
+use crate::ftx1::{Band, Mode};
+use crate::groups::MemoryGroup;
+use crate::import::{parse_freq, parse_mode};
+
+/// The fields of a channel a query looks at.
+pub struct ChannelView<'a> {
+    pub freq: u32,
+    pub mode: &'a str,
+    pub tag: &'a str,
+    pub split: bool,
+}
+
+#[derive(Debug, PartialEq)]
+enum Condition {
+    Freq(u32, u32),
+    Mode(Mode),
+    Group(MemoryGroup),
+    TagIs(String),
+    TagContains(String),
+    Split(bool),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Filter {
+    conditions: Vec<Condition>,
+}
+
+/// Band edges, the widest any region uses, in Hz (inclusive).
+pub fn band_edges(band: Band) -> Option<(u32, u32)> {
+    Some(match band {
+        Band::M160 => (1_800_000, 2_000_000),
+        Band::M80 => (3_500_000, 4_000_000),
+        Band::M60 => (5_250_000, 5_450_000),
+        Band::M40 => (7_000_000, 7_300_000),
+        Band::M30 => (10_100_000, 10_150_000),
+        Band::M20 => (14_000_000, 14_350_000),
+        Band::M17 => (18_068_000, 18_168_000),
+        Band::M15 => (21_000_000, 21_450_000),
+        Band::M12 => (24_890_000, 24_990_000),
+        Band::M10 => (28_000_000, 29_700_000),
+        Band::M6 => (50_000_000, 54_000_000),
+        Band::Mw => (530_000, 1_710_000),
+        Band::Air => (108_000_000, 137_000_000),
+        Band::M2 => (144_000_000, 148_000_000),
+        Band::Cm70 => (420_000_000, 450_000_000),
+        // GEN is everything else; there's no range to match.
+        Band::Gen => return None,
+    })
+}
+
+impl Filter {
+    pub fn parse(query: &str) -> Result<Self, String> {
+        let conditions = query.split_whitespace().map(parse_condition).collect::<Result<_, _>>()?;
+        Ok(Filter { conditions })
+    }
+
+    pub fn matches(&self, ch: &ChannelView) -> bool {
+        let tag = ch.tag.trim().to_ascii_uppercase();
+        self.conditions.iter().all(|c| match c {
+            Condition::Freq(lo, hi) => (*lo..=*hi).contains(&ch.freq),
+            Condition::Mode(mode) => parse_mode(ch.mode) == Some(*mode),
+            Condition::Group(group) => MemoryGroup::of(ch.freq) == *group,
+            Condition::TagIs(text) => tag == *text,
+            Condition::TagContains(text) => tag.contains(text.as_str()),
+            Condition::Split(split) => ch.split == *split,
+        })
+    }
+}
+
+fn parse_condition(term: &str) -> Result<Condition, String> {
+    if let Some(text) = term.strip_prefix("tag~") {
+        return Ok(Condition::TagContains(text.to_ascii_uppercase()));
+    }
+    let Some((key, value)) = term.split_once('=') else {
+        return Err(format!("'{}' isn't KEY=VALUE", term));
+    };
+    let bad = || format!("bad {} '{}'", key, value);
+    match key {
+        "band" => {
+            let band = Band::try_from(value).map_err(|_| bad())?;
+            let (lo, hi) = band_edges(band).ok_or_else(bad)?;
+            Ok(Condition::Freq(lo, hi))
+        }
+        "freq" => {
+            let (lo, hi) = value.split_once('-').unwrap_or((value, value));
+            let mhz = |v: &str| if v.contains('.') { parse_freq(v) } else { parse_freq(&format!("{}.0", v)) };
+            Ok(Condition::Freq(mhz(lo).ok_or_else(bad)?, mhz(hi).ok_or_else(bad)?))
+        }
+        "mode" => parse_mode(value).map(Condition::Mode).ok_or_else(bad),
+        "group" => MemoryGroup::try_from(value).map(Condition::Group).map_err(|_| bad()),
+        "tag" => Ok(Condition::TagIs(value.to_ascii_uppercase())),
+        "split" => match value {
+            "yes" => Ok(Condition::Split(true)),
+            "no" => Ok(Condition::Split(false)),
+            _ => Err(bad()),
+        },
+        _ => Err(format!("unknown condition '{}'", key)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view<'a>(freq: u32, mode: &'a str, tag: &'a str) -> ChannelView<'a> {
+        ChannelView { freq, mode, tag, split: false }
+    }
+
+    #[test]
+    fn test_filter_matches() {
+        let filter = Filter::parse("band=2m mode=FM tag~rpt").unwrap();
+        assert!(filter.matches(&view(146_940_000, "FM", "CLUB RPT    ")));
+        assert!(!filter.matches(&view(146_940_000, "FM", "SIMPLEX")));
+        assert!(!filter.matches(&view(433_500_000, "FM", "UHF RPT")));
+        assert!(!filter.matches(&view(146_940_000, "FM-N", "CLUB RPT")));
+
+        let filter = Filter::parse("freq=7-7.1 mode=usb split=no").unwrap();
+        assert!(filter.matches(&view(7_074_000, "USB", "")));
+        assert!(!filter.matches(&view(7_150_000, "USB", "")));
+        assert!(Filter::parse("").unwrap().matches(&view(7_150_000, "LSB", "")));
+        assert!(Filter::parse("tag=home").unwrap().matches(&view(145_000_000, "FM", "HOME        ")));
+    }
+
+    #[test]
+    fn test_filter_errors() {
+        assert_eq!(Filter::parse("band=3m"), Err("bad band '3m'".to_string()));
+        assert_eq!(Filter::parse("band=gen"), Err("bad band 'gen'".to_string()));
+        assert_eq!(Filter::parse("color=red"), Err("unknown condition 'color'".to_string()));
+        assert_eq!(Filter::parse("FM"), Err("'FM' isn't KEY=VALUE".to_string()));
+        assert!(Filter::parse("group=vhf freq=144.5").is_ok());
+    }
+}
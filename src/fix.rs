@@ -0,0 +1,112 @@
+// Automatic fixes for `--check-data --fix OUT`: a copy of the memory file
+// with the mistakes that have only one sensible correction put right.
+//
+//   Channel Number         "12" -> "00012"
+//   Mode                   "usb" -> "USB", "NFM" -> "FM-N" (see import.rs)
+//   Clarifier Offset (Hz)  "125" -> "130", to the radio's 10 Hz steps
+//
+// Cells are fixed as text, so everything else in the file — comment lines,
+// column order, cells that don't parse — is copied as it is.
+
+use std::io;
+
+use crate::dialect;
+use crate::ftx1::{ClarifierOffsetHz, Mode};
+use crate::import::parse_mode;
+
+/// The corrected value of a cell in `column` (a CSV_COLUMNS name); None
+/// when it's fine or can't be fixed.
+pub fn fix_cell(column: &str, value: &str) -> Option<String> {
+    let trimmed = value.trim();
+    let fixed = match column {
+        "Channel Number" if (1..5).contains(&trimmed.len()) && trimmed.bytes().all(|b| b.is_ascii_digit()) => {
+            format!("{:0>5}", trimmed)
+        }
+        "Mode" if Mode::try_from(trimmed.to_string()).is_err() => parse_mode(trimmed)?.to_string(),
+        "Clarifier Offset (Hz)" => {
+            let hz = trimmed.parse::<i16>().ok()?;
+            ClarifierOffsetHz::try_from(hz).is_err().then(|| ClarifierOffsetHz::rounded(hz).to_i16().to_string())?
+        }
+        _ => return None,
+    };
+    (fixed != value).then_some(fixed)
+}
+
+/// Copies a memory file from `rdr` to `out` with fix_cell applied to
+/// every cell, calling `report(record, column, old, new)` for each fix.
+/// Returns the number of fixes.
+pub fn fix_file<R: io::Read, W: io::Write>(
+    rdr: R,
+    out: W,
+    delimiter: u8,
+    mut report: impl FnMut(usize, &str, &str, &str),
+) -> csv::Result<usize> {
+    // Comment lines are records here too, so they're copied like the rest.
+    let mut rdr = csv::ReaderBuilder::new().delimiter(delimiter).has_headers(false).flexible(true).from_reader(rdr);
+    let mut wtr = csv::WriterBuilder::new().delimiter(delimiter).flexible(true).from_writer(out);
+    let mut columns: Option<Vec<String>> = None;
+    let mut changes = 0;
+    let mut record_number = 0;
+    for result in rdr.records() {
+        let record = result?;
+        let is_comment = record.get(0).is_some_and(|f| f.starts_with('#'));
+        let Some(names) = columns.as_ref().filter(|_| !is_comment) else {
+            if !is_comment {
+                columns = Some(record.iter().map(|h| dialect::canonical_column(h).to_string()).collect());
+            }
+            wtr.write_record(&record)?;
+            continue;
+        };
+        record_number += 1;
+        let mut fixed: Vec<String> = record.iter().map(str::to_string).collect();
+        for (cell, column) in fixed.iter_mut().zip(names) {
+            if let Some(value) = fix_cell(column, cell) {
+                report(record_number, column, cell, &value);
+                *cell = value;
+                changes += 1;
+            }
+        }
+        wtr.write_record(&fixed)?;
+    }
+    wtr.flush()?;
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fix_cell() {
+        assert_eq!(fix_cell("Channel Number", "12").as_deref(), Some("00012"));
+        assert_eq!(fix_cell("Channel Number", "00012"), None);
+        assert_eq!(fix_cell("Channel Number", "P-01L"), None);
+        assert_eq!(fix_cell("Mode", "usb").as_deref(), Some("USB"));
+        assert_eq!(fix_cell("Mode", "NFM").as_deref(), Some("FM-N"));
+        assert_eq!(fix_cell("Mode", "FM"), None);
+        assert_eq!(fix_cell("Mode", "SSTV"), None);
+        assert_eq!(fix_cell("Clarifier Offset (Hz)", "125").as_deref(), Some("130"));
+        assert_eq!(fix_cell("Clarifier Offset (Hz)", "-12345").as_deref(), Some("-9990"));
+        assert_eq!(fix_cell("Clarifier Offset (Hz)", "lots"), None);
+        assert_eq!(fix_cell("Clarifier Offset (Hz)", "12000").as_deref(), Some("9990"));
+        assert_eq!(fix_cell("Clarifier Offset (Hz)", "-120"), None);
+        assert_eq!(fix_cell("Memory Tag", "usb"), None);
+    }
+
+    #[test]
+    fn test_fix_file() {
+        let text = "# ftx1-mm memory file format 2\nChannel Number;Mode;Memory Tag\n12;usb;usb\n00013;FM;x\n";
+        let mut out = Vec::new();
+        let mut fixes = Vec::new();
+        let changes = fix_file(text.as_bytes(), &mut out, b';', |n, column, old, new| {
+            fixes.push(format!("{} {} {} {}", n, column, old, new))
+        })
+        .unwrap();
+        assert_eq!(changes, 2);
+        assert_eq!(fixes, ["1 Channel Number 12 00012", "1 Mode usb USB"]);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "# ftx1-mm memory file format 2\nChannel Number;Mode;Memory Tag\n00012;USB;usb\n00013;FM;x\n"
+        );
+    }
+}
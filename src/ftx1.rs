@@ -1,4 +1,9 @@
 #![allow(dead_code)]
+#![allow(
+    clippy::result_unit_err,
+    clippy::wrong_self_convention,
+    reason = "the protocol API reports bad input as Err(()) and converts through &self throughout"
+)]
 use core::fmt;
 use log::{debug, trace};
 use serde::{Deserialize, Serialize};
@@ -7,7 +12,65 @@ use serde::{Deserialize, Serialize};
 // can use the same implementation. The file `src/parsers.rs` lives next to this file.
 #[path = "parsers.rs"]
 pub mod parsers;
-use parsers::{buf3_to_u8, buf4_to_i16, buf4_to_u16, buf9_to_u32};
+#[path = "menu.rs"]
+pub mod menu;
+use parsers::{buf2_to_u8, buf3_to_u8, buf4_to_i16, buf4_to_u16, buf9_to_u32};
+
+// One string table per enum, `Variant => "name", "label";`, from which
+// serde (the name, as memory files and JSON spell it), Display (the label)
+// and FromStr (either) are all derived, so the spellings can't drift apart.
+// Deserializing also takes the variant's own identifier, which is what the
+// derived serde of older releases wrote.
+macro_rules! string_table {
+    ($ty:ident { $($variant:ident => $name:literal, $label:literal;)* }) => {
+        impl $ty {
+            /// Every variant with its name in files and its label on screen.
+            pub const NAMES: &'static [($ty, &'static str, &'static str)] = &[$(($ty::$variant, $name, $label)),*];
+
+            /// The spelling memory files and JSON use.
+            pub fn name(self) -> &'static str {
+                Self::NAMES.iter().find(|(v, _, _)| *v == self).map_or("", |(_, name, _)| name)
+            }
+        }
+
+        impl fmt::Display for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let label = Self::NAMES.iter().find(|(v, _, _)| v == self).map_or("", |(_, _, label)| label);
+                f.write_str(label)
+            }
+        }
+
+        impl std::str::FromStr for $ty {
+            type Err = ();
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Self::NAMES.iter().find(|(_, name, label)| *name == s || *label == s).map(|(v, _, _)| *v).ok_or(())
+            }
+        }
+
+        impl Serialize for $ty {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.name())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let s = String::deserialize(deserializer)?;
+                match s.parse() {
+                    Ok(v) => Ok(v),
+                    $(Err(()) if s == stringify!($variant) => Ok($ty::$variant),)*
+                    Err(()) => {
+                        let names: Vec<&str> = Self::NAMES.iter().map(|(_, name, _)| *name).collect();
+                        Err(serde::de::Error::custom(format!(
+                            "unknown {} '{}', expected one of {}", stringify!($ty), s, names.join(", ")
+                        )))
+                    }
+                }
+            }
+        }
+    };
+}
 
 //------------------------------------
 // Frequency
@@ -24,13 +87,12 @@ impl FrequencyHz {
     }
 
     /// True when the frequency falls inside the FTX-1's documented stock
-    /// receiver coverage (30 kHz–174 MHz, 400–470 MHz). Used as a soft
-    /// validation check — `--allow-any-frequency` bypasses it so users with
-    /// MARS-CAP-modified radios can program out-of-band channels (SATCOM,
-    /// 174–400 MHz gap, etc.).
+    /// receiver coverage (30 kHz–174 MHz, 400–470 MHz), which every regional
+    /// version shares (see Region). Used as a soft validation check —
+    /// `--allow-any-frequency` bypasses it so users with MARS-CAP-modified
+    /// radios can program out-of-band channels (SATCOM, 174–400 MHz gap, etc.).
     pub fn is_in_radio_range(&self) -> bool {
-        (30_000..174_000_000).contains(&self.value)
-            || (400_000_000..470_000_000).contains(&self.value)
+        Region::default().can_receive(*self)
     }
 }
 
@@ -85,6 +147,97 @@ impl fmt::Display for FrequencyHz {
     }
 }
 
+//------------------------------------
+// Model capabilities
+//------------------------------------
+// The FTX-1 ships in regional versions that receive the same general
+// coverage but transmit only on the amateur bands of their market. Ranges
+// are half-open [start, end) in Hz.
+
+const RX_COVERAGE: &[(u32, u32)] = &[(30_000, 174_000_000), (400_000_000, 470_000_000)];
+
+const TX_USA: &[(u32, u32)] = &[
+    (1_800_000, 2_000_000),
+    (3_500_000, 4_000_000),
+    (5_330_500, 5_406_500),
+    (7_000_000, 7_300_000),
+    (10_100_000, 10_150_000),
+    (14_000_000, 14_350_000),
+    (18_068_000, 18_168_000),
+    (21_000_000, 21_450_000),
+    (24_890_000, 24_990_000),
+    (28_000_000, 29_700_000),
+    (50_000_000, 54_000_000),
+    (144_000_000, 148_000_000),
+    (430_000_000, 450_000_000),
+];
+
+const TX_EXP: &[(u32, u32)] = &[
+    (1_810_000, 2_000_000),
+    (3_500_000, 3_800_000),
+    (5_351_500, 5_366_500),
+    (7_000_000, 7_200_000),
+    (10_100_000, 10_150_000),
+    (14_000_000, 14_350_000),
+    (18_068_000, 18_168_000),
+    (21_000_000, 21_450_000),
+    (24_890_000, 24_990_000),
+    (28_000_000, 29_700_000),
+    (50_000_000, 52_000_000),
+    (144_000_000, 146_000_000),
+    (430_000_000, 440_000_000),
+];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Region {
+    /// US version.
+    #[default]
+    Usa,
+    /// Export version (Europe and most of the rest of the world).
+    Exp,
+}
+
+pub const REGIONS: [Region; 2] = [Region::Usa, Region::Exp];
+
+impl Region {
+    pub fn rx_ranges(self) -> &'static [(u32, u32)] {
+        RX_COVERAGE
+    }
+
+    pub fn tx_ranges(self) -> &'static [(u32, u32)] {
+        match self {
+            Region::Usa => TX_USA,
+            Region::Exp => TX_EXP,
+        }
+    }
+
+    pub fn can_receive(self, freq: FrequencyHz) -> bool {
+        self.rx_ranges().iter().any(|(lo, hi)| (*lo..*hi).contains(&freq.value))
+    }
+
+    pub fn can_transmit(self, freq: FrequencyHz) -> bool {
+        self.tx_ranges().iter().any(|(lo, hi)| (*lo..*hi).contains(&freq.value))
+    }
+}
+
+impl TryFrom<&str> for Region {
+    type Error = ();
+
+    fn try_from(item: &str) -> Result<Self, Self::Error> {
+        REGIONS.into_iter().find(|r| r.to_string().eq_ignore_ascii_case(item)).ok_or(())
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Region::Usa => "USA",
+            Region::Exp => "EXP",
+        };
+        write!(f, "{}", name)
+    }
+}
+
 //------------------------------------
 // Clarifier offset
 //------------------------------------
@@ -94,16 +247,28 @@ pub struct ClarifierOffsetHz {
 }
 
 impl ClarifierOffsetHz {
+    pub const MAX: i16 = 9_990;
+    /// The radio steps the clarifier in 10 Hz; other values are rejected.
+    pub const STEP: i16 = 10;
+
     pub fn to_i16(&self) -> i16 {
         self.value
     }
+
+    /// Rounds `hz` to the nearest 10 Hz step (halves away from zero) and
+    /// clamps it to ±9990.
+    pub fn rounded(hz: i16) -> Self {
+        let steps = (hz as i32 + (hz.signum() as i32) * 5) / Self::STEP as i32;
+        let value = (steps * Self::STEP as i32).clamp(-(Self::MAX as i32), Self::MAX as i32);
+        ClarifierOffsetHz { value: value as i16 }
+    }
 }
 
 impl TryFrom<i16> for ClarifierOffsetHz {
     type Error = ();
 
     fn try_from(item: i16) -> Result<Self, Self::Error> {
-        if item.abs() > 9_990 {
+        if item.abs() > Self::MAX || item % Self::STEP != 0 {
             Err(())
         } else {
             Ok(ClarifierOffsetHz { value: item })
@@ -251,7 +416,7 @@ pub enum MemoryChannel {
     EmergencyChannel,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ChType {
     Vfo = 0x00,
     MemoryChannel = 0x01,
@@ -277,18 +442,14 @@ impl TryFrom<char> for ChType {
     }
 }
 
-impl fmt::Display for ChType {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            ChType::Vfo => write!(f, "VFO"),
-            ChType::MemoryChannel => write!(f, "Memory"),
-            ChType::MemoryTune => write!(f, "MemoryTune"),
-            ChType::Qmb => write!(f, "QMB"),
-            ChType::Reserved4 => write!(f, "Reserved"),
-            ChType::Pms => write!(f, "PMS"),
-        }
-    }
-}
+string_table!(ChType {
+    Vfo => "Vfo", "VFO";
+    MemoryChannel => "MemoryChannel", "Memory";
+    MemoryTune => "MemoryTune", "MemoryTune";
+    Qmb => "Qmb", "QMB";
+    Reserved4 => "Reserved4", "Reserved";
+    Pms => "Pms", "PMS";
+});
 
 impl From<ChType> for char {
     fn from(item: ChType) -> Self {
@@ -303,44 +464,51 @@ impl From<ChType> for char {
     }
 }
 
-impl TryFrom<&[char; 5]> for MemoryChannel {
+impl TryFrom<&[u8]> for MemoryChannel {
     type Error = ();
 
-    fn try_from(item: &[char; 5]) -> Result<Self, Self::Error> {
-        // diagnostic: show the incoming 5-char channel identifier
-        // debug!("DEBUG: MemoryChannel::try_from input: {:?}", item);
+    /// Parses the 5-byte channel field straight out of a reply buffer.
+    fn try_from(item: &[u8]) -> Result<Self, Self::Error> {
         match item {
-            ['0', '0', '0', '0', '0'] => Ok(Self::VfoMtQmb),
-            ['0', _, _, _, _] => {
-                // Memory channel: parse as u16 (00001 - 00999)
-                let ch =
-                    buf4_to_u16(&[item[1] as u8, item[2] as u8, item[3] as u8, item[4] as u8])?;
-                Ok(Self::Mem(ch))
-            }
-            ['P', _, _, _, _] => {
-                // PMS channel: e.g., P-01L, P-50U
-                // Parse slot (positions 2-3) and L/U suffix (position 4)
-                let slot_str = format!("{}{}", item[2], item[3]);
-                let slot = slot_str.parse::<u8>().map_err(|_| ())?;
-                let lower_upper = match item[4] {
-                    'L' => PmsLowerUpper::Lower,
-                    'U' => PmsLowerUpper::Upper,
+            b"00000" => Ok(Self::VfoMtQmb),
+            // Memory channel: 00001 - 00999
+            [b'0', rest @ ..] => Ok(Self::Mem(buf4_to_u16(rest)?)),
+            // PMS channel: e.g., P-01L, P-50U — slot in positions 2-3, L/U suffix in 4
+            [b'P', _, s1, s2, lu] => {
+                let slot = buf2_to_u8(&[*s1, *s2])?;
+                let lower_upper = match lu {
+                    b'L' => PmsLowerUpper::Lower,
+                    b'U' => PmsLowerUpper::Upper,
                     _ => return Err(()),
                 };
                 Ok(Self::Pms(PmsChannel { slot, lower_upper }))
             }
-            ['5', _, _, _, _] => {
-                // 5MHz band: parse as u16 (50001 - 50020)
-                let band =
-                    buf4_to_u16(&[item[1] as u8, item[2] as u8, item[3] as u8, item[4] as u8])?;
-                Ok(Self::FiveMHzBand(band as u8))
+            // 5MHz band: 50001 - 50020
+            [b'5', b'0', b'0', rest @ ..] => {
+                let band = buf2_to_u8(rest)?;
+                if !(1..=20).contains(&band) {
+                    return Err(());
+                }
+                Ok(Self::FiveMHzBand(band))
             }
-            ['E', 'M', 'G', 'C', 'H'] => Ok(Self::EmergencyChannel),
+            b"EMGCH" => Ok(Self::EmergencyChannel),
             _ => Err(()),
         }
     }
 }
 
+impl TryFrom<&[char; 5]> for MemoryChannel {
+    type Error = ();
+
+    fn try_from(item: &[char; 5]) -> Result<Self, Self::Error> {
+        let mut bytes = [0u8; 5];
+        for (b, c) in bytes.iter_mut().zip(item) {
+            *b = u8::try_from(*c).map_err(|_| ())?;
+        }
+        Self::try_from(&bytes[..])
+    }
+}
+
 impl TryFrom<String> for MemoryChannel {
     type Error = ();
 
@@ -348,9 +516,7 @@ impl TryFrom<String> for MemoryChannel {
         if item.len() != 5 {
             return Err(());
         }
-        let chars: Vec<char> = item.chars().collect();
-        let ch_array: [char; 5] = [chars[0], chars[1], chars[2], chars[3], chars[4]];
-        Self::try_from(&ch_array)
+        Self::try_from(item.as_bytes())
     }
 }
 
@@ -372,11 +538,12 @@ impl MemoryChannel {
                 let chars: Vec<char> = s.chars().collect();
                 Ok([chars[0], chars[1], chars[2], chars[3], chars[4]])
             }
-            MemoryChannel::FiveMHzBand(band) => {
-                let h = (band / 10) as char;
-                let l = (band % 10) as char;
+            MemoryChannel::FiveMHzBand(band @ 1..=20) => {
+                let h = char::from(b'0' + band / 10);
+                let l = char::from(b'0' + band % 10);
                 Ok(['5', '0', '0', h, l])
             }
+            MemoryChannel::FiveMHzBand(_) => Err(()),
             MemoryChannel::EmergencyChannel => Ok(['E', 'M', 'G', 'C', 'H']),
         }
     }
@@ -419,7 +586,8 @@ impl fmt::Display for MemoryChannel {
 // value). Reading a channel back will return the direction ARS resolved
 // to, not "Ars". To write a channel as ARS we send OS with P2=3 between
 // MW and AM in the write sequence.
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[allow(clippy::enum_variant_names)]
 pub enum Shift {
     Simplex = 0x00,
     PlusShift = 0x01,
@@ -441,16 +609,12 @@ impl TryFrom<char> for Shift {
     }
 }
 
-impl fmt::Display for Shift {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Shift::Simplex => write!(f, "SIMPLEX"),
-            Shift::PlusShift => write!(f, "PLUS SHIFT"),
-            Shift::MinusShift => write!(f, "MINUS SHIFT"),
-            Shift::Ars => write!(f, "ARS"),
-        }
-    }
-}
+string_table!(Shift {
+    Simplex => "Simplex", "SIMPLEX";
+    PlusShift => "PlusShift", "PLUS SHIFT";
+    MinusShift => "MinusShift", "MINUS SHIFT";
+    Ars => "Ars", "ARS";
+});
 
 impl From<Shift> for char {
     fn from(item: Shift) -> Self {
@@ -479,7 +643,7 @@ impl Shift {
 //------------------------------------
 // SqlType
 //------------------------------------
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum SqlType {
     CtcssOff = 0x00,
     CtcssEncDec = 0x01,
@@ -505,18 +669,14 @@ impl TryFrom<char> for SqlType {
     }
 }
 
-impl fmt::Display for SqlType {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            SqlType::CtcssOff => write!(f, "CTCSS_OFF"),
-            SqlType::CtcssEncDec => write!(f, "CTCSS_ENCDEC"),
-            SqlType::CtcssEnc => write!(f, "CTCSS_ENC"),
-            SqlType::Dcs => write!(f, "DCS"),
-            SqlType::PrFreq => write!(f, "PR FREQ"),
-            SqlType::RevTone => write!(f, "REV TONE"),
-        }
-    }
-}
+string_table!(SqlType {
+    CtcssOff => "CtcssOff", "CTCSS_OFF";
+    CtcssEncDec => "CtcssEncDec", "CTCSS_ENCDEC";
+    CtcssEnc => "CtcssEnc", "CTCSS_ENC";
+    Dcs => "Dcs", "DCS";
+    PrFreq => "PrFreq", "PR FREQ";
+    RevTone => "RevTone", "REV TONE";
+});
 
 impl From<SqlType> for char {
     fn from(item: SqlType) -> Self {
@@ -540,7 +700,7 @@ impl From<SqlType> for char {
 //          broadcast channels — verified empirically against a programmed slot)
 //   1:LSB 2:USB 3:CW-U 4:FM 5:AM 6:RTTY-L 7:CW-L 8:DATA-L 9:RTTY-U
 //   A:DATA-FM B:FM-N C:DATA-U D:AM-N E:PSK F:DATA-FM-N H:C4FM-DN I:C4FM-VW
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Mode {
     Wfm,
     Lsb,
@@ -623,56 +783,32 @@ impl TryFrom<u8> for Mode {
     }
 }
 
-impl fmt::Display for Mode {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Mode::Wfm => write!(f, "W-FM"),
-            Mode::Lsb => write!(f, "LSB"),
-            Mode::Usb => write!(f, "USB"),
-            Mode::CwU => write!(f, "CW-U"),
-            Mode::Fm => write!(f, "FM"),
-            Mode::Am => write!(f, "AM"),
-            Mode::RttyL => write!(f, "RTTY-L"),
-            Mode::CwL => write!(f, "CW-L"),
-            Mode::DataL => write!(f, "DATA-L"),
-            Mode::RttyU => write!(f, "RTTY-U"),
-            Mode::DataFm => write!(f, "DATA-FM"),
-            Mode::FmN => write!(f, "FM-N"),
-            Mode::DataU => write!(f, "DATA-U"),
-            Mode::AmN => write!(f, "AM-N"),
-            Mode::Psk => write!(f, "PSK"),
-            Mode::DataFmN => write!(f, "DATA-FM-N"),
-            Mode::C4fmDn => write!(f, "C4FM-DN"),
-            Mode::C4fmVw => write!(f, "C4FM-VW"),
-        }
-    }
-}
+string_table!(Mode {
+    Wfm => "W-FM", "W-FM";
+    Lsb => "LSB", "LSB";
+    Usb => "USB", "USB";
+    CwU => "CW-U", "CW-U";
+    Fm => "FM", "FM";
+    Am => "AM", "AM";
+    RttyL => "RTTY-L", "RTTY-L";
+    CwL => "CW-L", "CW-L";
+    DataL => "DATA-L", "DATA-L";
+    RttyU => "RTTY-U", "RTTY-U";
+    DataFm => "DATA-FM", "DATA-FM";
+    FmN => "FM-N", "FM-N";
+    DataU => "DATA-U", "DATA-U";
+    AmN => "AM-N", "AM-N";
+    Psk => "PSK", "PSK";
+    DataFmN => "DATA-FM-N", "DATA-FM-N";
+    C4fmDn => "C4FM-DN", "C4FM-DN";
+    C4fmVw => "C4FM-VW", "C4FM-VW";
+});
 
 impl TryFrom<String> for Mode {
     type Error = ();
 
     fn try_from(item: String) -> Result<Self, Self::Error> {
-        match item.as_str() {
-            "W-FM" => Ok(Self::Wfm),
-            "LSB" => Ok(Self::Lsb),
-            "USB" => Ok(Self::Usb),
-            "CW-U" => Ok(Self::CwU),
-            "FM" => Ok(Self::Fm),
-            "AM" => Ok(Self::Am),
-            "RTTY-L" => Ok(Self::RttyL),
-            "CW-L" => Ok(Self::CwL),
-            "DATA-L" => Ok(Self::DataL),
-            "RTTY-U" => Ok(Self::RttyU),
-            "DATA-FM" => Ok(Self::DataFm),
-            "FM-N" => Ok(Self::FmN),
-            "DATA-U" => Ok(Self::DataU),
-            "AM-N" => Ok(Self::AmN),
-            "PSK" => Ok(Self::Psk),
-            "DATA-FM-N" => Ok(Self::DataFmN),
-            "C4FM-DN" => Ok(Self::C4fmDn),
-            "C4FM-VW" => Ok(Self::C4fmVw),
-            _ => Err(()),
-        }
+        item.parse()
     }
 }
 
@@ -680,7 +816,34 @@ impl TryFrom<String> for Mode {
 // Cmd
 //------------------------------------
 
-type CmdError = ();
+/// A reply that didn't decode. It carries the frame as received, so a bug
+/// report from the field can say exactly what the radio sent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodeError {
+    /// The command the reply was decoded as.
+    pub code: [char; 2],
+    /// The frame length that command's reply has (the longest, for replies
+    /// carrying free text), code and terminator included.
+    pub expected_len: usize,
+    pub frame: Vec<u8>,
+    /// The field that failed, or None when the frame itself is wrong: a
+    /// different command, the wrong length or no terminator.
+    pub field: Option<&'static str>,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Can't decode {}{} reply: ", self.code[0], self.code[1])?;
+        match self.field {
+            Some(field) => write!(f, "bad {} field", field)?,
+            None => write!(f, "expected {} bytes, got {}", self.expected_len, self.frame.len())?,
+        }
+        let hex: Vec<String> = self.frame.iter().map(|b| format!("{:02X}", b)).collect();
+        write!(f, " [{}] {:?}", hex.join(" "), String::from_utf8_lossy(&self.frame))
+    }
+}
+
+type CmdError = DecodeError;
 pub struct Cmd<'a> {
     code: &'a [char; 2],
     read_params: usize,
@@ -698,11 +861,22 @@ impl Cmd<'_> {
         tx_vec
     }
 
+    /// The error for a reply to this command; `field` is None when the
+    /// frame as a whole is wrong.
+    fn error(&self, rx_buffer: &[u8], field: Option<&'static str>) -> CmdError {
+        DecodeError { code: *self.code, expected_len: self.read_params + 3, frame: rx_buffer.to_vec(), field }
+    }
+
+    /// Names the field a failed parse of a reply was for.
+    fn field<T>(&self, rx_buffer: &[u8], field: &'static str, value: Result<T, ()>) -> Result<T, CmdError> {
+        value.map_err(|_| self.error(rx_buffer, Some(field)))
+    }
+
     /// Validate received packet from a transceiver.
     /// Returns Ok() if the answer is valid, Error() otherwise.
     fn is_reply_ok(&self, rx_buffer: &[u8]) -> Result<(), CmdError> {
         if rx_buffer.len() < 3 {
-            return Err(());
+            return Err(self.error(rx_buffer, None));
         }
         let code0_ok = rx_buffer.contains(&(self.code[0] as u8));
         let code1_ok = rx_buffer.contains(&(self.code[1] as u8));
@@ -716,13 +890,208 @@ impl Cmd<'_> {
             &terminator_ok,
             rx_buffer.len()
         );
-        (terminator_ok & code0_ok & code1_ok & params_ok).then_some(()).ok_or(())
+        (terminator_ok & code0_ok & code1_ok & params_ok).then_some(()).ok_or_else(|| self.error(rx_buffer, None))
+    }
+
+    /// Like `is_reply_ok`, for replies carrying free text whose length
+    /// varies up to `max_params`.
+    fn is_variable_reply_ok(&self, rx_buffer: &[u8], max_params: usize) -> Result<(), CmdError> {
+        let ok = rx_buffer.len() >= 3
+            && rx_buffer[0] == self.code[0] as u8
+            && rx_buffer[1] == self.code[1] as u8
+            && rx_buffer.last() == Some(&b';')
+            && rx_buffer.len() - 3 <= max_params;
+        trace!("is_variable_reply_ok: {} len: {}", ok, rx_buffer.len());
+        let cmd = Cmd { code: self.code, read_params: max_params };
+        ok.then_some(()).ok_or_else(|| cmd.error(rx_buffer, None))
+    }
+}
+
+/// A fixed-width parameter of a CAT frame, for `cat_command!`.
+pub trait CatField: Sized {
+    /// Characters it takes in the frame.
+    const WIDTH: usize;
+    /// What a `DecodeError` calls it.
+    const NAME: &'static str;
+    fn to_chars(&self) -> Vec<char>;
+    fn from_chars(buffer: &[u8]) -> Result<Self, ()>;
+}
+
+impl CatField for bool {
+    const WIDTH: usize = 1;
+    const NAME: &'static str = "on/off";
+
+    fn to_chars(&self) -> Vec<char> {
+        vec![if *self { '1' } else { '0' }]
+    }
+
+    fn from_chars(buffer: &[u8]) -> Result<Self, ()> {
+        match buffer[0] {
+            b'0' => Ok(false),
+            b'1' => Ok(true),
+            _ => Err(()),
+        }
+    }
+}
+
+impl CatField for Side {
+    const WIDTH: usize = 1;
+    const NAME: &'static str = "side";
+
+    fn to_chars(&self) -> Vec<char> {
+        vec![(*self).into()]
+    }
+
+    fn from_chars(buffer: &[u8]) -> Result<Self, ()> {
+        Side::try_from(buffer[0] as char)
+    }
+}
+
+impl CatField for FrequencyHz {
+    const WIDTH: usize = 9;
+    const NAME: &'static str = "frequency";
+
+    fn to_chars(&self) -> Vec<char> {
+        String::from(*self).chars().collect()
+    }
+
+    fn from_chars(buffer: &[u8]) -> Result<Self, ()> {
+        FrequencyHz::try_from(&buffer[..9])
+    }
+}
+
+// Defines a CAT command from its code and parameter layout:
+//
+//   cat_command!(CmdFs, CMD_FS, 'F' 'S', (side: Side) => on: bool);
+//
+// gives `read(side)` ("FS0;"), `set(side, on)` ("FS01;") and `decode`,
+// which checks the reply's code, length and terminator, parses the keys
+// (the parameters a read sends) and returns the value after them. Each
+// type is a CatField. Commands that only act ("AB;") take just the code and
+// get `set()`. Commands with anything less regular are written out by hand.
+macro_rules! cat_command {
+    ($(#[$meta:meta])* $name:ident, $konst:ident, $c0:literal $c1:literal) => {
+        $(#[$meta])*
+        pub struct $name<'a> {
+            cmd: Cmd<'a>,
+        }
+
+        pub const $konst: $name<'static> = $name { cmd: Cmd { code: &[$c0, $c1], read_params: 0 } };
+
+        impl $name<'_> {
+            pub fn set(&self) -> Vec<u8> {
+                Cmd::tx_buffer(&self.cmd, None)
+            }
+        }
+    };
+    ($(#[$meta:meta])* $name:ident, $konst:ident, $c0:literal $c1:literal, ($($key:ident: $kty:ty),*) => $value:ident: $vty:ty) => {
+        $(#[$meta])*
+        pub struct $name<'a> {
+            cmd: Cmd<'a>,
+        }
+
+        pub const $konst: $name<'static> = $name {
+            cmd: Cmd { code: &[$c0, $c1], read_params: $(<$kty as CatField>::WIDTH +)* <$vty as CatField>::WIDTH },
+        };
+
+        impl $name<'_> {
+            pub fn read(&self $(, $key: $kty)*) -> Vec<u8> {
+                #[allow(unused_mut)]
+                let mut params: Vec<char> = Vec::new();
+                $(params.extend(CatField::to_chars(&$key));)*
+                Cmd::tx_buffer(&self.cmd, Some(params))
+            }
+
+            pub fn set(&self $(, $key: $kty)*, $value: $vty) -> Vec<u8> {
+                #[allow(unused_mut)]
+                let mut params: Vec<char> = Vec::new();
+                $(params.extend(CatField::to_chars(&$key));)*
+                params.extend(CatField::to_chars(&$value));
+                Cmd::tx_buffer(&self.cmd, Some(params))
+            }
+
+            pub fn decode(&self, buffer: &[u8]) -> Result<$vty, DecodeError> {
+                Cmd::is_reply_ok(&self.cmd, buffer)?;
+                #[allow(unused_mut)]
+                let mut at = 2;
+                $(
+                    let _: $kty = self.cmd.field(buffer, <$kty as CatField>::NAME, CatField::from_chars(&buffer[at..]))?;
+                    at += <$kty as CatField>::WIDTH;
+                )*
+                self.cmd.field(buffer, <$vty as CatField>::NAME, CatField::from_chars(&buffer[at..]))
+            }
+        }
+    };
+}
+
+/// The radio's reply to a command it can't execute — e.g. MR/MT for an
+/// unprogrammed channel.
+pub fn is_error_reply(buffer: &[u8]) -> bool {
+    buffer == b"?;"
+}
+
+/// Finds the reply to the query `sent` in `buffer`, skipping anything in
+/// front of it: stale replies left in the UART buffer, or line noise. A
+/// reply echoes the whole query, code and parameters, so each
+/// ';'-terminated frame is searched for the query minus its ';' and any
+/// bytes before it are dropped. A frame that doesn't echo the query, such
+/// as a late reply for the previous channel, is never taken for the reply.
+/// The radio's error reply ("?;") counts only as the last frame in the
+/// buffer, with no reply to the query anywhere in it.
+pub fn find_reply<'b>(sent: &[u8], buffer: &'b [u8]) -> Option<&'b [u8]> {
+    let query = sent.strip_suffix(b";").unwrap_or(sent);
+    if query.len() < 2 {
+        return None;
+    }
+    let mut last = None;
+    for frame in buffer.split_inclusive(|b| *b == b';').filter(|f| f.last() == Some(&b';')) {
+        if let Some(pos) = frame.windows(query.len()).position(|w| w == query) {
+            return Some(&frame[pos..]);
+        }
+        last = Some(frame);
     }
+    last.filter(|f| f.ends_with(b"?;") && !f[..f.len() - 2].windows(2).any(|w| w == &query[..2])).map(|f| &f[f.len() - 2..])
+}
+
+/// True once `buffer` holds a complete reply to the query `sent` (see
+/// find_reply).
+pub fn reply_complete(sent: &[u8], buffer: &[u8]) -> bool {
+    find_reply(sent, buffer).is_some()
+}
+
+/// Splits a buffer holding replies to several pipelined `frames` into one
+/// reply per frame. The radio answers in order, but a command may get no
+/// reply at all, so replies are matched by the query they echo; error
+/// replies ("?;") fill the remaining slots in order. Noise in front of a
+/// reply is dropped as in find_reply. A frame with no reply gets an empty
+/// buffer, which every decoder rejects.
+pub fn match_replies(frames: &[Vec<u8>], buffer: &[u8]) -> Vec<Vec<u8>> {
+    let mut replies: Vec<Vec<u8>> = vec![Vec::new(); frames.len()];
+    let mut errors: Vec<Vec<u8>> = Vec::new();
+    for chunk in buffer.split_inclusive(|b| *b == b';').filter(|r| r.last() == Some(&b';')) {
+        let slot = frames.iter().zip(&replies).enumerate().filter(|(_, (_, r))| r.is_empty()).find_map(|(i, (f, _))| {
+            find_reply(f, chunk).filter(|reply| !is_error_reply(reply)).map(|reply| (i, reply))
+        });
+        match slot {
+            Some((i, reply)) => replies[i] = reply.to_vec(),
+            None if chunk.ends_with(b"?;") => errors.push(b"?;".to_vec()),
+            None => {}
+        }
+    }
+    for (reply, error) in replies.iter_mut().filter(|r| r.is_empty()).zip(errors) {
+        *reply = error;
+    }
+    replies
 }
 
 //------------------------------------
 // VM - VFO/MEMORY MODE
+//   Set : VM p mm ;
+//   Read: VM p ;    → VM p mm ;
+//     p  — side (0 = MAIN, 1 = SUB)
+//     mm — mode, see `VmMode`
 //------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum VmMode {
     Vfo,
     Mt,
@@ -747,6 +1116,23 @@ impl From<VmMode> for &'static str {
     }
 }
 
+impl TryFrom<&[u8]> for VmMode {
+    type Error = ();
+
+    fn try_from(item: &[u8]) -> Result<Self, Self::Error> {
+        match item {
+            b"00" => Ok(VmMode::Vfo),
+            b"10" => Ok(VmMode::Mt),
+            b"11" => Ok(VmMode::Memory),
+            b"20" => Ok(VmMode::Pms),
+            b"21" => Ok(VmMode::PmsEdge),
+            b"51" => Ok(VmMode::BandMemory5Mhz),
+            b"91" => Ok(VmMode::Emg),
+            _ => Err(()),
+        }
+    }
+}
+
 pub struct CmdVm<'a> {
     cmd: Cmd<'a>,
 }
@@ -760,24 +1146,32 @@ impl CmdVm<'_> {
         let s: Vec<char> = format!("{}{}", side, mode).chars().collect();
         Cmd::tx_buffer(&self.cmd, Some(s))
     }
-}
 
-//------------------------------------
-// FA - VFO-A FREQUENCY
-//------------------------------------
-pub struct CmdFa<'a> {
-    cmd: Cmd<'a>,
-}
+    pub fn read(&self, side: Side) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, Some(vec![side.into()]))
+    }
 
-pub const CMD_FA: CmdFa<'static> = CmdFa { cmd: Cmd { code: &['F', 'A'], read_params: 0 } };
+    pub fn decode(&self, buffer: &[u8]) -> Result<VmMode, DecodeError> {
+        let cmd = Cmd { code: self.cmd.code, read_params: 3 };
+        Cmd::is_reply_ok(&cmd, buffer)?;
+        cmd.field(buffer, "memory/VFO mode", VmMode::try_from(&buffer[3..5]))
+    }
 
-impl CmdFa<'_> {
-    pub fn set(&self, freq: FrequencyHz) -> Vec<u8> {
-        let s: String = freq.into();
-        Cmd::tx_buffer(&self.cmd, Some(s.chars().collect()))
+    /// Frame that flips between VFO and memory: anything that isn't plain
+    /// VFO (memory, MT, PMS, ...) goes back to VFO.
+    pub fn toggle(&self, side: Side, current: VmMode) -> Vec<u8> {
+        let next = if current == VmMode::Vfo { VmMode::Memory } else { VmMode::Vfo };
+        self.set(side, next)
     }
 }
 
+//------------------------------------
+// FA - VFO-A FREQUENCY
+//   Set : FA fffffffff ;
+//   Read: FA ;          → FA fffffffff ;
+//------------------------------------
+cat_command!(CmdFa, CMD_FA, 'F' 'A', () => freq: FrequencyHz);
+
 //------------------------------------
 // AM - MAIN-SIDE TO MEMORY CHANNEL
 //------------------------------------
@@ -808,6 +1202,17 @@ impl CmdMd<'_> {
         let mode: char = mode.into();
         Cmd::tx_buffer(&self.cmd, Some(vec![side, mode]))
     }
+
+    pub fn read(&self, side: Side) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, Some(vec![side.into()]))
+    }
+
+    /// Reply: MD p m ;
+    pub fn decode(&self, buffer: &[u8]) -> Result<Mode, DecodeError> {
+        let cmd = Cmd { code: self.cmd.code, read_params: 2 };
+        Cmd::is_reply_ok(&cmd, buffer)?;
+        cmd.field(buffer, "mode", Mode::try_from(buffer[3] as char))
+    }
 }
 
 //------------------------------------
@@ -875,9 +1280,9 @@ impl CmdId<'_> {
         Cmd::tx_buffer(&self.cmd, None)
     }
 
-    pub fn decode(&self, buffer: &[u8]) -> Result<u16, ()> {
+    pub fn decode(&self, buffer: &[u8]) -> Result<u16, DecodeError> {
         Cmd::is_reply_ok(&self.cmd, buffer)?;
-        let id = buf4_to_u16(&buffer[2..6])?;
+        let id = self.cmd.field(buffer, "radio ID", buf4_to_u16(&buffer[2..6]))?;
         Ok(id)
     }
 
@@ -890,6 +1295,55 @@ impl CmdId<'_> {
     }
 }
 
+//------------------------------------
+// VE - FIRMWARE VERSION
+//   Read: VE u ;  → VE u vvvv ;
+//     u    — unit: 0 = MAIN
+//     vvvv — version × 100, e.g. 0108 = 1.08
+// The CAT manual asks for MAIN firmware 1.08 or later; older firmware
+// may not answer VE at all.
+//------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FirmwareVersion {
+    hundredths: u16,
+}
+
+impl FirmwareVersion {
+    pub const fn new(major: u16, minor: u16) -> Self {
+        FirmwareVersion { hundredths: major * 100 + minor }
+    }
+}
+
+impl fmt::Display for FirmwareVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{:02}", self.hundredths / 100, self.hundredths % 100)
+    }
+}
+
+/// The oldest MAIN firmware the CAT manual supports.
+pub const MIN_CAT_FIRMWARE: FirmwareVersion = FirmwareVersion::new(1, 8);
+
+pub struct CmdVe<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_VE: CmdVe<'static> = CmdVe { cmd: Cmd { code: &['V', 'E'], read_params: 5 } };
+
+impl CmdVe<'_> {
+    /// The MAIN unit's version.
+    pub fn read(&self) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, Some(vec!['0']))
+    }
+
+    pub fn decode(&self, buffer: &[u8]) -> Result<FirmwareVersion, DecodeError> {
+        Cmd::is_reply_ok(&self.cmd, buffer)?;
+        if buffer[2] != b'0' {
+            return Err(self.cmd.error(buffer, Some("CPU")));
+        }
+        Ok(FirmwareVersion { hundredths: self.cmd.field(buffer, "version", buf4_to_u16(&buffer[3..7]))? })
+    }
+}
+
 //------------------------------------
 // MR - MEMORY CHANNEL READ
 //------------------------------------
@@ -934,27 +1388,21 @@ impl CmdMr<'_> {
         Cmd::tx_buffer(&self.cmd, Some(s.to_vec()))
     }
 
-    pub fn decode(&self, buffer: &[u8]) -> Result<MemoryReadWrite, ()> {
+    pub fn decode(&self, buffer: &[u8]) -> Result<MemoryReadWrite, DecodeError> {
         // MR00001007000000+000000110000;
         let mut mr = MemoryReadWrite::default();
         Cmd::is_reply_ok(&self.cmd, buffer)?;
-        let ch_chars: [char; 5] = [
-            buffer[2] as char,
-            buffer[3] as char,
-            buffer[4] as char,
-            buffer[5] as char,
-            buffer[6] as char,
-        ];
-        mr.channel = MemoryChannel::try_from(&ch_chars)?;
-        mr.frequency_hz = FrequencyHz::try_from(&buffer[7..16])?;
-        mr.clarifier_offset_hz = ClarifierOffsetHz::try_from(&buffer[16..21])?;
-        mr.rx_clarifier_enabled = RxClarifierOnOff::try_from(buffer[21] as char)?;
-        mr.tx_clarifier_enabled = TxClarifierOnOff::try_from(buffer[22] as char)?;
-        mr.mode = Mode::try_from(buffer[23] as char)?;
-        mr.ch_type = ChType::try_from(buffer[24] as char)?;
-        mr.sql_type = SqlType::try_from(buffer[25] as char)?;
+        let cmd = &self.cmd;
+        mr.channel = cmd.field(buffer, "channel", MemoryChannel::try_from(&buffer[2..7]))?;
+        mr.frequency_hz = cmd.field(buffer, "frequency", FrequencyHz::try_from(&buffer[7..16]))?;
+        mr.clarifier_offset_hz = cmd.field(buffer, "clarifier offset", ClarifierOffsetHz::try_from(&buffer[16..21]))?;
+        mr.rx_clarifier_enabled = cmd.field(buffer, "RX clarifier", RxClarifierOnOff::try_from(buffer[21] as char))?;
+        mr.tx_clarifier_enabled = cmd.field(buffer, "TX clarifier", TxClarifierOnOff::try_from(buffer[22] as char))?;
+        mr.mode = cmd.field(buffer, "mode", Mode::try_from(buffer[23] as char))?;
+        mr.ch_type = cmd.field(buffer, "channel type", ChType::try_from(buffer[24] as char))?;
+        mr.sql_type = cmd.field(buffer, "squelch type", SqlType::try_from(buffer[25] as char))?;
         let _dummy = buffer[26] | buffer[27];
-        mr.shift = Shift::try_from(buffer[28] as char)?;
+        mr.shift = cmd.field(buffer, "shift", Shift::try_from(buffer[28] as char))?;
 
         Ok(mr)
     }
@@ -981,6 +1429,21 @@ impl fmt::Display for MemoryReadWrite {
 //------------------------------------
 // MT - MEMORY CHANNEL TAG WRITE
 //------------------------------------
+// Tags are 12 printable ASCII characters, space padded. ';' would end the
+// frame early, so it's not allowed either.
+pub const TAG_LEN: usize = 12;
+
+pub fn is_tag_char(c: char) -> bool {
+    (' '..='~').contains(&c) && c != ';'
+}
+
+/// Makes any string a storable tag: drops characters the radio can't
+/// store, truncates to 12 characters and pads with spaces.
+pub fn fix_tag(tag: &str) -> String {
+    let kept: String = tag.chars().filter(|c| is_tag_char(*c)).take(TAG_LEN).collect();
+    format!("{:<width$}", kept, width = TAG_LEN)
+}
+
 pub struct CmdMt<'a> {
     cmd: Cmd<'a>,
 }
@@ -993,20 +1456,16 @@ impl CmdMt<'_> {
         Cmd::tx_buffer(&self.cmd, Some(s.to_vec()))
     }
 
-    pub fn decode(&self, buffer: &[u8]) -> Result<String, ()> {
+    /// Borrows the 12-character tag from the reply, trailing padding included.
+    pub fn decode<'b>(&self, buffer: &'b [u8]) -> Result<&'b str, DecodeError> {
         Cmd::is_reply_ok(&self.cmd, buffer)?;
-        let _channel = &buffer[2..6];
-        let tag = buffer[7..19].iter().map(|&b| b as char).collect();
-        Ok(tag)
+        self.cmd.field(buffer, "tag", core::str::from_utf8(&buffer[7..19]).map_err(|_| ()))
     }
 
     pub fn set(&self, ch: MemoryChannel, tag: String) -> Result<Vec<u8>, ()> {
         let mut buffer = Vec::<char>::new();
         buffer.append(ch.to_chars().unwrap().to_vec().as_mut());
-        // Format tag to exactly 12 characters, padding with spaces if shorter, truncating if longer
-        let formatted_tag: String =
-            if tag.len() > 12 { tag.chars().take(12).collect() } else { format!("{: <12}", tag) };
-        buffer.append(formatted_tag.chars().collect::<Vec<char>>().as_mut());
+        buffer.extend(fix_tag(&tag).chars());
         Ok(Cmd::tx_buffer(&self.cmd, Some(buffer)))
     }
 }
@@ -1014,7 +1473,7 @@ impl CmdMt<'_> {
 //------------------------------------
 // MC - MEMORY CHANNEL
 //------------------------------------
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Side {
     Main = 0,
     Sub = 1,
@@ -1042,6 +1501,28 @@ impl From<Side> for char {
     }
 }
 
+impl TryFrom<&str> for Side {
+    type Error = ();
+
+    /// User-facing side names: main/sub, or a/b for the VFO letters.
+    fn try_from(item: &str) -> Result<Self, Self::Error> {
+        match item.trim().to_ascii_lowercase().as_str() {
+            "main" | "a" => Ok(Side::Main),
+            "sub" | "b" => Ok(Side::Sub),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Side::Main => write!(f, "MAIN"),
+            Side::Sub => write!(f, "SUB"),
+        }
+    }
+}
+
 pub struct McReply {
     pub side: Side,
     pub channel: MemoryChannel,
@@ -1065,17 +1546,10 @@ impl CmdMc<'_> {
         Cmd::tx_buffer(&self.cmd, Some(tx))
     }
 
-    pub fn decode(&self, buffer: &[u8]) -> Result<McReply, ()> {
+    pub fn decode(&self, buffer: &[u8]) -> Result<McReply, DecodeError> {
         Cmd::is_reply_ok(&self.cmd, buffer)?;
-        let side = Side::try_from(buffer[2] as char)?;
-        let ch: [char; 5] = [
-            buffer[3] as char,
-            buffer[4] as char,
-            buffer[5] as char,
-            buffer[6] as char,
-            buffer[7] as char,
-        ];
-        let channel = MemoryChannel::try_from(&ch).unwrap();
+        let side = self.cmd.field(buffer, "side", Side::try_from(buffer[2] as char))?;
+        let channel = self.cmd.field(buffer, "channel", MemoryChannel::try_from(&buffer[3..8]))?;
         Ok(McReply { side, channel })
     }
 }
@@ -1172,19 +1646,15 @@ impl CmdMz<'_> {
         Cmd::tx_buffer(&self.cmd, Some(s.to_vec()))
     }
 
-    pub fn decode(&self, buffer: &[u8]) -> Result<MzReply, ()> {
+    pub fn decode(&self, buffer: &[u8]) -> Result<MzReply, DecodeError> {
         Cmd::is_reply_ok(&self.cmd, buffer)?;
-        let chars: [char; 5] = [
-            buffer[2] as char, buffer[3] as char, buffer[4] as char,
-            buffer[5] as char, buffer[6] as char,
-        ];
-        let channel = MemoryChannel::try_from(&chars)?;
+        let channel = self.cmd.field(buffer, "channel", MemoryChannel::try_from(&buffer[2..7]))?;
         let split_on = match buffer[7] as char {
             '0' => false,
             '1' => true,
-            _ => return Err(()),
+            _ => return Err(self.cmd.error(buffer, Some("split"))),
         };
-        let tx_frequency_hz = FrequencyHz::try_from(&buffer[8..17])?;
+        let tx_frequency_hz = self.cmd.field(buffer, "TX frequency", FrequencyHz::try_from(&buffer[8..17]))?;
         Ok(MzReply { channel, split_on, tx_frequency_hz })
     }
 
@@ -1327,13 +1797,13 @@ impl CmdCn<'_> {
         Cmd::tx_buffer(&self.cmd, Some(s.chars().collect::<Vec<char>>()))
     }
 
-    pub fn decode(&self, buffer: &[u8]) -> Result<CnReply, ()> {
+    pub fn decode(&self, buffer: &[u8]) -> Result<CnReply, DecodeError> {
         Cmd::is_reply_ok(&self.cmd, buffer)?;
-        let side = Side::try_from(buffer[2] as char)?;
+        let side = self.cmd.field(buffer, "side", Side::try_from(buffer[2] as char))?;
         trace!("side: {:?}", side);
-        let tone_type = ToneType::try_from(buffer[3] as char)?;
+        let tone_type = self.cmd.field(buffer, "tone type", ToneType::try_from(buffer[3] as char))?;
         trace!("tone_type: {:?}", tone_type);
-        let tone_code = buf3_to_u8(&buffer[4..7])?;
+        let tone_code = self.cmd.field(buffer, "tone code", buf3_to_u8(&buffer[4..7]))?;
         trace!("tone_code: {:?}", tone_code);
         Ok(CnReply { side, tone_type, tone_code })
     }
@@ -1376,28 +1846,1852 @@ impl CmdCn<'_> {
 }
 
 //------------------------------------
-// TESTS
+// KP - KEY PITCH
+//
+// CW pitch (sidetone and receive offset). The wire value is a step
+// index, not Hz:
+//   Set : KP nn ;
+//   Read: KP ;     → KP nn ;
+//     nn — 00 = 300 Hz … 75 = 1050 Hz, 10 Hz per step
 //------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyPitchHz {
+    value: u16,
+}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_memory_channel_to_chars() {
-        // VFO
-        assert_eq!(MemoryChannel::VfoMtQmb.to_chars().unwrap(), ['0', '0', '0', '0', '0']);
-
-        // Memory Channel
-        assert_eq!(MemoryChannel::Mem(1).to_chars().unwrap(), ['0', '0', '0', '0', '1']);
-        assert_eq!(MemoryChannel::Mem(123).to_chars().unwrap(), ['0', '0', '1', '2', '3']);
+impl KeyPitchHz {
+    const MIN: u16 = 300;
+    const MAX: u16 = 1050;
+    const STEP: u16 = 10;
 
-        // PMS
-        let pms_l = PmsChannel { slot: 1, lower_upper: PmsLowerUpper::Lower };
-        assert_eq!(MemoryChannel::Pms(pms_l).to_chars().unwrap(), ['P', '-', '0', '1', 'L']);
+    pub fn to_u16(self) -> u16 {
+        self.value
+    }
+}
 
-        let pms_u = PmsChannel { slot: 50, lower_upper: PmsLowerUpper::Upper };
-        assert_eq!(MemoryChannel::Pms(pms_u).to_chars().unwrap(), ['P', '-', '5', '0', 'U']);
+impl TryFrom<u16> for KeyPitchHz {
+    type Error = ();
+
+    fn try_from(item: u16) -> Result<Self, Self::Error> {
+        if (Self::MIN..=Self::MAX).contains(&item) && item.is_multiple_of(Self::STEP) {
+            Ok(KeyPitchHz { value: item })
+        } else {
+            Err(())
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for KeyPitchHz {
+    type Error = ();
+
+    fn try_from(item: &[u8]) -> Result<Self, Self::Error> {
+        let index = buf2_to_u8(item)? as u16;
+        KeyPitchHz::try_from(KeyPitchHz::MIN + index * KeyPitchHz::STEP)
+    }
+}
+
+impl fmt::Display for KeyPitchHz {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+pub struct CmdKp<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_KP: CmdKp<'static> = CmdKp { cmd: Cmd { code: &['K', 'P'], read_params: 2 } };
+
+impl CmdKp<'_> {
+    pub fn read(&self) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, None)
+    }
+
+    pub fn set(&self, pitch: KeyPitchHz) -> Vec<u8> {
+        let index = (pitch.value - KeyPitchHz::MIN) / KeyPitchHz::STEP;
+        let s = format!("{:02}", index);
+        Cmd::tx_buffer(&self.cmd, Some(s.chars().collect()))
+    }
+
+    pub fn decode(&self, buffer: &[u8]) -> Result<KeyPitchHz, DecodeError> {
+        Cmd::is_reply_ok(&self.cmd, buffer)?;
+        self.cmd.field(buffer, "pitch", KeyPitchHz::try_from(&buffer[2..4]))
+    }
+}
+
+//------------------------------------
+// AI - AUTO INFORMATION
+//
+// While on, the radio sends the answer to a read (FA, FB, MD, ...)
+// unasked whenever that setting changes on the radio; --monitor uses it.
+//   Set : AI p ;
+//   Read: AI ;     → AI p ;
+//     p — '0' = OFF, '1' = ON
+//------------------------------------
+cat_command!(CmdAi, CMD_AI, 'A' 'I', () => on: bool);
+
+//------------------------------------
+// BI - BREAK-IN
+//   Set : BI p ;
+//   Read: BI ;     → BI p ;
+//     p — '0' = OFF, '1' = ON
+//------------------------------------
+cat_command!(CmdBi, CMD_BI, 'B' 'I', () => on: bool);
+
+//------------------------------------
+// SD - CW BREAK-IN DELAY
+//
+// Semi break-in hang time. With BI on and a short delay the radio behaves
+// close to full QSK; a longer delay gives classic semi break-in.
+//   Set : SD nnnn ;
+//   Read: SD ;       → SD nnnn ;
+//     nnnn — delay in ms, 0030–3000
+//------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BreakInDelayMs {
+    value: u16,
+}
+
+impl BreakInDelayMs {
+    pub fn to_u16(self) -> u16 {
+        self.value
+    }
+}
+
+impl TryFrom<u16> for BreakInDelayMs {
+    type Error = ();
+
+    fn try_from(item: u16) -> Result<Self, Self::Error> {
+        if (30..=3000).contains(&item) { Ok(BreakInDelayMs { value: item }) } else { Err(()) }
+    }
+}
+
+impl TryFrom<&[u8]> for BreakInDelayMs {
+    type Error = ();
+
+    fn try_from(item: &[u8]) -> Result<Self, Self::Error> {
+        BreakInDelayMs::try_from(buf4_to_u16(item)?)
+    }
+}
+
+impl fmt::Display for BreakInDelayMs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+pub struct CmdSd<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_SD: CmdSd<'static> = CmdSd { cmd: Cmd { code: &['S', 'D'], read_params: 4 } };
+
+impl CmdSd<'_> {
+    pub fn read(&self) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, None)
+    }
+
+    pub fn set(&self, delay: BreakInDelayMs) -> Vec<u8> {
+        let s = format!("{:04}", delay.value);
+        Cmd::tx_buffer(&self.cmd, Some(s.chars().collect()))
+    }
+
+    pub fn decode(&self, buffer: &[u8]) -> Result<BreakInDelayMs, DecodeError> {
+        Cmd::is_reply_ok(&self.cmd, buffer)?;
+        self.cmd.field(buffer, "delay", BreakInDelayMs::try_from(&buffer[2..6]))
+    }
+}
+
+//------------------------------------
+// CO - CONTOUR / APF
+//
+// Only the audio peak filter (APF) functions are used: a narrow audio
+// peak for CW, switched on and off and moved around the CW pitch.
+//   Set : CO p f vvvv ;
+//   Read: CO p f ;      → CO p f vvvv ;
+//     p    — side (0 = MAIN, 1 = SUB)
+//     f    — function: 2 = APF on/off, 3 = APF offset
+//     vvvv — on/off: 0000 / 0001
+//            offset: 0000–0050, -250…+250 Hz in 10 Hz steps (0025 = 0 Hz)
+//------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApfOffsetHz {
+    value: i16,
+}
+
+impl ApfOffsetHz {
+    pub fn to_i16(&self) -> i16 {
+        self.value
+    }
+}
+
+impl TryFrom<i16> for ApfOffsetHz {
+    type Error = ();
+
+    fn try_from(item: i16) -> Result<Self, Self::Error> {
+        if (-250..=250).contains(&item) && item % 10 == 0 { Ok(ApfOffsetHz { value: item }) } else { Err(()) }
+    }
+}
+
+impl fmt::Display for ApfOffsetHz {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:+}", self.value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApfFunction {
+    OnOff,
+    Offset,
+}
+
+impl From<ApfFunction> for char {
+    fn from(item: ApfFunction) -> Self {
+        match item {
+            ApfFunction::OnOff => '2',
+            ApfFunction::Offset => '3',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApfParam {
+    OnOff(bool),
+    Offset(ApfOffsetHz),
+}
+
+pub struct CmdCo<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_CO: CmdCo<'static> = CmdCo { cmd: Cmd { code: &['C', 'O'], read_params: 6 } };
+
+impl CmdCo<'_> {
+    pub fn read(&self, side: Side, function: ApfFunction) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, Some(vec![side.into(), function.into()]))
+    }
+
+    pub fn set(&self, side: Side, param: ApfParam) -> Vec<u8> {
+        let (function, value) = match param {
+            ApfParam::OnOff(on) => (ApfFunction::OnOff, u16::from(on)),
+            ApfParam::Offset(o) => (ApfFunction::Offset, (o.value / 10 + 25) as u16),
+        };
+        let side: char = side.into();
+        let function: char = function.into();
+        Cmd::tx_buffer(&self.cmd, Some(format!("{}{}{:04}", side, function, value).chars().collect()))
+    }
+
+    pub fn decode(&self, buffer: &[u8]) -> Result<ApfParam, DecodeError> {
+        Cmd::is_reply_ok(&self.cmd, buffer)?;
+        self.cmd.field(buffer, "side", Side::try_from(buffer[2] as char))?;
+        let value = self.cmd.field(buffer, "value", buf4_to_u16(&buffer[4..8]))?;
+        match (buffer[3], value) {
+            (b'2', 0) => Ok(ApfParam::OnOff(false)),
+            (b'2', 1) => Ok(ApfParam::OnOff(true)),
+            (b'3', 0..=50) => Ok(ApfParam::Offset(self.cmd.field(buffer, "value", ApfOffsetHz::try_from((value as i16 - 25) * 10))?)),
+            (b'2' | b'3', _) => Err(self.cmd.error(buffer, Some("value"))),
+            _ => Err(self.cmd.error(buffer, Some("function"))),
+        }
+    }
+
+    /// Decodes a reply to `read(side, ApfFunction::OnOff)`.
+    pub fn decode_on_off(&self, buffer: &[u8]) -> Result<bool, DecodeError> {
+        match self.decode(buffer)? {
+            ApfParam::OnOff(on) => Ok(on),
+            ApfParam::Offset(_) => Err(self.cmd.error(buffer, Some("function"))),
+        }
+    }
+
+    /// Decodes a reply to `read(side, ApfFunction::Offset)`.
+    pub fn decode_offset(&self, buffer: &[u8]) -> Result<ApfOffsetHz, DecodeError> {
+        match self.decode(buffer)? {
+            ApfParam::Offset(offset) => Ok(offset),
+            ApfParam::OnOff(_) => Err(self.cmd.error(buffer, Some("function"))),
+        }
+    }
+}
+
+//------------------------------------
+// KS - KEY SPEED
+//   Set : KS nnn ;
+//   Read: KS ;      → KS nnn ;
+//     nnn — keyer speed in WPM, 004–060
+//------------------------------------
+pub struct CmdKs<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_KS: CmdKs<'static> = CmdKs { cmd: Cmd { code: &['K', 'S'], read_params: 3 } };
+
+impl CmdKs<'_> {
+    pub fn read(&self) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, None)
+    }
+
+    pub fn set(&self, wpm: u8) -> Result<Vec<u8>, ()> {
+        if !(4..=60).contains(&wpm) {
+            return Err(());
+        }
+        let s = format!("{:03}", wpm);
+        Ok(Cmd::tx_buffer(&self.cmd, Some(s.chars().collect())))
+    }
+
+    pub fn decode(&self, buffer: &[u8]) -> Result<u8, DecodeError> {
+        Cmd::is_reply_ok(&self.cmd, buffer)?;
+        let wpm = self.cmd.field(buffer, "speed", buf3_to_u8(&buffer[2..5]))?;
+        if (4..=60).contains(&wpm) { Ok(wpm) } else { Err(self.cmd.error(buffer, Some("speed"))) }
+    }
+}
+
+//------------------------------------
+// KY - CW KEYING
+//
+// Sends free text through the radio's internal keyer. One frame carries
+// at most KY_MAX_CHARS characters, so longer messages are split on word
+// boundaries by `CmdKy::segments` and sent one frame at a time.
+//   Set : KY 0 <text> ;
+//     0    — fixed
+//     text — A–Z, 0–9, space and the keyer's punctuation set
+//------------------------------------
+pub const KY_MAX_CHARS: usize = 24;
+
+const KY_PUNCTUATION: &[char] = &['/', '?', '.', ',', '=', '+', '-', '(', ')', '\'', ':', '"', '@'];
+
+/// True for characters the internal keyer can send (after upper-casing).
+pub fn is_cw_char(c: char) -> bool {
+    c.is_ascii_uppercase() || c.is_ascii_digit() || c == ' ' || KY_PUNCTUATION.contains(&c)
+}
+
+pub struct CmdKy<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_KY: CmdKy<'static> = CmdKy { cmd: Cmd { code: &['K', 'Y'], read_params: 0 } };
+
+impl CmdKy<'_> {
+    /// Upper-cases `text`, checks every character is keyable and splits it
+    /// into frames of at most KY_MAX_CHARS. Runs of whitespace collapse to a
+    /// single word gap; words longer than a frame are hard-split.
+    pub fn segments(text: &str) -> Result<Vec<String>, ()> {
+        let text = text.to_ascii_uppercase();
+        if !text.chars().all(|c| c.is_whitespace() || is_cw_char(c)) {
+            return Err(());
+        }
+        let mut segments: Vec<String> = Vec::new();
+        let mut current = String::new();
+        for word in text.split_whitespace() {
+            let mut word = word;
+            while word.len() > KY_MAX_CHARS {
+                if !current.is_empty() {
+                    segments.push(std::mem::take(&mut current));
+                }
+                segments.push(word[..KY_MAX_CHARS].to_string());
+                word = &word[KY_MAX_CHARS..];
+            }
+            let needed = if current.is_empty() { word.len() } else { current.len() + 1 + word.len() };
+            if needed > KY_MAX_CHARS {
+                segments.push(std::mem::take(&mut current));
+            }
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(word);
+        }
+        if !current.is_empty() {
+            segments.push(current);
+        }
+        Ok(segments)
+    }
+
+    pub fn set(&self, segment: &str) -> Result<Vec<u8>, ()> {
+        if segment.is_empty() || segment.len() > KY_MAX_CHARS {
+            return Err(());
+        }
+        let mut buffer = vec!['0'];
+        buffer.extend(segment.chars());
+        Ok(Cmd::tx_buffer(&self.cmd, Some(buffer)))
+    }
+
+    /// Rough time the keyer needs to send `segment` at `wpm`, using the
+    /// PARIS convention (50 dot units per 5-letter word plus gap).
+    pub fn duration_ms(segment: &str, wpm: u8) -> u64 {
+        let units = (segment.len() as u64 + 1) * 50 / 6;
+        units * 1200 / wpm.max(1) as u64
+    }
+}
+
+//------------------------------------
+// KM - KEYER MEMORY
+//
+// The five CW message memories played back from the keyer. Replies are
+// variable length — the radio returns only the stored text.
+//   Set : KM n <text> ;
+//   Read: KM n ;        → KM n <text> ;
+//     n    — memory slot, 1–5
+//     text — up to KM_MAX_CHARS keyable characters
+//------------------------------------
+pub const KM_MAX_CHARS: usize = 50;
+pub const KEYER_MEMORIES: u8 = 5;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct KmReply {
+    pub slot: u8,
+    pub text: String,
+}
+
+pub struct CmdKm<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_KM: CmdKm<'static> = CmdKm { cmd: Cmd { code: &['K', 'M'], read_params: 0 } };
+
+impl CmdKm<'_> {
+    fn slot_char(slot: u8) -> Result<char, ()> {
+        if (1..=KEYER_MEMORIES).contains(&slot) { Ok((b'0' + slot) as char) } else { Err(()) }
+    }
+
+    pub fn read(&self, slot: u8) -> Result<Vec<u8>, ()> {
+        Ok(Cmd::tx_buffer(&self.cmd, Some(vec![Self::slot_char(slot)?])))
+    }
+
+    pub fn set(&self, slot: u8, text: &str) -> Result<Vec<u8>, ()> {
+        let text = text.trim_end().to_ascii_uppercase();
+        if text.len() > KM_MAX_CHARS || !text.chars().all(is_cw_char) {
+            return Err(());
+        }
+        let mut buffer = vec![Self::slot_char(slot)?];
+        if text.is_empty() {
+            // "KMn;" is the read form, so clear a memory with a lone space.
+            buffer.push(' ');
+        }
+        buffer.extend(text.chars());
+        Ok(Cmd::tx_buffer(&self.cmd, Some(buffer)))
+    }
+
+    pub fn decode(&self, buffer: &[u8]) -> Result<KmReply, DecodeError> {
+        Cmd::is_variable_reply_ok(&self.cmd, buffer, KM_MAX_CHARS + 1)?;
+        let cmd = Cmd { code: self.cmd.code, read_params: KM_MAX_CHARS + 1 };
+        let slot = cmd.field(buffer, "slot", (buffer[2] as char).to_digit(10).ok_or(()))? as u8;
+        cmd.field(buffer, "slot", Self::slot_char(slot))?;
+        let text = cmd.field(buffer, "text", core::str::from_utf8(&buffer[3..buffer.len() - 1]).map_err(|_| ()))?;
+        Ok(KmReply { slot, text: text.trim_end().to_string() })
+    }
+}
+
+//------------------------------------
+// PB - VOICE MEMORY PLAYBACK (DVS)
+// LM - VOICE MEMORY RECORD (DVS)
+//
+// The five voice-keyer slots. Playback keys the transmitter; recording
+// takes audio from the mic until stopped (or the slot's 20 s run out).
+//   Set : PB 0 n ;  LM 0 n ;
+//   Read: PB 0 ;    → PB 0 n ;   (likewise LM)
+//     n — slot 1–5 to start, 0 to stop; read: the slot playing
+//         (recording), 0 when idle
+//------------------------------------
+pub const VOICE_MEMORIES: u8 = 5;
+
+fn voice_slot_char(slot: u8) -> Result<char, ()> {
+    if (1..=VOICE_MEMORIES).contains(&slot) { Ok((b'0' + slot) as char) } else { Err(()) }
+}
+
+/// Decodes a PB/LM reply: the busy slot, or None when idle.
+fn decode_voice_slot(cmd: &Cmd, buffer: &[u8]) -> Result<Option<u8>, DecodeError> {
+    Cmd::is_reply_ok(cmd, buffer)?;
+    if buffer[2] != b'0' {
+        return Err(cmd.error(buffer, Some("VFO")));
+    }
+    match buffer[3] {
+        b'0' => Ok(None),
+        b @ b'1'..=b'5' => Ok(Some(b - b'0')),
+        _ => Err(cmd.error(buffer, Some("slot"))),
+    }
+}
+
+pub struct CmdPb<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_PB: CmdPb<'static> = CmdPb { cmd: Cmd { code: &['P', 'B'], read_params: 2 } };
+
+impl CmdPb<'_> {
+    pub fn read(&self) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, Some(vec!['0']))
+    }
+
+    pub fn play(&self, slot: u8) -> Result<Vec<u8>, ()> {
+        Ok(Cmd::tx_buffer(&self.cmd, Some(vec!['0', voice_slot_char(slot)?])))
+    }
+
+    pub fn stop(&self) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, Some(vec!['0', '0']))
+    }
+
+    pub fn decode(&self, buffer: &[u8]) -> Result<Option<u8>, DecodeError> {
+        decode_voice_slot(&self.cmd, buffer)
+    }
+}
+
+pub struct CmdLm<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_LM: CmdLm<'static> = CmdLm { cmd: Cmd { code: &['L', 'M'], read_params: 2 } };
+
+impl CmdLm<'_> {
+    pub fn read(&self) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, Some(vec!['0']))
+    }
+
+    pub fn record(&self, slot: u8) -> Result<Vec<u8>, ()> {
+        Ok(Cmd::tx_buffer(&self.cmd, Some(vec!['0', voice_slot_char(slot)?])))
+    }
+
+    pub fn stop(&self) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, Some(vec!['0', '0']))
+    }
+
+    pub fn decode(&self, buffer: &[u8]) -> Result<Option<u8>, DecodeError> {
+        decode_voice_slot(&self.cmd, buffer)
+    }
+}
+
+//------------------------------------
+// DT - DATE AND TIME
+//
+// The radio's real-time clock, set one field at a time.
+//   Set : DT 0 yyyymmdd ;   date
+//         DT 1 hhmmss ;     time (24 h)
+//         DT 2 ±hhmm ;      time zone offset from UTC
+//   Read: DT p ;            → DT p <value> ;
+//------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DtField {
+    Date,
+    Time,
+    UtcOffset,
+}
+
+impl From<DtField> for char {
+    fn from(item: DtField) -> Self {
+        match item {
+            DtField::Date => '0',
+            DtField::Time => '1',
+            DtField::UtcOffset => '2',
+        }
+    }
+}
+
+pub struct CmdDt<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_DT: CmdDt<'static> = CmdDt { cmd: Cmd { code: &['D', 'T'], read_params: 0 } };
+
+impl CmdDt<'_> {
+    pub fn read(&self, field: DtField) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, Some(vec![field.into()]))
+    }
+
+    pub fn set_date(&self, year: u16, month: u8, day: u8) -> Result<Vec<u8>, ()> {
+        if !(2000..=2099).contains(&year) || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return Err(());
+        }
+        let s = format!("{}{:04}{:02}{:02}", char::from(DtField::Date), year, month, day);
+        Ok(Cmd::tx_buffer(&self.cmd, Some(s.chars().collect())))
+    }
+
+    pub fn set_time(&self, hour: u8, minute: u8, second: u8) -> Result<Vec<u8>, ()> {
+        if hour > 23 || minute > 59 || second > 59 {
+            return Err(());
+        }
+        let s = format!("{}{:02}{:02}{:02}", char::from(DtField::Time), hour, minute, second);
+        Ok(Cmd::tx_buffer(&self.cmd, Some(s.chars().collect())))
+    }
+
+    pub fn set_utc_offset(&self, minutes: i16) -> Result<Vec<u8>, ()> {
+        if minutes.abs() > 14 * 60 {
+            return Err(());
+        }
+        let sign = if minutes < 0 { '-' } else { '+' };
+        let m = minutes.abs();
+        let s = format!("{}{}{:02}{:02}", char::from(DtField::UtcOffset), sign, m / 60, m % 60);
+        Ok(Cmd::tx_buffer(&self.cmd, Some(s.chars().collect())))
+    }
+
+    /// Returns the raw value field of a DT reply (digits, or ±hhmm for the
+    /// UTC offset) after checking it matches the requested field.
+    pub fn decode(&self, field: DtField, buffer: &[u8]) -> Result<String, DecodeError> {
+        let width = match field {
+            DtField::Date => 8,
+            DtField::Time => 6,
+            DtField::UtcOffset => 5,
+        };
+        let cmd = Cmd { code: self.cmd.code, read_params: 1 + width };
+        Cmd::is_reply_ok(&cmd, buffer)?;
+        if buffer[2] as char != char::from(field) {
+            return Err(cmd.error(buffer, Some("date/time item")));
+        }
+        Ok(buffer[3..3 + width].iter().map(|&b| b as char).collect())
+    }
+}
+
+//------------------------------------
+// ZI - ZERO IN
+//
+// Starts the CW auto zero-beat on one side. No reply.
+//   Set: ZI p ;   p — side (0 = MAIN, 1 = SUB)
+//------------------------------------
+pub struct CmdZi<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_ZI: CmdZi<'static> = CmdZi { cmd: Cmd { code: &['Z', 'I'], read_params: 0 } };
+
+impl CmdZi<'_> {
+    pub fn set(&self, side: Side) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, Some(vec![side.into()]))
+    }
+}
+
+//------------------------------------
+// BS - BAND SELECT
+//   Set: BS p bb ;
+//     p  — side (0 = MAIN, 1 = SUB)
+//     bb — band code, see `Band`
+//------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Band {
+    M160,
+    M80,
+    M60,
+    M40,
+    M30,
+    M20,
+    M17,
+    M15,
+    M12,
+    M10,
+    M6,
+    Gen,
+    Mw,
+    Air,
+    M2,
+    Cm70,
+}
+
+pub const BANDS: [Band; 16] = [
+    Band::M160, Band::M80, Band::M60, Band::M40, Band::M30, Band::M20, Band::M17, Band::M15,
+    Band::M12, Band::M10, Band::M6, Band::Gen, Band::Mw, Band::Air, Band::M2, Band::Cm70,
+];
+
+impl From<Band> for &'static str {
+    fn from(item: Band) -> Self {
+        match item {
+            Band::M160 => "00",
+            Band::M80  => "01",
+            Band::M60  => "02",
+            Band::M40  => "03",
+            Band::M30  => "04",
+            Band::M20  => "05",
+            Band::M17  => "06",
+            Band::M15  => "07",
+            Band::M12  => "08",
+            Band::M10  => "09",
+            Band::M6   => "10",
+            Band::Gen  => "11",
+            Band::Mw   => "12",
+            Band::Air  => "14",
+            Band::M2   => "15",
+            Band::Cm70 => "16",
+        }
+    }
+}
+
+impl fmt::Display for Band {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Band::M160 => write!(f, "160m"),
+            Band::M80 => write!(f, "80m"),
+            Band::M60 => write!(f, "60m"),
+            Band::M40 => write!(f, "40m"),
+            Band::M30 => write!(f, "30m"),
+            Band::M20 => write!(f, "20m"),
+            Band::M17 => write!(f, "17m"),
+            Band::M15 => write!(f, "15m"),
+            Band::M12 => write!(f, "12m"),
+            Band::M10 => write!(f, "10m"),
+            Band::M6 => write!(f, "6m"),
+            Band::Gen => write!(f, "GEN"),
+            Band::Mw => write!(f, "MW"),
+            Band::Air => write!(f, "AIR"),
+            Band::M2 => write!(f, "2m"),
+            Band::Cm70 => write!(f, "70cm"),
+        }
+    }
+}
+
+impl TryFrom<&str> for Band {
+    type Error = ();
+
+    /// Accepts the names printed by Display, case-insensitively.
+    fn try_from(item: &str) -> Result<Self, Self::Error> {
+        BANDS.iter().copied().find(|b| b.to_string().eq_ignore_ascii_case(item.trim())).ok_or(())
+    }
+}
+
+pub struct CmdBs<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_BS: CmdBs<'static> = CmdBs { cmd: Cmd { code: &['B', 'S'], read_params: 0 } };
+
+impl CmdBs<'_> {
+    pub fn set(&self, side: Side, band: Band) -> Vec<u8> {
+        let side: char = side.into();
+        let band: &str = band.into();
+        let s: Vec<char> = format!("{}{}", side, band).chars().collect();
+        Cmd::tx_buffer(&self.cmd, Some(s))
+    }
+}
+
+//------------------------------------
+// Step direction shared by BU/BD, UP/DN, CH and SC
+//------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+}
+
+impl TryFrom<&str> for Direction {
+    type Error = ();
+
+    fn try_from(item: &str) -> Result<Self, Self::Error> {
+        match item.trim().to_ascii_lowercase().as_str() {
+            "up" | "next" | "+" => Ok(Direction::Up),
+            "down" | "prev" | "-" => Ok(Direction::Down),
+            _ => Err(()),
+        }
+    }
+}
+
+//------------------------------------
+// BU / BD - BAND UP / BAND DOWN
+//   Set: BU p ;  BD p ;   p — side (0 = MAIN, 1 = SUB)
+//------------------------------------
+pub struct CmdBu<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_BU: CmdBu<'static> = CmdBu { cmd: Cmd { code: &['B', 'U'], read_params: 0 } };
+
+impl CmdBu<'_> {
+    pub fn set(&self, side: Side) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, Some(vec![side.into()]))
+    }
+}
+
+pub struct CmdBd<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_BD: CmdBd<'static> = CmdBd { cmd: Cmd { code: &['B', 'D'], read_params: 0 } };
+
+impl CmdBd<'_> {
+    pub fn set(&self, side: Side) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, Some(vec![side.into()]))
+    }
+}
+
+/// Frame for one band step in `dir`.
+pub fn band_step(side: Side, dir: Direction) -> Vec<u8> {
+    match dir {
+        Direction::Up => CMD_BU.set(side),
+        Direction::Down => CMD_BD.set(side),
+    }
+}
+
+//------------------------------------
+// UP / DN - MIC UP / DOWN
+//
+// Same as pressing the microphone UP/DWN key once: steps the VFO,
+// memory channel or menu, depending on what the radio is showing.
+//   Set: UP ;  DN ;
+//------------------------------------
+pub struct CmdUp<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_UP: CmdUp<'static> = CmdUp { cmd: Cmd { code: &['U', 'P'], read_params: 0 } };
+
+impl CmdUp<'_> {
+    pub fn set(&self) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, None)
+    }
+}
+
+pub struct CmdDn<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_DN: CmdDn<'static> = CmdDn { cmd: Cmd { code: &['D', 'N'], read_params: 0 } };
+
+impl CmdDn<'_> {
+    pub fn set(&self) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, None)
+    }
+}
+
+/// Frame for one mic-key step in `dir`.
+pub fn mic_step(dir: Direction) -> Vec<u8> {
+    match dir {
+        Direction::Up => CMD_UP.set(),
+        Direction::Down => CMD_DN.set(),
+    }
+}
+
+//------------------------------------
+// CH - MEMORY CHANNEL UP / DOWN
+//
+// Steps the current memory channel, skipping empty ones.
+//   Set: CH d ;   d — 0 = next, 1 = previous
+//------------------------------------
+pub struct CmdCh<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_CH: CmdCh<'static> = CmdCh { cmd: Cmd { code: &['C', 'H'], read_params: 0 } };
+
+impl CmdCh<'_> {
+    pub fn set(&self, dir: Direction) -> Vec<u8> {
+        let d = match dir {
+            Direction::Up => '0',
+            Direction::Down => '1',
+        };
+        Cmd::tx_buffer(&self.cmd, Some(vec![d]))
+    }
+}
+
+//------------------------------------
+// SC - SCAN
+//   Set : SC p s ;
+//   Read: SC p ;   → SC p s ;
+//     p — side (0 = MAIN, 1 = SUB)
+//     s — 0 = off, 1 = scan up, 2 = scan down
+//------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanState {
+    Off,
+    Up,
+    Down,
+}
+
+impl From<Direction> for ScanState {
+    fn from(item: Direction) -> Self {
+        match item {
+            Direction::Up => ScanState::Up,
+            Direction::Down => ScanState::Down,
+        }
+    }
+}
+
+pub struct CmdSc<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_SC: CmdSc<'static> = CmdSc { cmd: Cmd { code: &['S', 'C'], read_params: 2 } };
+
+impl CmdSc<'_> {
+    pub fn set(&self, side: Side, state: ScanState) -> Vec<u8> {
+        let s = match state {
+            ScanState::Off => '0',
+            ScanState::Up => '1',
+            ScanState::Down => '2',
+        };
+        Cmd::tx_buffer(&self.cmd, Some(vec![side.into(), s]))
+    }
+
+    pub fn read(&self, side: Side) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, Some(vec![side.into()]))
+    }
+
+    pub fn decode(&self, buffer: &[u8]) -> Result<ScanState, DecodeError> {
+        Cmd::is_reply_ok(&self.cmd, buffer)?;
+        match buffer[3] {
+            b'0' => Ok(ScanState::Off),
+            b'1' => Ok(ScanState::Up),
+            b'2' => Ok(ScanState::Down),
+            _ => Err(self.cmd.error(buffer, Some("scan state"))),
+        }
+    }
+}
+
+//------------------------------------
+// LK - LOCK
+//
+// Front-panel dial/key lock. CAT keeps working while locked.
+//   Set : LK p ;
+//   Read: LK ;     → LK p ;
+//     p — '0' = unlocked, '1' = locked
+//------------------------------------
+cat_command!(CmdLk, CMD_LK, 'L' 'K', () => on: bool);
+
+//------------------------------------
+// PS - POWER SWITCH
+//
+// While the radio is off its CAT port is asleep: the first byte only
+// wakes it and is discarded. Send `wake()`, wait PS_WAKE_DELAY_MS (the
+// radio accepts PS1 between 1 and 2 s after the dummy), then `set(true)`.
+// A powered-off radio doesn't answer the read at all.
+//   Set : PS p ;
+//   Read: PS ;     → PS p ;
+//     p — '0' = OFF, '1' = ON
+//------------------------------------
+pub const PS_WAKE_DELAY_MS: u64 = 1200;
+
+pub struct CmdPs<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_PS: CmdPs<'static> = CmdPs { cmd: Cmd { code: &['P', 'S'], read_params: 1 } };
+
+impl CmdPs<'_> {
+    pub fn read(&self) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, None)
+    }
+
+    /// Dummy frame that wakes a powered-off radio's CAT port.
+    pub fn wake(&self) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, None)
+    }
+
+    pub fn set(&self, on: bool) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, Some(vec![if on { '1' } else { '0' }]))
+    }
+
+    pub fn decode(&self, buffer: &[u8]) -> Result<bool, DecodeError> {
+        Cmd::is_reply_ok(&self.cmd, buffer)?;
+        match buffer[2] as char {
+            '0' => Ok(false),
+            '1' => Ok(true),
+            _ => Err(self.cmd.error(buffer, Some("on/off"))),
+        }
+    }
+}
+
+//------------------------------------
+// AB / BA / SV - VFO COPY AND SWAP
+//   Set: AB ;   copy MAIN (VFO-A) to SUB (VFO-B)
+//        BA ;   copy SUB to MAIN
+//        SV ;   swap MAIN and SUB
+//------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VfoOp {
+    AToB,
+    BToA,
+    Swap,
+}
+
+impl TryFrom<&str> for VfoOp {
+    type Error = ();
+
+    fn try_from(item: &str) -> Result<Self, Self::Error> {
+        match item.trim().to_ascii_lowercase().as_str() {
+            "copy" | "a-to-b" | "a>b" => Ok(VfoOp::AToB),
+            "b-to-a" | "b>a" => Ok(VfoOp::BToA),
+            "swap" => Ok(VfoOp::Swap),
+            _ => Err(()),
+        }
+    }
+}
+
+cat_command!(CmdAb, CMD_AB, 'A' 'B');
+
+cat_command!(CmdBa, CMD_BA, 'B' 'A');
+
+cat_command!(CmdSv, CMD_SV, 'S' 'V');
+
+/// Frame for one VFO copy/swap operation.
+pub fn vfo_op(op: VfoOp) -> Vec<u8> {
+    match op {
+        VfoOp::AToB => CMD_AB.set(),
+        VfoOp::BToA => CMD_BA.set(),
+        VfoOp::Swap => CMD_SV.set(),
+    }
+}
+
+//------------------------------------
+// FR / FT - RECEIVE / TRANSMIT VFO
+//
+// Which side the radio receives (FR) and transmits (FT) on. RX on MAIN
+// with TX on SUB is split operation.
+//   Set : FR p ;  FT p ;
+//   Read: FR ;    → FR p ;   (likewise FT)
+//     p — side (0 = MAIN / VFO-A, 1 = SUB / VFO-B)
+//------------------------------------
+cat_command!(CmdFr, CMD_FR, 'F' 'R', () => side: Side);
+
+cat_command!(CmdFt, CMD_FT, 'F' 'T', () => side: Side);
+
+//------------------------------------
+// VS - VFO SELECT
+//
+// Which side is the operating one: the one the front-panel controls
+// and most single-side commands act on.
+//   Set : VS p ;
+//   Read: VS ;    → VS p ;
+//     p — side (0 = MAIN / VFO-A, 1 = SUB / VFO-B)
+//------------------------------------
+cat_command!(CmdVs, CMD_VS, 'V' 'S', () => side: Side);
+
+//------------------------------------
+// FB - VFO-B FREQUENCY
+//   Set : FB fffffffff ;
+//   Read: FB ;          → FB fffffffff ;
+//------------------------------------
+cat_command!(CmdFb, CMD_FB, 'F' 'B', () => freq: FrequencyHz);
+
+//------------------------------------
+// ST - SPLIT
+//   Set : ST p ;
+//   Read: ST ;     → ST p ;
+//     p — '0' = OFF, '1' = ON
+//------------------------------------
+cat_command!(CmdSt, CMD_ST, 'S' 'T', () => on: bool);
+
+//------------------------------------
+// TX - TRANSMIT (PTT)
+//   Set : TX p ;
+//   Read: TX ;     → TX p ;
+//     p — '0' = RX, '1' = TX keyed over CAT, '2' = TX keyed otherwise
+//         (mic PTT, DATA jack); only read back
+//------------------------------------
+pub struct CmdTx<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_TX: CmdTx<'static> = CmdTx { cmd: Cmd { code: &['T', 'X'], read_params: 1 } };
+
+impl CmdTx<'_> {
+    pub fn read(&self) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, None)
+    }
+
+    pub fn set(&self, on: bool) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, Some(vec![if on { '1' } else { '0' }]))
+    }
+
+    /// True while transmitting, however the radio was keyed.
+    pub fn decode(&self, buffer: &[u8]) -> Result<bool, DecodeError> {
+        Cmd::is_reply_ok(&self.cmd, buffer)?;
+        match buffer[2] as char {
+            '0' => Ok(false),
+            '1' | '2' => Ok(true),
+            _ => Err(self.cmd.error(buffer, Some("on/off"))),
+        }
+    }
+}
+
+//------------------------------------
+// RT / XT - RX / TX CLARIFIER ON/OFF
+//   Set : RT p ;  XT p ;
+//   Read: RT ;    → RT p ;   (likewise XT)
+//     p — '0' = OFF, '1' = ON
+//------------------------------------
+cat_command!(CmdRt, CMD_RT, 'R' 'T', () => on: bool);
+
+cat_command!(CmdXt, CMD_XT, 'X' 'T', () => on: bool);
+
+//------------------------------------
+// RC - CLARIFIER CLEAR
+//   Set: RC ;   resets the clarifier offset to 0
+//------------------------------------
+pub struct CmdRc<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_RC: CmdRc<'static> = CmdRc { cmd: Cmd { code: &['R', 'C'], read_params: 0 } };
+
+impl CmdRc<'_> {
+    pub fn set(&self) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, None)
+    }
+}
+
+//------------------------------------
+// RU / RD - CLARIFIER UP / DOWN
+//   Set: RU nnnn ;  RD nnnn ;   nnnn — step in Hz, 0000–9990
+//------------------------------------
+pub const CLAR_STEP_MAX_HZ: u16 = 9_990;
+
+pub struct CmdRu<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_RU: CmdRu<'static> = CmdRu { cmd: Cmd { code: &['R', 'U'], read_params: 0 } };
+
+impl CmdRu<'_> {
+    pub fn set(&self, hz: u16) -> Result<Vec<u8>, ()> {
+        if hz > CLAR_STEP_MAX_HZ {
+            return Err(());
+        }
+        Ok(Cmd::tx_buffer(&self.cmd, Some(format!("{:04}", hz).chars().collect())))
+    }
+}
+
+pub struct CmdRd<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_RD: CmdRd<'static> = CmdRd { cmd: Cmd { code: &['R', 'D'], read_params: 0 } };
+
+impl CmdRd<'_> {
+    pub fn set(&self, hz: u16) -> Result<Vec<u8>, ()> {
+        if hz > CLAR_STEP_MAX_HZ {
+            return Err(());
+        }
+        Ok(Cmd::tx_buffer(&self.cmd, Some(format!("{:04}", hz).chars().collect())))
+    }
+}
+
+/// Frame that moves the clarifier by a signed number of Hz.
+pub fn clar_step(hz: i16) -> Result<Vec<u8>, ()> {
+    if hz >= 0 { CMD_RU.set(hz.unsigned_abs()) } else { CMD_RD.set(hz.unsigned_abs()) }
+}
+
+//------------------------------------
+// DA - DIMMER
+//   Set : DA ll tt ;
+//   Read: DA ;       → DA ll tt ;
+//     ll — front-panel LED brightness, 00–20
+//     tt — TFT display brightness, 00–20
+//------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Dimmer {
+    led: u8,
+    tft: u8,
+}
+
+impl Dimmer {
+    pub const MAX: u8 = 20;
+
+    pub fn new(led: u8, tft: u8) -> Result<Self, ()> {
+        if led <= Self::MAX && tft <= Self::MAX {
+            Ok(Dimmer { led, tft })
+        } else {
+            Err(())
+        }
+    }
+
+    pub fn led(self) -> u8 {
+        self.led
+    }
+
+    pub fn tft(self) -> u8 {
+        self.tft
+    }
+}
+
+impl TryFrom<&str> for Dimmer {
+    type Error = ();
+
+    /// "LED/TFT", e.g. "5/12" — the same form Display prints.
+    fn try_from(item: &str) -> Result<Self, Self::Error> {
+        let (led, tft) = item.trim().split_once('/').ok_or(())?;
+        Dimmer::new(led.trim().parse().map_err(|_| ())?, tft.trim().parse().map_err(|_| ())?)
+    }
+}
+
+impl fmt::Display for Dimmer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.led, self.tft)
+    }
+}
+
+pub struct CmdDa<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_DA: CmdDa<'static> = CmdDa { cmd: Cmd { code: &['D', 'A'], read_params: 4 } };
+
+impl CmdDa<'_> {
+    pub fn read(&self) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, None)
+    }
+
+    pub fn set(&self, dimmer: Dimmer) -> Vec<u8> {
+        let s = format!("{:02}{:02}", dimmer.led, dimmer.tft);
+        Cmd::tx_buffer(&self.cmd, Some(s.chars().collect()))
+    }
+
+    pub fn decode(&self, buffer: &[u8]) -> Result<Dimmer, DecodeError> {
+        Cmd::is_reply_ok(&self.cmd, buffer)?;
+        let led = self.cmd.field(buffer, "LED level", buf2_to_u8(&buffer[2..4]))?;
+        let tft = self.cmd.field(buffer, "TFT level", buf2_to_u8(&buffer[4..6]))?;
+        self.cmd.field(buffer, "dimmer", Dimmer::new(led, tft))
+    }
+}
+
+//------------------------------------
+// SS - SPECTRUM SCOPE
+//   Set : SS p f v ;
+//   Read: SS p f ;   → SS p f v ;
+//     p — side (0 = MAIN, 1 = SUB)
+//     f — function: 4 = reference level, 5 = span, 6 = display mode
+//     v — level: sign + dB with one decimal, -30.0…+30.0 in 0.5 dB steps
+//         span: one digit, see `ScopeSpan`
+//         mode: 0 = CENTER, 1 = CURSOR, 2 = FIX
+//------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeMode {
+    Center,
+    Cursor,
+    Fix,
+}
+
+impl fmt::Display for ScopeMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScopeMode::Center => write!(f, "CENTER"),
+            ScopeMode::Cursor => write!(f, "CURSOR"),
+            ScopeMode::Fix => write!(f, "FIX"),
+        }
+    }
+}
+
+impl TryFrom<&str> for ScopeMode {
+    type Error = ();
+
+    fn try_from(item: &str) -> Result<Self, Self::Error> {
+        match item.trim().to_ascii_uppercase().as_str() {
+            "CENTER" => Ok(ScopeMode::Center),
+            "CURSOR" => Ok(ScopeMode::Cursor),
+            "FIX" => Ok(ScopeMode::Fix),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Scope span; the wire digit is the index into SPANS_KHZ.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScopeSpan {
+    index: u8,
+}
+
+impl ScopeSpan {
+    pub const SPANS_KHZ: [u16; 10] = [1, 2, 5, 10, 20, 50, 100, 200, 500, 1000];
+
+    pub fn khz(self) -> u16 {
+        Self::SPANS_KHZ[self.index as usize]
+    }
+}
+
+impl TryFrom<u16> for ScopeSpan {
+    type Error = ();
+
+    fn try_from(khz: u16) -> Result<Self, Self::Error> {
+        let index = Self::SPANS_KHZ.iter().position(|s| *s == khz).ok_or(())?;
+        Ok(ScopeSpan { index: index as u8 })
+    }
+}
+
+impl fmt::Display for ScopeSpan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.khz())
+    }
+}
+
+/// Scope reference level, held in half-dB units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScopeRefLevel {
+    half_db: i8,
+}
+
+impl ScopeRefLevel {
+    const MAX_HALF_DB: i8 = 60;
+
+    pub fn to_db(self) -> f32 {
+        f32::from(self.half_db) / 2.0
+    }
+}
+
+impl TryFrom<&str> for ScopeRefLevel {
+    type Error = ();
+
+    /// Decibels, e.g. "-12.5" or "+3"; must land on a 0.5 dB step.
+    fn try_from(item: &str) -> Result<Self, Self::Error> {
+        let db: f32 = item.trim().parse().map_err(|_| ())?;
+        let halves = db * 2.0;
+        if halves.fract() != 0.0 || halves.abs() > f32::from(Self::MAX_HALF_DB) {
+            return Err(());
+        }
+        Ok(ScopeRefLevel { half_db: halves as i8 })
+    }
+}
+
+impl fmt::Display for ScopeRefLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:+.1}", self.to_db())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeFunction {
+    RefLevel,
+    Span,
+    Mode,
+}
+
+impl From<ScopeFunction> for char {
+    fn from(item: ScopeFunction) -> Self {
+        match item {
+            ScopeFunction::RefLevel => '4',
+            ScopeFunction::Span => '5',
+            ScopeFunction::Mode => '6',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScopeParam {
+    RefLevel(ScopeRefLevel),
+    Span(ScopeSpan),
+    Mode(ScopeMode),
+}
+
+pub struct CmdSs<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_SS: CmdSs<'static> = CmdSs { cmd: Cmd { code: &['S', 'S'], read_params: 0 } };
+
+impl CmdSs<'_> {
+    pub fn read(&self, side: Side, function: ScopeFunction) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, Some(vec![side.into(), function.into()]))
+    }
+
+    pub fn set(&self, side: Side, param: ScopeParam) -> Vec<u8> {
+        let (function, value) = match param {
+            ScopeParam::RefLevel(l) => (ScopeFunction::RefLevel, format!("{:+05.1}", l.to_db())),
+            ScopeParam::Span(s) => (ScopeFunction::Span, s.index.to_string()),
+            ScopeParam::Mode(m) => (ScopeFunction::Mode, (m as u8).to_string()),
+        };
+        let side: char = side.into();
+        let function: char = function.into();
+        Cmd::tx_buffer(&self.cmd, Some(format!("{}{}{}", side, function, value).chars().collect()))
+    }
+
+    pub fn decode(&self, buffer: &[u8]) -> Result<ScopeParam, DecodeError> {
+        Cmd::is_variable_reply_ok(&self.cmd, buffer, 7)?;
+        let cmd = Cmd { code: self.cmd.code, read_params: 7 };
+        if buffer.len() < 6 {
+            return Err(cmd.error(buffer, None));
+        }
+        let value = cmd.field(buffer, "value", core::str::from_utf8(&buffer[4..buffer.len() - 1]).map_err(|_| ()))?;
+        match buffer[3] {
+            b'4' if value.len() == 5 => Ok(ScopeParam::RefLevel(cmd.field(buffer, "reference level", ScopeRefLevel::try_from(value))?)),
+            b'5' if value.len() == 1 => {
+                let index: u8 = cmd.field(buffer, "span", value.parse().map_err(|_| ()))?;
+                let khz = *cmd.field(buffer, "span", ScopeSpan::SPANS_KHZ.get(index as usize).ok_or(()))?;
+                Ok(ScopeParam::Span(cmd.field(buffer, "span", ScopeSpan::try_from(khz))?))
+            }
+            b'6' => match value {
+                "0" => Ok(ScopeParam::Mode(ScopeMode::Center)),
+                "1" => Ok(ScopeParam::Mode(ScopeMode::Cursor)),
+                "2" => Ok(ScopeParam::Mode(ScopeMode::Fix)),
+                _ => Err(cmd.error(buffer, Some("scope mode"))),
+            },
+            _ => Err(cmd.error(buffer, Some("function"))),
+        }
+    }
+}
+
+//------------------------------------
+// RI - RADIO INFORMATION (STATUS FLAGS)
+//   Read: RI ;  → RI abcdefg ;   one '0'/'1' digit per flag, in order:
+//     a — HI-SWR        b — voice recording   c — voice playback
+//     d — transmitting  e — TX inhibited      f — MAIN busy (squelch open)
+//     g — SUB busy
+//------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct StatusFlags {
+    bits: u8,
+}
+
+impl StatusFlags {
+    pub const HI_SWR: u8 = 1 << 0;
+    pub const RECORDING: u8 = 1 << 1;
+    pub const PLAYING: u8 = 1 << 2;
+    pub const TX: u8 = 1 << 3;
+    pub const TX_INHIBIT: u8 = 1 << 4;
+    pub const MAIN_BUSY: u8 = 1 << 5;
+    pub const SUB_BUSY: u8 = 1 << 6;
+
+    const NAMES: [(u8, &'static str); 7] = [
+        (Self::HI_SWR, "HI-SWR"),
+        (Self::RECORDING, "REC"),
+        (Self::PLAYING, "PLAY"),
+        (Self::TX, "TX"),
+        (Self::TX_INHIBIT, "TX-INH"),
+        (Self::MAIN_BUSY, "MAIN-BUSY"),
+        (Self::SUB_BUSY, "SUB-BUSY"),
+    ];
+
+    pub fn bits(self) -> u8 {
+        self.bits
+    }
+
+    pub fn contains(self, flag: u8) -> bool {
+        self.bits & flag == flag
+    }
+
+    /// A condition that should stop anything that keys the transmitter.
+    pub fn is_fault(self) -> bool {
+        self.contains(Self::HI_SWR)
+    }
+}
+
+impl fmt::Display for StatusFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<&str> = Self::NAMES.iter().filter(|(b, _)| self.contains(*b)).map(|(_, n)| *n).collect();
+        if names.is_empty() { write!(f, "-") } else { write!(f, "{}", names.join(" ")) }
+    }
+}
+
+pub struct CmdRi<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_RI: CmdRi<'static> = CmdRi { cmd: Cmd { code: &['R', 'I'], read_params: 7 } };
+
+impl CmdRi<'_> {
+    pub fn read(&self) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, None)
+    }
+
+    pub fn decode(&self, buffer: &[u8]) -> Result<StatusFlags, DecodeError> {
+        Cmd::is_reply_ok(&self.cmd, buffer)?;
+        let mut bits = 0u8;
+        for (i, c) in buffer[2..9].iter().enumerate() {
+            match c {
+                b'0' => {}
+                b'1' => bits |= 1 << i,
+                _ => return Err(self.cmd.error(buffer, Some("status flags"))),
+            }
+        }
+        Ok(StatusFlags { bits })
+    }
+}
+
+//------------------------------------
+// CS - CW SPOT
+//
+// Sidetone at the CW pitch for zero-beating by ear; nothing is sent.
+//   Set : CS p ;
+//   Read: CS ;     → CS p ;
+//     p — '0' = OFF, '1' = ON
+//------------------------------------
+cat_command!(CmdCs, CMD_CS, 'C' 'S', () => on: bool);
+
+//------------------------------------
+// MS - METER SWITCH
+//
+// Picks which meter the radio shows while transmitting.
+//   Set : MS m ;
+//   Read: MS ;     → MS m ;
+//     m — 0 = COMP, 1 = ALC, 2 = PO, 3 = SWR, 4 = IDD, 5 = VDD
+//------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxMeter {
+    Comp,
+    Alc,
+    Po,
+    Swr,
+    Idd,
+    Vdd,
+}
+
+pub const TX_METERS: [TxMeter; 6] = [TxMeter::Comp, TxMeter::Alc, TxMeter::Po, TxMeter::Swr, TxMeter::Idd, TxMeter::Vdd];
+
+impl fmt::Display for TxMeter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TxMeter::Comp => write!(f, "COMP"),
+            TxMeter::Alc => write!(f, "ALC"),
+            TxMeter::Po => write!(f, "PO"),
+            TxMeter::Swr => write!(f, "SWR"),
+            TxMeter::Idd => write!(f, "IDD"),
+            TxMeter::Vdd => write!(f, "VDD"),
+        }
+    }
+}
+
+impl TryFrom<&str> for TxMeter {
+    type Error = ();
+
+    fn try_from(item: &str) -> Result<Self, Self::Error> {
+        TX_METERS.iter().copied().find(|m| m.to_string().eq_ignore_ascii_case(item.trim())).ok_or(())
+    }
+}
+
+pub struct CmdMs<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_MS: CmdMs<'static> = CmdMs { cmd: Cmd { code: &['M', 'S'], read_params: 1 } };
+
+impl CmdMs<'_> {
+    pub fn read(&self) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, None)
+    }
+
+    pub fn set(&self, meter: TxMeter) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, Some(vec![char::from(b'0' + meter as u8)]))
+    }
+
+    pub fn decode(&self, buffer: &[u8]) -> Result<TxMeter, DecodeError> {
+        Cmd::is_reply_ok(&self.cmd, buffer)?;
+        let meter = buffer[2].checked_sub(b'0').and_then(|i| TX_METERS.get(i as usize));
+        self.cmd.field(buffer, "meter", meter.copied().ok_or(()))
+    }
+}
+
+//------------------------------------
+// RM - READ METER
+//   Read: RM m ;  → RM m nnn ;
+//     m   — 1 = S (MAIN), 2 = S (SUB), 3 = COMP, 4 = ALC, 5 = PO,
+//           6 = SWR, 7 = IDD, 8 = VDD
+//     nnn — raw needle position, 000–255
+// TX meters only read non-zero while transmitting.
+//------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Meter {
+    SMain,
+    SSub,
+    Comp,
+    Alc,
+    Po,
+    Swr,
+    Idd,
+    Vdd,
+}
+
+impl From<Meter> for char {
+    fn from(item: Meter) -> Self {
+        match item {
+            Meter::SMain => '1',
+            Meter::SSub => '2',
+            Meter::Comp => '3',
+            Meter::Alc => '4',
+            Meter::Po => '5',
+            Meter::Swr => '6',
+            Meter::Idd => '7',
+            Meter::Vdd => '8',
+        }
+    }
+}
+
+/// Approximate SWR for a raw RM6 reading, interpolated between the
+/// scale marks (1.0, 1.5, 2.0, 3.0). Anything at or past full scale is
+/// reported as infinite.
+pub fn swr_from_raw(raw: u8) -> f32 {
+    const MARKS: [(u8, f32); 4] = [(0, 1.0), (48, 1.5), (80, 2.0), (120, 3.0)];
+    if raw == u8::MAX {
+        return f32::INFINITY;
+    }
+    for pair in MARKS.windows(2) {
+        let ((r0, s0), (r1, s1)) = (pair[0], pair[1]);
+        if raw <= r1 {
+            return s0 + (s1 - s0) * f32::from(raw - r0) / f32::from(r1 - r0);
+        }
+    }
+    // Past the 3.0 mark the scale is too compressed to read; extrapolate.
+    3.0 + f32::from(raw - 120) / 40.0
+}
+
+pub struct CmdRm<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_RM: CmdRm<'static> = CmdRm { cmd: Cmd { code: &['R', 'M'], read_params: 4 } };
+
+impl CmdRm<'_> {
+    pub fn read(&self, meter: Meter) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, Some(vec![meter.into()]))
+    }
+
+    pub fn decode(&self, meter: Meter, buffer: &[u8]) -> Result<u8, DecodeError> {
+        Cmd::is_reply_ok(&self.cmd, buffer)?;
+        if buffer[2] as char != char::from(meter) {
+            return Err(self.cmd.error(buffer, Some("meter")));
+        }
+        self.cmd.field(buffer, "reading", buf3_to_u8(&buffer[3..6]))
+    }
+}
+
+//------------------------------------
+// PC - RF POWER
+//   Set : PC p www ;
+//   Read: PC ;       → PC p www ;
+//     p   — which amplifier the setting is for: 1 = the FTX-1's own
+//           (field head), 2 = the SPA-1 optional amplifier
+//     www — watts, 001-010 (field head) or 005-100 (SPA-1)
+// Set only takes a value for the amplifier in use; the radio ignores the
+// other.
+//------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Amplifier {
+    FieldHead,
+    Spa1,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RfPower {
+    pub amplifier: Amplifier,
+    pub watts: u8,
+}
+
+pub struct CmdPc<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_PC: CmdPc<'static> = CmdPc { cmd: Cmd { code: &['P', 'C'], read_params: 4 } };
+
+impl CmdPc<'_> {
+    pub fn read(&self) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, None)
+    }
+
+    pub fn set(&self, power: RfPower) -> Vec<u8> {
+        let amplifier = match power.amplifier {
+            Amplifier::FieldHead => '1',
+            Amplifier::Spa1 => '2',
+        };
+        let mut params = vec![amplifier];
+        params.extend(format!("{:03}", power.watts.min(100)).chars());
+        Cmd::tx_buffer(&self.cmd, Some(params))
+    }
+
+    pub fn decode(&self, buffer: &[u8]) -> Result<RfPower, DecodeError> {
+        Cmd::is_reply_ok(&self.cmd, buffer)?;
+        let amplifier = match buffer[2] {
+            b'1' => Ok(Amplifier::FieldHead),
+            b'2' => Ok(Amplifier::Spa1),
+            _ => Err(()),
+        };
+        let amplifier = self.cmd.field(buffer, "amplifier", amplifier)?;
+        let watts = self.cmd.field(buffer, "watts", buf3_to_u8(&buffer[3..6]))?;
+        Ok(RfPower { amplifier, watts })
+    }
+}
+
+//------------------------------------
+// AC - ANTENNA TUNER CONTROL
+//   Set : AC 0 0 s ;
+//   Read: AC ;       → AC 0 0 s ;
+//     s — 0 = tuner off, 1 = tuner on, 2 = start tuning (read: tuning)
+// Tuning keys the transmitter.
+//------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TunerState {
+    Off,
+    On,
+    Tuning,
+}
+
+impl fmt::Display for TunerState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TunerState::Off => write!(f, "OFF"),
+            TunerState::On => write!(f, "ON"),
+            TunerState::Tuning => write!(f, "TUNING"),
+        }
+    }
+}
+
+pub struct CmdAc<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_AC: CmdAc<'static> = CmdAc { cmd: Cmd { code: &['A', 'C'], read_params: 3 } };
+
+impl CmdAc<'_> {
+    pub fn read(&self) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, None)
+    }
+
+    pub fn set(&self, state: TunerState) -> Vec<u8> {
+        let s = match state {
+            TunerState::Off => '0',
+            TunerState::On => '1',
+            TunerState::Tuning => '2',
+        };
+        Cmd::tx_buffer(&self.cmd, Some(vec!['0', '0', s]))
+    }
+
+    pub fn decode(&self, buffer: &[u8]) -> Result<TunerState, DecodeError> {
+        Cmd::is_reply_ok(&self.cmd, buffer)?;
+        match &buffer[2..5] {
+            b"000" => Ok(TunerState::Off),
+            b"001" => Ok(TunerState::On),
+            b"002" => Ok(TunerState::Tuning),
+            _ => Err(self.cmd.error(buffer, Some("tuner state"))),
+        }
+    }
+}
+
+//------------------------------------
+// AN - ANTENNA SELECT
+//   Set : AN p a ;
+//   Read: AN p ;   → AN p a ;
+//     p — side (0 = MAIN, 1 = SUB)
+//     a — 1 = ANT 1, 2 = ANT 2
+// The radio keeps the choice per band, so it applies to the band the side
+// is on. ANT 2 is on the SPA-1 (Optima); a Field answers ANT 2 with '?;'.
+//------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Antenna {
+    Ant1,
+    Ant2,
+}
+
+impl fmt::Display for Antenna {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Antenna::Ant1 => write!(f, "1"),
+            Antenna::Ant2 => write!(f, "2"),
+        }
+    }
+}
+
+impl TryFrom<&str> for Antenna {
+    type Error = ();
+
+    /// "1"/"2", optionally as "ant1"/"ANT 2".
+    fn try_from(item: &str) -> Result<Self, Self::Error> {
+        let item = item.trim().to_ascii_lowercase();
+        match item.strip_prefix("ant").unwrap_or(&item).trim() {
+            "1" => Ok(Antenna::Ant1),
+            "2" => Ok(Antenna::Ant2),
+            _ => Err(()),
+        }
+    }
+}
+
+pub struct CmdAn<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_AN: CmdAn<'static> = CmdAn { cmd: Cmd { code: &['A', 'N'], read_params: 2 } };
+
+impl CmdAn<'_> {
+    pub fn read(&self, side: Side) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, Some(vec![side.into()]))
+    }
+
+    pub fn set(&self, side: Side, antenna: Antenna) -> Vec<u8> {
+        let a = match antenna {
+            Antenna::Ant1 => '1',
+            Antenna::Ant2 => '2',
+        };
+        Cmd::tx_buffer(&self.cmd, Some(vec![side.into(), a]))
+    }
+
+    pub fn decode(&self, buffer: &[u8]) -> Result<Antenna, DecodeError> {
+        Cmd::is_reply_ok(&self.cmd, buffer)?;
+        self.cmd.field(buffer, "side", Side::try_from(buffer[2] as char))?;
+        match buffer[3] {
+            b'1' => Ok(Antenna::Ant1),
+            b'2' => Ok(Antenna::Ant2),
+            _ => Err(self.cmd.error(buffer, Some("antenna"))),
+        }
+    }
+}
+
+//------------------------------------
+// FS - FAST STEP
+//
+// Coarser VFO dial/UP-DOWN steps on one side.
+//   Set : FS p s ;
+//   Read: FS p ;    → FS p s ;
+//     p — side (0 = MAIN, 1 = SUB)
+//     s — '0' = OFF, '1' = ON
+//------------------------------------
+cat_command!(CmdFs, CMD_FS, 'F' 'S', (side: Side) => on: bool);
+
+//------------------------------------
+// MEMORY CHANNEL WRITE SEQUENCE
+//
+// MW alone doesn't store tones, so a channel is programmed through the
+// VFO and committed with AM; the tag and split follow. None of these
+// frames gets a reply.
+//------------------------------------
+
+/// The frames that program one memory channel, in the order to send them.
+/// `tones` are the CTCSS and DCS tone codes; `tx` is the split TX
+/// frequency, None to turn split off.
+pub fn write_channel_frames(
+    mem: &MemoryReadWrite,
+    tones: (ToneCode, ToneCode),
+    tag: Option<String>,
+    tx: Option<FrequencyHz>,
+) -> Result<Vec<Vec<u8>>, ()> {
+    let mut frames = vec![
+        // MW first to ensure the channel slot exists. AM-only fails to create
+        // new (empty) channels because MC can't reliably select an empty slot.
+        // MW resets tones, but the AM step below re-commits them from VFO state.
+        CMD_MW.set(mem.clone())?,
+        // Put main in Memory mode and select the channel so AM later writes
+        // back to the correct memory slot; switch to VFO to build up state.
+        CMD_VM.set(Side::Main, VmMode::Memory),
+        CMD_MC.set(Side::Main, mem.channel),
+        CMD_VM.set(Side::Main, VmMode::Vfo),
+        // Set shift while in FM mode (OS is only accepted in FM), then flip to
+        // the target mode. This also clears stale shift state on non-FM channels.
+        CMD_MD.set(Side::Main, Mode::Fm),
+        CMD_OS.set(Side::Main, mem.shift),
+        CMD_MD.set(Side::Main, mem.mode),
+        CMD_FA.set(mem.frequency_hz),
+        CMD_CT.set(Side::Main, mem.sql_type),
+        CMD_CN.set(Side::Main, ToneType::Ctcss, tones.0),
+        CMD_CN.set(Side::Main, ToneType::Dcs, tones.1),
+        // Commit the full VFO state to the selected memory channel.
+        CMD_AM.save(),
+    ];
+    if let Some(tag) = tag {
+        frames.push(CMD_MT.set(mem.channel, tag)?);
+    }
+    // Split memory: enable with the explicit TX freq when set, or
+    // explicitly disable so a re-import correctly clears prior split state.
+    // P3 is required even when P2=0; reuse the RX freq as a valid placeholder.
+    frames.push(CMD_MZ.set(mem.channel, tx.is_some(), tx.unwrap_or(mem.frequency_hz))?);
+    Ok(frames)
+}
+
+//------------------------------------
+// TESTS
+//------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_channel_to_chars() {
+        // VFO
+        assert_eq!(MemoryChannel::VfoMtQmb.to_chars().unwrap(), ['0', '0', '0', '0', '0']);
+
+        // Memory Channel
+        assert_eq!(MemoryChannel::Mem(1).to_chars().unwrap(), ['0', '0', '0', '0', '1']);
+        assert_eq!(MemoryChannel::Mem(123).to_chars().unwrap(), ['0', '0', '1', '2', '3']);
+
+        // PMS
+        let pms_l = PmsChannel { slot: 1, lower_upper: PmsLowerUpper::Lower };
+        assert_eq!(MemoryChannel::Pms(pms_l).to_chars().unwrap(), ['P', '-', '0', '1', 'L']);
+
+        let pms_u = PmsChannel { slot: 50, lower_upper: PmsLowerUpper::Upper };
+        assert_eq!(MemoryChannel::Pms(pms_u).to_chars().unwrap(), ['P', '-', '5', '0', 'U']);
 
         // Emergency
         assert_eq!(MemoryChannel::EmergencyChannel.to_chars().unwrap(), ['E', 'M', 'G', 'C', 'H']);
@@ -1528,13 +3822,25 @@ mod tests {
     fn test_clarifier_offset_hz_from_i16_invalid() {
         assert!(ClarifierOffsetHz::try_from(9991).is_err());
         assert!(ClarifierOffsetHz::try_from(-9991).is_err());
+        // Not a 10 Hz step.
+        assert!(ClarifierOffsetHz::try_from(1234).is_err());
+        assert!(ClarifierOffsetHz::try_from(-5).is_err());
+    }
+
+    #[test]
+    fn test_clarifier_offset_hz_rounded() {
+        assert_eq!(ClarifierOffsetHz::rounded(1234).to_i16(), 1230);
+        assert_eq!(ClarifierOffsetHz::rounded(1235).to_i16(), 1240);
+        assert_eq!(ClarifierOffsetHz::rounded(-1235).to_i16(), -1240);
+        assert_eq!(ClarifierOffsetHz::rounded(-4).to_i16(), 0);
+        assert_eq!(ClarifierOffsetHz::rounded(9999).to_i16(), 9990);
     }
 
     #[test]
     fn test_clarifier_offset_hz_from_bytes_valid() {
         assert!(ClarifierOffsetHz::try_from("+0000".as_bytes()).is_ok());
-        assert_eq!(ClarifierOffsetHz::try_from("+1234".as_bytes()).unwrap().value, 1234);
-        assert_eq!(ClarifierOffsetHz::try_from("-1234".as_bytes()).unwrap().value, -1234);
+        assert_eq!(ClarifierOffsetHz::try_from("+1230".as_bytes()).unwrap().value, 1230);
+        assert_eq!(ClarifierOffsetHz::try_from("-1230".as_bytes()).unwrap().value, -1230);
     }
 
     #[test]
@@ -1556,6 +3862,25 @@ mod tests {
         assert_eq!(format!("{}", Mode::C4fmVw), "C4FM-VW");
     }
 
+    #[test]
+    fn test_string_tables() {
+        for (v, name, label) in SqlType::NAMES {
+            assert_eq!(v.to_string(), *label);
+            assert_eq!(name.parse(), Ok(*v));
+            assert_eq!(label.parse(), Ok(*v));
+            assert_eq!(serde_json::to_string(v).unwrap(), format!("\"{}\"", name));
+        }
+        assert_eq!(Shift::NAMES.len(), 4);
+        assert_eq!("PLUS SHIFT".parse(), Ok(Shift::PlusShift));
+        assert_eq!(ChType::MemoryChannel.name(), "MemoryChannel");
+        assert_eq!(ChType::MemoryChannel.to_string(), "Memory");
+        assert_eq!("fm".parse::<Mode>(), Err(()));
+        assert_eq!(serde_json::to_string(&Mode::DataFmN).unwrap(), "\"DATA-FM-N\"");
+        // What the derived serde of older releases wrote still reads.
+        assert_eq!(serde_json::from_str::<Mode>("\"DataFmN\"").unwrap(), Mode::DataFmN);
+        assert!(serde_json::from_str::<Shift>("\"Sideways\"").unwrap_err().to_string().contains("unknown Shift 'Sideways'"));
+    }
+
     #[test]
     fn test_cmd_mz_set_wire_format() {
         let ch = MemoryChannel::Mem(19);
@@ -1629,4 +3954,685 @@ mod tests {
         let offset = ClarifierOffsetHz { value: 0 };
         assert_eq!(format!("{}", offset), "+0000");
     }
+
+    #[test]
+    fn test_cmd_kp_set_wire_format() {
+        // 600 Hz is step 30 above the 300 Hz floor.
+        let pitch = KeyPitchHz::try_from(600).unwrap();
+        assert_eq!(CMD_KP.set(pitch).as_slice(), b"KP30;");
+        assert_eq!(CMD_KP.set(KeyPitchHz::try_from(1050).unwrap()).as_slice(), b"KP75;");
+    }
+
+    #[test]
+    fn test_cmd_kp_decode() {
+        assert_eq!(CMD_KP.decode(b"KP00;").unwrap().to_u16(), 300);
+        assert_eq!(CMD_KP.decode(b"KP30;").unwrap().to_u16(), 600);
+        // Index past 1050 Hz and non-digits are rejected.
+        assert!(CMD_KP.decode(b"KP76;").is_err());
+        assert!(CMD_KP.decode(b"KPx0;").is_err());
+    }
+
+    #[test]
+    fn test_key_pitch_hz_range_and_step() {
+        assert!(KeyPitchHz::try_from(300).is_ok());
+        assert!(KeyPitchHz::try_from(290).is_err());
+        assert!(KeyPitchHz::try_from(1060).is_err());
+        assert!(KeyPitchHz::try_from(605).is_err()); // not a 10 Hz step
+    }
+
+    #[test]
+    fn test_cmd_ai_wire_format() {
+        assert_eq!(CMD_AI.set(true).as_slice(), b"AI1;");
+        assert_eq!(CMD_AI.read().as_slice(), b"AI;");
+        assert_eq!(CMD_AI.decode(b"AI0;"), Ok(false));
+    }
+
+    #[test]
+    fn test_cmd_bi_wire_format() {
+        assert_eq!(CMD_BI.set(true).as_slice(), b"BI1;");
+        assert_eq!(CMD_BI.set(false).as_slice(), b"BI0;");
+        assert_eq!(CMD_BI.read().as_slice(), b"BI;");
+        assert_eq!(CMD_BI.decode(b"BI1;"), Ok(true));
+        assert_eq!(CMD_BI.decode(b"BI0;"), Ok(false));
+        assert!(CMD_BI.decode(b"BI2;").is_err());
+    }
+
+    #[test]
+    fn test_cmd_sd_wire_format() {
+        let delay = BreakInDelayMs::try_from(250).unwrap();
+        assert_eq!(CMD_SD.set(delay).as_slice(), b"SD0250;");
+        assert_eq!(CMD_SD.decode(b"SD0030;").unwrap().to_u16(), 30);
+        assert_eq!(CMD_SD.decode(b"SD3000;").unwrap().to_u16(), 3000);
+        // Below the 30 ms floor / above the 3000 ms ceiling.
+        assert!(CMD_SD.decode(b"SD0029;").is_err());
+        assert!(CMD_SD.decode(b"SD3001;").is_err());
+    }
+
+    #[test]
+    fn test_cmd_co_apf_wire_format() {
+        assert_eq!(CMD_CO.read(Side::Main, ApfFunction::OnOff).as_slice(), b"CO02;");
+        assert_eq!(CMD_CO.set(Side::Main, ApfParam::OnOff(true)).as_slice(), b"CO020001;");
+        let offset = ApfOffsetHz::try_from(-40).unwrap();
+        assert_eq!(CMD_CO.set(Side::Sub, ApfParam::Offset(offset)).as_slice(), b"CO130021;");
+        assert_eq!(CMD_CO.decode(b"CO030050;"), Ok(ApfParam::Offset(ApfOffsetHz::try_from(250).unwrap())));
+        assert_eq!(CMD_CO.decode(b"CO020000;"), Ok(ApfParam::OnOff(false)));
+        assert!(CMD_CO.decode(b"CO030051;").is_err());
+        // Contour functions (0, 1) aren't decoded.
+        assert!(CMD_CO.decode(b"CO000001;").is_err());
+        assert!(ApfOffsetHz::try_from(15).is_err());
+        assert!(ApfOffsetHz::try_from(260).is_err());
+        assert_eq!(offset.to_string(), "-40");
+    }
+
+    #[test]
+    fn test_cmd_ky_segments_split_on_words() {
+        let segs = CmdKy::segments("cq cq de ew1abz ew1abz k").unwrap();
+        assert_eq!(segs, vec!["CQ CQ DE EW1ABZ EW1ABZ K"]);
+        let segs = CmdKy::segments("CQ TEST DE EW1ABZ EW1ABZ EW1ABZ TEST").unwrap();
+        assert_eq!(segs, vec!["CQ TEST DE EW1ABZ EW1ABZ", "EW1ABZ TEST"]);
+        assert!(segs.iter().all(|s| s.len() <= KY_MAX_CHARS));
+    }
+
+    #[test]
+    fn test_cmd_ky_segments_hard_split_and_charset() {
+        let long = "A".repeat(30);
+        let segs = CmdKy::segments(&long).unwrap();
+        assert_eq!(segs, vec!["A".repeat(24), "A".repeat(6)]);
+        assert!(CmdKy::segments("5NN TU 73?").is_ok());
+        // Characters the keyer can't send are rejected, not dropped.
+        assert!(CmdKy::segments("CQ #1").is_err());
+        assert!(CmdKy::segments("CQ ;").is_err());
+    }
+
+    #[test]
+    fn test_cmd_ky_set_wire_format() {
+        assert_eq!(CMD_KY.set("CQ DE EW1ABZ").unwrap().as_slice(), b"KY0CQ DE EW1ABZ;");
+        assert!(CMD_KY.set("").is_err());
+        assert!(CMD_KY.set(&"A".repeat(25)).is_err());
+    }
+
+    #[test]
+    fn test_cmd_ks_wire_format() {
+        assert_eq!(CMD_KS.set(25).unwrap().as_slice(), b"KS025;");
+        assert!(CMD_KS.set(3).is_err());
+        assert_eq!(CMD_KS.decode(b"KS020;"), Ok(20));
+        assert!(CMD_KS.decode(b"KS099;").is_err());
+    }
+
+    #[test]
+    fn test_cmd_km_wire_format() {
+        assert_eq!(CMD_KM.read(3).unwrap().as_slice(), b"KM3;");
+        assert_eq!(CMD_KM.set(1, "cq test ew1abz").unwrap().as_slice(), b"KM1CQ TEST EW1ABZ;");
+        assert_eq!(CMD_KM.set(2, "").unwrap().as_slice(), b"KM2 ;");
+        assert!(CMD_KM.read(0).is_err());
+        assert!(CMD_KM.set(6, "CQ").is_err());
+        assert!(CMD_KM.set(1, &"A".repeat(51)).is_err());
+        assert!(CMD_KM.set(1, "CQ #").is_err());
+    }
+
+    #[test]
+    fn test_cmd_km_decode_variable_length() {
+        let reply = CMD_KM.decode(b"KM2TU 5NN;").unwrap();
+        assert_eq!(reply, KmReply { slot: 2, text: "TU 5NN".to_string() });
+        // Empty memory and space-padded replies both decode.
+        assert_eq!(CMD_KM.decode(b"KM5;").unwrap().text, "");
+        assert_eq!(CMD_KM.decode(b"KM4TEST   ;").unwrap().text, "TEST");
+        assert!(CMD_KM.decode(b"KM9TEST;").is_err());
+        assert!(CMD_KM.decode(b"KY2TEST;").is_err());
+    }
+
+    #[test]
+    fn test_cmd_pb_lm_wire_format() {
+        assert_eq!(CMD_PB.read().as_slice(), b"PB0;");
+        assert_eq!(CMD_PB.play(1).unwrap().as_slice(), b"PB01;");
+        assert_eq!(CMD_PB.stop().as_slice(), b"PB00;");
+        assert!(CMD_PB.play(0).is_err());
+        assert!(CMD_PB.play(6).is_err());
+        assert_eq!(CMD_PB.decode(b"PB03;"), Ok(Some(3)));
+        assert_eq!(CMD_PB.decode(b"PB00;"), Ok(None));
+        assert!(CMD_PB.decode(b"PB06;").is_err());
+        assert_eq!(CMD_LM.record(5).unwrap().as_slice(), b"LM05;");
+        assert_eq!(CMD_LM.stop().as_slice(), b"LM00;");
+        assert_eq!(CMD_LM.decode(b"LM02;"), Ok(Some(2)));
+        assert!(CMD_LM.decode(b"PB02;").is_err());
+    }
+
+    #[test]
+    fn test_cmd_dt_wire_format() {
+        assert_eq!(CMD_DT.set_date(2026, 3, 9).unwrap().as_slice(), b"DT020260309;");
+        assert_eq!(CMD_DT.set_time(7, 5, 0).unwrap().as_slice(), b"DT1070500;");
+        assert_eq!(CMD_DT.set_utc_offset(-330).unwrap().as_slice(), b"DT2-0530;");
+        assert_eq!(CMD_DT.set_utc_offset(60).unwrap().as_slice(), b"DT2+0100;");
+        assert!(CMD_DT.set_date(2026, 13, 1).is_err());
+        assert!(CMD_DT.set_time(24, 0, 0).is_err());
+        assert!(CMD_DT.set_utc_offset(15 * 60).is_err());
+    }
+
+    #[test]
+    fn test_cmd_dt_decode() {
+        assert_eq!(CMD_DT.read(DtField::Time).as_slice(), b"DT1;");
+        assert_eq!(CMD_DT.decode(DtField::Time, b"DT1235959;").unwrap(), "235959");
+        assert_eq!(CMD_DT.decode(DtField::UtcOffset, b"DT2+0100;").unwrap(), "+0100");
+        // Reply for a different field is rejected.
+        assert!(CMD_DT.decode(DtField::Date, b"DT120260309;").is_err());
+    }
+
+    #[test]
+    fn test_cmd_zi_wire_format() {
+        assert_eq!(CMD_ZI.set(Side::Main).as_slice(), b"ZI0;");
+        assert_eq!(CMD_ZI.set(Side::Sub).as_slice(), b"ZI1;");
+    }
+
+    #[test]
+    fn test_cmd_bs_wire_format() {
+        assert_eq!(CMD_BS.set(Side::Main, Band::M20).as_slice(), b"BS005;");
+        assert_eq!(CMD_BS.set(Side::Sub, Band::Cm70).as_slice(), b"BS116;");
+    }
+
+    #[test]
+    fn test_band_from_str() {
+        assert_eq!(Band::try_from("20m"), Ok(Band::M20));
+        assert_eq!(Band::try_from("70CM"), Ok(Band::Cm70));
+        assert_eq!(Band::try_from("air"), Ok(Band::Air));
+        assert!(Band::try_from("11m").is_err());
+        for band in BANDS {
+            assert_eq!(Band::try_from(band.to_string().as_str()), Ok(band));
+        }
+    }
+
+    #[test]
+    fn test_cmd_bu_bd_up_dn_wire_format() {
+        assert_eq!(band_step(Side::Main, Direction::Up).as_slice(), b"BU0;");
+        assert_eq!(band_step(Side::Sub, Direction::Down).as_slice(), b"BD1;");
+        assert_eq!(mic_step(Direction::Up).as_slice(), b"UP;");
+        assert_eq!(mic_step(Direction::Down).as_slice(), b"DN;");
+        assert_eq!(Direction::try_from("Next"), Ok(Direction::Up));
+        assert_eq!(Direction::try_from("prev"), Ok(Direction::Down));
+        assert!(Direction::try_from("left").is_err());
+    }
+
+    #[test]
+    fn test_cmd_ch_wire_format() {
+        assert_eq!(CMD_CH.set(Direction::Up).as_slice(), b"CH0;");
+        assert_eq!(CMD_CH.set(Direction::Down).as_slice(), b"CH1;");
+    }
+
+    #[test]
+    fn test_cmd_fa_read_decode() {
+        assert_eq!(CMD_FA.read().as_slice(), b"FA;");
+        assert_eq!(CMD_FA.decode(b"FA014074000;").map(|f| f.to_u32()), Ok(14_074_000));
+        assert!(CMD_FA.decode(b"FA14074000;").is_err());
+    }
+
+    #[test]
+    fn test_cmd_sc_wire_format() {
+        assert_eq!(CMD_SC.set(Side::Main, ScanState::Up).as_slice(), b"SC01;");
+        assert_eq!(CMD_SC.set(Side::Main, Direction::Down.into()).as_slice(), b"SC02;");
+        assert_eq!(CMD_SC.set(Side::Sub, ScanState::Off).as_slice(), b"SC10;");
+        assert_eq!(CMD_SC.read(Side::Main).as_slice(), b"SC0;");
+        assert_eq!(CMD_SC.decode(b"SC01;"), Ok(ScanState::Up));
+        assert_eq!(CMD_SC.decode(b"SC00;"), Ok(ScanState::Off));
+        assert!(CMD_SC.decode(b"SC03;").is_err());
+    }
+
+    #[test]
+    fn test_cmd_lk_wire_format() {
+        assert_eq!(CMD_LK.read().as_slice(), b"LK;");
+        assert_eq!(CMD_LK.set(true).as_slice(), b"LK1;");
+        assert_eq!(CMD_LK.set(false).as_slice(), b"LK0;");
+        assert_eq!(CMD_LK.decode(b"LK1;"), Ok(true));
+        assert!(CMD_LK.decode(b"LK2;").is_err());
+    }
+
+    #[test]
+    fn test_cmd_ps_wire_format() {
+        assert_eq!(CMD_PS.read().as_slice(), b"PS;");
+        assert_eq!(CMD_PS.wake().as_slice(), b"PS;");
+        assert_eq!(CMD_PS.set(true).as_slice(), b"PS1;");
+        assert_eq!(CMD_PS.set(false).as_slice(), b"PS0;");
+        assert_eq!(CMD_PS.decode(b"PS1;"), Ok(true));
+        assert!(CMD_PS.decode(b"").is_err());
+    }
+
+    #[test]
+    fn test_vfo_op_wire_format() {
+        assert_eq!(vfo_op(VfoOp::AToB).as_slice(), b"AB;");
+        assert_eq!(vfo_op(VfoOp::BToA).as_slice(), b"BA;");
+        assert_eq!(vfo_op(VfoOp::Swap).as_slice(), b"SV;");
+        assert_eq!(VfoOp::try_from("copy"), Ok(VfoOp::AToB));
+        assert_eq!(VfoOp::try_from("B-to-A"), Ok(VfoOp::BToA));
+        assert!(VfoOp::try_from("merge").is_err());
+    }
+
+    #[test]
+    fn test_cmd_vm_read_toggle() {
+        assert_eq!(CMD_VM.read(Side::Main).as_slice(), b"VM0;");
+        assert_eq!(CMD_VM.decode(b"VM011;"), Ok(VmMode::Memory));
+        assert_eq!(CMD_VM.decode(b"VM100;"), Ok(VmMode::Vfo));
+        assert!(CMD_VM.decode(b"VM099;").is_err());
+        assert_eq!(CMD_VM.toggle(Side::Main, VmMode::Vfo).as_slice(), b"VM011;");
+        assert_eq!(CMD_VM.toggle(Side::Main, VmMode::Pms).as_slice(), b"VM000;");
+    }
+
+    #[test]
+    fn test_cmd_fr_ft_wire_format() {
+        assert_eq!(CMD_FR.read().as_slice(), b"FR;");
+        assert_eq!(CMD_FR.set(Side::Main).as_slice(), b"FR0;");
+        assert_eq!(CMD_FT.set(Side::Sub).as_slice(), b"FT1;");
+        assert_eq!(CMD_FT.decode(b"FT1;"), Ok(Side::Sub));
+        assert!(CMD_FR.decode(b"FR2;").is_err());
+        assert_eq!(Side::try_from("b"), Ok(Side::Sub));
+        assert_eq!(Side::try_from("Main"), Ok(Side::Main));
+        assert!(Side::try_from("left").is_err());
+    }
+
+    #[test]
+    fn test_cat_command_macro() {
+        assert_eq!(CMD_FS.read(Side::Sub).as_slice(), b"FS1;");
+        assert_eq!(CMD_FS.set(Side::Main, true).as_slice(), b"FS01;");
+        assert_eq!(CMD_FS.decode(b"FS11;"), Ok(true));
+        assert_eq!(CMD_FS.decode(b"FS21;").unwrap_err().field, Some("side"));
+        assert_eq!(CMD_FS.decode(b"FS1;").unwrap_err().expected_len, 5);
+        let f = FrequencyHz::try_from(&b"014074000"[..]).unwrap();
+        assert_eq!(CMD_FB.set(f).as_slice(), b"FB014074000;");
+        assert_eq!(CMD_FB.decode(b"FB014074000;"), Ok(f));
+        assert_eq!(CMD_SV.set().as_slice(), b"SV;");
+    }
+
+    #[test]
+    fn test_decode_error() {
+        let e = CMD_FA.decode(b"FA;").unwrap_err();
+        assert_eq!((e.code, e.expected_len, e.field), (['F', 'A'], 12, None));
+        assert_eq!(e.to_string(), "Can't decode FA reply: expected 12 bytes, got 3 [46 41 3B] \"FA;\"");
+        let e = CMD_FA.decode(b"FA01450000X;").unwrap_err();
+        assert_eq!(e.field, Some("frequency"));
+        assert_eq!(e.frame, b"FA01450000X;");
+        assert!(e.to_string().starts_with("Can't decode FA reply: bad frequency field [46 41 30"));
+        assert_eq!(CMD_TX.decode(b"TX7;").unwrap_err().field, Some("on/off"));
+    }
+
+    #[test]
+    fn test_cmd_ve_wire_format() {
+        assert_eq!(CMD_VE.read().as_slice(), b"VE0;");
+        let v = CMD_VE.decode(b"VE00108;").unwrap();
+        assert_eq!(v, MIN_CAT_FIRMWARE);
+        assert_eq!(v.to_string(), "1.08");
+        assert_eq!(CMD_VE.decode(b"VE00215;").unwrap().to_string(), "2.15");
+        assert!(CMD_VE.decode(b"VE00105;").unwrap() < MIN_CAT_FIRMWARE);
+        assert!(FirmwareVersion::new(1, 10) > FirmwareVersion::new(1, 9));
+        assert!(CMD_VE.decode(b"VE10108;").is_err());
+        assert!(CMD_VE.decode(b"VE0108;").is_err());
+    }
+
+    #[test]
+    fn test_cmd_vs_wire_format() {
+        assert_eq!(CMD_VS.read().as_slice(), b"VS;");
+        assert_eq!(CMD_VS.set(Side::Sub).as_slice(), b"VS1;");
+        assert_eq!(CMD_VS.decode(b"VS0;"), Ok(Side::Main));
+        assert!(CMD_VS.decode(b"VS2;").is_err());
+        assert!(CMD_VS.decode(b"FR0;").is_err());
+    }
+
+    #[test]
+    fn test_cmd_fb_st_wire_format() {
+        let f = FrequencyHz::try_from(14_025_000).unwrap();
+        assert_eq!(CMD_FB.set(f).as_slice(), b"FB014025000;");
+        assert_eq!(CMD_FB.decode(b"FB014030000;").map(|f| f.to_u32()), Ok(14_030_000));
+        assert_eq!(CMD_ST.set(true).as_slice(), b"ST1;");
+        assert_eq!(CMD_ST.decode(b"ST0;"), Ok(false));
+    }
+
+    #[test]
+    fn test_clarifier_commands_wire_format() {
+        assert_eq!(CMD_RT.set(true).as_slice(), b"RT1;");
+        assert_eq!(CMD_XT.decode(b"XT0;"), Ok(false));
+        assert_eq!(CMD_RC.set().as_slice(), b"RC;");
+        assert_eq!(clar_step(100).unwrap().as_slice(), b"RU0100;");
+        assert_eq!(clar_step(-50).unwrap().as_slice(), b"RD0050;");
+        assert!(clar_step(10_000).is_err());
+    }
+
+    #[test]
+    fn test_cmd_da_wire_format() {
+        let d = Dimmer::try_from("5/12").unwrap();
+        assert_eq!(CMD_DA.set(d).as_slice(), b"DA0512;");
+        assert_eq!(CMD_DA.decode(b"DA2000;"), Ok(Dimmer::new(20, 0).unwrap()));
+        assert!(CMD_DA.decode(b"DA2100;").is_err());
+        assert!(Dimmer::try_from("12").is_err());
+        assert_eq!(d.to_string(), "5/12");
+    }
+
+    #[test]
+    fn test_cmd_ss_wire_format() {
+        let level = ScopeRefLevel::try_from("-12.5").unwrap();
+        assert_eq!(CMD_SS.set(Side::Main, ScopeParam::RefLevel(level)).as_slice(), b"SS04-12.5;");
+        let span = ScopeSpan::try_from(100).unwrap();
+        assert_eq!(CMD_SS.set(Side::Main, ScopeParam::Span(span)).as_slice(), b"SS056;");
+        assert_eq!(CMD_SS.set(Side::Sub, ScopeParam::Mode(ScopeMode::Fix)).as_slice(), b"SS162;");
+        assert_eq!(CMD_SS.read(Side::Main, ScopeFunction::Span).as_slice(), b"SS05;");
+        assert_eq!(CMD_SS.decode(b"SS04+03.0;"), Ok(ScopeParam::RefLevel(ScopeRefLevel::try_from("3").unwrap())));
+        assert_eq!(CMD_SS.decode(b"SS059;"), Ok(ScopeParam::Span(ScopeSpan::try_from(1000).unwrap())));
+        assert_eq!(CMD_SS.decode(b"SS060;"), Ok(ScopeParam::Mode(ScopeMode::Center)));
+        assert!(CMD_SS.decode(b"SS063;").is_err());
+        assert!(ScopeRefLevel::try_from("1.2").is_err());
+        assert!(ScopeRefLevel::try_from("31").is_err());
+        assert!(ScopeSpan::try_from(3).is_err());
+    }
+
+    #[test]
+    fn test_cmd_ri_decode() {
+        assert_eq!(CMD_RI.read().as_slice(), b"RI;");
+        let flags = CMD_RI.decode(b"RI0001010;").unwrap();
+        assert!(flags.contains(StatusFlags::TX));
+        assert!(flags.contains(StatusFlags::MAIN_BUSY));
+        assert!(!flags.is_fault());
+        assert_eq!(flags.to_string(), "TX MAIN-BUSY");
+        assert!(CMD_RI.decode(b"RI1000000;").unwrap().is_fault());
+        assert_eq!(CMD_RI.decode(b"RI0000000;").unwrap().to_string(), "-");
+        assert!(CMD_RI.decode(b"RI000000;").is_err());
+        assert!(CMD_RI.decode(b"RI0000002;").is_err());
+    }
+
+    #[test]
+    fn test_cmd_cs_wire_format() {
+        assert_eq!(CMD_CS.read().as_slice(), b"CS;");
+        assert_eq!(CMD_CS.set(true).as_slice(), b"CS1;");
+        assert_eq!(CMD_CS.decode(b"CS0;"), Ok(false));
+        assert!(CMD_CS.decode(b"CSX;").is_err());
+    }
+
+    #[test]
+    fn test_cmd_ms_wire_format() {
+        assert_eq!(CMD_MS.set(TxMeter::Comp).as_slice(), b"MS0;");
+        assert_eq!(CMD_MS.set(TxMeter::Swr).as_slice(), b"MS3;");
+        assert_eq!(CMD_MS.decode(b"MS2;"), Ok(TxMeter::Po));
+        assert!(CMD_MS.decode(b"MS6;").is_err());
+        assert_eq!(TxMeter::try_from("alc"), Ok(TxMeter::Alc));
+        assert!(TxMeter::try_from("S").is_err());
+    }
+
+    #[test]
+    fn test_cmd_rm_wire_format() {
+        assert_eq!(CMD_RM.read(Meter::Swr).as_slice(), b"RM6;");
+        assert_eq!(CMD_RM.decode(Meter::Swr, b"RM6048;"), Ok(48));
+        // Reply for a different meter is rejected.
+        assert!(CMD_RM.decode(Meter::Swr, b"RM5048;").is_err());
+        assert_eq!(swr_from_raw(0), 1.0);
+        assert_eq!(swr_from_raw(48), 1.5);
+        assert_eq!(swr_from_raw(64), 1.75);
+        assert_eq!(swr_from_raw(120), 3.0);
+        assert!(swr_from_raw(255).is_infinite());
+    }
+
+    #[test]
+    fn test_cmd_pc_wire_format() {
+        assert_eq!(CMD_PC.read().as_slice(), b"PC;");
+        assert_eq!(CMD_PC.set(RfPower { amplifier: Amplifier::FieldHead, watts: 5 }).as_slice(), b"PC1005;");
+        assert_eq!(CMD_PC.set(RfPower { amplifier: Amplifier::Spa1, watts: 100 }).as_slice(), b"PC2100;");
+        assert_eq!(CMD_PC.decode(b"PC1010;"), Ok(RfPower { amplifier: Amplifier::FieldHead, watts: 10 }));
+        assert_eq!(CMD_PC.decode(b"PC3010;").unwrap_err().field, Some("amplifier"));
+        assert!(CMD_PC.decode(b"PC10X0;").is_err());
+    }
+
+    #[test]
+    fn test_cmd_ac_wire_format() {
+        assert_eq!(CMD_AC.read().as_slice(), b"AC;");
+        assert_eq!(CMD_AC.set(TunerState::On).as_slice(), b"AC001;");
+        assert_eq!(CMD_AC.set(TunerState::Tuning).as_slice(), b"AC002;");
+        assert_eq!(CMD_AC.decode(b"AC000;"), Ok(TunerState::Off));
+        assert!(CMD_AC.decode(b"AC003;").is_err());
+    }
+
+    #[test]
+    fn test_cmd_an_wire_format() {
+        assert_eq!(CMD_AN.read(Side::Main).as_slice(), b"AN0;");
+        assert_eq!(CMD_AN.set(Side::Sub, Antenna::Ant2).as_slice(), b"AN12;");
+        assert_eq!(CMD_AN.decode(b"AN01;"), Ok(Antenna::Ant1));
+        assert!(CMD_AN.decode(b"AN03;").is_err());
+        assert!(CMD_AN.decode(b"AN0;").is_err());
+        assert_eq!(Antenna::try_from("ANT 2"), Ok(Antenna::Ant2));
+        assert_eq!(Antenna::try_from("1"), Ok(Antenna::Ant1));
+        assert!(Antenna::try_from("3").is_err());
+    }
+
+    #[test]
+    fn test_cmd_fs_wire_format() {
+        assert_eq!(CMD_FS.read(Side::Main).as_slice(), b"FS0;");
+        assert_eq!(CMD_FS.set(Side::Sub, true).as_slice(), b"FS11;");
+        assert_eq!(CMD_FS.decode(b"FS01;"), Ok(true));
+        assert!(CMD_FS.decode(b"FS0;").is_err());
+    }
+
+    #[test]
+    fn test_match_replies() {
+        let frames = vec![b"MR00001;".to_vec(), b"MT00001;".to_vec()];
+        let replies = match_replies(&frames, b"MR00001014074000+000000200000;MT00001HOME            ;");
+        assert_eq!(replies[0], b"MR00001014074000+000000200000;");
+        assert_eq!(replies[1], b"MT00001HOME            ;");
+        // Empty channel: both commands fail.
+        let replies = match_replies(&frames, b"?;?;");
+        assert_eq!(replies, vec![b"?;".to_vec(), b"?;".to_vec()]);
+        // No tag reply at all, and a trailing partial frame.
+        let replies = match_replies(&frames, b"MR00001;MT0");
+        assert_eq!(replies[0], b"MR00001;");
+        assert!(replies[1].is_empty());
+        // An Auto Information frame takes no slot: the tag is still missing.
+        let replies = match_replies(&frames, b"FA014074000;MR00001014074000+000000200000;");
+        assert_eq!(replies[0], b"MR00001014074000+000000200000;");
+        assert!(replies[1].is_empty());
+    }
+
+    #[test]
+    fn test_is_error_reply() {
+        assert!(is_error_reply(b"?;"));
+        assert!(!is_error_reply(b"MR?;"));
+        assert!(!is_error_reply(b""));
+    }
+
+    #[test]
+    fn test_reply_complete() {
+        assert!(reply_complete(b"KS;", b"KS020;"));
+        assert!(reply_complete(b"MR00001;", b"?;"));
+        assert!(!reply_complete(b"KS;", b"KS02"));
+        // Shorter than the query: not the reply we're waiting for.
+        assert!(!reply_complete(b"MT00001;", b"MT;"));
+        assert!(!reply_complete(b"KS;", b"KP10;"));
+        assert!(reply_complete(b"KS;", b"KP10;KS020;"));
+    }
+
+    #[test]
+    fn test_memory_channel_five_mhz() {
+        assert_eq!(MemoryChannel::try_from(&b"50001"[..]), Ok(MemoryChannel::FiveMHzBand(1)));
+        assert_eq!(MemoryChannel::try_from(&b"50020"[..]), Ok(MemoryChannel::FiveMHzBand(20)));
+        assert!(MemoryChannel::try_from(&b"50000"[..]).is_err());
+        assert!(MemoryChannel::try_from(&b"50021"[..]).is_err());
+        assert!(MemoryChannel::try_from(&b"51001"[..]).is_err());
+        assert_eq!(MemoryChannel::FiveMHzBand(7).to_chars(), Ok(['5', '0', '0', '0', '7']));
+        assert_eq!(MemoryChannel::FiveMHzBand(12).to_string(), Ok("50012".to_string()));
+        assert!(MemoryChannel::FiveMHzBand(21).to_chars().is_err());
+    }
+
+    #[test]
+    fn test_memory_channel_from_bytes() {
+        assert_eq!(MemoryChannel::try_from(&b"00012"[..]), Ok(MemoryChannel::Mem(12)));
+        assert_eq!(
+            MemoryChannel::try_from(&b"P-10U"[..]),
+            Ok(MemoryChannel::Pms(PmsChannel { slot: 10, lower_upper: PmsLowerUpper::Upper }))
+        );
+        assert_eq!(MemoryChannel::try_from(&b"EMGCH"[..]), Ok(MemoryChannel::EmergencyChannel));
+        assert!(MemoryChannel::try_from(&b"0001"[..]).is_err());
+        assert!(MemoryChannel::try_from(&b"P-1AL"[..]).is_err());
+    }
+
+    #[test]
+    fn test_cmd_mt_decode_borrows_tag() {
+        let rx = b"MT00001HOME RPT    ;";
+        assert_eq!(CMD_MT.decode(rx), Ok("HOME RPT    "));
+        assert!(CMD_MT.decode(b"MT00001HOME;").is_err());
+    }
+
+    #[test]
+    fn test_find_reply_resyncs() {
+        assert_eq!(find_reply(b"KS;", b"KS020;"), Some(&b"KS020;"[..]));
+        // A stale reply ahead of ours is skipped.
+        assert_eq!(find_reply(b"KS;", b"FA014074000;KS020;"), Some(&b"KS020;"[..]));
+        // Line noise running into the reply is dropped.
+        assert_eq!(find_reply(b"KS;", b"\x00\xfeKS020;"), Some(&b"KS020;"[..]));
+        assert_eq!(find_reply(b"MR00001;", b"\xff?;"), Some(&b"?;"[..]));
+        assert_eq!(find_reply(b"KS;", b"\x00\xfe;KP10;"), None);
+    }
+
+    #[test]
+    fn test_find_reply_needs_the_same_parameters() {
+        let five = b"MR00005014074000+000000200000;";
+        let six = b"MR00006007074000+000000200000;";
+        // A late reply for the channel before isn't the answer.
+        assert_eq!(find_reply(b"MR00006;", five), None);
+        assert!(!reply_complete(b"MR00006;", five));
+        assert_eq!(find_reply(b"MR00006;", &[&five[..], six].concat()), Some(&six[..]));
+        // Nor is a stale error reply ahead of the real one.
+        assert_eq!(find_reply(b"MR00006;", &[&b"?;"[..], six].concat()), Some(&six[..]));
+        assert_eq!(find_reply(b"MR00006;", &[&five[..], b"?;"].concat()), Some(&b"?;"[..]));
+        assert_eq!(find_reply(b"MD0;", b"MD13;"), None);
+        assert_eq!(find_reply(b"MD0;", b"MD13;MD02;"), Some(&b"MD02;"[..]));
+        let frames = vec![b"MR00006;".to_vec(), b"MT00006;".to_vec()];
+        let replies = match_replies(&frames, &[&five[..], b"MT00005HOME            ;"].concat());
+        assert!(replies.iter().all(Vec::is_empty));
+    }
+
+    #[test]
+    fn test_match_replies_skips_noise() {
+        let frames = vec![b"MR00001;".to_vec(), b"MT00001;".to_vec()];
+        let replies = match_replies(&frames, b"KS020;\x00MR00001014074000+000000C00000;MT00001HOME RPT    ;");
+        assert_eq!(replies[0], b"MR00001014074000+000000C00000;");
+        assert_eq!(replies[1], b"MT00001HOME RPT    ;");
+    }
+
+    #[test]
+    fn test_region_tx_coverage() {
+        let f = |hz| FrequencyHz::try_from(hz).unwrap();
+        assert!(Region::Usa.can_transmit(f(147_000_000)));
+        assert!(!Region::Exp.can_transmit(f(147_000_000)));
+        assert!(Region::Exp.can_transmit(f(433_500_000)));
+        assert!(!Region::Exp.can_transmit(f(445_000_000)));
+        // Receive-only: inside RX coverage but no amateur allocation.
+        assert!(Region::Usa.can_receive(f(118_100_000)));
+        assert!(!Region::Usa.can_transmit(f(118_100_000)));
+        assert!(!Region::Usa.can_receive(f(255_250_000)));
+    }
+
+    #[test]
+    fn test_region_from_str() {
+        assert_eq!(Region::try_from("usa"), Ok(Region::Usa));
+        assert_eq!(Region::try_from("EXP"), Ok(Region::Exp));
+        assert!(Region::try_from("jp").is_err());
+        assert_eq!(Region::default().to_string(), "USA");
+    }
+
+    #[test]
+    fn test_fix_tag() {
+        assert_eq!(fix_tag("HOME"), "HOME        ");
+        assert_eq!(fix_tag("REPEATER NORTH"), "REPEATER NOR");
+        assert_eq!(fix_tag("A;B\u{e9}C"), "ABC         ");
+        assert!(is_tag_char('~') && !is_tag_char(';') && !is_tag_char('\t'));
+        assert_eq!(CMD_MT.set(MemoryChannel::Mem(1), "HOME;".to_string()).unwrap().as_slice(), b"MT00001HOME        ;");
+    }
+
+    #[test]
+    fn test_cmd_md_read_decode() {
+        assert_eq!(CMD_MD.read(Side::Main).as_slice(), b"MD0;");
+        assert_eq!(CMD_MD.decode(b"MD02;"), Ok(Mode::Usb));
+        assert_eq!(CMD_MD.decode(b"MD1B;"), Ok(Mode::FmN));
+        assert!(CMD_MD.decode(b"MD0G;").is_err());
+    }
+
+    #[test]
+    fn test_cmd_tx_wire_format() {
+        assert_eq!(CMD_TX.set(true).as_slice(), b"TX1;");
+        assert_eq!(CMD_TX.read().as_slice(), b"TX;");
+        assert_eq!(CMD_TX.decode(b"TX0;"), Ok(false));
+        assert_eq!(CMD_TX.decode(b"TX2;"), Ok(true));
+        assert!(CMD_TX.decode(b"TX3;").is_err());
+    }
+}
+
+// Round-trip properties: anything the radio can send decodes and encodes
+// back to the same bytes. These pin the wire format for every field of an
+// MR frame.
+#[cfg(test)]
+mod proptests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn channel_wire() -> impl Strategy<Value = String> {
+        prop_oneof![
+            Just("00000".to_string()),
+            (1u8..=20).prop_map(|band| format!("500{:02}", band)),
+            (1u16..=999).prop_map(|ch| format!("{:05}", ch)),
+            (1u8..=50, prop::bool::ANY).prop_map(|(slot, upper)| format!("P-{:02}{}", slot, if upper { 'U' } else { 'L' })),
+            Just("EMGCH".to_string()),
+        ]
+    }
+
+    fn mode_char() -> impl Strategy<Value = char> {
+        prop::sample::select("0123456789ABCDEFHI".chars().collect::<Vec<_>>())
+    }
+
+    proptest! {
+        #[test]
+        fn memory_channel_round_trips(wire in channel_wire()) {
+            let ch = MemoryChannel::try_from(wire.as_bytes()).unwrap();
+            prop_assert_eq!(ch.to_string().unwrap(), wire);
+        }
+
+        #[test]
+        fn frequency_round_trips(hz in 0u32..1_000_000_000) {
+            let wire = format!("{:09}", hz);
+            let f = FrequencyHz::try_from(wire.as_bytes()).unwrap();
+            prop_assert_eq!(f.to_u32(), hz);
+            prop_assert_eq!(String::from(f), wire);
+        }
+
+        #[test]
+        fn clarifier_offset_round_trips(steps in -999i16..=999) {
+            let wire = format!("{:+05}", steps * 10);
+            let offset = ClarifierOffsetHz::try_from(wire.as_bytes()).unwrap();
+            prop_assert_eq!(String::from(offset), wire);
+        }
+
+        #[test]
+        fn mode_round_trips(c in mode_char()) {
+            prop_assert_eq!(char::from(Mode::try_from(c).unwrap()), c);
+        }
+
+        #[test]
+        fn sql_type_round_trips(c in prop::char::range('0', '5')) {
+            prop_assert_eq!(char::from(SqlType::try_from(c).unwrap()), c);
+        }
+
+        #[test]
+        fn shift_round_trips(c in prop::char::range('0', '3')) {
+            prop_assert_eq!(char::from(Shift::try_from(c).unwrap()), c);
+        }
+
+        // An MR reply decodes to a MemoryReadWrite that MW encodes to the
+        // same fields. MR never reports ARS (see Shift), so the shift digit
+        // stays in 0–2.
+        #[test]
+        fn mr_frame_round_trips(
+            ch in channel_wire(),
+            hz in 0u32..1_000_000_000,
+            clar in -999i16..=999,
+            rx_clar in prop::char::range('0', '1'),
+            tx_clar in prop::char::range('0', '1'),
+            mode in mode_char(),
+            ch_type in prop::char::range('0', '5'),
+            sql in prop::char::range('0', '5'),
+            shift in prop::char::range('0', '2'),
+        ) {
+            let fields = format!("{}{:09}{:+05}{}{}{}{}{}00{}", ch, hz, clar * 10, rx_clar, tx_clar, mode, ch_type, sql, shift);
+            let mem = CMD_MR.decode(format!("MR{};", fields).as_bytes()).unwrap();
+            prop_assert_eq!(CMD_MW.set(mem).unwrap(), format!("MW{};", fields).into_bytes());
+        }
+    }
 }
@@ -0,0 +1,90 @@
+// Memory groups. The FTX-1 files every memory channel under a band group
+// (M-HF, M-50, M-AIR, M-VHF, M-UHF) by its frequency, and the memory list
+// can be limited to one group. The "Group" column of a memory CSV shows
+// it, and `--group` limits --read-radio, --write-radio, --print and
+// --export-hamlib to the channels in one group.
+//
+// The band groups follow from the frequency, so there's nothing to write;
+// the user-defined M-GRP flag is a different matter: it isn't in MR/MW or
+// any documented CAT command (see doc/memory-channel-fields.md), so it
+// can only be set on the radio. Group edges are half-open [start, end) in
+// Hz and follow the band keys; the manual doesn't give them exactly.
+
+use core::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MemoryGroup {
+    Hf,
+    Mhz50,
+    Air,
+    Vhf,
+    Uhf,
+}
+
+pub const MEMORY_GROUPS: [MemoryGroup; 5] =
+    [MemoryGroup::Hf, MemoryGroup::Mhz50, MemoryGroup::Air, MemoryGroup::Vhf, MemoryGroup::Uhf];
+
+impl MemoryGroup {
+    /// The group the radio files a channel on `freq_hz` under.
+    pub fn of(freq_hz: u32) -> Self {
+        match freq_hz {
+            0..30_000_000 => MemoryGroup::Hf,
+            30_000_000..108_000_000 => MemoryGroup::Mhz50,
+            108_000_000..137_000_000 => MemoryGroup::Air,
+            137_000_000..400_000_000 => MemoryGroup::Vhf,
+            _ => MemoryGroup::Uhf,
+        }
+    }
+}
+
+/// Accepts the radio's names with or without the "M-" prefix, in any case
+/// ("M-VHF", "vhf", "50").
+impl TryFrom<&str> for MemoryGroup {
+    type Error = ();
+
+    fn try_from(item: &str) -> Result<Self, Self::Error> {
+        let item = item.trim();
+        let name = item.get(..2).filter(|p| p.eq_ignore_ascii_case("M-")).map_or(item, |_| &item[2..]);
+        MEMORY_GROUPS.into_iter().find(|g| g.to_string()[2..].eq_ignore_ascii_case(name)).ok_or(())
+    }
+}
+
+impl fmt::Display for MemoryGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            MemoryGroup::Hf => "M-HF",
+            MemoryGroup::Mhz50 => "M-50",
+            MemoryGroup::Air => "M-AIR",
+            MemoryGroup::Vhf => "M-VHF",
+            MemoryGroup::Uhf => "M-UHF",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_of_frequency() {
+        assert_eq!(MemoryGroup::of(7_074_000), MemoryGroup::Hf);
+        assert_eq!(MemoryGroup::of(29_999_999), MemoryGroup::Hf);
+        assert_eq!(MemoryGroup::of(50_313_000), MemoryGroup::Mhz50);
+        assert_eq!(MemoryGroup::of(118_700_000), MemoryGroup::Air);
+        assert_eq!(MemoryGroup::of(145_500_000), MemoryGroup::Vhf);
+        assert_eq!(MemoryGroup::of(433_500_000), MemoryGroup::Uhf);
+    }
+
+    #[test]
+    fn test_group_names() {
+        for group in MEMORY_GROUPS {
+            assert_eq!(MemoryGroup::try_from(group.to_string().as_str()), Ok(group));
+        }
+        assert_eq!(MemoryGroup::try_from("vhf"), Ok(MemoryGroup::Vhf));
+        assert_eq!(MemoryGroup::try_from("m-air"), Ok(MemoryGroup::Air));
+        assert_eq!(MemoryGroup::try_from("50"), Ok(MemoryGroup::Mhz50));
+        assert_eq!(MemoryGroup::try_from("M-GRP"), Err(()));
+        assert_eq!(MemoryGroup::try_from(""), Err(()));
+    }
+}
@@ -0,0 +1,131 @@
+// Hamlib's names and formats, shared by the rigctld server (--serve) and
+// the memory-CSV exporter (--export-hamlib).
+//
+// The exported file follows the layout of rigctl's `save_channels` /
+// `load_channels` CSV: a header row naming the fields, one channel per
+// row. Tones are in tenths of Hz (88.5 Hz -> 885), DCS codes as numbers,
+// repeater shift as "+", "-" or "None". Fields this tool doesn't track
+// (vfo, width, tuning step, ...) are left out; Hamlib fills in defaults.
+
+use crate::ftx1::{Mode, Shift};
+
+/// Hamlib mode names, in the order `M ?` lists them. Where Hamlib has
+/// one name for two FTX-1 modes, the first listed is used when setting.
+pub const MODES: [(&str, Mode); 17] = [
+    ("USB", Mode::Usb),
+    ("LSB", Mode::Lsb),
+    ("CW", Mode::CwU),
+    ("CWR", Mode::CwL),
+    ("RTTY", Mode::RttyL),
+    ("RTTYR", Mode::RttyU),
+    ("AM", Mode::Am),
+    ("AMN", Mode::AmN),
+    ("FM", Mode::Fm),
+    ("FMN", Mode::FmN),
+    ("WFM", Mode::Wfm),
+    ("PKTLSB", Mode::DataL),
+    ("PKTUSB", Mode::DataU),
+    ("PKTFM", Mode::DataFm),
+    ("PKTFMN", Mode::DataFmN),
+    ("PSK", Mode::Psk),
+    ("C4FM", Mode::C4fmDn),
+];
+
+pub fn hamlib_mode(mode: Mode) -> &'static str {
+    match mode {
+        Mode::C4fmVw => "C4FM",
+        _ => MODES.iter().find(|(_, m)| *m == mode).map_or("FM", |(name, _)| name),
+    }
+}
+
+pub const CSV_HEADER: [&str; 11] = [
+    "num",
+    "channel_desc",
+    "freq",
+    "mode",
+    "split",
+    "tx_freq",
+    "rptr_shift",
+    "ctcss_tone",
+    "ctcss_sql",
+    "dcs_code",
+    "dcs_sql",
+];
+
+/// One memory channel in Hamlib's terms.
+#[derive(Debug, PartialEq)]
+pub struct Channel {
+    pub num: u16,
+    pub desc: String,
+    pub freq: u32,
+    pub mode: Mode,
+    pub tx_freq: Option<u32>,
+    pub shift: Shift,
+    /// CTCSS tone in tenths of Hz, and whether it also opens the squelch.
+    pub ctcss: Option<(u16, bool)>,
+    /// DCS code, and whether it also opens the squelch.
+    pub dcs: Option<(u16, bool)>,
+}
+
+impl Channel {
+    pub fn to_row(&self) -> [String; 11] {
+        let tone = |t: Option<(u16, bool)>| t.map_or(0, |(v, _)| v).to_string();
+        let sql = |t: Option<(u16, bool)>| match t {
+            Some((v, true)) => v.to_string(),
+            _ => "0".to_string(),
+        };
+        let shift = match self.shift {
+            Shift::PlusShift => "+",
+            Shift::MinusShift => "-",
+            Shift::Simplex | Shift::Ars => "None",
+        };
+        [
+            self.num.to_string(),
+            self.desc.trim().to_string(),
+            self.freq.to_string(),
+            hamlib_mode(self.mode).to_string(),
+            if self.tx_freq.is_some() { "on" } else { "off" }.to_string(),
+            self.tx_freq.unwrap_or(0).to_string(),
+            shift.to_string(),
+            tone(self.ctcss),
+            sql(self.ctcss),
+            tone(self.dcs),
+            sql(self.dcs),
+        ]
+    }
+}
+
+/// "88.5" -> 885; None for anything that isn't a tone.
+pub fn tone_tenths(tone: &str) -> Option<u16> {
+    let hz = tone.trim().parse::<f64>().ok()?;
+    (hz > 0.0 && hz < 1000.0).then(|| (hz * 10.0).round() as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hamlib_mode() {
+        assert_eq!(hamlib_mode(Mode::DataU), "PKTUSB");
+        assert_eq!(hamlib_mode(Mode::CwL), "CWR");
+        assert_eq!(hamlib_mode(Mode::C4fmVw), "C4FM");
+    }
+
+    #[test]
+    fn test_channel_row() {
+        let ch = Channel {
+            num: 2,
+            desc: "REPEATER    ".to_string(),
+            freq: 146_940_000,
+            mode: Mode::Fm,
+            tx_freq: None,
+            shift: Shift::MinusShift,
+            ctcss: Some((1035, true)),
+            dcs: None,
+        };
+        assert_eq!(ch.to_row(), ["2", "REPEATER", "146940000", "FM", "off", "0", "-", "1035", "1035", "0", "0"]);
+        assert_eq!(tone_tenths("88.5"), Some(885));
+        assert_eq!(tone_tenths(""), None);
+    }
+}
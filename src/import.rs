@@ -0,0 +1,399 @@
+// Importer for plain frequency lists (`--import-list`): turns lines like
+//
+//   146.520 FM Natl Simplex
+//   146.940 - 100.0 PL Club repeater
+//   7.074 USB FT8
+//
+// or a scanner/web export with a header row (Frequency, Tone, Alpha Tag,
+// Mode, ...) into a memory CSV. Whatever a line doesn't say is filled in:
+// the mode from the frequency, no shift, no tone. Lines that can't be read
+// are reported by number and skipped.
+//
+// On a line without a header, a field is recognised by its shape: the
+// first number is the frequency (MHz when it has a decimal point or is
+// below 1000, otherwise kHz or Hz by size), a mode name is the mode, "+"
+// or "-" (optionally with an offset) is the shift, "88.5", "PL 88.5",
+// "T88.5" or "TSQL 88.5" is a CTCSS tone, "D023N" or "DCS 023" a DCS code.
+// The words left over make the name.
+//
+// With --auto-shift a channel the list gives no shift for gets one when
+// its frequency is a repeater output in the band plan (see bandplan.rs).
+// An offset the radio's repeater-shift menu already uses becomes a plain
+// +/- shift; any other offset is stored as an explicit Split TX frequency.
+
+use crate::ftx1::*;
+use crate::bandplan::BandPlan;
+use crate::hamlib::MODES;
+
+#[derive(Debug, PartialEq)]
+pub struct ListEntry {
+    pub freq: u32,
+    pub mode: Mode,
+    pub shift: Shift,
+    pub squelch: SqlType,
+    /// The tone value for `squelch`: a CTCSS frequency or a DCS code.
+    pub tone: Option<String>,
+    pub name: String,
+    /// The station's callsign, if the list gives one (for {callsign} in
+    /// --auto-tag templates).
+    pub callsign: String,
+}
+
+/// Column names recognised in a header row, lowercase, by field.
+const FREQ_COLUMNS: [&str; 5] = ["frequency", "freq", "output", "rx freq", "frequency output"];
+const MODE_COLUMNS: [&str; 1] = ["mode"];
+const TONE_COLUMNS: [&str; 6] = ["tone", "pl", "pl tone", "ctcss", "tone out", "ctcss/dcs"];
+const SHIFT_COLUMNS: [&str; 4] = ["offset", "duplex", "shift", "offset direction"];
+// In order of preference: the short scanner label fits a 12-character tag.
+const NAME_COLUMNS: [&str; 6] = ["alpha tag", "name", "memory tag", "tag", "label", "description"];
+const CALLSIGN_COLUMNS: [&str; 3] = ["callsign", "call sign", "call"];
+
+struct Columns {
+    freq: usize,
+    mode: Option<usize>,
+    tone: Option<usize>,
+    shift: Option<usize>,
+    name: Option<usize>,
+    callsign: Option<usize>,
+}
+
+impl Columns {
+    fn from_header(fields: &[&str]) -> Option<Self> {
+        let find = |names: &[&str]| {
+            names.iter().find_map(|n| fields.iter().position(|f| f.trim().eq_ignore_ascii_case(n)))
+        };
+        Some(Columns {
+            freq: find(&FREQ_COLUMNS)?,
+            mode: find(&MODE_COLUMNS),
+            tone: find(&TONE_COLUMNS),
+            shift: find(&SHIFT_COLUMNS),
+            name: find(&NAME_COLUMNS),
+            callsign: find(&CALLSIGN_COLUMNS),
+        })
+    }
+}
+
+/// Parses a whole list; returns the channels and, for each skipped line,
+/// its number and why.
+pub fn parse_list(text: &str) -> (Vec<ListEntry>, Vec<(usize, String)>) {
+    let mut entries = Vec::new();
+    let mut skipped = Vec::new();
+    let mut columns: Option<Columns> = None;
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with("//") {
+            continue;
+        }
+        let fields = split_fields(line);
+        if entries.is_empty() && columns.is_none() && parse_freq(fields[0]).is_none() {
+            columns = Columns::from_header(&fields);
+            if columns.is_some() {
+                continue;
+            }
+        }
+        let parsed = match &columns {
+            Some(cols) => parse_columns(&fields, cols),
+            None => parse_line(&fields),
+        };
+        match parsed {
+            Ok(entry) => entries.push(entry),
+            Err(reason) => skipped.push((i + 1, reason)),
+        }
+    }
+    (entries, skipped)
+}
+
+fn split_fields(line: &str) -> Vec<&str> {
+    if line.contains(',') || line.contains('\t') || line.contains(';') {
+        line.split([',', '\t', ';']).map(|f| f.trim().trim_matches('"')).collect()
+    } else {
+        line.split_whitespace().collect()
+    }
+}
+
+fn parse_columns(fields: &[&str], cols: &Columns) -> Result<ListEntry, String> {
+    let get = |col: Option<usize>| col.and_then(|c| fields.get(c)).copied().unwrap_or("").trim();
+    let freq_field = get(Some(cols.freq));
+    let freq = parse_freq(freq_field).ok_or_else(|| format!("'{}' is not a frequency", freq_field))?;
+    let mode = match get(cols.mode) {
+        "" => default_mode(freq),
+        m => parse_mode(m).ok_or_else(|| format!("unknown mode '{}'", m))?,
+    };
+    let (squelch, tone) = parse_tone(get(cols.tone)).unwrap_or((SqlType::CtcssOff, None));
+    let shift = parse_shift(get(cols.shift)).unwrap_or(Shift::Simplex);
+    let entry = finish(freq, mode, shift, squelch, tone, get(cols.name).to_string())?;
+    Ok(ListEntry { callsign: get(cols.callsign).to_uppercase(), ..entry })
+}
+
+fn parse_line(fields: &[&str]) -> Result<ListEntry, String> {
+    let mut freq = None;
+    let mut mode = None;
+    let mut shift = Shift::Simplex;
+    let mut squelch = (SqlType::CtcssOff, None);
+    let mut name: Vec<&str> = Vec::new();
+    let mut callsign = None;
+    let mut i = 0;
+    while i < fields.len() {
+        let field = fields[i];
+        let pair = fields.get(i + 1).map(|next| format!("{} {}", field, next));
+        if freq.is_none() && let Some(hz) = parse_freq(field) {
+            freq = Some(hz);
+        } else if let Some(t) = pair.as_deref().and_then(parse_tone) {
+            squelch = t;
+            i += 1;
+        } else if let Some(t) = parse_tone(field) {
+            squelch = t;
+        } else if let Some(s) = parse_shift(field) {
+            shift = s;
+        } else if mode.is_none() && let Some(m) = parse_mode(field) {
+            mode = Some(m);
+        } else if field.parse::<f64>().is_err() && !field.is_empty() {
+            // Stray numbers (an unsigned offset, a channel number) aren't names.
+            // A callsign stays in the name too.
+            if callsign.is_none() && is_callsign(field) {
+                callsign = Some(field);
+            }
+            name.push(field);
+        }
+        i += 1;
+    }
+    let freq = freq.ok_or("no frequency")?;
+    let mode = mode.unwrap_or_else(|| default_mode(freq));
+    let entry = finish(freq, mode, shift, squelch.0, squelch.1, name.join(" "))?;
+    Ok(ListEntry { callsign: callsign.unwrap_or("").to_string(), ..entry })
+}
+
+/// Whether a word of a free-text line looks like a callsign: "W1AW",
+/// "EW1ABZ", "GB3XX/P". A prefix with a letter, a digit, then one to four
+/// letters; upper case only, so ordinary words aren't taken for one.
+fn is_callsign(word: &str) -> bool {
+    let call = word.split('/').next().unwrap_or("");
+    let Some(digit) = call.rfind(|c: char| c.is_ascii_digit()) else { return false };
+    let (prefix, suffix) = (&call[..digit], &call[digit + 1..]);
+    (1..=3).contains(&prefix.len())
+        && prefix.bytes().all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+        && prefix.bytes().any(|b| b.is_ascii_uppercase())
+        && (1..=4).contains(&suffix.len())
+        && suffix.bytes().all(|b| b.is_ascii_uppercase())
+}
+
+fn finish(freq: u32, mode: Mode, shift: Shift, squelch: SqlType, tone: Option<String>, name: String) -> Result<ListEntry, String> {
+    if !FrequencyHz::try_from(freq).is_ok_and(|f| f.is_in_radio_range()) {
+        return Err(format!("{} Hz is outside the radio's range", freq));
+    }
+    Ok(ListEntry { freq, mode, shift, squelch, tone, name, callsign: String::new() })
+}
+
+/// A frequency in Hz: "146.520", "146.52MHz", "7074 kHz", "14074000".
+pub fn parse_freq(field: &str) -> Option<u32> {
+    let lower = field.trim().to_ascii_lowercase();
+    let (number, scale) = if let Some(n) = lower.strip_suffix("mhz") {
+        (n, 1e6)
+    } else if let Some(n) = lower.strip_suffix("khz") {
+        (n, 1e3)
+    } else if let Some(n) = lower.strip_suffix("hz") {
+        (n, 1.0)
+    } else {
+        (lower.as_str(), 0.0)
+    };
+    let number = number.trim();
+    if !number.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    let value: f64 = number.parse().ok()?;
+    let scale = match scale {
+        0.0 if number.contains('.') || value < 1000.0 => 1e6,
+        0.0 if value < 100_000.0 => 1e3,
+        0.0 => 1.0,
+        s => s,
+    };
+    let hz = (value * scale).round();
+    (hz > 0.0 && hz < u32::MAX as f64).then_some(hz as u32)
+}
+
+/// The CSV's own mode names, Hamlib's, and a few common spellings.
+pub fn parse_mode(field: &str) -> Option<Mode> {
+    let upper = field.to_ascii_uppercase();
+    if let Ok(mode) = Mode::try_from(upper.clone()) {
+        return Some(mode);
+    }
+    if let Some((_, mode)) = MODES.iter().find(|(name, _)| *name == upper) {
+        return Some(*mode);
+    }
+    match upper.as_str() {
+        "NFM" | "FM-NARROW" => Some(Mode::FmN),
+        "DIG" | "DIGI" | "DATA" | "FT8" | "FT4" => Some(Mode::DataU),
+        _ => None,
+    }
+}
+
+fn parse_shift(field: &str) -> Option<Shift> {
+    let (offset, shift) = if let Some(r) = field.strip_prefix('+') {
+        (r, Shift::PlusShift)
+    } else if let Some(r) = field.strip_prefix('-') {
+        (r, Shift::MinusShift)
+    } else {
+        return None;
+    };
+    (offset.is_empty() || offset.parse::<f64>().is_ok()).then_some(shift)
+}
+
+/// A tone in any of the usual spellings; None if the field isn't one.
+fn parse_tone(field: &str) -> Option<(SqlType, Option<String>)> {
+    let upper = field.trim().to_ascii_uppercase();
+    let words: Vec<&str> = upper.split_whitespace().collect();
+    let (keyword, value) = match words.as_slice() {
+        [k @ ("PL" | "T" | "TONE" | "CTCSS" | "TSQL" | "DCS" | "DPL"), v] => (*k, *v),
+        [v, k @ ("PL" | "DPL")] => (*k, *v),
+        [v] => ("", *v),
+        _ => return None,
+    };
+    let dcs = |code: &str| {
+        let code = code.trim_end_matches(['N', 'I']);
+        let value = code.parse::<u16>().ok()?.to_string();
+        CmdCn::tone_code_from_string(ToneType::Dcs, &value).ok()?;
+        Some((SqlType::Dcs, Some(value)))
+    };
+    let ctcss = |hz: &str, squelch: SqlType| {
+        let code = CmdCn::tone_code_from_string(ToneType::Ctcss, hz).ok()?;
+        Some((squelch, Some(CmdCn::tone_code_to_string(ToneType::Ctcss, code).ok()?)))
+    };
+    match keyword {
+        "DCS" | "DPL" => dcs(value),
+        "TSQL" => ctcss(value, SqlType::CtcssEncDec),
+        "PL" | "T" | "TONE" | "CTCSS" => ctcss(value, SqlType::CtcssEnc),
+        _ => match value.strip_prefix('D') {
+            Some(code) if code.starts_with(|c: char| c.is_ascii_digit()) => dcs(code),
+            _ => {
+                let hz = value.strip_prefix('T').or_else(|| value.strip_prefix("PL")).unwrap_or(value);
+                // A bare number only counts when it has the tone's decimal.
+                if !hz.contains('.') {
+                    return None;
+                }
+                ctcss(hz, SqlType::CtcssEnc)
+            }
+        },
+    }
+}
+
+/// The radio's factory repeater-shift menu values by band: a guessed
+/// offset equal to these needs no split.
+const RADIO_OFFSETS: [(u32, u32, u32); 4] = [
+    (28_000_000, 29_700_000, 100_000),
+    (50_000_000, 54_000_000, 1_000_000),
+    (144_000_000, 148_000_000, 600_000),
+    (430_000_000, 450_000_000, 5_000_000),
+];
+
+/// The shift of a repeater with output `freq` under `plan`, and the split
+/// TX frequency when the radio's own offset for the band doesn't fit.
+/// None when `freq` isn't a repeater output.
+pub fn guess_shift(freq: u32, plan: BandPlan) -> Option<(Shift, Option<u32>)> {
+    let offset = plan.repeater_offset(freq)?;
+    let shift = if offset < 0 { Shift::MinusShift } else { Shift::PlusShift };
+    let radio = RADIO_OFFSETS.iter().find(|(lo, hi, _)| (*lo..*hi).contains(&freq)).map(|(_, _, o)| *o);
+    if radio == Some(offset.unsigned_abs()) {
+        return Some((shift, None));
+    }
+    Some((Shift::Simplex, Some(freq.checked_add_signed(offset)?)))
+}
+
+/// The mode a channel on `hz` most likely uses when the list doesn't say.
+pub fn default_mode(hz: u32) -> Mode {
+    match hz {
+        0..1_800_000 => Mode::Am,
+        5_250_000..5_450_000 => Mode::Usb,
+        1_800_000..10_000_000 => Mode::Lsb,
+        10_000_000..29_500_000 => Mode::Usb,
+        108_000_000..137_000_000 => Mode::Am,
+        _ => Mode::Fm,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(freq: u32, mode: Mode, shift: Shift, squelch: SqlType, tone: Option<&str>, name: &str) -> ListEntry {
+        ListEntry { freq, mode, shift, squelch, tone: tone.map(str::to_string), name: name.to_string(), callsign: String::new() }
+    }
+
+    #[test]
+    fn test_parse_freq() {
+        assert_eq!(parse_freq("146.520"), Some(146_520_000));
+        assert_eq!(parse_freq("146.52MHz"), Some(146_520_000));
+        assert_eq!(parse_freq("7074"), Some(7_074_000));
+        assert_eq!(parse_freq("7074 kHz"), Some(7_074_000));
+        assert_eq!(parse_freq("14074000"), Some(14_074_000));
+        assert_eq!(parse_freq("FM"), None);
+    }
+
+    #[test]
+    fn test_parse_tone() {
+        assert_eq!(parse_tone("88.5"), Some((SqlType::CtcssEnc, Some("88.5".to_string()))));
+        assert_eq!(parse_tone("136.5 PL"), Some((SqlType::CtcssEnc, Some("136.5".to_string()))));
+        assert_eq!(parse_tone("TSQL 100"), Some((SqlType::CtcssEncDec, Some("100.0".to_string()))));
+        assert_eq!(parse_tone("D023N"), Some((SqlType::Dcs, Some("23".to_string()))));
+        assert_eq!(parse_tone("DCS 754"), Some((SqlType::Dcs, Some("754".to_string()))));
+        assert_eq!(parse_tone("88.6"), None);
+        assert_eq!(parse_tone("Simplex"), None);
+    }
+
+    #[test]
+    fn test_guess_shift() {
+        assert_eq!(guess_shift(146_940_000, BandPlan::Us), Some((Shift::MinusShift, None)));
+        assert_eq!(guess_shift(444_100_000, BandPlan::Us), Some((Shift::PlusShift, None)));
+        assert_eq!(guess_shift(439_000_000, BandPlan::R1), Some((Shift::Simplex, Some(431_400_000))));
+        assert_eq!(guess_shift(145_650_000, BandPlan::R1), Some((Shift::MinusShift, None)));
+        assert_eq!(guess_shift(146_520_000, BandPlan::Us), None);
+    }
+
+    #[test]
+    fn test_parse_free_text() {
+        let text = "# my list\n146.520 FM Natl Simplex\n146.940 - 100.0 PL Club rptr\n7.074 FT8\n\nno frequency here\n";
+        let (entries, skipped) = parse_list(text);
+        assert_eq!(
+            entries,
+            [
+                entry(146_520_000, Mode::Fm, Shift::Simplex, SqlType::CtcssOff, None, "Natl Simplex"),
+                entry(146_940_000, Mode::Fm, Shift::MinusShift, SqlType::CtcssEnc, Some("100.0"), "Club rptr"),
+                entry(7_074_000, Mode::DataU, Shift::Simplex, SqlType::CtcssOff, None, ""),
+            ]
+        );
+        assert_eq!(skipped, [(6, "no frequency".to_string())]);
+    }
+
+    #[test]
+    fn test_parse_scanner_export() {
+        let text = "Frequency,License,Type,Tone,Alpha Tag,Description,Mode\n\
+                    154.4300,KNFQ123,BM,136.5 PL,FD Dispatch,Fire Dispatch,FMN\n\
+                    118.300,,,,Tower,Airport tower,\n\
+                    abc,,,,Bad,,\n";
+        let (entries, skipped) = parse_list(text);
+        assert_eq!(
+            entries,
+            [
+                entry(154_430_000, Mode::FmN, Shift::Simplex, SqlType::CtcssEnc, Some("136.5"), "FD Dispatch"),
+                entry(118_300_000, Mode::Am, Shift::Simplex, SqlType::CtcssOff, None, "Tower"),
+            ]
+        );
+        assert_eq!(skipped, [(4, "'abc' is not a frequency".to_string())]);
+    }
+
+    #[test]
+    fn test_callsigns() {
+        assert!(is_callsign("W1AW"));
+        assert!(is_callsign("EW1ABZ"));
+        assert!(is_callsign("GB3XX/P"));
+        assert!(is_callsign("9A1A"));
+        assert!(!is_callsign("FT8"));
+        assert!(!is_callsign("70CM"));
+        assert!(!is_callsign("Club"));
+        assert!(!is_callsign("w1aw"));
+
+        let (entries, _) = parse_list("146.940 - 100.0 PL W1AW Club\n");
+        assert_eq!((entries[0].callsign.as_str(), entries[0].name.as_str()), ("W1AW", "W1AW Club"));
+        let (entries, _) = parse_list("Frequency,Call,Name\n147.000,k1abc,Hilltop\n");
+        assert_eq!((entries[0].callsign.as_str(), entries[0].name.as_str()), ("K1ABC", "Hilltop"));
+    }
+}
@@ -1,4 +1,24 @@
 pub mod ftx1;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "files")]
+pub mod bandplan;
+#[cfg(feature = "files")]
+pub mod dialect;
+#[cfg(feature = "files")]
+pub mod fix;
+#[cfg(feature = "files")]
+pub mod groups;
+#[cfg(feature = "files")]
+pub mod hamlib;
+#[cfg(feature = "files")]
+pub mod import;
+#[cfg(feature = "files")]
+pub mod memfile;
+#[cfg(feature = "files")]
+pub mod migrate;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 // Re-export the parsers module from `ftx1` so callers can use `ftdx_1chm::parsers::...`.
 pub use ftx1::parsers;
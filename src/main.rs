@@ -1,19 +1,91 @@
-use clap::Parser;
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches, Parser};
 use comfy_table::presets::{ASCII_FULL_CONDENSED, UTF8_FULL_CONDENSED};
 use comfy_table::{Attribute, Cell, Color, ContentArrangement, Table};
 use indicatif::ProgressBar;
 use log::{debug, error, trace};
-use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
-use std::io;
-use std::iter::zip;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 mod ftx1;
 use ftx1::*;
+use ftx1::menu::{MenuItem, CMD_EX, MENU_ITEMS};
+mod audit;
+mod backup;
+mod bandplan;
+mod bandscan;
+mod checkpoint;
+mod config;
+mod crypt;
+mod daemon;
+mod dialect;
+mod events;
+mod filter;
+mod fix;
+mod groups;
+mod hamlib;
+mod import;
+mod memfile;
+mod migrate;
+mod monitor;
+mod moves;
+mod mqtt;
+mod ports;
+mod queue;
+mod settings;
+mod smeter;
+mod snapshot;
+mod stats;
+mod scanlog;
+mod serve;
+mod summary;
+mod sweep;
+mod sync;
+mod tags;
+mod trace;
+mod transport;
+mod watchdog;
+use transport::{host_port, is_rfcomm, Flrig, RawTcp, Reconnecting, Rfcomm, Rigctld, Transport, FLRIG_SCHEME, READ_TIMEOUT, RIGCTLD_SCHEME, TCP_SCHEME};
+use bandplan::BandPlan;
+use events::{Event, UdpEvents};
+use groups::MemoryGroup;
+use memfile::{check_headers, effective_tone, hamlib_channel, normalize_record, validate_record, CheckOptions, CheckedRecord, CsvRecord, CHANNELS, CSV_COLUMNS};
+use settings::{on_off, parse_on_off, SettingRecord, SETTINGS};
 
 const RX_BUFFER_SIZE: usize = 255;
-const CHANNELS: u16 = 999;
+
+// The MAIN firmware version read while identifying the radio (VE), for
+// reports and require_firmware. None until then, or if VE isn't answered.
+static FIRMWARE: std::sync::Mutex<Option<FirmwareVersion>> = std::sync::Mutex::new(None);
+
+fn firmware() -> Option<FirmwareVersion> {
+    *FIRMWARE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Refuses `what` on firmware older than `min`. Unknown firmware (no VE
+/// reply) is let through: older releases of it don't answer VE at all.
+fn require_firmware(min: FirmwareVersion, what: &str, quiet: bool) -> Result<(), ()> {
+    check_firmware(min, what).map_err(|e| {
+        if !quiet { println!("{}", e); }
+    })
+}
+
+/// require_firmware with the refusal as text, for callers that pass it on.
+fn check_firmware(min: FirmwareVersion, what: &str) -> Result<(), String> {
+    match firmware() {
+        Some(v) if v < min => Err(format!("{} needs MAIN firmware {} or later; this radio has {}.", what, min, v)),
+        _ => Ok(()),
+    }
+}
+
+/// The comment line backups and reports record the firmware in.
+fn firmware_comment() -> Option<String> {
+    firmware().map(|v| format!("# Radio firmware: MAIN {}", v))
+}
 
 /// A simple program to interact with Yaesu FT-DX1 series radios
 #[derive(Parser, Debug)]
@@ -26,17 +98,32 @@ const CHANNELS: u16 = 999;
 Usage:
   ftdx-1chm --read-radio --port /dev/ttyUSB0 --speed 38400 --file output.csv
   ftdx-1chm --write-radio --port /dev/ttyUSB0 --speed 38400 --file input.csv
-  ftdx-1chm --check-data --file data.csv"
+  ftdx-1chm --check-data --file data.csv
+  ftdx-1chm --read-settings --port /dev/ttyUSB0 --file settings.csv
+  ftdx-1chm --write-settings --port /dev/ttyUSB0 --file settings.csv"
 )]
 struct Cli {
-    /// Port to connect to the radio
-    #[arg(short, long, default_value = "/dev/ttyUSB0")]
+    /// Port to connect to the radio: a serial port, `auto` to find the
+    /// radio's CAT port (the default on macOS), tcp://host:port for a
+    /// network serial bridge, rigctld://host[:port] to go through a running
+    /// Hamlib rigctld, or flrig://host[:port] to go through a running flrig
+    #[arg(short, long, default_value = ports::DEFAULT_PORT)]
     port: String,
 
     /// Speed for the serial port
     #[arg(short, long, default_value_t = 38_400)]
     speed: u32,
 
+    /// Take --port, --speed, --region and --file, where not given, from
+    /// the [radio.NAME] profile in the configuration file
+    #[arg(long, value_name = "NAME")]
+    radio: Option<String>,
+
+    /// With --radio: the configuration file to read, instead of
+    /// config.toml in the user's configuration directory
+    #[arg(long, value_name = "PATH", requires = "radio")]
+    config: Option<String>,
+
     /// File to save/read memory data (default for --read-radio: ftx1_YYYYMMDD_HHMMSS.csv)
     #[arg(short, long)]
     file: Option<String>,
@@ -49,6 +136,95 @@ struct Cli {
     #[arg(short = 'w', long, group = "action")]
     write_radio: bool,
 
+    /// With --read-radio or --write-radio: carry on an interrupted run
+    /// from the --file's checkpoint. A read appends the channels not read
+    /// yet; a write skips the channels already written and verified
+    #[arg(long, requires = "file")]
+    resume: bool,
+
+    /// Write the channels the last --write-radio overwrote back to the
+    /// radio, from the newest snapshot in --snapshot-dir
+    #[arg(long, group = "action")]
+    undo: bool,
+
+    /// Compare the channel layout in LAYOUT with the radio (or the dump in
+    /// --file) and list the fewest writes that get there: moves, tag
+    /// changes, writes and erases
+    #[arg(long, group = "action", value_name = "LAYOUT")]
+    plan_moves: Option<String>,
+
+    /// With --plan-moves: carry the plan out on the radio
+    #[arg(long, requires = "plan_moves")]
+    apply: bool,
+
+    /// Sync the memory file (--file) with the radio both ways: edits made
+    /// on either side since the last --sync go to the other, tracked in a
+    /// state file next to it (<file>.sync); edits made on both are asked
+    /// about
+    #[arg(long, group = "action")]
+    sync: bool,
+
+    /// With --sync: settle conflicts with the radio's or the file's
+    /// version instead of asking
+    #[arg(long, value_name = "radio|file", requires = "sync")]
+    prefer: Option<String>,
+
+    /// Back up the radio's memory channels and settings into a new
+    /// timestamped directory under DIR (default ftx1_backups)
+    #[arg(long, group = "action", value_name = "DIR", num_args = 0..=1, default_missing_value = backup::DEFAULT_DIR)]
+    backup: Option<String>,
+
+    /// Check a --backup against its manifest and write its channels and
+    /// settings back to the radio. BACKUP is one backup's directory, or a
+    /// --backup DIR to restore the newest in (default ftx1_backups)
+    #[arg(long, group = "action", value_name = "BACKUP", num_args = 0..=1, default_missing_value = backup::DEFAULT_DIR)]
+    restore: Option<String>,
+
+    /// Encrypt --backup's files with the passphrase on the first line of
+    /// FILE; --restore and --decrypt decrypt with it
+    #[arg(long, value_name = "FILE")]
+    passphrase_file: Option<String>,
+
+    /// Decrypt one encrypted backup file (IN) to --file
+    #[arg(long, group = "action", value_name = "IN")]
+    decrypt: Option<String>,
+
+    /// With --backup: take another backup every DURATION (90s, 30m, 24h,
+    /// 7d) until Ctrl-C, instead of once
+    #[arg(long, value_name = "DURATION", requires = "backup")]
+    every: Option<String>,
+
+    /// With --backup: delete all but the newest N backups in DIR
+    #[arg(long, value_name = "N", requires = "backup")]
+    keep: Option<usize>,
+
+    /// Append every memory channel written, with what it held before, to
+    /// FILE as JSON lines (see --reason and --operator)
+    #[arg(long, value_name = "FILE")]
+    audit_log: Option<String>,
+
+    /// Why the channels are being written, for the --audit-log entries
+    #[arg(long, value_name = "TEXT", default_value = "")]
+    reason: String,
+
+    /// Who is writing, for the --audit-log entries (default: the login name)
+    #[arg(long, value_name = "NAME")]
+    operator: Option<String>,
+
+    /// Where --write-radio saves the channels it's about to overwrite
+    #[arg(long, value_name = "DIR", default_value = snapshot::DEFAULT_DIR)]
+    snapshot_dir: String,
+
+    /// With --write-radio: the file is a patch. Only its channels are
+    /// written, and only its columns; the other columns keep what the
+    /// radio has, so it may give just "Channel Number" and what changes
+    #[arg(long, requires = "write_radio")]
+    patch: bool,
+
+    /// Don't save a snapshot before --write-radio
+    #[arg(long, requires = "write_radio")]
+    no_snapshot: bool,
+
     /// Check data in the file
     #[arg(long, group = "action")]
     check_data: bool,
@@ -57,7 +233,337 @@ struct Cli {
     #[arg(long, group = "action")]
     print: bool,
 
-    /// Use plain ASCII table style without colors
+    /// Convert the memory file (--file) to Hamlib's memory CSV layout for
+    /// `rigctl load_channels`, writing it to OUT
+    #[arg(long, group = "action", value_name = "OUT")]
+    export_hamlib: Option<String>,
+
+    /// Turn a plain frequency list or scanner export (IN) into a memory
+    /// file (--file), numbering channels from 1
+    #[arg(long, group = "action", value_name = "IN")]
+    import_list: Option<String>,
+
+    /// With --import-list, give repeater outputs the list has no shift for
+    /// the band plan's usual offset (--band-plan, default us) and report
+    /// each guess
+    #[arg(long, requires = "import_list")]
+    auto_shift: bool,
+
+    /// Back up radio-wide settings (CW pitch, ...) to a CSV file
+    /// (default: ftx1_settings_YYYYMMDD_HHMMSS.csv)
+    #[arg(long, group = "action")]
+    read_settings: bool,
+
+    /// Restore radio-wide settings from a CSV file made by --read-settings
+    #[arg(long, group = "action")]
+    write_settings: bool,
+
+    /// Read one setup-menu item by name (see --ex-list)
+    #[arg(long, group = "action", value_name = "NAME")]
+    ex_get: Option<String>,
+
+    /// Set one setup-menu item, e.g. --ex-set cw-sidetone-level=50
+    #[arg(long, group = "action", value_name = "NAME=VALUE")]
+    ex_set: Option<String>,
+
+    /// List the setup-menu items known to --ex-get/--ex-set
+    #[arg(long, group = "action")]
+    ex_list: bool,
+
+    /// Save every known setup-menu item to a CSV file
+    /// (default: ftx1_menu_YYYYMMDD_HHMMSS.csv)
+    #[arg(long, group = "action")]
+    menu_dump: bool,
+
+    /// Restore setup-menu items from a CSV file made by --menu-dump
+    #[arg(long, group = "action")]
+    menu_restore: bool,
+
+    /// Set the radio's clock from the host (local time; see --utc)
+    #[arg(long, group = "action")]
+    set_clock: bool,
+
+    /// Show VFO frequencies, mode, split and status flags
+    #[arg(long, group = "action")]
+    info: bool,
+
+    /// Print frequency and status-flag changes, as the radio reports
+    /// them, until Ctrl-C
+    #[arg(long, group = "action")]
+    monitor: bool,
+
+    /// Log the --side S-meter to FILE (CSV, appended to) until Ctrl-C
+    #[arg(long, group = "action", value_name = "FILE")]
+    smeter_log: Option<String>,
+
+    /// With --smeter-log: time between readings (500ms, 2s, 1m; default 1s)
+    #[arg(long, value_name = "DURATION", requires = "smeter_log")]
+    interval: Option<String>,
+
+    /// With --smeter-log: log the --side VFO frequency with each reading
+    #[arg(long, requires = "smeter_log")]
+    log_frequency: bool,
+
+    /// With --monitor: also send frequency, mode and memory-channel changes
+    /// as JSON datagrams to ADDR (host:port, may be a broadcast address)
+    #[arg(long, value_name = "ADDR", requires = "monitor")]
+    udp_events: Option<String>,
+
+    /// With --monitor or --daemon: publish frequency, mode, S-meter and TX
+    /// state to the MQTT broker at HOST[:PORT] (default port 1883)
+    #[arg(long, value_name = "HOST[:PORT]")]
+    mqtt: Option<String>,
+
+    /// Topic prefix for --mqtt
+    #[arg(long, value_name = "PREFIX", default_value = "ftx1", requires = "mqtt")]
+    mqtt_topic: String,
+
+    /// Seconds between --mqtt publishes
+    #[arg(long, value_name = "SECS", default_value_t = 5, requires = "mqtt")]
+    mqtt_interval: u64,
+
+    /// Serve a REST API (GET /status, GET /channels, GET|PUT /channels/N)
+    /// on ADDR (default 127.0.0.1:8080) until Ctrl-C
+    #[arg(long, group = "action", value_name = "ADDR", num_args = 0..=1, default_missing_value = "127.0.0.1:8080")]
+    daemon: Option<String>,
+
+    /// List serial ports with their USB chip and description, marking the
+    /// radio's CAT port
+    #[arg(long, group = "action")]
+    list_ports: bool,
+
+    /// List paired Bluetooth devices and bound /dev/rfcomm ttys, to find
+    /// the FTX-1 Field's Bluetooth CAT port
+    #[arg(long, group = "action")]
+    list_bluetooth: bool,
+
+    /// Serve the radio to Hamlib clients (WSJT-X, loggers) over the rigctld
+    /// network protocol on ADDR (default 127.0.0.1:4532) until Ctrl-C; PTT
+    /// needs --allow-tx
+    #[arg(long, group = "action", value_name = "ADDR", num_args = 0..=1, default_missing_value = "127.0.0.1:4532")]
+    serve: Option<String>,
+
+    /// List the channels of --file (or of the radio without --file) that
+    /// match QUERY, e.g. "band=2m mode=FM tag~REPEATER"; conditions: band,
+    /// freq=LO-HI (MHz), mode, group, tag=, tag~, split=yes|no
+    #[arg(long, group = "action", value_name = "QUERY")]
+    filter: Option<String>,
+
+    /// Summarise the memory map of --file (or of the radio without --file):
+    /// channels per band and mode, tone usage, empty slots, coverage
+    #[arg(long, group = "action")]
+    summary: bool,
+
+    /// Output of --filter: table, csv (a memory file), json or hamlib;
+    /// --summary takes table or json
+    #[arg(long, value_name = "FORMAT", default_value = "table", value_parser = ["table", "csv", "json", "hamlib"])]
+    format: String,
+
+    /// Switch the --side (default main) to BAND (160m…10m, 6m, 2m, 70cm, GEN, MW, AIR)
+    #[arg(long, group = "action", value_name = "BAND")]
+    band: Option<String>,
+
+    /// Step the --side one band up or down (DIR: up/down)
+    #[arg(long, group = "action", value_name = "DIR")]
+    band_step: Option<String>,
+
+    /// Step to the next or previous memory channel (DIR: next/prev)
+    #[arg(long, group = "action", value_name = "DIR")]
+    channel: Option<String>,
+
+    /// Start a scan on the --side (DIR: up/down, default up); Ctrl-C stops it
+    #[arg(long, group = "action", value_name = "DIR", num_args = 0..=1, default_missing_value = "up")]
+    scan: Option<String>,
+
+    /// Step the --side VFO across LO-HI (MHz, e.g. 146.4-146.6) reading the
+    /// S-meter, then list busy and clear frequencies; --file saves a CSV
+    #[arg(long, group = "action", value_name = "LO-HI")]
+    bandscan: Option<String>,
+
+    /// With --bandscan: kHz between steps
+    #[arg(long, value_name = "KHZ", default_value_t = 15, requires = "bandscan")]
+    bandscan_step: u32,
+
+    /// With --bandscan: milliseconds to listen at each step before reading
+    #[arg(long, value_name = "MS", default_value_t = 150, requires = "bandscan")]
+    bandscan_dwell: u64,
+
+    /// With --bandscan: how many times to sweep the range
+    #[arg(long, value_name = "N", default_value_t = 1, requires = "bandscan")]
+    bandscan_passes: u32,
+
+    /// With --bandscan: raw S-meter reading (0-255) that counts as busy
+    /// (default: the noise floor plus about two S-units)
+    #[arg(long, value_name = "RAW", requires = "bandscan")]
+    busy_level: Option<u8>,
+
+    /// With --scan: append every halt (frequency, memory channel, peak
+    /// S-meter, dwell) to FILE as CSV
+    #[arg(long, value_name = "FILE", requires = "scan")]
+    scan_log: Option<String>,
+
+    /// With --scan: when the radio stops on a signal, start the scan again
+    /// once the squelch closes, until Ctrl-C
+    #[arg(long, requires = "scan")]
+    scan_resume: bool,
+
+    /// Report per channel how often and how long scans halted, from a
+    /// --scan-log FILE
+    #[arg(long, group = "action", value_name = "FILE")]
+    scan_report: Option<String>,
+
+    /// Press the mic UP/DWN key once (DIR: up/down)
+    #[arg(long, group = "action", value_name = "DIR")]
+    mic_step: Option<String>,
+
+    /// Copy or swap the VFOs (OP: copy/a-to-b, b-to-a, swap)
+    #[arg(long, group = "action", value_name = "OP")]
+    vfo: Option<String>,
+
+    /// Switch the --side to VFO or memory mode (MODE: vfo, memory, toggle, status)
+    #[arg(long, group = "action", value_name = "MODE")]
+    vm: Option<String>,
+
+    /// Receive on VFO (a/main or b/sub), or show the receive VFO (status)
+    #[arg(long, group = "action", value_name = "VFO")]
+    rx_vfo: Option<String>,
+
+    /// Transmit on VFO (a/main or b/sub), or show the transmit VFO (status)
+    #[arg(long, group = "action", value_name = "VFO")]
+    tx_vfo: Option<String>,
+
+    /// Make VFO (a/main or b/sub) the operating side, or show it (status)
+    #[arg(long, group = "action", value_name = "VFO")]
+    select_vfo: Option<String>,
+
+    /// The side --band, --band-step, --fast-step, --zero-in, --scan, --bandscan,
+    /// --vm, --info, --monitor and --smeter-log act on: main or sub
+    #[arg(long, value_name = "SIDE", default_value = "main")]
+    side: String,
+
+    /// Split on/off/status, or an offset in kHz (e.g. +5): set VFO-B to VFO-A
+    /// plus the offset and turn split on
+    #[arg(long, group = "action", value_name = "STATE|KHZ", allow_hyphen_values = true)]
+    split: Option<String>,
+
+    /// RX clarifier on/off, or show its state (status)
+    #[arg(long, group = "action", value_name = "STATE")]
+    rx_clar: Option<String>,
+
+    /// TX clarifier on/off, or show its state (status)
+    #[arg(long, group = "action", value_name = "STATE")]
+    tx_clar: Option<String>,
+
+    /// Reset the clarifier offset to zero
+    #[arg(long, group = "action")]
+    clar_clear: bool,
+
+    /// Move the clarifier by HZ (signed, e.g. +100 or -50)
+    #[arg(long, group = "action", value_name = "HZ", allow_hyphen_values = true)]
+    clar_step: Option<String>,
+
+    /// Set display brightness: TFT level 0–20, or LED/TFT (e.g. 5/12); status shows it
+    #[arg(long, group = "action", value_name = "LEVEL")]
+    dimmer: Option<String>,
+
+    /// Lock or unlock the front panel (on/off), or show the lock state (status)
+    #[arg(long, group = "action", value_name = "STATE")]
+    lock: Option<String>,
+
+    /// Power the radio on or off (on/off), or show whether it's on (status)
+    #[arg(long, group = "action", value_name = "STATE")]
+    power: Option<String>,
+
+    /// Select the TX meter (PO, SWR, ALC, COMP, IDD, VDD), or show it (status)
+    #[arg(long, group = "action", value_name = "METER")]
+    meter: Option<String>,
+
+    /// Antenna for the --side's current band: 1 or 2, or show it (status);
+    /// BAND=N[,BAND=N...] sets bands (e.g. 20m=2,6m=1), `bands` lists them
+    #[arg(long, group = "action", value_name = "VALUE")]
+    antenna: Option<String>,
+
+    /// Antenna tuner on/off, or show its state (status)
+    #[arg(long, group = "action", value_name = "STATE")]
+    tuner: Option<String>,
+
+    /// Run a tuner cycle and report the resulting SWR (requires --allow-tx)
+    #[arg(long, group = "action")]
+    tune: bool,
+
+    /// Key briefly at low power across LO-HI (MHz, e.g. 14.0-14.3) and
+    /// read the SWR at each --sweep-step, saving a CSV to --file
+    /// (default: ftx1_swr_YYYYMMDD_HHMMSS.csv); requires --allow-tx
+    #[arg(long, group = "action", value_name = "LO-HI")]
+    swr_sweep: Option<String>,
+
+    /// With --swr-sweep: kHz between readings
+    #[arg(long, value_name = "KHZ", default_value_t = 50, requires = "swr_sweep")]
+    sweep_step: u32,
+
+    /// With --swr-sweep: RF power in watts while keyed (at most 10)
+    #[arg(long, value_name = "WATTS", default_value_t = sweep::DEFAULT_WATTS, requires = "swr_sweep")]
+    sweep_power: u8,
+
+    /// CW audio peak filter (APF) on the --side on/off, or show it (status)
+    #[arg(long, group = "action", value_name = "STATE")]
+    apf: Option<String>,
+
+    /// Move the APF peak -250…+250 Hz (10 Hz steps) from the CW pitch, or
+    /// show the offset (status)
+    #[arg(long, group = "action", value_name = "HZ|status", allow_hyphen_values = true)]
+    apf_offset: Option<String>,
+
+    /// Fast dial step on the --side on/off, or show it (status)
+    #[arg(long, group = "action", value_name = "STATE")]
+    fast_step: Option<String>,
+
+    /// CW spot tone on/off, or show its state (status)
+    #[arg(long, group = "action", value_name = "STATE")]
+    spot: Option<String>,
+
+    /// Trigger CW auto zero-beat (ZI) on the --side
+    #[arg(long, group = "action")]
+    zero_in: bool,
+
+    /// Key TEXT as CW through the radio's keyer (requires --allow-tx)
+    #[arg(long, group = "action", value_name = "TEXT")]
+    cw_send: Option<String>,
+
+    /// With --cw-send: a beacon, sending TEXT again every DURATION (30s,
+    /// 10m, 1h) until --beacon-count or Ctrl-C
+    #[arg(long, value_name = "DURATION", requires = "cw_send")]
+    beacon_interval: Option<String>,
+
+    /// With --beacon-interval: stop after sending TEXT N times
+    #[arg(long, value_name = "N", requires = "beacon_interval")]
+    beacon_count: Option<u32>,
+
+    /// Voice keyer (DVS): `play N` (requires --allow-tx), `record N` from
+    /// the mic, `stop`, or `status`; N is slot 1–5. `cq N` plays slot N
+    /// over and over (see --cq-interval) until Enter, PTT or Ctrl-C
+    #[arg(long, group = "action", value_names = ["ACTION", "SLOT"], num_args = 1..=2)]
+    voice: Option<Vec<String>>,
+
+    /// With --voice cq: how long to listen between calls (default 8s)
+    #[arg(long, value_name = "DURATION", requires = "voice")]
+    cq_interval: Option<String>,
+
+    /// Unkey the radio now: TX0, and stop any voice memory playing
+    #[arg(long, group = "action")]
+    panic_stop: bool,
+
+    /// After the action, print per-command CAT latencies and throughput
+    #[arg(long)]
+    stats: bool,
+
+    /// Log every CAT frame sent and received to FILE as a timestamped hex
+    /// dump, for attaching to protocol bug reports
+    #[arg(long, value_name = "FILE")]
+    trace_frames: Option<String>,
+
+    /// Plain output for screen readers and logs: ASCII tables without
+    /// colors, and progress as "n/total" lines instead of a bar
     #[arg(long)]
     plain: bool,
 
@@ -65,7 +571,8 @@ struct Cli {
     #[arg(short, long)]
     quiet: bool,
 
-    /// Disable non-blocking validation warnings (currently: duplicate-frequency detection)
+    /// Disable non-blocking validation warnings (shared frequencies,
+    /// duplicate channel settings, receive-only channels, band plan)
     #[arg(long)]
     no_warnings: bool,
 
@@ -75,64 +582,102 @@ struct Cli {
     /// reject anything it can't actually tune.
     #[arg(long)]
     allow_any_frequency: bool,
+
+    /// Regional version of the radio (usa, exp). Sets the transmit coverage
+    /// --check-data warns against.
+    #[arg(long, default_value = "usa")]
+    region: String,
+
+    /// Check each channel's frequency and mode against a band plan (r1,
+    /// r2, r3, us, eu, ja) in --check-data
+    #[arg(long)]
+    band_plan: Option<String>,
+
+    /// Only the channels in one memory group (hf, 50, air, vhf, uhf) for
+    /// --read-radio, --write-radio, --print and --export-hamlib
+    #[arg(long, value_name = "GROUP")]
+    group: Option<String>,
+
+    /// Use UTC instead of host local time for --set-clock / --sync-clock
+    #[arg(long)]
+    utc: bool,
+
+    /// Set the radio's clock before --read-radio, --read-settings or
+    /// --menu-dump so radio and backup timestamps line up
+    #[arg(long)]
+    sync_clock: bool,
+
+    /// Fix memory tags the radio can't store instead of rejecting them:
+    /// drop unsupported characters and truncate to 12 (--check-data,
+    /// --write-radio)
+    #[arg(long)]
+    fix_tags: bool,
+
+    /// Field delimiter for memory files this writes (',', ';' or tab).
+    /// Reading detects the delimiter on its own.
+    #[arg(long, default_value = ",")]
+    delimiter: String,
+
+    /// Write decimal numbers (CTCSS tones) with a decimal comma, for
+    /// spreadsheets in European locales. Reading accepts both.
+    #[arg(long)]
+    decimal_comma: bool,
+
+    /// Unit of the frequency columns in memory files this writes: hz
+    /// (145525000) or mhz (145.52500). Reading accepts both.
+    #[arg(long, value_name = "UNIT", default_value = "hz", value_parser = ["hz", "mhz"])]
+    freq_unit: String,
+
+    /// With --check-data, write a copy of the file to OUT with channel
+    /// numbers padded, mode spellings normalised and clarifier offsets
+    /// rounded to 10 Hz, listing each change, then check the copy
+    #[arg(long, value_name = "OUT", requires = "check_data")]
+    fix: Option<String>,
+
+    /// Tag channels whose Memory Tag is empty from TEMPLATE (default
+    /// "{freq_mhz} {mode}"; also {freq_khz}, {freq_hz}, {channel}, {group},
+    /// {callsign}, with formats like {freq_mhz:.4}) in --import-list and
+    /// --write-radio
+    #[arg(long, value_name = "TEMPLATE", num_args = 0..=1, default_missing_value = tags::DEFAULT_TEMPLATE)]
+    auto_tag: Option<String>,
+
+    /// Permit actions that key the transmitter (--cw-send, ...). Without it
+    /// those actions refuse to run, so a typo can't put RF on the air.
+    #[arg(long)]
+    allow_tx: bool,
+
+    /// Longest the radio may stay keyed (90s, 3m) before the TX watchdog
+    /// sends TX0 and ends the action (default 3m)
+    #[arg(long, value_name = "DURATION")]
+    max_tx: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
-struct CsvRecord {
-    #[serde(rename = "Channel Number")]
-    channel: String,
-    #[serde(rename = "Frequency (Hz)")]
-    freq: u32, // FrequencyHz,
-    #[serde(rename = "Memory Tag")]
-    tag: Option<String>,
-    #[serde(rename = "Mode")]
-    mode: String,
-    #[serde(rename = "Channel Type")]
-    ch_type: ChType,
-    #[serde(rename = "Squelch Type")]
-    tone: SqlType,
-    #[serde(rename = "Shift (Hz)")]
-    shift: Shift,
-    #[serde(rename = "Clarifier Offset (Hz)")]
-    clarifier_offset_hz: i16,
-    #[serde(rename = "Rx Clarifier Enabled")]
-    rx_clarifier_enabled: RxClarifierOnOff,
-    #[serde(rename = "Tx Clarifier Enabled")]
-    tx_clarifier_enabled: TxClarifierOnOff,
-    #[serde(rename = "CTCSS Tone")]
-    ctcss_tone: String,
-    #[serde(rename = "DCS Tone")]
-    dcs_tone: String,
-    /// Optional split-memory TX frequency. Empty cell or missing column = no
-    /// split (TX = RX). Set to a Hz value to enable per-channel split via MZ.
-    #[serde(rename = "Split TX (Hz)", default)]
-    tx_frequency_hz: Option<u32>,
-}
-
-impl TryFrom<CsvRecord> for MemoryReadWrite {
-    type Error = ();
-
-    fn try_from(item: CsvRecord) -> Result<Self, Self::Error> {
-        let channel = MemoryChannel::try_from(item.channel)?;
-        let mem = MemoryReadWrite {
-            channel,
-            frequency_hz: FrequencyHz::try_from(item.freq)?,
-            clarifier_offset_hz: ClarifierOffsetHz::try_from(item.clarifier_offset_hz)?,
-            rx_clarifier_enabled: item.rx_clarifier_enabled,
-            tx_clarifier_enabled: item.tx_clarifier_enabled,
-            mode: Mode::try_from(item.mode)?,
-            ch_type: item.ch_type,
-            sql_type: item.tone,
-            shift: item.shift,
-        };
-        Ok(mem)
-    }
+fn require_headers<R: io::Read>(rdr: &mut csv::Reader<R>, file_path: &str, quiet: bool) -> Result<(), ()> {
+    let headers = rdr.headers().cloned().unwrap_or_default();
+    check_headers(&headers).map_err(|errors| {
+        if !quiet {
+            println!("File '{}' doesn't have the expected columns:", file_path);
+            for error in errors {
+                println!("  - {}", error);
+            }
+            println!("Expected: {}", CSV_COLUMNS.join(", "));
+        }
+    })
 }
 
 fn default_filename() -> String {
+    timestamped_filename("ftx1")
+}
+
+fn timestamped_filename(prefix: &str) -> String {
+    format!("{}.csv", timestamped_name(prefix))
+}
+
+/// `prefix` with the current UTC time: "ftx1_YYYYMMDD_HHMMSS".
+fn timestamped_name(prefix: &str) -> String {
     let secs = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
     let (y, mo, d, h, mi, s) = secs_to_datetime(secs);
-    format!("ftx1_{:04}{:02}{:02}_{:02}{:02}{:02}.csv", y, mo, d, h, mi, s)
+    format!("{}_{:04}{:02}{:02}_{:02}{:02}{:02}", prefix, y, mo, d, h, mi, s)
 }
 
 fn secs_to_datetime(secs: u64) -> (u64, u64, u64, u64, u64, u64) {
@@ -171,21 +716,311 @@ fn require_file(file: &Option<String>, flag: &str) -> Result<String, ()> {
     file.clone().ok_or_else(|| println!("Error: --file is required for {}", flag))
 }
 
+fn require_tx(cli: &Cli, flag: &str) -> Result<(), ()> {
+    if cli.allow_tx {
+        Ok(())
+    } else {
+        println!("Error: {} keys the transmitter; pass --allow-tx to confirm.", flag);
+        Err(())
+    }
+}
+
+/// Opens the radio for an action that can key it, behind the TX watchdog
+/// (see watchdog.rs).
+fn open_tx_radio(cli: &Cli) -> Result<Box<dyn Transport>, ()> {
+    Ok(Box::new(watched_radio(cli)?))
+}
+
+/// The radio behind the TX watchdog, for callers that also tick it. The
+/// port isn't wrapped in Reconnecting: a reconnect would hide the drop from
+/// the watchdog, which has to see it to unkey on the reopened port.
+fn watched_radio(cli: &Cli) -> Result<watchdog::Watchdog, ()> {
+    let max = match &cli.max_tx {
+        Some(text) => backup::parse_every(text).ok_or_else(|| {
+            if !cli.quiet { println!("Expected a --max-tx like 90s or 3m, got '{}'.", text); }
+        })?,
+        None => watchdog::DEFAULT_MAX_TX,
+    };
+    let port = connect_radio(&cli.port, cli.speed, cli.quiet)?;
+    let (name, speed) = (cli.port.clone(), cli.speed);
+    let reopen: watchdog::Reopen = Box::new(move || open_port(&name, speed, true).ok());
+    Ok(watchdog::Watchdog::new(port, max, reopen))
+}
+
+/// --panic-stop: unkeys straight away. The radio ID isn't checked first,
+/// so it's as quick as the port opens.
+fn panic_stop(cli: &Cli) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let mut port = open_port(&cli.port, cli.speed, quiet)?;
+    watchdog::unkey(&mut *port).map_err(|e| {
+        if !quiet { println!("Failed to send TX0: {}", e); }
+    })?;
+    match cat_send(&mut *port, &CMD_TX.read()).and_then(|rx| CMD_TX.decode(&rx).map_err(reported)) {
+        Ok(false) => if !quiet { println!("Sent TX0; the radio is receiving."); },
+        Ok(true) => {
+            if !quiet { println!("Sent TX0, but the radio still transmits; is PTT held on the mic or DATA jack?"); }
+            return Err(());
+        }
+        Err(()) => if !quiet { println!("Sent TX0; the radio didn't confirm it."); },
+    }
+    Ok(())
+}
+
+fn parse_direction(dir: &str, quiet: bool) -> Result<Direction, ()> {
+    Direction::try_from(dir).map_err(|_| {
+        if !quiet { println!("Unknown direction '{}' (expected up/down or next/prev).", dir); }
+    })
+}
+
 fn main() -> Result<(), ()> {
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    apply_profile(&mut cli, &matches)?;
+    let cli = cli;
     env_logger::init();
+    dialect::set_write_format(cli.freq_unit == "mhz", cli.decimal_comma);
+    if let Some(path) = &cli.trace_frames
+        && let Err(e) = trace::open(path)
+    {
+        if !cli.quiet { println!("Failed to create trace file '{}': {}", path, e); }
+        return Err(());
+    }
+
+    let result = run(&cli);
+    if cli.stats {
+        stats::print();
+    }
+    result
+}
+
+/// --radio: fills in the options the command line left out from the
+/// named profile (see config.rs).
+fn apply_profile(cli: &mut Cli, matches: &clap::ArgMatches) -> Result<(), ()> {
+    let Some(name) = cli.radio.clone() else { return Ok(()) };
+    let quiet = cli.quiet;
+    let path = match &cli.config {
+        Some(path) => std::path::PathBuf::from(path),
+        None => config::default_path().ok_or_else(|| {
+            if !quiet { println!("Can't tell where the configuration directory is; name the file with --config."); }
+        })?,
+    };
+    let text = std::fs::read_to_string(&path).map_err(|e| {
+        if !quiet { println!("Failed to read configuration file '{}': {}", path.display(), e); }
+    })?;
+    let config = config::parse(&text).map_err(|e| {
+        if !quiet { println!("Error in configuration file '{}':\n{}", path.display(), e); }
+    })?;
+    let profile = config.radios.get(&name).ok_or_else(|| {
+        if !quiet {
+            let known: Vec<&str> = config.radios.keys().map(String::as_str).collect();
+            println!("No [radio.{}] in '{}' (radios: {}).", name, path.display(), if known.is_empty() { "none".to_string() } else { known.join(", ") });
+        }
+    })?;
+    if let Some(model) = &profile.model
+        && Region::try_from(model.as_str()).is_err()
+    {
+        if !quiet { println!("Unknown model '{}' for radio '{}' (expected usa or exp).", model, name); }
+        return Err(());
+    }
+    let given = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+    if !given("port") && let Some(port) = &profile.port { cli.port = port.clone(); }
+    if !given("speed") && let Some(speed) = profile.speed { cli.speed = speed; }
+    if !given("region") && let Some(model) = &profile.model { cli.region = model.clone(); }
+    if !given("file") && let Some(file) = &profile.file { cli.file = Some(file.clone()); }
+    if !given("audit_log") && let Some(log) = &profile.audit_log { cli.audit_log = Some(log.clone()); }
+    Ok(())
+}
 
+fn run(cli: &Cli) -> Result<(), ()> {
     if cli.read_radio {
-        read_radio_data(&cli)?;
+        read_radio_data(cli)?;
     } else if cli.write_radio {
         let file = require_file(&cli.file, "--write-radio")?;
-        write_radio_data(&cli, &file)?;
+        write_radio_data(cli, &file)?;
+    } else if cli.undo {
+        undo(cli)?;
+    } else if let Some(layout) = &cli.plan_moves {
+        plan_moves(cli, layout)?;
+    } else if cli.sync {
+        let file = require_file(&cli.file, "--sync")?;
+        sync(cli, &file)?;
+    } else if let Some(dir) = &cli.backup {
+        backup(cli, dir)?;
+    } else if let Some(path) = &cli.restore {
+        restore(cli, path)?;
+    } else if let Some(input) = &cli.decrypt {
+        let file = require_file(&cli.file, "--decrypt")?;
+        decrypt_file(cli, input, &file)?;
     } else if cli.check_data {
         let file = require_file(&cli.file, "--check-data")?;
-        check_data(&file, cli.quiet, true, !cli.no_warnings, cli.allow_any_frequency)?;
+        match &cli.fix {
+            Some(out) => fix_data(cli, &file, out)?,
+            None => check_data(&file, cli.quiet, true, &check_options(cli)?)?,
+        }
     } else if cli.print {
         let file = require_file(&cli.file, "--print")?;
-        print_table(&file, cli.plain, cli.quiet)?;
+        print_table(&file, cli.plain, group_filter(cli)?, cli.quiet)?;
+    } else if let Some(out) = &cli.export_hamlib {
+        let file = require_file(&cli.file, "--export-hamlib")?;
+        export_hamlib(cli, &file, out)?;
+    } else if let Some(list) = &cli.import_list {
+        let file = require_file(&cli.file, "--import-list")?;
+        import_list(cli, list, &file)?;
+    } else if cli.read_settings {
+        read_settings(cli)?;
+    } else if cli.write_settings {
+        let file = require_file(&cli.file, "--write-settings")?;
+        write_settings(cli, &file)?;
+    } else if let Some(name) = &cli.ex_get {
+        ex_get(cli, name)?;
+    } else if let Some(assignment) = &cli.ex_set {
+        ex_set(cli, assignment)?;
+    } else if cli.ex_list {
+        ex_list(cli.quiet);
+    } else if cli.menu_dump {
+        menu_dump(cli)?;
+    } else if cli.menu_restore {
+        let file = require_file(&cli.file, "--menu-restore")?;
+        menu_restore(cli, &file)?;
+    } else if cli.set_clock {
+        let mut port = open_radio(&cli.port, cli.speed, cli.quiet)?;
+        set_clock(&mut *port, cli.utc, cli.quiet)?;
+    } else if cli.info {
+        info(cli)?;
+    } else if cli.monitor {
+        monitor(cli)?;
+    } else if let Some(path) = &cli.smeter_log {
+        smeter_log(cli, path)?;
+    } else if let Some(range) = &cli.bandscan {
+        bandscan(cli, range)?;
+    } else if cli.list_ports {
+        list_ports();
+    } else if cli.list_bluetooth {
+        list_bluetooth();
+    } else if let Some(addr) = &cli.serve {
+        serve(cli, addr)?;
+    } else if let Some(addr) = &cli.daemon {
+        run_daemon(cli, addr)?;
+    } else if let Some(query) = &cli.filter {
+        filter_channels(cli, query)?;
+    } else if cli.summary {
+        summarize_channels(cli)?;
+    } else if let Some(band) = &cli.band {
+        let band = Band::try_from(band.as_str()).map_err(|_| {
+            if !cli.quiet { println!("Unknown band '{}'.", band); }
+        })?;
+        let side = cli_side(cli)?;
+        let mut port = open_radio(&cli.port, cli.speed, cli.quiet)?;
+        let _ = cat_send(&mut *port, &CMD_BS.set(side, band))?;
+    } else if let Some(dir) = &cli.band_step {
+        let dir = parse_direction(dir, cli.quiet)?;
+        let side = cli_side(cli)?;
+        let mut port = open_radio(&cli.port, cli.speed, cli.quiet)?;
+        let _ = cat_send(&mut *port, &band_step(side, dir))?;
+    } else if let Some(dir) = &cli.channel {
+        let dir = parse_direction(dir, cli.quiet)?;
+        let mut port = open_radio(&cli.port, cli.speed, cli.quiet)?;
+        let _ = cat_send(&mut *port, &CMD_CH.set(dir))?;
+    } else if let Some(dir) = &cli.scan {
+        let dir = parse_direction(dir, cli.quiet)?;
+        scan(cli, dir)?;
+    } else if let Some(path) = &cli.scan_report {
+        scan_report(cli, path)?;
+    } else if let Some(dir) = &cli.mic_step {
+        let dir = parse_direction(dir, cli.quiet)?;
+        let mut port = open_radio(&cli.port, cli.speed, cli.quiet)?;
+        let _ = cat_send(&mut *port, &mic_step(dir))?;
+    } else if let Some(op) = &cli.vfo {
+        let op = VfoOp::try_from(op.as_str()).map_err(|_| {
+            if !cli.quiet { println!("Unknown VFO operation '{}' (expected copy, a-to-b, b-to-a or swap).", op); }
+        })?;
+        let mut port = open_radio(&cli.port, cli.speed, cli.quiet)?;
+        let _ = cat_send(&mut *port, &vfo_op(op))?;
+    } else if let Some(mode) = &cli.vm {
+        vm_action(cli, mode)?;
+    } else if let Some(vfo) = &cli.rx_vfo {
+        side_action(cli, "RX", vfo, &CMD_FR.read(), |side| CMD_FR.set(side), |rx| CMD_FR.decode(rx))?;
+    } else if let Some(vfo) = &cli.tx_vfo {
+        side_action(cli, "TX", vfo, &CMD_FT.read(), |side| CMD_FT.set(side), |rx| CMD_FT.decode(rx))?;
+    } else if let Some(vfo) = &cli.select_vfo {
+        side_action(cli, "Operating side", vfo, &CMD_VS.read(), |side| CMD_VS.set(side), |rx| CMD_VS.decode(rx))?;
+    } else if let Some(value) = &cli.split {
+        if value.starts_with(['+', '-']) {
+            split_offset(cli, value)?;
+        } else {
+            switch_action(cli, "Split", value, &CMD_ST.read(), |on| CMD_ST.set(on), |rx| CMD_ST.decode(rx))?;
+        }
+    } else if let Some(state) = &cli.rx_clar {
+        switch_action(cli, "RX clarifier", state, &CMD_RT.read(), |on| CMD_RT.set(on), |rx| CMD_RT.decode(rx))?;
+    } else if let Some(state) = &cli.tx_clar {
+        switch_action(cli, "TX clarifier", state, &CMD_XT.read(), |on| CMD_XT.set(on), |rx| CMD_XT.decode(rx))?;
+    } else if cli.clar_clear {
+        let mut port = open_radio(&cli.port, cli.speed, cli.quiet)?;
+        let _ = cat_send(&mut *port, &CMD_RC.set())?;
+    } else if let Some(hz) = &cli.clar_step {
+        let frame = hz.trim().parse::<i16>().map_err(|_| ()).and_then(clar_step).map_err(|_| {
+            if !cli.quiet { println!("Invalid clarifier step '{}' (expected -9990..+9990 Hz).", hz); }
+        })?;
+        let mut port = open_radio(&cli.port, cli.speed, cli.quiet)?;
+        let _ = cat_send(&mut *port, &frame)?;
+    } else if let Some(level) = &cli.dimmer {
+        dimmer(cli, level)?;
+    } else if let Some(state) = &cli.lock {
+        switch_action(cli, "Lock", state, &CMD_LK.read(), |on| CMD_LK.set(on), |rx| CMD_LK.decode(rx))?;
+    } else if let Some(state) = &cli.power {
+        power(cli, state)?;
+    } else if let Some(meter) = &cli.meter {
+        let status = meter.eq_ignore_ascii_case("status");
+        let selected = TxMeter::try_from(meter.as_str());
+        if !status && selected.is_err() {
+            if !cli.quiet { println!("Unknown meter '{}' (expected PO, SWR, ALC, COMP, IDD, VDD or status).", meter); }
+            return Err(());
+        }
+        let mut port = open_radio(&cli.port, cli.speed, cli.quiet)?;
+        match selected {
+            Ok(m) => { let _ = cat_send(&mut *port, &CMD_MS.set(m))?; }
+            Err(_) => println!("Meter: {}", CMD_MS.decode(&cat_send(&mut *port, &CMD_MS.read())?).map_err(reported)?),
+        }
+    } else if let Some(value) = &cli.antenna {
+        antenna(cli, value)?;
+    } else if let Some(state) = &cli.tuner {
+        switch_action(
+            cli,
+            "Tuner",
+            state,
+            &CMD_AC.read(),
+            |on| CMD_AC.set(if on { TunerState::On } else { TunerState::Off }),
+            |rx| CMD_AC.decode(rx).map(|s| s != TunerState::Off),
+        )?;
+    } else if cli.tune {
+        require_tx(cli, "--tune")?;
+        tune(cli)?;
+    } else if let Some(range) = &cli.swr_sweep {
+        require_tx(cli, "--swr-sweep")?;
+        swr_sweep(cli, range)?;
+    } else if let Some(state) = &cli.fast_step {
+        let side = cli_side(cli)?;
+        let read = CMD_FS.read(side);
+        switch_action(cli, "Fast step", state, &read, |on| CMD_FS.set(side, on), |rx| CMD_FS.decode(rx))?;
+    } else if let Some(state) = &cli.apf {
+        let side = cli_side(cli)?;
+        let read = CMD_CO.read(side, ApfFunction::OnOff);
+        switch_action(cli, "APF", state, &read, |on| CMD_CO.set(side, ApfParam::OnOff(on)), |rx| CMD_CO.decode_on_off(rx))?;
+    } else if let Some(value) = &cli.apf_offset {
+        apf_offset(cli, value)?;
+    } else if let Some(state) = &cli.spot {
+        switch_action(cli, "Spot", state, &CMD_CS.read(), |on| CMD_CS.set(on), |rx| CMD_CS.decode(rx))?;
+    } else if cli.zero_in {
+        let side = cli_side(cli)?;
+        let mut port = open_radio(&cli.port, cli.speed, cli.quiet)?;
+        let _ = cat_send(&mut *port, &CMD_ZI.set(side))?;
+    } else if let Some(text) = &cli.cw_send {
+        require_tx(cli, "--cw-send")?;
+        cw_send(cli, text)?;
+    } else if let Some(args) = &cli.voice {
+        voice(cli, args)?;
+    } else if cli.panic_stop {
+        panic_stop(cli)?;
     } else {
         println!("No action specified. Use --help for options.");
     }
@@ -193,22 +1028,109 @@ fn main() -> Result<(), ()> {
     Ok(())
 }
 
-fn check_data(file_path: &str, quiet: bool, verbose: bool, warnings_enabled: bool, allow_any_frequency: bool) -> Result<(), ()> {
-    let mut rdr = csv::ReaderBuilder::new()
-        .comment(Some(b'#'))
-        .from_path(file_path)
-        .map_err(|e| {
-            if !quiet { println!("Error opening file '{}': {}", file_path, e); }
-        })?;
+fn check_options(cli: &Cli) -> Result<CheckOptions, ()> {
+    let region = Region::try_from(cli.region.as_str()).map_err(|_| {
+        if !cli.quiet { println!("Unknown region '{}' (expected usa or exp).", cli.region); }
+    })?;
+    let band_plan = match &cli.band_plan {
+        Some(name) => Some(BandPlan::try_from(name.as_str()).map_err(|_| {
+            if !cli.quiet { println!("Unknown band plan '{}' (expected r1, r2, r3, us, eu or ja).", name); }
+        })?),
+        None => None,
+    };
+    Ok(CheckOptions {
+        warnings_enabled: !cli.no_warnings,
+        allow_any_frequency: cli.allow_any_frequency,
+        region,
+        fix_tags: cli.fix_tags,
+        band_plan,
+    })
+}
+
+/// A memory-file writer in the --delimiter dialect.
+fn memory_writer<W: io::Write>(cli: &Cli, mut out: W) -> Result<csv::Writer<W>, ()> {
+    let delimiter = dialect::parse_delimiter(&cli.delimiter).ok_or_else(|| {
+        if !cli.quiet { println!("Unknown delimiter '{}' (expected ',', ';' or tab).", cli.delimiter); }
+    })?;
+    writeln!(out, "{}", migrate::stamp()).map_err(|_| ())?;
+    if let Some(line) = firmware_comment() { writeln!(out, "{}", line).map_err(|_| ())?; }
+    let mut wtr = dialect::writer(out, delimiter);
+    wtr.write_record(CSV_COLUMNS.iter().map(|c| dialect::column_header(c))).map_err(|_| ())?;
+    Ok(wtr)
+}
+
+fn create_memory_file(cli: &Cli, file: &str) -> Result<csv::Writer<std::fs::File>, ()> {
+    let out = std::fs::File::create(file).map_err(|e| {
+        if !cli.quiet { println!("Failed to create '{}': {}", file, e); }
+    })?;
+    memory_writer(cli, out)
+}
+
+/// Writes numbers with a decimal comma when --decimal-comma is on.
+fn localize(record: &mut CsvRecord, cli: &Cli) {
+    if cli.decimal_comma {
+        record.ctcss_tone = dialect::to_decimal_comma(&record.ctcss_tone);
+    }
+}
+
+/// The --auto-tag template, checked.
+fn auto_tag_template(cli: &Cli) -> Result<Option<&str>, ()> {
+    let Some(template) = &cli.auto_tag else { return Ok(None) };
+    tags::check(template).map_err(|e| {
+        if !cli.quiet { println!("Bad --auto-tag template '{}': {}.", template, e); }
+    })?;
+    Ok(Some(template))
+}
+
+/// Gives an untagged record a tag from the --auto-tag template;
+/// `callsign` is the station's, when the source row gives one.
+fn auto_tag(record: &mut CsvRecord, template: &str, callsign: &str) {
+    if record.tag.as_deref().is_some_and(|t| !t.trim().is_empty()) {
+        return;
+    }
+    let fields = tags::TagFields { freq: record.freq, mode: &record.mode, channel: &record.channel, callsign };
+    if let Ok(tag) = tags::render(template, &fields) {
+        record.tag = Some(fix_tag(&tag));
+    }
+}
+
+/// The --group filter, if one was given.
+fn group_filter(cli: &Cli) -> Result<Option<MemoryGroup>, ()> {
+    let Some(name) = &cli.group else { return Ok(None) };
+    MemoryGroup::try_from(name.as_str()).map(Some).map_err(|_| {
+        if !cli.quiet { println!("Unknown memory group '{}' (expected hf, 50, air, vhf or uhf).", name); }
+    })
+}
+
+fn in_group(freq: u32, group: Option<MemoryGroup>) -> bool {
+    group.is_none_or(|g| MemoryGroup::of(freq) == g)
+}
+
+fn check_data(file_path: &str, quiet: bool, verbose: bool, options: &CheckOptions) -> Result<(), ()> {
+    let rdr = dialect::reader(file_path).map_err(|e| {
+        if !quiet { println!("Error opening file '{}': {}", file_path, e); }
+    })?;
+    check_rows(rdr, file_path, quiet, verbose, options)
+}
+
+/// check_data for a memory file already open; `file_path` names it in
+/// messages.
+fn check_rows<R: io::Read>(mut rdr: csv::Reader<R>, file_path: &str, quiet: bool, verbose: bool, options: &CheckOptions) -> Result<(), ()> {
+    require_headers(&mut rdr, file_path, quiet)?;
     let mut valid_records = 0;
     let mut invalid_records = 0;
     let mut warnings_count: u32 = 0;
-    let mut seen_channels: HashSet<String> = HashSet::new();
-    let mut seen_frequencies: HashMap<u32, (String, Option<String>)> = HashMap::new();
-    let mut duplicates_found = false;
+    let mut cross = memfile::CrossCheck::default();
+
+    // Per-record checks are independent, so they run in parallel; the
+    // cross-record checks (duplicates, shared frequencies) and all output
+    // stay in a sequential pass so messages come out in file order.
+    let results: Vec<Result<CsvRecord, csv::Error>> = rdr.deserialize().collect();
+    let checked: Vec<Result<CheckedRecord, csv::Error>> =
+        results.into_par_iter().map(|result| Ok(memfile::check_record(result?, options))).collect();
 
-    for (i, result) in rdr.deserialize().enumerate() {
-        let mut record: CsvRecord = match result {
+    for (i, result) in checked.into_iter().enumerate() {
+        let (record, mut errors, notes) = match result {
             Ok(r) => r,
             Err(e) => {
                 if !quiet { println!("Error deserializing record {}: {}", i + 1, e); }
@@ -216,39 +1138,7 @@ fn check_data(file_path: &str, quiet: bool, verbose: bool, warnings_enabled: boo
                 continue;
             }
         };
-        normalize_record(&mut record);
-
-        let mut errors = match validate_record(&record, allow_any_frequency) {
-            Ok(_) => Vec::new(),
-            Err(e) => e,
-        };
-        if !seen_channels.insert(record.channel.clone()) {
-            errors.push(format!("Channel '{}' appears more than once.", record.channel));
-            duplicates_found = true;
-        }
-
-        let mut warnings: Vec<String> = Vec::new();
-        if warnings_enabled {
-            match seen_frequencies.get(&record.freq) {
-                Some((prev_ch, prev_tag)) => {
-                    let prev_label = match prev_tag {
-                        Some(t) if !t.trim().is_empty() => format!("'{}' ({})", prev_ch, t.trim()),
-                        _ => format!("'{}'", prev_ch),
-                    };
-                    let cur_label = match &record.tag {
-                        Some(t) if !t.trim().is_empty() => format!(" ({})", t.trim()),
-                        _ => String::new(),
-                    };
-                    warnings.push(format!(
-                        "Frequency {} Hz{} is also used by channel {}.",
-                        record.freq, cur_label, prev_label
-                    ));
-                }
-                None => {
-                    seen_frequencies.insert(record.freq, (record.channel.clone(), record.tag.clone()));
-                }
-            }
-        }
+        let warnings = cross.check(i + 1, &record, &mut errors, notes, options);
 
         if errors.is_empty() {
             valid_records += 1;
@@ -257,16 +1147,7 @@ fn check_data(file_path: &str, quiet: bool, verbose: bool, warnings_enabled: boo
         }
         warnings_count += warnings.len() as u32;
 
-        if !quiet && (!errors.is_empty() || !warnings.is_empty()) {
-            let label = if errors.is_empty() { "has warnings" } else { "is invalid" };
-            println!("Record {} {}:", i + 1, label);
-            for error in errors {
-                println!("  - {}", error);
-            }
-            for warning in &warnings {
-                println!("  ! {}", warning);
-            }
-        }
+        if !quiet { print_findings(i + 1, &errors, &warnings); }
     }
 
     if verbose && !quiet {
@@ -290,7 +1171,7 @@ fn check_data(file_path: &str, quiet: bool, verbose: bool, warnings_enabled: boo
         Ok(())
     } else {
         if verbose && !quiet { println!("\nData has issues and may not be processable."); }
-        if duplicates_found && !quiet {
+        if cross.duplicates_found && !quiet {
             println!(
                 "\nTip: to renumber duplicate channels sequentially, run\n     python3 renumber_channels.py {} > fixed.csv",
                 file_path
@@ -300,71 +1181,140 @@ fn check_data(file_path: &str, quiet: bool, verbose: bool, warnings_enabled: boo
     }
 }
 
-// Normalise fields a spreadsheet (LibreOffice, Excel) is likely to have
-// mangled on a save round-trip. Only the leading-zero numeric memory channel
-// format (00001–00999) is affected: spreadsheets open it as Number and strip
-// the zeros. PMS (`P-01L`), 5MHz band (`50001`), and EMGCH already survive
-// because they contain non-digits or no leading zero.
-fn normalize_record(record: &mut CsvRecord) {
-    let ch = &record.channel;
-    if (1..5).contains(&ch.len()) && ch.chars().all(|c| c.is_ascii_digit()) {
-        record.channel = format!("{:0>5}", ch);
+/// Prints a record's errors and warnings, if it has any.
+fn print_findings(number: usize, errors: &[String], warnings: &[String]) {
+    if errors.is_empty() && warnings.is_empty() {
+        return;
+    }
+    let label = if errors.is_empty() { "has warnings" } else { "is invalid" };
+    println!("Record {} {}:", number, label);
+    for error in errors {
+        println!("  - {}", error);
+    }
+    for warning in warnings {
+        println!("  ! {}", warning);
     }
 }
 
-fn validate_record(record: &CsvRecord, allow_any_frequency: bool) -> Result<(), Vec<String>> {
-    let mut errors = Vec::new();
+/// Copies a memory file to `out` with the fixes in fix.rs applied,
+/// reports them, and checks the result.
+fn fix_data(cli: &Cli, file: &str, out: &str) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let input = std::fs::File::open(file).map_err(|e| {
+        if !quiet { println!("Error opening file '{}': {}", file, e); }
+    })?;
+    let output = std::fs::File::create(out).map_err(|e| {
+        if !quiet { println!("Failed to create '{}': {}", out, e); }
+    })?;
+    let report = |n: usize, column: &str, old: &str, new: &str| {
+        if !quiet { println!("Record {}: {} '{}' -> '{}'.", n, column, old, new); }
+    };
+    let changes = fix::fix_file(input, output, dialect::file_delimiter(file), report).map_err(|e| {
+        if !quiet { println!("Error reading '{}': {}", file, e); }
+    })?;
+    if !quiet { println!("Made {} fix(es); wrote {}.\n", changes, out); }
+    check_data(out, quiet, true, &check_options(cli)?)
+}
 
-    // Validate channel
-    if record.channel.len() != 5 {
-        errors.push(format!("Channel '{}' has invalid length. Expected 5.", record.channel));
-    } else {
-        let chars: Vec<char> = record.channel.chars().collect();
-        let ch_array: [char; 5] = [chars[0], chars[1], chars[2], chars[3], chars[4]];
-        if MemoryChannel::try_from(&ch_array).is_err() {
-            errors.push(format!("Channel '{}' is not a valid memory channel.", record.channel));
-        }
+/// Writes the memory channels to a CSV that rigctl's load_channels reads.
+/// Only the numbered channels (1-999) carry over; Hamlib has no slot
+/// numbers for PMS and 5 MHz channels, so those are skipped.
+fn export_hamlib(cli: &Cli, file: &str, out: &str) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let group = group_filter(cli)?;
+    check_data(file, quiet, false, &check_options(cli)?)?;
+    let mut rdr = dialect::reader(file).map_err(|_| ())?;
+    let mut wtr = csv::Writer::from_path(out).map_err(|e| {
+        if !quiet { println!("Failed to create '{}': {}", out, e); }
+    })?;
+    wtr.write_record(hamlib::CSV_HEADER).map_err(|_| ())?;
+    let (mut written, mut skipped) = (0, 0);
+    for result in rdr.deserialize::<CsvRecord>() {
+        let mut r = result.map_err(|_| ())?;
+        normalize_record(&mut r, cli.fix_tags);
+        if !in_group(r.freq, group) { continue; }
+        let Some(channel) = hamlib_channel(&r)? else {
+            skipped += 1;
+            continue;
+        };
+        wtr.write_record(channel.to_row()).map_err(|_| ())?;
+        written += 1;
     }
-
-    // Validate frequency. The type-level check enforces the 9-char wire-format
-    // limit; the radio-coverage range check is gated by --allow-any-frequency
-    // so MARS-CAP units can program out-of-band channels.
-    match FrequencyHz::try_from(record.freq) {
-        Err(_) => errors.push(format!("Frequency '{}' is not valid.", record.freq)),
-        Ok(f) if !allow_any_frequency && !f.is_in_radio_range() => errors.push(format!(
-            "Frequency '{}' is not valid: outside the radio's documented coverage (30 kHz\u{2013}174 MHz, 400\u{2013}470 MHz). Pass --allow-any-frequency for MARS-CAP units.",
-            record.freq
-        )),
-        Ok(_) => {}
+    wtr.flush().map_err(|_| ())?;
+    if !quiet {
+        println!("Exported {} channel(s) to {}.", written, out);
+        if skipped > 0 { println!("Skipped {} PMS/5 MHz/special channel(s) Hamlib can't number.", skipped); }
     }
+    Ok(())
+}
 
-    // Validate clarifier offset: 0000 - 9990 (Hz)
-    if ClarifierOffsetHz::try_from(record.clarifier_offset_hz).is_err() {
-        errors.push(format!(
-            "Clarifier offset '{}' is not a valid number.",
-            record.clarifier_offset_hz
-        ));
+/// Converts a frequency list (see import.rs) into a memory CSV.
+fn import_list(cli: &Cli, list: &str, file: &str) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let text = std::fs::read_to_string(list).map_err(|e| {
+        if !quiet { println!("Failed to read '{}': {}", list, e); }
+    })?;
+    let template = auto_tag_template(cli)?;
+    let (entries, skipped) = import::parse_list(&text);
+    let plan = match (&cli.auto_shift, &cli.band_plan) {
+        (false, _) => None,
+        (true, None) => Some(BandPlan::Us),
+        (true, Some(name)) => Some(BandPlan::try_from(name.as_str()).map_err(|_| {
+            if !quiet { println!("Unknown band plan '{}' (expected r1, r2, r3, us, eu or ja).", name); }
+        })?),
+    };
+    if !quiet {
+        for (line, reason) in &skipped {
+            println!("Line {}: {}; skipped.", line, reason);
+        }
     }
-
-    // Validate mode via the canonical Mode::try_from rather than a duplicated
-    // allowlist, so check_data never drifts from what MemoryReadWrite accepts.
-    if Mode::try_from(record.mode.clone()).is_err() {
-        errors.push(format!("Mode '{}' is not a valid mode.", record.mode));
+    if entries.len() > CHANNELS as usize {
+        if !quiet { println!("The list has {} channels; the radio holds {}.", entries.len(), CHANNELS); }
+        return Err(());
     }
-
-    if errors.is_empty() {
-        Ok(())
-    } else {
-        Err(errors)
+    let mut wtr = create_memory_file(cli, file)?;
+    let mut guessed = 0;
+    for (i, entry) in entries.iter().enumerate() {
+        let channel = format!("{:05}", i + 1);
+        let mut shift = entry.shift;
+        let mut tx_frequency_hz = None;
+        if let Some(plan) = plan
+            && entry.shift == Shift::Simplex
+            && let Some((guess, tx)) = import::guess_shift(entry.freq, plan)
+        {
+            (shift, tx_frequency_hz) = (guess, tx);
+            guessed += 1;
+            if !quiet {
+                let how = match tx {
+                    Some(hz) => format!("split TX {} Hz", hz),
+                    None => shift.to_string(),
+                };
+                println!("Channel {} ({} Hz): guessed {} from the {} band plan.", channel, entry.freq, how, plan);
+            }
+        }
+        let mut rec = memfile::list_record(entry, channel, shift, tx_frequency_hz);
+        if let Some(template) = template { auto_tag(&mut rec, template, &entry.callsign); }
+        localize(&mut rec, cli);
+        wtr.serialize(&rec).map_err(|_| ())?;
+    }
+    wtr.flush().map_err(|_| ())?;
+    if !quiet {
+        println!("Imported {} channel(s) into {}.", entries.len(), file);
+        if guessed > 0 { println!("Guessed the repeater shift of {} channel(s); check them before writing.", guessed); }
     }
+    print_table(file, cli.plain, None, quiet)
 }
 
-fn print_table(file_path: &str, plain: bool, quiet: bool) -> Result<(), ()> {
+fn print_table(file_path: &str, plain: bool, group: Option<MemoryGroup>, quiet: bool) -> Result<(), ()> {
     if quiet { return Ok(()); }
-    let mut rdr = csv::ReaderBuilder::new()
-        .comment(Some(b'#'))
-        .from_path(file_path)
-        .map_err(|_| ())?;
+    let mut rdr = dialect::reader(file_path).map_err(|e| println!("Error opening file '{}': {}", file_path, e))?;
+    require_headers(&mut rdr, file_path, quiet)?;
+    let records: Vec<CsvRecord> = rdr.deserialize::<CsvRecord>().collect::<Result<_, _>>().map_err(|_| ())?;
+    print_records(records.into_iter().filter(|r| in_group(r.freq, group)), plain);
+    Ok(())
+}
+
+fn print_records(records: impl Iterator<Item = CsvRecord>, plain: bool) {
     let mut table = Table::new();
 
     if plain {
@@ -375,7 +1325,7 @@ fn print_table(file_path: &str, plain: bool, quiet: bool) -> Result<(), ()> {
             .set_content_arrangement(ContentArrangement::Dynamic);
     }
 
-    let headers = ["Ch", "Frequency", "Tag", "Mode", "Type", "Squelch", "Shift", "Clar (Hz)", "RX Clar", "TX Clar", "CTCSS", "DCS", "Split TX"];
+    let headers = ["Ch", "Frequency", "Tag", "Mode", "Type", "Squelch", "Shift", "Clar (Hz)", "RX Clar", "TX Clar", "CTCSS", "DCS", "Split TX", "Group"];
     table.set_header(headers.iter().map(|h| {
         if plain {
             Cell::new(h)
@@ -386,8 +1336,7 @@ fn print_table(file_path: &str, plain: bool, quiet: bool) -> Result<(), ()> {
         }
     }));
 
-    for result in rdr.deserialize::<CsvRecord>() {
-        let r = result.map_err(|_| ())?;
+    for r in records {
         let freq = format!("{:.3} MHz", r.freq as f64 / 1_000_000.0);
         let tag = r.tag.as_deref().unwrap_or("").to_string();
         let squelch = r.tone.to_string();
@@ -424,228 +1373,2940 @@ fn print_table(file_path: &str, plain: bool, quiet: bool) -> Result<(), ()> {
                 Some(hz) => make(format!("{:.3} MHz", hz as f64 / 1_000_000.0),      Color::Magenta),
                 None     => make(String::new(),                                      Color::DarkGrey),
             },
+            make(MemoryGroup::of(r.freq).to_string(),                                Color::DarkGrey),
         ]);
     }
     println!("{table}");
-    Ok(())
 }
 
-fn read_radio_data(cli: &Cli) -> Result<(), ()> {
+/// The channels of --file, or of the radio when there's no --file.
+fn load_channels(cli: &Cli) -> Result<Vec<CsvRecord>, ()> {
     let quiet = cli.quiet;
-    let file = cli.file.clone().unwrap_or_else(default_filename);
-    let mut port = open_radio(&cli.port, cli.speed, quiet)?;
-    let mut wtr = csv::Writer::from_path(&file).map_err(|_| ())?;
-
-    if !quiet { println!("Reading memory channels..."); }
-    let bar = if quiet { ProgressBar::hidden() } else { ProgressBar::new(CHANNELS as u64) };
-    let mut memory_list: Vec<MemoryReadWrite> = Vec::new();
-    for ch in 1..=CHANNELS {
-        bar.inc(1);
-        let mem = read_mem(&mut *port, ch);
-        if let Ok(m) = mem {
-            memory_list.push(m);
+    let mut records: Vec<CsvRecord> = match &cli.file {
+        Some(file) => read_memory_file(file, quiet)?,
+        None => {
+            let mut port = open_radio(&cli.port, cli.speed, quiet)?;
+            // Only the table goes to a terminal; keep CSV and JSON clean.
+            let bar = if cli.format == "table" { Progress::new(cli, usize::from(CHANNELS)) } else { Progress::Bar(ProgressBar::hidden()) };
+            read_channels(&mut *port, bar)?
         }
+    };
+    for r in &mut records {
+        normalize_record(r, cli.fix_tags);
+    }
+    Ok(records)
+}
+
+/// Every record of a memory file, as written.
+fn read_memory_file(file: &str, quiet: bool) -> Result<Vec<CsvRecord>, ()> {
+    let mut rdr = dialect::reader(file).map_err(|e| {
+        if !quiet { println!("Error opening file '{}': {}", file, e); }
+    })?;
+    require_headers(&mut rdr, file, quiet)?;
+    rdr.deserialize::<CsvRecord>().collect::<Result<_, _>>().map_err(|e| {
+        if !quiet { println!("Error reading '{}': {}", file, e); }
+    })
+}
+
+/// Every numbered channel on the radio that isn't empty.
+fn read_channels(port: &mut dyn Transport, mut bar: Progress) -> Result<Vec<CsvRecord>, ()> {
+    let mut records = Vec::new();
+    for ch in 1..=CHANNELS {
+        bar.inc();
+        records.extend(resumed(|| read_channel(port, ch))?);
     }
     bar.finish();
+    Ok(records)
+}
+
+/// Prints the channels of --file, or of the radio when there's no --file,
+/// that match the --filter query (see filter.rs), in --format.
+fn filter_channels(cli: &Cli, query: &str) -> Result<(), ()> {
+    let filter = filter::Filter::parse(query).map_err(|e| {
+        if !cli.quiet { println!("Bad --filter query '{}': {}.", query, e); }
+    })?;
+    let matching = load_channels(cli)?.into_iter().filter(|r| {
+        filter.matches(&filter::ChannelView {
+            freq: r.freq,
+            mode: &r.mode,
+            tag: r.tag.as_deref().unwrap_or(""),
+            split: r.tx_frequency_hz.is_some(),
+        })
+    });
+    let stdout = io::stdout();
+    match cli.format.as_str() {
+        "csv" => {
+            let mut wtr = memory_writer(cli, stdout.lock())?;
+            for mut r in matching {
+                localize(&mut r, cli);
+                wtr.serialize(&r).map_err(|_| ())?;
+            }
+            wtr.flush().map_err(|_| ())?;
+        }
+        "json" => {
+            let records: Vec<CsvRecord> = matching.collect();
+            println!("{}", serde_json::to_string_pretty(&records).map_err(|_| ())?);
+        }
+        "hamlib" => {
+            let mut wtr = csv::Writer::from_writer(stdout.lock());
+            wtr.write_record(hamlib::CSV_HEADER).map_err(|_| ())?;
+            for r in matching {
+                if let Some(channel) = hamlib_channel(&r)? {
+                    wtr.write_record(channel.to_row()).map_err(|_| ())?;
+                }
+            }
+            wtr.flush().map_err(|_| ())?;
+        }
+        _ => print_records(matching, cli.plain),
+    }
+    Ok(())
+}
 
-    // Pre-compute the per-channel numeric ids from the MR results. Earlier
-    // versions iterated 1..=memory_list.len() which silently broke when the
-    // programmed channels weren't contiguous starting at 1 (tags landed on
-    // the wrong rows). This pulls the real channel number out of each MR
-    // response so the secondary lookups can't go out of sync.
-    let channel_numbers: Vec<u16> = memory_list
+fn summarize_channels(cli: &Cli) -> Result<(), ()> {
+    if !matches!(cli.format.as_str(), "table" | "json") {
+        if !cli.quiet { println!("--summary prints a table or json, not {}.", cli.format); }
+        return Err(());
+    }
+    let records = load_channels(cli)?;
+    let tones: Vec<String> = records.iter().map(effective_tone).collect();
+    let entries: Vec<summary::Entry> = records
         .iter()
-        .filter_map(|m| if let MemoryChannel::Mem(n) = &m.channel { Some(*n) } else { None })
+        .zip(&tones)
+        .map(|(r, tone)| summary::Entry { freq: r.freq, mode: &r.mode, tone, split: r.tx_frequency_hz.is_some() })
         .collect();
+    let s = summary::summarize(&entries, usize::from(CHANNELS));
+    if cli.format == "json" {
+        println!("{}", serde_json::to_string_pretty(&s).map_err(|_| ())?);
+        return Ok(());
+    }
 
-    if !quiet { println!("Reading memory tags..."); }
-    let bar = if quiet { ProgressBar::hidden() } else { ProgressBar::new(channel_numbers.len() as u64) };
-    let mut tag_list: Vec<Option<String>> = Vec::new();
-    for &ch in &channel_numbers {
-        bar.inc(1);
-        tag_list.push(read_tag(&mut *port, ch));
+    println!("{} of {} channels used, {} empty, {} split.", s.used, CHANNELS, s.empty, s.split);
+    if let Some(c) = &s.coverage {
+        println!(
+            "Coverage: {:.3} MHz to {:.3} MHz.",
+            c.lowest_hz as f64 / 1_000_000.0,
+            c.highest_hz as f64 / 1_000_000.0
+        );
     }
-    bar.finish();
+    for (heading, counts) in [("Band", &s.bands), ("Mode", &s.modes), ("Tone", &s.tones)] {
+        if counts.is_empty() { continue; }
+        let mut table = Table::new();
+        if cli.plain {
+            table.load_preset(ASCII_FULL_CONDENSED);
+        } else {
+            table.load_preset(UTF8_FULL_CONDENSED);
+        }
+        table.set_header([heading, "Channels"]);
+        for c in counts {
+            table.add_row([c.name.clone(), c.channels.to_string()]);
+        }
+        println!("{table}");
+    }
+    Ok(())
+}
+
+fn read_radio_data(cli: &Cli) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let group = group_filter(cli)?;
+    let file = cli.file.clone().unwrap_or_else(default_filename);
+    // Checked before the radio is opened, and again as the file reopens.
+    if cli.resume { resumable_read(&file, &read_options(cli, group)?, quiet)?; }
+    let mut port = open_radio(&cli.port, cli.speed, quiet)?;
+    if cli.sync_clock { set_clock(&mut *port, cli.utc, quiet)?; }
+    save_channels(cli, &mut *port, &file, group, cli.resume)?;
+    print_table(&file, cli.plain, group, quiet)
+}
 
-    if !quiet { println!("Reading tone info..."); }
-    let bar = if quiet { ProgressBar::hidden() } else { ProgressBar::new(channel_numbers.len() as u64) };
-    let mut tone_list: Vec<(ToneCode, ToneCode)> = Vec::new();
-    for &ch in &channel_numbers {
-        bar.inc(1);
-        // There is no answer for this command, so we ignore the result
-        let _ = cat_send(&mut *port, &CMD_MC.set(Side::Sub, MemoryChannel::Mem(ch)))?;
-        let ctcss_tone_reply = cat_send(&mut *port, &CMD_CN.read(Side::Sub, ToneType::Ctcss))?;
-        let ctcss_tone_decoded = CMD_CN.decode(&ctcss_tone_reply)?;
-        let dcs_tone_reply = cat_send(&mut *port, &CMD_CN.read(Side::Sub, ToneType::Dcs))?;
-        let dcs_tone_decoded = CMD_CN.decode(&dcs_tone_reply)?;
-        tone_list.push((ctcss_tone_decoded.tone_code, dcs_tone_decoded.tone_code));
-    }
-
-    if !quiet { println!("Reading split memory info..."); }
-    let bar = if quiet { ProgressBar::hidden() } else { ProgressBar::new(channel_numbers.len() as u64) };
-    let mut split_list: Vec<Option<u32>> = Vec::new();
-    for &ch in &channel_numbers {
-        bar.inc(1);
-        split_list.push(read_split(&mut *port, ch));
+/// Reads every programmed channel (in `group`, if given) into a memory
+/// file. Each channel is written out as soon as it's read, and a
+/// checkpoint kept (see checkpoint.rs), so with `resume` an interrupted
+/// read carries on after the last channel it got through.
+fn save_channels(cli: &Cli, port: &mut dyn Transport, file: &str, group: Option<MemoryGroup>, resume: bool) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let options = read_options(cli, group)?;
+    let (mut wtr, first) = if resume { reopen_memory_file(cli, file, &options)? } else { (create_memory_file(cli, file)?, 1) };
+    let checkpoint_failed = |e: io::Error| {
+        if !quiet { println!("Failed to save the checkpoint '{}': {}", checkpoint::path(file).display(), e); }
+    };
+    let saved = |ch| checkpoint::save_read(file, ch, &options).map_err(checkpoint_failed);
+    write_channel_rows(cli, port, &mut wtr, group, first, saved)?;
+    checkpoint::remove(file).map_err(checkpoint_failed)?;
+    if !quiet { println!("Memory data saved to CSV file: {}", file); }
+    Ok(())
+}
+
+/// Reads channels `first` on into `wtr`, calling `done` after each one
+/// once its row has been flushed. Stops on the first channel that can't
+/// be read, without calling `done` for it.
+fn write_channel_rows<W: io::Write>(
+    cli: &Cli,
+    port: &mut dyn Transport,
+    wtr: &mut csv::Writer<W>,
+    group: Option<MemoryGroup>,
+    first: u16,
+    mut done: impl FnMut(u16) -> Result<(), ()>,
+) -> Result<(), ()> {
+    if !cli.quiet { println!("Reading memory channels..."); }
+    let mut bar = Progress::new(cli, usize::from(CHANNELS + 1 - first));
+    let mut previous_empty = false;
+    for ch in first..=CHANNELS {
+        bar.inc();
+        match resumed(|| read_mem_and_tag(&mut *port, ch, !previous_empty)) {
+            Ok(Some((m, tag))) => {
+                if in_group(m.frequency_hz.to_u32(), group) {
+                    let tone = resumed(|| read_tones(&mut *port, ch))?;
+                    let tx = read_split(&mut *port, ch);
+                    let mut rec = channel_record(m, tag, tone, tx)?;
+                    localize(&mut rec, cli);
+                    wtr.serialize(&rec).map_err(|_| ())?;
+                    // The row is on disk before the checkpoint moves past it.
+                    wtr.flush().map_err(|_| ())?;
+                }
+                previous_empty = false;
+            }
+            Ok(None) => previous_empty = true,
+            // Going on would move the checkpoint past a channel the file
+            // doesn't have; stop on it instead.
+            Err(()) => {
+                if !cli.quiet { println!("Failed to read channel {}.", ch); }
+                return Err(());
+            }
+        }
+        done(ch)?;
     }
     bar.finish();
+    wtr.flush().map_err(|_| ())
+}
 
-    // Combine memory data, tags, tones and split memory into CSV records
-    for (m, ((tag, tone), tx)) in zip(memory_list, zip(zip(tag_list, tone_list), split_list)) {
-        let rec = CsvRecord {
-            channel: m.channel.to_string()?,
-            tag,
-            freq: m.frequency_hz.to_u32(),
-            clarifier_offset_hz: m.clarifier_offset_hz.to_i16(),
-            rx_clarifier_enabled: m.rx_clarifier_enabled,
-            tx_clarifier_enabled: m.tx_clarifier_enabled,
-            mode: m.mode.to_string(),
-            ch_type: m.ch_type,
-            tone: m.sql_type,
-            shift: m.shift,
-            ctcss_tone: CmdCn::tone_code_to_string(ToneType::Ctcss, tone.0)?,
-            dcs_tone: CmdCn::tone_code_to_string(ToneType::Dcs, tone.1)?,
-            tx_frequency_hz: tx,
-        };
-        // println!("{:?}", rec);
-        wtr.serialize(&rec).unwrap();
+/// The options that shape a read's rows, as its checkpoint records them.
+fn read_options(cli: &Cli, group: Option<MemoryGroup>) -> Result<String, ()> {
+    let delimiter = match dialect::parse_delimiter(&cli.delimiter) {
+        Some(b',') => "comma",
+        Some(b';') => "semicolon",
+        Some(_) => "tab",
+        None => {
+            if !cli.quiet { println!("Unknown delimiter '{}' (expected ',', ';' or tab).", cli.delimiter); }
+            return Err(());
+        }
+    };
+    Ok(format!(
+        "delimiter={} freq-unit={} decimal-comma={} group={}",
+        delimiter,
+        cli.freq_unit,
+        if cli.decimal_comma { "yes" } else { "no" },
+        group.map_or_else(|| "all".to_string(), |g| g.to_string())
+    ))
+}
+
+/// The memory file an interrupted read left, opened to append to, and the
+/// channel to go on from: the one after the checkpoint, or after the last
+/// row in the file if that row got in before the checkpoint moved. The
+/// read must go on with the `options` it started with.
+fn reopen_memory_file(cli: &Cli, file: &str, options: &str) -> Result<(csv::Writer<std::fs::File>, u16), ()> {
+    let quiet = cli.quiet;
+    let last = resumable_read(file, options, quiet)?;
+    let rows = read_memory_file(file, quiet)?;
+    let last = rows.iter().filter_map(|r| r.channel.parse::<u16>().ok()).fold(last, u16::max).min(CHANNELS);
+    if last == CHANNELS && !quiet {
+        println!("The read of '{}' had already got through every channel.", file);
     }
-    wtr.flush().unwrap();
-    if !quiet { println!("Memory data saved to CSV file: {}", file); }
-    print_table(&file, cli.plain, quiet)
+    let delimiter = dialect::parse_delimiter(&cli.delimiter).ok_or(())?;
+    let out = std::fs::OpenOptions::new().append(true).open(file).map_err(|e| {
+        if !quiet { println!("Failed to open '{}': {}", file, e); }
+    })?;
+    if !quiet { println!("Resuming the read of '{}' after channel {} ({} rows so far).", file, last, rows.len()); }
+    Ok((dialect::writer(out, delimiter), last.saturating_add(1)))
+}
+
+/// The last channel the interrupted read into `file` checkpointed, once
+/// it's known the read can go on with `options`.
+fn resumable_read(file: &str, options: &str, quiet: bool) -> Result<u16, ()> {
+    let saved = checkpoint::load_read(file).map_err(|e| {
+        if !quiet { println!("Can't use the checkpoint '{}': {}", checkpoint::path(file).display(), e); }
+    })?;
+    let Some(saved) = saved else {
+        if !quiet { println!("No interrupted read of '{}' to resume ({} not found).", file, checkpoint::path(file).display()); }
+        return Err(());
+    };
+    if saved.options != options {
+        if !quiet {
+            println!("The interrupted read of '{}' was made with other options; resume it with the same ones.", file);
+            println!("  then: {}", if saved.options.is_empty() { "(not recorded)" } else { &saved.options });
+            println!("  now:  {}", options);
+        }
+        return Err(());
+    }
+    Ok(saved.last)
+}
+
+fn channel_record(
+    m: MemoryReadWrite,
+    tag: Option<String>,
+    tone: (ToneCode, ToneCode),
+    tx: Option<u32>,
+) -> Result<CsvRecord, ()> {
+    Ok(CsvRecord {
+        channel: m.channel.to_string()?,
+        tag,
+        freq: m.frequency_hz.to_u32(),
+        clarifier_offset_hz: m.clarifier_offset_hz.to_i16(),
+        rx_clarifier_enabled: m.rx_clarifier_enabled,
+        tx_clarifier_enabled: m.tx_clarifier_enabled,
+        mode: m.mode.to_string(),
+        ch_type: m.ch_type,
+        tone: m.sql_type,
+        shift: m.shift,
+        ctcss_tone: CmdCn::tone_code_to_string(ToneType::Ctcss, tone.0)?,
+        dcs_tone: CmdCn::tone_code_to_string(ToneType::Dcs, tone.1)?,
+        tx_frequency_hz: tx,
+        group: Some(MemoryGroup::of(m.frequency_hz.to_u32()).to_string()),
+    })
+}
+
+/// Reads one numbered channel with its tag, tones and split; None when
+/// the slot is empty.
+fn read_channel(port: &mut dyn Transport, ch: u16) -> Result<Option<CsvRecord>, ()> {
+    let Some((m, tag)) = read_mem_and_tag(port, ch, false)? else { return Ok(None) };
+    let tone = read_tones(port, ch)?;
+    let tx = read_split(port, ch);
+    channel_record(m, tag, tone, tx).map(Some)
+}
+
+/// The CTCSS and DCS codes of channel `ch`, read through the SUB side.
+fn read_tones(port: &mut dyn Transport, ch: u16) -> Result<(ToneCode, ToneCode), ()> {
+    // There is no answer for this command, so we ignore the result
+    let _ = cat_send(port, &CMD_MC.set(Side::Sub, MemoryChannel::Mem(ch)))?;
+    let ctcss = CMD_CN.decode(&cat_send(port, &CMD_CN.read(Side::Sub, ToneType::Ctcss))?).map_err(reported)?;
+    let dcs = CMD_CN.decode(&cat_send(port, &CMD_CN.read(Side::Sub, ToneType::Dcs))?).map_err(reported)?;
+    Ok((ctcss.tone_code, dcs.tone_code))
+}
+
+/// Longest a stale-data drain may take when the radio keeps sending.
+const DRAIN_LIMIT: Duration = Duration::from_secs(2);
+/// ID exchanges tried before the link is taken as out of step.
+const SYNC_ATTEMPTS: usize = 3;
+/// Times a channel is tried when the radio keeps dropping out during it.
+const RESUME_ATTEMPTS: usize = 3;
+
+/// Throws away what the radio sent before it was asked (a late reply to
+/// an aborted run, AI frames) and checks that an ID exchange comes back
+/// in step, so the run doesn't start by decoding the wrong replies. AI
+/// (auto information) is left as it is: find_reply skips its frames.
+fn synced_id(port: &mut dyn Transport, quiet: bool) -> Result<u16, ()> {
+    for attempt in 0..SYNC_ATTEMPTS {
+        if attempt > 0 {
+            stats::record_retry(&CMD_ID.read());
+        }
+        let stale = transport::drain(port, DRAIN_LIMIT);
+        if !stale.is_empty() {
+            debug!("Discarded {} stale bytes: {:?}", stale.len(), String::from_utf8_lossy(&stale));
+        }
+        if transport::has_frames(&stale) {
+            debug!("The radio is sending without being asked (AI on?); its frames are skipped");
+        }
+        if let Ok(id) = CMD_ID.decode(&cat_send(port, &CMD_ID.read())?) {
+            return Ok(id);
+        }
+    }
+    if !quiet { println!("The radio's replies are out of step after {} tries; reconnect or power-cycle it.", SYNC_ATTEMPTS); }
+    Err(())
 }
 
-fn read_validate_id(port: &mut dyn serialport::SerialPort, quiet: bool) -> Result<(), ()> {
-    let rx = cat_send(port, &CMD_ID.read())?;
-    let id = CMD_ID.decode(&rx)?;
+fn read_validate_id(port: &mut dyn Transport, quiet: bool) -> Result<(), ()> {
+    let id = synced_id(port, quiet)?;
     match CMD_ID.validate(id) {
         Ok(_) => { if !quiet { println!("Yaesu FTX-1 found (radio ID: {:04})", &id); } }
         Err(e) => { if !quiet { println!("Can't connect to Yaesu FTX-1: {:?}", e); } }
     }
+    // Firmware older than VE doesn't answer it; that's no error.
+    let version = cat_send(port, &CMD_VE.read()).ok().and_then(|rx| CMD_VE.decode(&rx).ok());
+    *FIRMWARE.lock().unwrap_or_else(|e| e.into_inner()) = version;
+    match version {
+        Some(v) if v < MIN_CAT_FIRMWARE => {
+            if !quiet { println!("MAIN firmware {} is older than the {} CAT needs; reads may be incomplete.", v, MIN_CAT_FIRMWARE); }
+        }
+        Some(v) => { if !quiet { println!("MAIN firmware {}", v); } }
+        None => debug!("No firmware version reply"),
+    }
     Ok(())
 }
 
-fn read_mem(port: &mut dyn serialport::SerialPort, ch: u16) -> Result<MemoryReadWrite, ()> {
-    let rx = cat_send(port, &CMD_MR.read(MemoryChannel::Mem(ch)))?;
-    CMD_MR.decode(&rx)
-}
-
-fn read_tag(port: &mut dyn serialport::SerialPort, ch: u16) -> Option<String> {
-    debug!("Reading tag for channel: {:?}", ch);
-    let rx = cat_send(port, &CMD_MT.read(MemoryChannel::Mem(ch))).ok()?;
-    let d = CMD_MT.decode(&rx);
-    match &d {
-        Ok(tag) => debug!("Tag: {:}", &tag),
-        Err(e) => error!("Error: {:?}", e),
+// Ok(None) means the radio reported the channel as empty.
+//
+// In a run of programmed channels MR and MT go out back-to-back so a
+// channel costs one wait instead of two. Programmed and empty channels
+// tend to come in runs, so after an empty one MR is sent alone and MT
+// only follows if the channel turns out to be programmed — a sparse
+// memory map then costs one short MR exchange per empty slot.
+fn read_mem_and_tag(
+    port: &mut dyn Transport,
+    ch: u16,
+    pipeline: bool,
+) -> Result<Option<(MemoryReadWrite, Option<String>)>, ()> {
+    let mr = CMD_MR.read(MemoryChannel::Mem(ch));
+    let mt = CMD_MT.read(MemoryChannel::Mem(ch));
+    let replies = if pipeline {
+        cat_send_pipelined(port, &[mr, mt])?
+    } else {
+        let rx = cat_query(port, &mr)?;
+        if is_error_reply(&rx) {
+            return Ok(None);
+        }
+        vec![rx, cat_send(port, &mt)?]
+    };
+    if is_error_reply(&replies[0]) {
+        return Ok(None);
     }
-    d.ok()
+    let mem = CMD_MR.decode(&replies[0]).map_err(reported)?;
+    debug!("Reading tag for channel: {:?}", ch);
+    // A tag that didn't come back is an error, not a blank tag: the CSV
+    // would lose it and a later write would erase it on the radio.
+    let tag = CMD_MT.decode(&replies[1]).map_err(reported)?;
+    debug!("Tag: {:}", &tag);
+    Ok(Some((mem, Some(tag.to_string()))))
 }
 
 // Returns Some(tx_freq_hz) only when split memory is enabled on the channel.
 // None means split is off (TX = RX) or the channel couldn't be read.
-fn read_split(port: &mut dyn serialport::SerialPort, ch: u16) -> Option<u32> {
+fn read_split(port: &mut dyn Transport, ch: u16) -> Option<u32> {
     let rx = cat_send(port, &CMD_MZ.read(MemoryChannel::Mem(ch))).ok()?;
     let reply = CMD_MZ.decode(&rx).ok()?;
     if reply.split_on { Some(reply.tx_frequency_hz.to_u32()) } else { None }
 }
 
-fn cat_send(port: &mut dyn serialport::SerialPort, data: &[u8]) -> Result<Vec<u8>, ()> {
+/// Logs a reply that didn't decode, raw frame and all, leaving the `()`
+/// error the rest of this file passes around.
+fn reported(e: DecodeError) {
+    error!("{}", e);
+}
+
+/// Set when an exchange ended with no reply (set commands, or a query the
+/// radio was slow to answer): whatever the radio sends late lands in front
+/// of the next reply.
+static LATE_REPLY: AtomicBool = AtomicBool::new(false);
+
+fn cat_send(port: &mut dyn Transport, data: &[u8]) -> Result<Vec<u8>, ()> {
+    let started = Instant::now();
     port.write_all(data).map_err(|_| ())?;
     trace!("Sent: {:?} {:?}", String::from_utf8_lossy(data), data);
+    trace::frame(trace::Direction::Tx, data);
 
     // CAT replies end with ';'. On Linux the kernel usually delivers the whole
     // reply in one read; on Windows the driver hands it back byte by byte, so
-    // we must accumulate until we see the terminator or actually time out.
+    // we accumulate until a complete reply to this query is in the buffer.
+    // Set commands get no reply and still end on the timeout.
     let mut buffer: Vec<u8> = Vec::with_capacity(RX_BUFFER_SIZE);
     let mut chunk: Vec<u8> = vec![0; RX_BUFFER_SIZE];
     loop {
         match port.read(chunk.as_mut_slice()) {
             Ok(n) => {
                 buffer.extend_from_slice(&chunk[..n]);
-                if buffer.last() == Some(&b';') || buffer.len() >= RX_BUFFER_SIZE {
+                if reply_complete(data, &buffer) || buffer.len() >= RX_BUFFER_SIZE {
                     break;
                 }
             }
             Err(ref e) if e.kind() == io::ErrorKind::TimedOut => break,
-            Err(e) => eprintln!("{:?}", e),
+            // A network link that closed won't recover by reading again.
+            Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                eprintln!("Connection to the radio closed.");
+                return Err(());
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            // Anything else won't clear by reading again; the radio is gone
+            // (and wasn't got back, see open_radio).
+            Err(e) => {
+                eprintln!("Lost the connection to the radio: {}", e);
+                return Err(());
+            }
         }
     }
     trace!("Received: {:?} {:?}", String::from_utf8_lossy(&buffer), buffer);
-    Ok(buffer)
+    trace::frame(trace::Direction::Rx, &buffer);
+    stats::record(data, buffer.len(), started.elapsed(), reply_complete(data, &buffer));
+
+    // Stale replies or line noise around the reply would make every decoder
+    // reject the buffer, or decode the wrong channel; hand back just the
+    // reply to this query, or nothing.
+    let found = find_reply(data, &buffer);
+    LATE_REPLY.store(found.is_none(), Ordering::SeqCst);
+    let reply = match found {
+        Some(reply) if reply.len() < buffer.len() => {
+            debug!("Resync: dropped {} byte(s) around the reply", buffer.len() - reply.len());
+            reply.to_vec()
+        }
+        Some(reply) => reply.to_vec(),
+        None => Vec::new(),
+    };
+    if is_error_reply(&reply) {
+        stats::record_error(data);
+    }
+    Ok(reply)
 }
 
-fn open_radio(port_name: &String, port_peed: u32, quiet: bool) -> Result<Box<dyn serialport::SerialPort>, ()> {
-    match serialport::new(port_name, port_peed).timeout(Duration::from_millis(200)).open() {
-        Ok(mut port) => {
-            if let Err(e) = read_validate_id(&mut *port, quiet) {
-                if !quiet { println!("Error validating radio ID: {:?}", e); }
+/// cat_send for a query whose "?;" means something, like an empty channel.
+/// If the exchange before got no reply, a "?;" may be its late answer
+/// rather than this query's, so the query is asked once more.
+fn cat_query(port: &mut dyn Transport, query: &[u8]) -> Result<Vec<u8>, ()> {
+    let late = LATE_REPLY.load(Ordering::SeqCst);
+    let reply = cat_send(port, query)?;
+    if late && is_error_reply(&reply) {
+        debug!("Resync: asking {:?} again in case its error reply was late", String::from_utf8_lossy(query));
+        stats::record_retry(query);
+        return cat_send(port, query);
+    }
+    Ok(reply)
+}
+
+/// Writes several frames at once and collects one reply per frame (see
+/// match_replies).
+fn cat_send_pipelined(port: &mut dyn Transport, frames: &[Vec<u8>]) -> Result<Vec<Vec<u8>>, ()> {
+    let started = Instant::now();
+    let out: Vec<u8> = frames.concat();
+    port.write_all(&out).map_err(|_| ())?;
+    trace!("Sent: {:?} {:?}", String::from_utf8_lossy(&out), out);
+    trace::frame(trace::Direction::Tx, &out);
+
+    // Counting ';' isn't enough: a stale or Auto Information frame would
+    // end the wait before the last reply is in. Read until every frame has
+    // its reply, or the timeout.
+    let mut buffer: Vec<u8> = Vec::with_capacity(RX_BUFFER_SIZE * frames.len());
+    let mut chunk: Vec<u8> = vec![0; RX_BUFFER_SIZE];
+    while match_replies(frames, &buffer).iter().any(Vec::is_empty) {
+        match port.read(chunk.as_mut_slice()) {
+            Ok(n) => {
+                buffer.extend_from_slice(&chunk[..n]);
+                if buffer.len() >= RX_BUFFER_SIZE * frames.len() {
+                    break;
+                }
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::TimedOut => break,
+            Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                eprintln!("Connection to the radio closed.");
+                return Err(());
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => {
+                eprintln!("Lost the connection to the radio: {}", e);
                 return Err(());
             }
-            Ok(port)
         }
-        Err(e) => {
-            if !quiet { println!("Failed to open port '{}': {:?}", port_name, e); }
-            Err(())
+    }
+    trace!("Received: {:?} {:?}", String::from_utf8_lossy(&buffer), buffer);
+    trace::frame(trace::Direction::Rx, &buffer);
+
+    // The batch shares one wait; each frame is charged an equal share.
+    let replies = match_replies(frames, &buffer);
+    let share = started.elapsed() / frames.len().max(1) as u32;
+    for (frame, reply) in frames.iter().zip(&replies) {
+        stats::record(frame, reply.len(), share, !reply.is_empty());
+        if is_error_reply(reply) {
+            stats::record_error(frame);
+        }
+    }
+    Ok(replies)
+}
+
+fn open_port(port_name: &String, port_peed: u32, quiet: bool) -> Result<Box<dyn Transport>, ()> {
+    if let Some(addr) = port_name.strip_prefix(RIGCTLD_SCHEME) {
+        return Rigctld::connect(addr).map(|r| Box::new(r) as Box<dyn Transport>).map_err(|e| {
+            if !quiet { println!("Failed to connect to rigctld at '{}': {}", addr, e); }
+        });
+    }
+    if let Some(addr) = port_name.strip_prefix(TCP_SCHEME) {
+        return RawTcp::connect(addr).map(|r| Box::new(r) as Box<dyn Transport>).map_err(|e| {
+            if !quiet { println!("Failed to connect to '{}': {}", addr, e); }
+        });
+    }
+    if let Some(addr) = port_name.strip_prefix(FLRIG_SCHEME) {
+        return Flrig::connect(addr).map(|r| Box::new(r) as Box<dyn Transport>).map_err(|e| {
+            if !quiet { println!("Failed to connect to flrig at '{}': {}", addr, e); }
+        });
+    }
+    if is_rfcomm(port_name) {
+        return Rfcomm::open(port_name, port_peed).map(|r| Box::new(r) as Box<dyn Transport>).map_err(|e| {
+            if !quiet { println!("Failed to open Bluetooth port '{}': {}", port_name, e); }
+        });
+    }
+    let port_name = &if cfg!(windows) { ports::normalize_port_name(port_name) } else { port_name.clone() };
+    let port_name = &if port_name == ports::AUTO_PORT {
+        let found = serialport::available_ports().ok().and_then(|p| ports::pick_cat_port(&p));
+        let Some(found) = found else {
+            if !quiet { println!("Couldn't tell which serial port is the radio; pass --port (see --list-ports)."); }
+            return Err(());
+        };
+        if !quiet { println!("Using port {}.", found); }
+        found
+    } else if let Some(cu) = ports::callout_twin(port_name) {
+        if !quiet { println!("Warning: {} waits for carrier detect and hangs on open; using {}.", port_name, cu); }
+        cu
+    } else {
+        port_name.clone()
+    };
+    serialport::new(port_name, port_peed)
+        .timeout(READ_TIMEOUT)
+        .open()
+        .map(|p| Box::new(p) as Box<dyn Transport>)
+        .map_err(|e| {
+            if !quiet {
+                println!("Failed to open port '{}': {:?}", port_name, e);
+                println!("Run --list-ports to see the serial ports on this machine.");
+            }
+        })
+}
+
+/// Opens the port and identifies the radio, with no reconnecting.
+fn connect_radio(port_name: &String, port_peed: u32, quiet: bool) -> Result<Box<dyn Transport>, ()> {
+    let mut port = open_port(port_name, port_peed, quiet)?;
+    if let Err(e) = read_validate_id(&mut *port, quiet) {
+        if !quiet { println!("Error validating radio ID: {:?}", e); }
+        return Err(());
+    }
+    Ok(port)
+}
+
+/// Opens the port and identifies the radio. If the radio goes away later
+/// (USB unplugged, link dropped), the port is reopened with the same
+/// settings and the radio identified again before the run goes on.
+fn open_radio(port_name: &String, port_peed: u32, quiet: bool) -> Result<Box<dyn Transport>, ()> {
+    let port = connect_radio(port_name, port_peed, quiet)?;
+    let port_name = port_name.clone();
+    let reopen: transport::Reopen = Box::new(move || {
+        let mut port = open_port(&port_name, port_peed, true).ok()?;
+        let id = synced_id(&mut *port, true).ok()?;
+        CMD_ID.validate(id).ok()?;
+        Some(port)
+    });
+    Ok(Box::new(Reconnecting::new(port, reopen)))
+}
+
+/// Runs `op` on one channel, again if the radio was reconnected while it
+/// ran: a channel the drop cut off is redone rather than skipped, and the
+/// run goes on from there.
+fn resumed<T>(mut op: impl FnMut() -> Result<T, ()>) -> Result<T, ()> {
+    let mut attempts = 1;
+    loop {
+        let before = transport::reconnects();
+        let result = op();
+        if result.is_ok() || transport::reconnects() == before || attempts == RESUME_ATTEMPTS {
+            return result;
         }
+        attempts += 1;
     }
 }
 
 fn write_radio_data(cli: &Cli, file: &str) -> Result<(), ()> {
+    let text = std::fs::read_to_string(file).map_err(|e| {
+        if !cli.quiet { println!("Error opening file '{}': {}", file, e); }
+    })?;
+    write_memory(cli, file, &text)
+}
+
+/// write_radio_data for a memory file already in memory, such as a
+/// decrypted backup. `file` names it in messages and its checkpoint.
+fn write_memory(cli: &Cli, file: &str, text: &str) -> Result<(), ()> {
     let quiet = cli.quiet;
-    check_data(file, quiet, false, !cli.no_warnings, cli.allow_any_frequency)?;
+    let group = group_filter(cli)?;
+    let template = auto_tag_template(cli)?;
+    let patch = if cli.patch {
+        Some(read_patch(memory_reader(file, text, quiet)?, file, quiet)?)
+    } else {
+        check_rows(memory_reader(file, text, quiet)?, file, quiet, false, &check_options(cli)?)?;
+        None
+    };
+    let done = if cli.resume { Some(resumable_write(file, quiet)?) } else { None };
+    let mut log = audit_log(cli)?;
     let mut port = open_radio(&cli.port, cli.speed, quiet)?;
+    require_firmware(MIN_CAT_FIRMWARE, "Writing memory channels", quiet)?;
 
-    let mut rdr = csv::ReaderBuilder::new()
-        .comment(Some(b'#'))
-        .from_path(file)
-        .map_err(|_| ())?;
-    let mut records: Vec<CsvRecord> = rdr.deserialize::<CsvRecord>().filter_map(|r| r.ok()).collect();
+    let mut records: Vec<CsvRecord> = match patch {
+        Some((headers, rows)) => patched_records(cli, &mut *port, &headers, rows)?,
+        None => {
+            let mut rdr = memory_reader(file, text, quiet)?;
+            rdr.deserialize::<CsvRecord>().filter_map(|r| r.ok()).collect()
+        }
+    };
     for r in &mut records {
-        normalize_record(r);
+        normalize_record(r, cli.fix_tags);
+        if let Some(template) = template { auto_tag(r, template, ""); }
     }
+    records.retain(|r| in_group(r.freq, group));
+    let checkpoint_failed = |e: io::Error| {
+        if !quiet { println!("Failed to save the checkpoint '{}': {}", checkpoint::path(file).display(), e); }
+    };
+    if let Some(done) = done {
+        let total = records.len();
+        records.retain(|r| done.get(&r.channel) != Some(&checkpoint::fingerprint(r)));
+        if !quiet { println!("Resuming the write from '{}': {} of {} channels already written and verified.", file, total - records.len(), total); }
+    } else {
+        checkpoint::start_write(file).map_err(checkpoint_failed)?;
+    }
+    // A resumed write's snapshot holds only what it's still to overwrite;
+    // the first run's snapshot has the rest.
+    if !cli.no_snapshot { take_snapshot(cli, &mut *port, &records)?; }
     if !quiet { println!("Writing memory data from CSV file: {} ({} records)... ", file, records.len()); }
-    let bar = if quiet { ProgressBar::hidden() } else { ProgressBar::new(records.len() as u64) };
+    let mut bar = Progress::new(cli, records.len());
+    let mut unverified = Vec::new();
     for rec in records {
-        bar.inc(1);
-        let mem = MemoryReadWrite::try_from(rec.clone())?;
-        debug!("Writing memory data for channel: {:?}", mem);
-        // MW first to ensure the channel slot exists. AM-only fails to create
-        // new (empty) channels because MC can't reliably select an empty slot.
-        // MW resets tones, but the AM step below re-commits them from VFO state.
-        let _ = cat_send(&mut *port, &CMD_MW.set(mem.clone())?)?;
-        // Put main in Memory mode and select the channel so AM later writes
-        // back to the correct memory slot; switch to VFO to build up state.
-        let _ = cat_send(&mut *port, &CMD_VM.set(Side::Main, VmMode::Memory))?;
-        let _ = cat_send(&mut *port, &CMD_MC.set(Side::Main, mem.channel.clone()))?;
-        let _ = cat_send(&mut *port, &CMD_VM.set(Side::Main, VmMode::Vfo))?;
-        // Set shift while in FM mode (OS is only accepted in FM), then flip to
-        // the target mode. This also clears stale shift state on non-FM channels.
-        let _ = cat_send(&mut *port, &CMD_MD.set(Side::Main, Mode::Fm))?;
-        let _ = cat_send(&mut *port, &CMD_OS.set(Side::Main, mem.shift.clone()))?;
-        let _ = cat_send(&mut *port, &CMD_MD.set(Side::Main, mem.mode.clone()))?;
-        let _ = cat_send(&mut *port, &CMD_FA.set(mem.frequency_hz))?;
-        let _ = cat_send(&mut *port, &CMD_CT.set(Side::Main, mem.sql_type.clone()))?;
-        let ctcss_code = CmdCn::tone_code_from_string(ToneType::Ctcss, &rec.ctcss_tone)?;
-        let _ = cat_send(&mut *port, &CMD_CN.set(Side::Main, ToneType::Ctcss, ctcss_code))?;
-        let dcs_code = CmdCn::tone_code_from_string(ToneType::Dcs, &rec.dcs_tone)?;
-        let _ = cat_send(&mut *port, &CMD_CN.set(Side::Main, ToneType::Dcs, dcs_code))?;
-        // Commit the full VFO state to the selected memory channel.
-        let _ = cat_send(&mut *port, &CMD_AM.save())?;
-        if let Some(tag) = rec.tag {
-            debug!("Writing tag for channel: {:?}, tag: {:?}", mem.channel, tag);
-            let _ = cat_send(&mut *port, &CMD_MT.set(mem.channel.clone(), tag)?)?;
-        }
-        // Split memory: enable with the explicit TX freq when set, or
-        // explicitly disable so a re-import correctly clears prior split state.
-        // P3 is required even when P2=0; reuse the RX freq as a valid placeholder.
-        let (split_on, tx_freq) = match rec.tx_frequency_hz {
-            Some(hz) => (true, FrequencyHz::try_from(hz)?),
-            None => (false, mem.frequency_hz),
-        };
-        let _ = cat_send(&mut *port, &CMD_MZ.set(mem.channel, split_on, tx_freq)?)?;
+        bar.inc();
+        resumed(|| audited(&mut *port, log.as_mut(), audit::Operation::Write, &rec, quiet, |port| write_channel(port, rec.clone())))?;
+        if resumed(|| verify_channel(&mut *port, &rec)) == Ok(true) {
+            checkpoint::save_written(file, &rec).map_err(checkpoint_failed)?;
+        } else {
+            unverified.push(rec.channel);
+        }
     }
     bar.finish();
+    if !unverified.is_empty() {
+        if !quiet {
+            println!("These channels didn't read back as written: {}", unverified.join(" "));
+            println!("Run the write again with --resume to retry just those.");
+        }
+        return Err(());
+    }
+    checkpoint::remove(file).map_err(checkpoint_failed)?;
     if !quiet { println!("Memory data written to radio."); }
 
     Ok(())
 }
+
+/// The channels an interrupted write from `file` got written and verified.
+fn resumable_write(file: &str, quiet: bool) -> Result<HashMap<String, String>, ()> {
+    match checkpoint::load_written(file) {
+        Ok(Some(done)) => Ok(done),
+        Ok(None) => {
+            if !quiet { println!("No interrupted write from '{}' to resume ({} not found).", file, checkpoint::path(file).display()); }
+            Err(())
+        }
+        Err(e) => {
+            if !quiet { println!("Can't use the checkpoint '{}': {}", checkpoint::path(file).display(), e); }
+            Err(())
+        }
+    }
+}
+
+/// Whether channel `rec` reads back from the radio as it was written. Only
+/// the numbered channels can be read back; the others are taken on trust.
+fn verify_channel(port: &mut dyn Transport, rec: &CsvRecord) -> Result<bool, ()> {
+    let Some(ch) = rec.channel.parse::<u16>().ok().filter(|ch| (1..=CHANNELS).contains(ch)) else { return Ok(true) };
+    let Some(mut back) = read_channel(port, ch)? else { return Ok(false) };
+    normalize_record(&mut back, false);
+    Ok(memfile::same_contents(&back, rec))
+}
+
+/// A reader for memory file `file`, whose contents are `text`.
+fn memory_reader(file: &str, text: &str, quiet: bool) -> Result<csv::Reader<io::Cursor<Vec<u8>>>, ()> {
+    dialect::text_reader(text.to_string()).map_err(|e| {
+        if !quiet { println!("Error opening file '{}': {}", file, e); }
+    })
+}
+
+/// The header and rows of a --patch file, its columns checked.
+fn read_patch<R: io::Read>(mut rdr: csv::Reader<R>, file: &str, quiet: bool) -> Result<(csv::StringRecord, Vec<csv::StringRecord>), ()> {
+    let headers = rdr.headers().cloned().unwrap_or_default();
+    memfile::check_patch_headers(&headers).map_err(|errors| {
+        if !quiet {
+            println!("File '{}' doesn't work as a patch:", file);
+            for error in errors {
+                println!("  - {}", error);
+            }
+            println!("A patch needs 'Channel Number' and any of: {}", CSV_COLUMNS[1..].join(", "));
+        }
+    })?;
+    let rows = rdr.records().collect::<Result<_, _>>().map_err(|e| {
+        if !quiet { println!("Error reading '{}': {}", file, e); }
+    })?;
+    Ok((headers, rows))
+}
+
+/// The --patch rows laid over the radio's channels (see
+/// memfile::patch_record) and checked like --check-data would.
+fn patched_records(cli: &Cli, port: &mut dyn Transport, headers: &csv::StringRecord, rows: Vec<csv::StringRecord>) -> Result<Vec<CsvRecord>, ()> {
+    let quiet = cli.quiet;
+    let options = check_options(cli)?;
+    let column = CSV_COLUMNS[0];
+    let channel_cell = headers.iter().position(|h| h == column).ok_or(())?;
+    if !quiet { println!("Reading the {} channel(s) the patch changes...", rows.len()); }
+    let mut records = Vec::new();
+    let mut cross = memfile::CrossCheck::default();
+    let mut invalid = 0;
+    for (i, row) in rows.iter().enumerate() {
+        let cell = row.get(channel_cell).unwrap_or("").trim();
+        // Only the numbered channels can be read back (see read_radio_data).
+        let current = match cell.parse::<u16>() {
+            Ok(ch) if (1..=CHANNELS).contains(&ch) => read_channel(port, ch)?,
+            _ => None,
+        };
+        let record = match memfile::patch_record(current.as_ref(), headers, row) {
+            Ok(record) => record,
+            Err(e) => {
+                if !quiet { print_findings(i + 1, &[format!("Channel '{}': {}.", cell, e)], &[]); }
+                invalid += 1;
+                continue;
+            }
+        };
+        let (record, mut errors, notes) = memfile::check_record(record, &options);
+        let warnings = cross.check(i + 1, &record, &mut errors, notes, &options);
+        if !quiet { print_findings(i + 1, &errors, &warnings); }
+        if errors.is_empty() {
+            records.push(record);
+        } else {
+            invalid += 1;
+        }
+    }
+    if invalid > 0 {
+        if !quiet { println!("The patch has {} invalid record(s); nothing was written.", invalid); }
+        return Err(());
+    }
+    if !quiet { println!("Channels not in the patch are left as they are."); }
+    Ok(records)
+}
+
+/// Reads the channels `records` are about to overwrite into a new
+/// snapshot for --undo.
+fn take_snapshot(cli: &Cli, port: &mut dyn Transport, records: &[CsvRecord]) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let dir = std::path::Path::new(&cli.snapshot_dir);
+    std::fs::create_dir_all(dir).map_err(|e| {
+        if !quiet { println!("Failed to create snapshot directory '{}': {}", cli.snapshot_dir, e); }
+    })?;
+    let file = dir.join(timestamped_filename(snapshot::PREFIX)).to_string_lossy().into_owned();
+    let mut wtr = create_memory_file(cli, &file)?;
+    // Only the numbered channels can be read back (see read_radio_data).
+    let channels: Vec<u16> = records.iter().filter_map(|r| r.channel.parse().ok()).filter(|n| (1..=CHANNELS).contains(n)).collect();
+    if !quiet { println!("Saving {} channel(s) to snapshot {}...", channels.len(), file); }
+    let mut empty = Vec::new();
+    let mut bar = Progress::new(cli, channels.len());
+    for &ch in &channels {
+        bar.inc();
+        match read_channel(port, ch)? {
+            Some(mut r) => {
+                localize(&mut r, cli);
+                wtr.serialize(&r).map_err(|_| ())?;
+            }
+            None => empty.push(ch),
+        }
+    }
+    bar.finish();
+    wtr.flush().map_err(|_| ())?;
+    if !empty.is_empty() {
+        let mut out = wtr.into_inner().map_err(|_| ())?;
+        writeln!(out, "{}", snapshot::empty_line(&empty)).map_err(|_| ())?;
+    }
+    if !quiet { println!("Snapshot saved; --undo puts these channels back."); }
+    Ok(())
+}
+
+/// Prints the steps from the radio's channels (or the --file dump) to
+/// `layout_file` (see moves.rs), and with --apply carries them out.
+fn plan_moves(cli: &Cli, layout_file: &str) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    check_data(layout_file, quiet, false, &check_options(cli)?)?;
+    let mut rdr = dialect::reader(layout_file).map_err(|_| ())?;
+    let mut layout: Vec<CsvRecord> = rdr.deserialize().collect::<Result<_, _>>().map_err(|_| ())?;
+    for r in &mut layout {
+        normalize_record(r, cli.fix_tags);
+    }
+    let current = load_channels(cli)?;
+    let steps = moves::plan(&current, &layout);
+    let count = |kind: fn(&moves::Step) -> bool| steps.iter().filter(|s| kind(s)).count();
+    let erases = count(|s| matches!(s, moves::Step::Erase { .. }));
+    let changes = steps.len() - erases;
+    if !quiet {
+        for step in &steps {
+            println!("{}", step);
+        }
+        println!(
+            "{} move(s), {} retag(s), {} write(s), {} erase(s); {} channel(s) already in place.",
+            count(|s| matches!(s, moves::Step::Move { .. })),
+            count(|s| matches!(s, moves::Step::Retag { .. })),
+            count(|s| matches!(s, moves::Step::Write { .. })),
+            erases,
+            layout.len() - changes
+        );
+    }
+    if !cli.apply {
+        if !quiet && changes > 0 { println!("Pass --apply to carry it out."); }
+        return Ok(());
+    }
+
+    let record = |channel: &str| layout.iter().find(|r| r.channel == channel).cloned().ok_or(());
+    let changed: Vec<CsvRecord> = steps.iter().filter(|s| !matches!(s, moves::Step::Erase { .. })).map(|s| record(s.channel())).collect::<Result<_, _>>()?;
+    let mut log = audit_log(cli)?;
+    let mut port = open_radio(&cli.port, cli.speed, quiet)?;
+    require_firmware(MIN_CAT_FIRMWARE, "Writing memory channels", quiet)?;
+    if !changed.is_empty() { take_snapshot(cli, &mut *port, &changed)?; }
+    let mut bar = Progress::new(cli, changes);
+    for step in &steps {
+        match step {
+            moves::Step::Retag { channel, tag } => {
+                bar.inc();
+                let frame = CMD_MT.set(MemoryChannel::try_from(channel.clone())?, tag.clone())?;
+                audited(&mut *port, log.as_mut(), audit::Operation::Retag, &record(channel)?, quiet, |port| cat_send(port, &frame).map(|_| ()))?;
+            }
+            moves::Step::Move { .. } | moves::Step::Write { .. } => {
+                bar.inc();
+                let operation = if matches!(step, moves::Step::Move { .. }) { audit::Operation::Move } else { audit::Operation::Write };
+                let rec = record(step.channel())?;
+                audited(&mut *port, log.as_mut(), operation, &rec, quiet, |port| write_channel(port, rec.clone()))?;
+            }
+            moves::Step::Erase { .. } => {}
+        }
+    }
+    bar.finish();
+    if !quiet {
+        println!("Applied {} change(s).", changes);
+        for step in steps.iter().filter(|s| matches!(s, moves::Step::Erase { .. })) {
+            println!("Channel {} isn't in the layout; CAT can't clear it, so delete it on the radio.", step.channel());
+        }
+    }
+    Ok(())
+}
+
+/// --backup: one backup, or with --every one per period until Ctrl-C. A
+/// failed periodic backup is reported and retried at the next period.
+fn backup(cli: &Cli, dir: &str) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    if cli.keep == Some(0) {
+        if !quiet { println!("--keep needs at least 1."); }
+        return Err(());
+    }
+    let passphrase = read_passphrase(cli)?;
+    let Some(every) = &cli.every else { return backup_once(cli, dir, passphrase.as_deref()) };
+    let period = backup::parse_every(every).ok_or_else(|| {
+        if !quiet { println!("Expected a period like 30m, 24h or 7d for --every, got '{}'.", every); }
+    })?;
+    let stop = stop_on_ctrlc()?;
+    if !quiet { println!("Backing up to {} every {}; Ctrl-C to stop.", dir, every); }
+    while !stop.load(Ordering::SeqCst) {
+        if backup_once(cli, dir, passphrase.as_deref()).is_err() && !quiet {
+            println!("Backup failed; trying again in {}.", every);
+        }
+        let next = Instant::now() + period;
+        while !stop.load(Ordering::SeqCst) && Instant::now() < next {
+            std::thread::sleep(Duration::from_millis(500));
+        }
+    }
+    Ok(())
+}
+
+/// --sync: reconciles `file` with the radio through its state file (see
+/// sync.rs). Conflicts go by --prefer, else are asked about on a terminal,
+/// else are left for the next sync and fail the run.
+fn sync(cli: &Cli, file: &str) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let prefer = match &cli.prefer {
+        Some(p) => Some(sync::Side::try_from(p.as_str()).map_err(|_| {
+            if !quiet { println!("Expected radio or file for --prefer, got '{}'.", p); }
+        })?),
+        None => None,
+    };
+    check_data(file, quiet, false, &check_options(cli)?)?;
+    let normalized = |mut records: Vec<CsvRecord>| {
+        for r in &mut records {
+            normalize_record(r, cli.fix_tags);
+        }
+        records
+    };
+    let records = normalized(read_memory_file(file, quiet)?);
+    let state_file = sync::state_file(file);
+    let state = if std::path::Path::new(&state_file).exists() {
+        Some(normalized(read_memory_file(&state_file, quiet)?))
+    } else {
+        if !quiet { println!("No sync state in {} yet; channels on both sides that differ are conflicts.", state_file); }
+        None
+    };
+
+    let mut log = audit_log(cli)?;
+    let mut port = open_radio(&cli.port, cli.speed, quiet)?;
+    if !quiet { println!("Reading the radio's channels..."); }
+    let radio = normalized(read_channels(&mut *port, Progress::new(cli, usize::from(CHANNELS)))?);
+    let mut changes = sync::reconcile(state.as_deref(), &radio, &records);
+
+    let ask = prefer.is_none() && !quiet && io::stdin().is_terminal();
+    let mut unsettled = 0;
+    for (channel, change) in &mut changes {
+        let sync::Change::Conflict { radio, file } = &change else { continue };
+        if !quiet {
+            println!("Channel {} changed on both sides:", channel);
+            println!("  radio: {}", sync::describe(radio.as_ref()));
+            println!("  file:  {}", sync::describe(file.as_ref()));
+        }
+        let side = if ask { ask_side() } else { prefer };
+        match side {
+            Some(side) => *change = change.clone().resolve(side),
+            None => unsettled += 1,
+        }
+    }
+
+    let to_radio: Vec<CsvRecord> = changes.iter().filter_map(|(_, c)| match c {
+        sync::Change::ToRadio(r) => Some(r.clone()),
+        _ => None,
+    }).collect();
+    // What the written channels read back as, for the state.
+    let mut written = Vec::new();
+    if !to_radio.is_empty() {
+        require_firmware(MIN_CAT_FIRMWARE, "Writing memory channels", quiet)?;
+        take_snapshot(cli, &mut *port, &to_radio)?;
+        let mut bar = Progress::new(cli, to_radio.len());
+        for r in &to_radio {
+            bar.inc();
+            audited(&mut *port, log.as_mut(), audit::Operation::Sync, r, quiet, |port| write_channel(port, r.clone()))?;
+            if let Ok(ch) = r.channel.parse::<u16>()
+                && let Ok(Some(back)) = read_channel(&mut *port, ch)
+            {
+                written.extend(normalized(vec![back]));
+            }
+        }
+        bar.finish();
+    }
+    let to_file = changes.iter().filter(|(_, c)| matches!(c, sync::Change::ToFile(_) | sync::Change::DropFromFile)).count();
+    if to_file > 0 {
+        let mut wtr = create_memory_file(cli, file)?;
+        for mut r in sync::merged_file(&records, &changes) {
+            localize(&mut r, cli);
+            wtr.serialize(&r).map_err(|_| ())?;
+        }
+        wtr.flush().map_err(|_| ())?;
+    }
+    let mut wtr = create_memory_file(cli, &state_file)?;
+    for r in sync::next_state(state.as_deref(), &radio, &changes, &written) {
+        wtr.serialize(&r).map_err(|_| ())?;
+    }
+    wtr.flush().map_err(|_| ())?;
+
+    if !quiet {
+        for (channel, change) in &changes {
+            match change {
+                sync::Change::ToRadio(_) => println!("{}: file -> radio", channel),
+                sync::Change::ToFile(_) => println!("{}: radio -> file", channel),
+                sync::Change::DropFromFile => println!("{}: deleted on the radio, dropped from the file", channel),
+                sync::Change::EraseOnRadio => println!("{}: deleted from the file; CAT can't clear it, so delete it on the radio", channel),
+                sync::Change::Conflict { .. } => println!("{}: conflict left for the next sync", channel),
+            }
+        }
+        println!("{} channel(s) to the radio, {} into the file, {} conflict(s) left.", to_radio.len(), to_file, unsettled);
+    }
+    if unsettled > 0 {
+        if !quiet { println!("Settle conflicts with --prefer radio|file, or run --sync from a terminal to be asked."); }
+        return Err(());
+    }
+    Ok(())
+}
+
+/// Asks which side of a --sync conflict to keep; None to skip it.
+fn ask_side() -> Option<sync::Side> {
+    loop {
+        print!("Keep [r]adio, [f]ile or [s]kip? ");
+        io::stdout().flush().ok()?;
+        let mut answer = String::new();
+        if io::stdin().read_line(&mut answer).ok()? == 0 {
+            return None;
+        }
+        match answer.trim() {
+            "s" | "S" | "" => return None,
+            answer => {
+                if let Ok(side) = sync::Side::try_from(answer) {
+                    return Some(side);
+                }
+            }
+        }
+    }
+}
+
+/// --passphrase-file's passphrase: its first line.
+fn read_passphrase(cli: &Cli) -> Result<Option<Vec<u8>>, ()> {
+    let Some(file) = &cli.passphrase_file else { return Ok(None) };
+    let text = std::fs::read_to_string(file).map_err(|e| {
+        if !cli.quiet { println!("Failed to read passphrase file '{}': {}", file, e); }
+    })?;
+    let passphrase = text.lines().next().unwrap_or("");
+    if passphrase.is_empty() {
+        if !cli.quiet { println!("Passphrase file '{}' is empty.", file); }
+        return Err(());
+    }
+    Ok(Some(passphrase.as_bytes().to_vec()))
+}
+
+/// Writes one of the backup's files into `dir`, encrypted (see crypt.rs)
+/// when there's a passphrase. The plaintext never reaches the disk then.
+fn write_backup_file(cli: &Cli, dir: &std::path::Path, name: &str, data: &[u8], passphrase: Option<&[u8]>) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let (path, data) = match passphrase {
+        Some(passphrase) => {
+            let sealed = crypt::encrypt(passphrase, data).map_err(|e| {
+                if !quiet { println!("Failed to encrypt '{}': {}", name, e); }
+            })?;
+            (dir.join(format!("{}{}", name, backup::ENCRYPTED)), sealed)
+        }
+        None => (dir.join(name), data.to_vec()),
+    };
+    std::fs::write(&path, data).map_err(|e| {
+        if !quiet { println!("Failed to write '{}': {}", path.display(), e); }
+    })
+}
+
+/// Writes the manifest.txt that --restore checks a backup against (see
+/// backup.rs).
+fn write_manifest(cli: &Cli, dir: &std::path::Path, encrypted: bool) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let names = backup::files(encrypted);
+    let mut files = Vec::new();
+    for name in &names {
+        let data = std::fs::read(dir.join(name)).map_err(|e| {
+            if !quiet { println!("Failed to read back '{}': {}", dir.join(name).display(), e); }
+        })?;
+        files.push((name.as_str(), data));
+    }
+    let mut metadata = vec![
+        ("created", jiff::Timestamp::now().strftime("%Y-%m-%dT%H:%M:%SZ").to_string()),
+        ("tool", format!("ftx1-mm {}", env!("CARGO_PKG_VERSION"))),
+        ("port", cli.port.clone()),
+    ];
+    if let Some(v) = firmware() { metadata.push(("firmware", format!("MAIN {}", v))); }
+    if encrypted { metadata.push(("encrypted", format!("ChaCha20-Poly1305, PBKDF2-HMAC-SHA256 {} rounds", crypt::ROUNDS))); }
+    let files: Vec<(&str, &[u8])> = files.iter().map(|(name, data)| (*name, data.as_slice())).collect();
+    let text = backup::manifest(&metadata, &files);
+    let file = dir.join(backup::MANIFEST_FILE);
+    std::fs::write(&file, text).map_err(|e| {
+        if !quiet { println!("Failed to write '{}': {}", file.display(), e); }
+    })
+}
+
+/// --restore: checks a backup against its manifest, then writes its
+/// channels and settings back. `path` is a backup, or a --backup DIR to
+/// restore the newest in.
+fn restore(cli: &Cli, path: &str) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let mut dir = std::path::PathBuf::from(path);
+    if !dir.join(backup::MANIFEST_FILE).exists()
+        && let Ok(found) = backup::backups(&dir)
+        && let Some(newest) = found.last()
+    {
+        dir = newest.clone();
+    }
+    let manifest = dir.join(backup::MANIFEST_FILE);
+    let text = std::fs::read_to_string(&manifest).map_err(|e| {
+        if !quiet { println!("Can't read '{}': {}. Only backups made by --backup can be restored.", manifest.display(), e); }
+    })?;
+    let metadata = backup::verify(&text, |name| std::fs::read(dir.join(name))).map_err(|problems| {
+        if !quiet {
+            println!("Refusing to restore {}:", dir.display());
+            for problem in problems {
+                println!("  - {}", problem);
+            }
+        }
+    })?;
+    if !quiet {
+        println!("Backup {} checks out:", dir.display());
+        for (key, value) in metadata {
+            println!("  {}: {}", key, value);
+        }
+    }
+    let encrypted = dir.join(&backup::files(true)[0]).exists();
+    if !encrypted {
+        let file = |name: &str| dir.join(name).to_string_lossy().into_owned();
+        write_radio_data(cli, &file(backup::MEMORY_FILE))?;
+        return write_settings(cli, &file(backup::SETTINGS_FILE));
+    }
+
+    let Some(passphrase) = read_passphrase(cli)? else {
+        if !quiet { println!("Backup {} is encrypted; give its passphrase with --passphrase-file.", dir.display()); }
+        return Err(());
+    };
+    // Both are decrypted before anything is written, and only ever in
+    // memory (see backup.rs).
+    let open = |sealed: &str| {
+        let path = dir.join(sealed);
+        let data = std::fs::read(&path).map_err(|e| {
+            if !quiet { println!("Can't read '{}': {}", path.display(), e); }
+        })?;
+        let plain = crypt::decrypt(&passphrase, &data).map_err(|e| {
+            if !quiet { println!("Can't decrypt '{}': {}.", path.display(), e); }
+        })?;
+        Ok((path.to_string_lossy().into_owned(), plain))
+    };
+    let [memory, settings] = backup::files(true);
+    let (memory, text) = open(&memory)?;
+    let (settings, data) = open(&settings)?;
+    let text = String::from_utf8(text).map_err(|_| {
+        if !quiet { println!("'{}' doesn't hold a memory file.", memory); }
+    })?;
+    let records = setting_records(&settings, &data, quiet)?;
+    write_memory(cli, &memory, &text)?;
+    write_setting_records(cli, &settings, &records)
+}
+
+/// Writes a file only its owner can read.
+fn write_private(path: &std::path::Path, data: &[u8]) -> io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)?.write_all(data)
+}
+
+/// --decrypt: one encrypted backup file (see crypt.rs) back to plaintext
+/// in --file.
+fn decrypt_file(cli: &Cli, input: &str, output: &str) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let Some(passphrase) = read_passphrase(cli)? else {
+        if !quiet { println!("Error: --passphrase-file is required for --decrypt"); }
+        return Err(());
+    };
+    let data = std::fs::read(input).map_err(|e| {
+        if !quiet { println!("Error opening file '{}': {}", input, e); }
+    })?;
+    let plain = crypt::decrypt(&passphrase, &data).map_err(|e| {
+        if !quiet { println!("Can't decrypt '{}': {}.", input, e); }
+    })?;
+    write_private(std::path::Path::new(output), &plain).map_err(|e| {
+        if !quiet { println!("Failed to write '{}': {}", output, e); }
+    })?;
+    if !quiet { println!("Decrypted {} to {}", input, output); }
+    Ok(())
+}
+
+/// Reads the radio into backup directory `dir`. Both files are read whole
+/// before anything is written, so an encrypted backup never has plaintext
+/// on disk.
+fn fill_backup(cli: &Cli, port: &mut dyn Transport, dir: &std::path::Path, passphrase: Option<&[u8]>) -> Result<(), ()> {
+    let mut wtr = memory_writer(cli, Vec::new())?;
+    write_channel_rows(cli, &mut *port, &mut wtr, None, 1, |_| Ok(()))?;
+    let memory = wtr.into_inner().map_err(|_| ())?;
+    let settings = write_setting_rows(cli, &mut *port, Vec::new())?;
+    write_backup_file(cli, dir, backup::MEMORY_FILE, &memory, passphrase)?;
+    write_backup_file(cli, dir, backup::SETTINGS_FILE, &settings, passphrase)?;
+    write_manifest(cli, dir, passphrase.is_some())
+}
+
+/// Takes one backup into a new directory under `dir` (see backup.rs) and
+/// prunes to --keep. Fails when the radio can't be reached.
+fn backup_once(cli: &Cli, dir: &str, passphrase: Option<&[u8]>) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let mut port = open_radio(&cli.port, cli.speed, quiet)?;
+    let dir = std::path::Path::new(dir);
+    let name = timestamped_name(backup::PREFIX);
+    let done = dir.join(&name);
+    let partial = dir.join(format!("{}{}", name, backup::PARTIAL));
+    std::fs::create_dir_all(&partial).map_err(|e| {
+        if !quiet { println!("Failed to create backup directory '{}': {}", partial.display(), e); }
+    })?;
+    let saved = fill_backup(cli, &mut *port, &partial, passphrase);
+    if saved.is_err() {
+        let _ = std::fs::remove_dir_all(&partial);
+        return Err(());
+    }
+    std::fs::rename(&partial, &done).map_err(|e| {
+        if !quiet { println!("Failed to finish backup '{}': {}", done.display(), e); }
+    })?;
+    if !quiet { println!("Backup saved to {}", done.display()); }
+    if let Some(keep) = cli.keep {
+        let removed = backup::prune(dir, keep).map_err(|e| {
+            if !quiet { println!("Failed to prune old backups in '{}': {}", dir.display(), e); }
+        })?;
+        for path in removed {
+            if !quiet { println!("Deleted old backup {}", path.display()); }
+        }
+    }
+    Ok(())
+}
+
+/// Writes the newest snapshot back to the radio and marks it undone.
+fn undo(cli: &Cli) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let dir = std::path::Path::new(&cli.snapshot_dir);
+    let latest = snapshot::latest(dir).map_err(|e| {
+        if !quiet { println!("Can't read snapshot directory '{}': {}", cli.snapshot_dir, e); }
+    })?;
+    let Some(path) = latest else {
+        if !quiet { println!("No snapshot to undo in '{}'.", cli.snapshot_dir); }
+        return Err(());
+    };
+    let file = path.to_string_lossy().into_owned();
+    let mut rdr = dialect::reader(&file).map_err(|e| {
+        if !quiet { println!("Error opening file '{}': {}", file, e); }
+    })?;
+    require_headers(&mut rdr, &file, quiet)?;
+    let records: Vec<CsvRecord> = rdr.deserialize().collect::<Result<_, _>>().map_err(|e| {
+        if !quiet { println!("Error reading '{}': {}", file, e); }
+    })?;
+    let empty = snapshot::empty_channels(&std::fs::read_to_string(&path).unwrap_or_default());
+    let mut log = audit_log(cli)?;
+    let mut port = open_radio(&cli.port, cli.speed, quiet)?;
+    require_firmware(MIN_CAT_FIRMWARE, "Undoing a write", quiet)?;
+    if !quiet { println!("Restoring {} channel(s) from snapshot {}...", records.len(), file); }
+    let mut bar = Progress::new(cli, records.len());
+    for mut rec in records {
+        bar.inc();
+        normalize_record(&mut rec, false);
+        resumed(|| audited(&mut *port, log.as_mut(), audit::Operation::Restore, &rec, quiet, |port| write_channel(port, rec.clone())))?;
+    }
+    bar.finish();
+    let undone = snapshot::mark_undone(&path).map_err(|e| {
+        if !quiet { println!("Restored, but couldn't mark '{}' as undone: {}", file, e); }
+    })?;
+    if !quiet {
+        for ch in empty {
+            println!("Channel {:05} was empty before the write; CAT can't clear it, so delete it on the radio.", ch);
+        }
+        println!("Memory data restored; the snapshot is now {}.", undone.display());
+    }
+    Ok(())
+}
+
+/// --audit-log, opened for appending; None without it.
+fn audit_log(cli: &Cli) -> Result<Option<audit::Log>, ()> {
+    let Some(path) = &cli.audit_log else { return Ok(None) };
+    let operator = cli.operator.clone().unwrap_or_else(audit::default_operator);
+    audit::Log::open(path, operator, cli.reason.clone()).map(Some).map_err(|e| {
+        if !cli.quiet { println!("Failed to open audit log '{}': {}", path, e); }
+    })
+}
+
+/// Runs `write`, which puts `new` in its slot, and logs it to `log` with
+/// what the slot held before (see audit.rs).
+fn audited(
+    port: &mut dyn Transport,
+    log: Option<&mut audit::Log>,
+    operation: audit::Operation,
+    new: &CsvRecord,
+    quiet: bool,
+    write: impl FnOnce(&mut dyn Transport) -> Result<(), ()>,
+) -> Result<(), ()> {
+    let Some(log) = log else { return write(port) };
+    // Only the numbered channels can be read back (see read_radio_data).
+    let old = match new.channel.parse::<u16>() {
+        Ok(ch) if (1..=CHANNELS).contains(&ch) => read_channel(port, ch)?,
+        _ => None,
+    };
+    write(port)?;
+    log.append(operation, old.as_ref(), new).map_err(|e| {
+        if !quiet { println!("Channel {} was written, but the audit log failed: {}", new.channel, e); }
+    })
+}
+
+/// Programs one channel (see write_channel_frames).
+fn write_channel(port: &mut dyn Transport, rec: CsvRecord) -> Result<(), ()> {
+    let mem = MemoryReadWrite::try_from(rec.clone())?;
+    debug!("Writing memory data for channel: {:?}", mem);
+    let ctcss_code = CmdCn::tone_code_from_string(ToneType::Ctcss, &rec.ctcss_tone)?;
+    let dcs_code = CmdCn::tone_code_from_string(ToneType::Dcs, &rec.dcs_tone)?;
+    if let Some(tag) = &rec.tag {
+        debug!("Writing tag for channel: {:?}, tag: {:?}", mem.channel, tag);
+    }
+    let tx = rec.tx_frequency_hz.map(FrequencyHz::try_from).transpose()?;
+    for frame in write_channel_frames(&mem, (ctcss_code, dcs_code), rec.tag, tx)? {
+        let _ = cat_send(port, &frame)?;
+    }
+    Ok(())
+}
+
+fn read_settings(cli: &Cli) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let file = cli.file.clone().unwrap_or_else(|| timestamped_filename("ftx1_settings"));
+    let mut port = open_radio(&cli.port, cli.speed, quiet)?;
+    if cli.sync_clock { set_clock(&mut *port, cli.utc, quiet)?; }
+    save_settings(cli, &mut *port, &file)
+}
+
+/// Reads the radio-wide settings (see settings.rs) into a settings file.
+fn save_settings(cli: &Cli, port: &mut dyn Transport, file: &str) -> Result<(), ()> {
+    let out = std::fs::File::create(file).map_err(|_| ())?;
+    write_setting_rows(cli, port, out)?;
+    if !cli.quiet { println!("Settings saved to CSV file: {}", file); }
+    Ok(())
+}
+
+/// Reads the radio-wide settings into `out` as a settings file.
+fn write_setting_rows<W: io::Write>(cli: &Cli, port: &mut dyn Transport, mut out: W) -> Result<W, ()> {
+    if let Some(line) = firmware_comment() { writeln!(out, "{}", line).map_err(|_| ())?; }
+    let mut wtr = csv::Writer::from_writer(out);
+    // The per-band antenna settings move VFO-A; see settings.rs.
+    let home = vfo_decode(Side::Main, &cat_send(&mut *port, &vfo_read(Side::Main))?).ok();
+
+    if !cli.quiet { println!("Reading radio settings..."); }
+    let mut bar = Progress::new(cli, SETTINGS.len());
+    for setting in SETTINGS {
+        bar.inc();
+        let rx = cat_send(&mut *port, &(setting.read)())?;
+        match (setting.decode)(&rx) {
+            Ok(value) => {
+                debug!("Setting {}: {}", setting.name, value);
+                wtr.serialize(SettingRecord { name: setting.name.to_string(), value }).map_err(|_| ())?;
+            }
+            Err(e) => error!("Setting {}: {}", setting.name, e),
+        }
+    }
+    bar.finish();
+    if let Some(f) = home { let _ = cat_send(&mut *port, &vfo_set(Side::Main, f))?; }
+    wtr.into_inner().map_err(|_| ())
+}
+
+fn read_setting_records(file: &str, quiet: bool) -> Result<Vec<SettingRecord>, ()> {
+    let data = std::fs::read(file).map_err(|e| {
+        if !quiet { println!("Error opening file '{}': {}", file, e); }
+    })?;
+    setting_records(file, &data, quiet)
+}
+
+/// read_setting_records for a settings file already in memory.
+fn setting_records(file: &str, data: &[u8], quiet: bool) -> Result<Vec<SettingRecord>, ()> {
+    let mut rdr = csv::ReaderBuilder::new().comment(Some(b'#')).from_reader(data);
+    rdr.deserialize().collect::<Result<_, _>>().map_err(|e| {
+        if !quiet { println!("Error reading settings file '{}': {}", file, e); }
+    })
+}
+
+fn write_settings(cli: &Cli, file: &str) -> Result<(), ()> {
+    let records = read_setting_records(file, cli.quiet)?;
+    write_setting_records(cli, file, &records)
+}
+
+/// write_settings for settings already read; `file` names them in
+/// messages.
+fn write_setting_records(cli: &Cli, file: &str, records: &[SettingRecord]) -> Result<(), ()> {
+    let quiet = cli.quiet;
+
+    // Encode everything up front so a typo in row 10 doesn't leave the radio
+    // half-restored.
+    let mut commands: Vec<Vec<u8>> = Vec::new();
+    for rec in records {
+        let Some(setting) = settings::find(&rec.name) else {
+            if !quiet { println!("Unknown setting '{}'.", rec.name); }
+            return Err(());
+        };
+        match (setting.encode)(&rec.value) {
+            Ok(cmd) => commands.push(cmd),
+            Err(_) => {
+                if !quiet { println!("Setting '{}' has invalid value '{}'.", rec.name, rec.value); }
+                return Err(());
+            }
+        }
+    }
+
+    let mut port = open_radio(&cli.port, cli.speed, quiet)?;
+    require_firmware(MIN_CAT_FIRMWARE, "Writing settings", quiet)?;
+    let home = vfo_decode(Side::Main, &cat_send(&mut *port, &vfo_read(Side::Main))?).ok();
+    if !quiet { println!("Writing settings from CSV file: {} ({} settings)... ", file, commands.len()); }
+    let mut bar = Progress::new(cli, commands.len());
+    for cmd in commands {
+        bar.inc();
+        let _ = cat_send(&mut *port, &cmd)?;
+    }
+    bar.finish();
+    if let Some(f) = home { let _ = cat_send(&mut *port, &vfo_set(Side::Main, f))?; }
+    if !quiet { println!("Settings written to radio."); }
+    Ok(())
+}
+
+/// Beacons repeat no faster than this.
+const BEACON_MIN_INTERVAL: Duration = Duration::from_secs(10);
+/// Most of each --beacon-interval a beacon may spend keyed, in percent,
+/// so the finals get as long to cool as they transmit.
+const BEACON_MAX_DUTY: u64 = 50;
+
+fn cw_send(cli: &Cli, text: &str) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let segments = CmdKy::segments(text).map_err(|_| {
+        if !quiet { println!("Text '{}' contains characters the keyer can't send.", text); }
+    })?;
+    if segments.is_empty() {
+        if !quiet { println!("Nothing to send."); }
+        return Ok(());
+    }
+    if let Some(interval) = &cli.beacon_interval {
+        return cw_beacon(cli, &segments, interval);
+    }
+    let mut port = open_tx_radio(cli)?;
+    let wpm = CMD_KS.decode(&cat_send(&mut *port, &CMD_KS.read())?).map_err(reported)?;
+    key_cw(&mut *port, &segments, wpm, None, quiet)
+}
+
+/// Sends `segments` through the keyer. The keyer only holds one frame, so
+/// each segment is waited out before the next; a fault or `stop` ends the
+/// message between segments.
+fn key_cw(port: &mut dyn Transport, segments: &[String], wpm: u8, stop: Option<&AtomicBool>, quiet: bool) -> Result<(), ()> {
+    for segment in segments {
+        if stop.is_some_and(|stop| stop.load(Ordering::SeqCst)) {
+            break;
+        }
+        check_fault(port, quiet)?;
+        if !quiet { println!("Sending: {}", segment); }
+        let _ = cat_send(port, &CMD_KY.set(segment)?)?;
+        std::thread::sleep(Duration::from_millis(CmdKy::duration_ms(segment, wpm)));
+    }
+    Ok(())
+}
+
+/// --cw-send with --beacon-interval. Before every transmission the radio
+/// must still be in CW on the frequency the beacon started on, inside the
+/// region's TX coverage, and report no fault; the message may take at most
+/// BEACON_MAX_DUTY percent of the interval.
+fn cw_beacon(cli: &Cli, segments: &[String], interval: &str) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let interval = backup::parse_every(interval).filter(|i| *i >= BEACON_MIN_INTERVAL).ok_or_else(|| {
+        if !quiet { println!("Expected a --beacon-interval of at least 10s, like 60s or 10m, got '{}'.", interval); }
+    })?;
+    if cli.beacon_count == Some(0) {
+        if !quiet { println!("--beacon-count must be at least 1."); }
+        return Err(());
+    }
+    let region = check_options(cli)?.region;
+    let mut port = open_tx_radio(cli)?;
+    let port = &mut *port;
+    let wpm = CMD_KS.decode(&cat_send(port, &CMD_KS.read())?).map_err(reported)?;
+    let keyed: u64 = segments.iter().map(|s| CmdKy::duration_ms(s, wpm)).sum();
+    if keyed * 100 > interval.as_millis() as u64 * BEACON_MAX_DUTY {
+        if !quiet {
+            println!(
+                "The message takes about {:.0} s at {} WPM, more than {}% of the {} s interval; lengthen the interval.",
+                keyed as f64 / 1000.0, wpm, BEACON_MAX_DUTY, interval.as_secs()
+            );
+        }
+        return Err(());
+    }
+    let freq = CMD_FA.decode(&cat_send(port, &CMD_FA.read())?).map_err(reported)?;
+    let stop = stop_on_ctrlc()?;
+    // Ctrl-C or an error can land mid-message; nothing stays keyed.
+    let mut port = watchdog::UnkeyGuard::new(port);
+    let port = &mut *port;
+    if !quiet {
+        let count = cli.beacon_count.map(|n| format!("{} times", n)).unwrap_or_else(|| "until Ctrl-C".to_string());
+        println!("Beacon on {} Hz every {} s, {}.", freq.to_u32(), interval.as_secs(), count);
+    }
+
+    let mut sent = 0;
+    let mut result = Ok(());
+    while !stop.load(Ordering::SeqCst) && cli.beacon_count.is_none_or(|n| sent < n) {
+        let started = Instant::now();
+        let now_freq = CMD_FA.decode(&cat_send(port, &CMD_FA.read())?).map_err(reported)?;
+        let mode = CMD_MD.decode(&cat_send(port, &CMD_MD.read(Side::Main))?).map_err(reported)?;
+        let refusal = if now_freq != freq {
+            Some(format!("the frequency changed to {} Hz", now_freq.to_u32()))
+        } else if !region.can_transmit(freq) {
+            Some(format!("{} Hz is outside the {} version's TX coverage", freq.to_u32(), region))
+        } else if !matches!(mode, Mode::CwU | Mode::CwL) {
+            Some(format!("the radio is in {}, not CW", mode))
+        } else {
+            None
+        };
+        if let Some(refusal) = refusal {
+            if !quiet { println!("Beacon stopped: {}.", refusal); }
+            result = Err(());
+            break;
+        }
+        if let Err(()) = key_cw(port, segments, wpm, Some(&stop), quiet) {
+            result = Err(());
+            break;
+        }
+        sent += 1;
+        if !quiet { println!("Beacon {} sent.", sent); }
+        while !stop.load(Ordering::SeqCst) && started.elapsed() < interval && cli.beacon_count.is_none_or(|n| sent < n) {
+            std::thread::sleep(Duration::from_millis(200));
+        }
+    }
+    if !quiet { println!("Beacon ended after {} transmission(s).", sent); }
+    result
+}
+
+fn voice(cli: &Cli, args: &[String]) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let action = args[0].to_ascii_lowercase();
+    let slot = match (action.as_str(), args.get(1)) {
+        ("play" | "record" | "cq", Some(n)) => match n.parse::<u8>() {
+            Ok(n) if (1..=VOICE_MEMORIES).contains(&n) => Some(n),
+            _ => {
+                if !quiet { println!("Voice slot must be 1–{}, got '{}'.", VOICE_MEMORIES, n); }
+                return Err(());
+            }
+        },
+        ("stop" | "status", None) => None,
+        _ => {
+            if !quiet { println!("Expected play N, record N, cq N, stop or status for --voice."); }
+            return Err(());
+        }
+    };
+    if action == "play" { require_tx(cli, "--voice play")?; }
+    if let ("cq", Some(n)) = (action.as_str(), slot) {
+        require_tx(cli, "--voice cq")?;
+        return voice_cq(cli, n);
+    }
+    let mut port = open_tx_radio(cli)?;
+    match (action.as_str(), slot) {
+        ("play", Some(n)) => {
+            check_fault(&mut *port, quiet)?;
+            let _ = cat_send(&mut *port, &CMD_PB.play(n)?)?;
+            if !quiet { println!("Playing voice memory {}.", n); }
+        }
+        ("record", Some(n)) => {
+            let _ = cat_send(&mut *port, &CMD_LM.record(n)?)?;
+            if !quiet { println!("Recording voice memory {}; --voice stop ends it.", n); }
+        }
+        ("stop", _) => {
+            // Whichever is running; stopping an idle one is harmless.
+            let _ = cat_send(&mut *port, &CMD_PB.stop())?;
+            let _ = cat_send(&mut *port, &CMD_LM.stop())?;
+            if !quiet { println!("Voice memory stopped."); }
+        }
+        _ => {
+            let playing = CMD_PB.decode(&cat_send(&mut *port, &CMD_PB.read())?).map_err(reported)?;
+            let recording = CMD_LM.decode(&cat_send(&mut *port, &CMD_LM.read())?).map_err(reported)?;
+            match (playing, recording) {
+                (Some(n), _) => println!("Voice: playing memory {}", n),
+                (_, Some(n)) => println!("Voice: recording memory {}", n),
+                _ => println!("Voice: idle"),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A voice memory that plays longer than this is taken as stuck, and
+/// stopped; a DVS slot holds far less.
+const VOICE_MAX_PLAY: Duration = Duration::from_secs(30);
+
+/// --voice cq: plays `slot`, listens for --cq-interval, and again, until
+/// Enter, Ctrl-C, or the operator keys the radio to answer a caller.
+fn voice_cq(cli: &Cli, slot: u8) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let interval = match &cli.cq_interval {
+        Some(text) => backup::parse_every(text).ok_or_else(|| {
+            if !quiet { println!("Expected a --cq-interval like 8s or 1m, got '{}'.", text); }
+        })?,
+        None => Duration::from_secs(8),
+    };
+    let mut port = open_tx_radio(cli)?;
+    // Enter, Ctrl-C or an error may come mid-call.
+    let mut guard = watchdog::UnkeyGuard::new(&mut *port);
+    let port = &mut *guard;
+    let stop = stop_on_ctrlc()?;
+    if io::stdin().is_terminal() {
+        let enter = Arc::clone(&stop);
+        std::thread::spawn(move || {
+            let _ = io::stdin().read_line(&mut String::new());
+            enter.store(true, Ordering::SeqCst);
+        });
+    }
+    if !quiet { println!("Calling CQ from voice memory {} every {} s. Press Enter or key the radio to stop.", slot, interval.as_secs()); }
+
+    let mut calls = 0;
+    let mut answering = false;
+    let result = loop {
+        if stop.load(Ordering::SeqCst) {
+            break Ok(());
+        }
+        if let Err(()) = check_fault(port, quiet) {
+            break Err(());
+        }
+        let _ = cat_send(port, &CMD_PB.play(slot)?)?;
+        calls += 1;
+        let started = Instant::now();
+        std::thread::sleep(Duration::from_millis(500));
+        while !stop.load(Ordering::SeqCst) && CMD_PB.decode(&cat_send(port, &CMD_PB.read())?) != Ok(None) {
+            if started.elapsed() > VOICE_MAX_PLAY {
+                if !quiet { println!("Voice memory {} still playing after {} s; stopped.", slot, VOICE_MAX_PLAY.as_secs()); }
+                return Err(());
+            }
+            std::thread::sleep(Duration::from_millis(200));
+        }
+        // Listening: keying the radio means a caller is being answered.
+        let listening = Instant::now();
+        while !stop.load(Ordering::SeqCst) && listening.elapsed() < interval {
+            if CMD_RI.decode(&cat_send(port, &CMD_RI.read())?).is_ok_and(|flags| flags.contains(StatusFlags::TX)) {
+                if !quiet { println!("Radio keyed; CQ loop stopped."); }
+                answering = true;
+                stop.store(true, Ordering::SeqCst);
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(200));
+        }
+    };
+    // The operator is on the air; our call has already ended.
+    if answering { guard.disarm(); }
+    if !quiet { println!("{} call(s).", calls); }
+    result
+}
+
+/// Progress of the long CAT loops: indicatif's bar, or with --plain one
+/// "n/total" line per tenth done, which screen readers and log
+/// collectors can follow (the bar redraws itself with carriage returns).
+enum Progress {
+    Bar(ProgressBar),
+    Lines { done: usize, total: usize },
+}
+
+impl Progress {
+    fn new(cli: &Cli, total: usize) -> Self {
+        if cli.quiet {
+            Progress::Bar(ProgressBar::hidden())
+        } else if cli.plain {
+            Progress::Lines { done: 0, total }
+        } else {
+            Progress::Bar(ProgressBar::new(total as u64))
+        }
+    }
+
+    fn inc(&mut self) {
+        match self {
+            Progress::Bar(bar) => bar.inc(1),
+            Progress::Lines { done, total } => {
+                *done += 1;
+                if is_progress_step(*done, *total) { println!("{}/{}", done, total); }
+            }
+        }
+    }
+
+    fn finish(&self) {
+        if let Progress::Bar(bar) = self { bar.finish(); }
+    }
+}
+
+/// Whether `done` is the first count past a tenth of `total`.
+fn is_progress_step(done: usize, total: usize) -> bool {
+    done == total || done * 10 / total.max(1) != (done - 1) * 10 / total.max(1)
+}
+
+/// Flag that flips to true on Ctrl-C, for the loops that run until stopped.
+fn stop_on_ctrlc() -> Result<Arc<AtomicBool>, ()> {
+    let stop = Arc::new(AtomicBool::new(false));
+    let handler_stop = Arc::clone(&stop);
+    ctrlc::set_handler(move || handler_stop.store(true, Ordering::SeqCst)).map_err(|_| ())?;
+    Ok(stop)
+}
+
+/// Reads RI and fails if the radio reports a fault, so TX actions can
+/// bail out instead of keying into a bad antenna.
+fn check_fault(port: &mut dyn Transport, quiet: bool) -> Result<StatusFlags, ()> {
+    let flags = CMD_RI.decode(&cat_send(port, &CMD_RI.read())?).map_err(reported)?;
+    if flags.is_fault() {
+        if !quiet { println!("Radio reports a fault ({}); aborting.", flags); }
+        return Err(());
+    }
+    Ok(flags)
+}
+
+/// Starts a tune cycle and watches it to the end. The SWR meter only
+/// moves while the tuner keys the transmitter, so the last reading taken
+/// during the cycle is the result.
+fn tune(cli: &Cli) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let mut port = open_tx_radio(cli)?;
+    check_fault(&mut *port, quiet)?;
+    let _ = cat_send(&mut *port, &CMD_MS.set(TxMeter::Swr))?;
+    let _ = cat_send(&mut *port, &CMD_AC.set(TunerState::Tuning))?;
+    if !quiet { println!("Tuning..."); }
+
+    let mut swr: Option<u8> = None;
+    let mut done = false;
+    for _ in 0..60 {
+        std::thread::sleep(Duration::from_millis(250));
+        let rx = cat_send(&mut *port, &CMD_RM.read(Meter::Swr))?;
+        if let Ok(raw @ 1..) = CMD_RM.decode(Meter::Swr, &rx) {
+            swr = Some(raw);
+        }
+        let flags = CMD_RI.decode(&cat_send(&mut *port, &CMD_RI.read())?).map_err(reported)?;
+        if flags.is_fault() {
+            let _ = cat_send(&mut *port, &CMD_AC.set(TunerState::On))?;
+            if !quiet { println!("Radio reports a fault ({}); tuning aborted.", flags); }
+            return Err(());
+        }
+        if CMD_AC.decode(&cat_send(&mut *port, &CMD_AC.read())?).map_err(reported)? != TunerState::Tuning {
+            done = true;
+            break;
+        }
+    }
+    if !done {
+        let _ = cat_send(&mut *port, &CMD_AC.set(TunerState::On))?;
+        if !quiet { println!("Tuner did not finish within 15 s; stopped."); }
+        return Err(());
+    }
+    if !quiet {
+        match swr {
+            Some(raw) => println!("Tuned. SWR {:.1}:1", swr_from_raw(raw)),
+            None => println!("Tuned. No SWR reading was captured."),
+        }
+    }
+    Ok(())
+}
+
+/// --swr-sweep: keys on each step of `range` and reads the SWR meter
+/// (see sweep.rs). The radio's frequency, mode and power are restored
+/// however the sweep ends.
+fn swr_sweep(cli: &Cli, range: &str) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let (lo, hi) = sweep::parse_range(range).ok_or_else(|| {
+        if !quiet { println!("Expected a range in MHz like 14.0-14.3 for --swr-sweep, got '{}'.", range); }
+    })?;
+    if cli.sweep_power == 0 || cli.sweep_power > sweep::MAX_WATTS {
+        if !quiet { println!("--sweep-power must be 1-{} W, got {}.", sweep::MAX_WATTS, cli.sweep_power); }
+        return Err(());
+    }
+    if cli.sweep_step == 0 {
+        if !quiet { println!("--sweep-step must be at least 1 kHz."); }
+        return Err(());
+    }
+    let step = cli.sweep_step.saturating_mul(1000);
+    // Counted first: a small step over a wide range is a lot to build.
+    if sweep::step_count(lo, hi, step) as usize > sweep::MAX_STEPS {
+        if !quiet { println!("A --sweep-step of {} kHz gives more than {} steps; use a larger step.", cli.sweep_step, sweep::MAX_STEPS); }
+        return Err(());
+    }
+    let freqs = sweep::steps(lo, hi, step);
+    let region = check_options(cli)?.region;
+    if let Some(freq) = freqs.iter().find(|f| !FrequencyHz::try_from(**f).is_ok_and(|f| region.can_transmit(f))) {
+        if !quiet { println!("{} Hz is outside the {} version's TX coverage; not sweeping.", freq, region); }
+        return Err(());
+    }
+    let file = cli.file.clone().unwrap_or_else(|| timestamped_filename("ftx1_swr"));
+
+    let mut port = open_tx_radio(cli)?;
+    let port = &mut *port;
+    check_fault(port, quiet)?;
+    let old_freq = CMD_FA.decode(&cat_send(port, &CMD_FA.read())?).map_err(reported)?;
+    let old_mode = CMD_MD.decode(&cat_send(port, &CMD_MD.read(Side::Main))?).map_err(reported)?;
+    let old_power = CMD_PC.decode(&cat_send(port, &CMD_PC.read())?).map_err(reported)?;
+    let stop = stop_on_ctrlc()?;
+
+    let mut points = Vec::new();
+    let swept = sweep_points(cli, port, &freqs, old_power.amplifier, &stop, &mut points);
+    // Unkeyed in any case, then put back as it was.
+    let _ = cat_send(port, &CMD_TX.set(false));
+    let _ = cat_send(port, &CMD_PC.set(old_power));
+    let _ = cat_send(port, &CMD_MD.set(Side::Main, old_mode));
+    let _ = cat_send(port, &CMD_FA.set(old_freq));
+
+    if !points.is_empty() {
+        let mut text = format!("{}\n", sweep::CSV_HEADER);
+        points.iter().for_each(|p| text.push_str(&format!("{}\n", p.row())));
+        std::fs::write(&file, text).map_err(|e| {
+            if !quiet { println!("Failed to write '{}': {}", file, e); }
+        })?;
+        if !quiet {
+            if let Some(best) = sweep::best(&points) {
+                println!("Lowest SWR {:.2}:1 at {:.4} MHz.", best.swr(), f64::from(best.freq) / 1e6);
+            }
+            match sweep::bandwidth(&points, 2.0) {
+                Some((lo, hi)) => println!("Under 2:1 from {:.4} to {:.4} MHz.", f64::from(lo) / 1e6, f64::from(hi) / 1e6),
+                None => println!("Nowhere under 2:1."),
+            }
+            println!("{} readings saved to {}", points.len(), file);
+        }
+    }
+    swept
+}
+
+/// The keyed part of --swr-sweep; the caller unkeys and restores.
+fn sweep_points(
+    cli: &Cli,
+    port: &mut dyn Transport,
+    freqs: &[u32],
+    amplifier: Amplifier,
+    stop: &AtomicBool,
+    points: &mut Vec<sweep::Point>,
+) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let power = RfPower { amplifier, watts: cli.sweep_power };
+    let _ = cat_send(port, &CMD_PC.set(power))?;
+    let set = CMD_PC.decode(&cat_send(port, &CMD_PC.read())?).map_err(reported)?;
+    if set.watts > cli.sweep_power {
+        if !quiet { println!("The radio's power reads {} W after setting {} W; not keying.", set.watts, cli.sweep_power); }
+        return Err(());
+    }
+    let _ = cat_send(port, &CMD_MD.set(Side::Main, Mode::Fm))?;
+    let _ = cat_send(port, &CMD_MS.set(TxMeter::Swr))?;
+    if !quiet { println!("Sweeping {} steps at {} W. Press Ctrl-C to stop.", freqs.len(), set.watts); }
+    for &freq in freqs {
+        if stop.load(Ordering::SeqCst) {
+            if !quiet { println!("Stopped."); }
+            break;
+        }
+        let _ = cat_send(port, &CMD_FA.set(FrequencyHz::try_from(freq)?))?;
+        check_fault(port, quiet)?;
+        let _ = cat_send(port, &CMD_TX.set(true))?;
+        // The meter settles within a few hundred ms; the highest of three
+        // readings is kept, as a needle that hasn't risen yet reads low.
+        let mut raw = None;
+        for _ in 0..3 {
+            std::thread::sleep(Duration::from_millis(150));
+            if let Ok(r) = CMD_RM.decode(Meter::Swr, &cat_send(port, &CMD_RM.read(Meter::Swr))?) {
+                raw = raw.max(Some(r));
+            }
+        }
+        let flags = CMD_RI.decode(&cat_send(port, &CMD_RI.read())?).map_err(reported);
+        let _ = cat_send(port, &CMD_TX.set(false))?;
+        if let Ok(flags) = flags && flags.is_fault() {
+            if !quiet { println!("Radio reports a fault ({}) at {} Hz; sweep aborted.", flags, freq); }
+            return Err(());
+        }
+        match raw {
+            Some(raw) => {
+                let point = sweep::Point { freq, raw };
+                if !quiet { println!("{}", point.chart_line()); }
+                points.push(point);
+            }
+            None => if !quiet { println!("No SWR reading at {} Hz.", freq); },
+        }
+        // Gives the finals a rest between steps.
+        std::thread::sleep(Duration::from_millis(200));
+    }
+    Ok(())
+}
+
+fn info(cli: &Cli) -> Result<(), ()> {
+    let side = cli_side(cli)?;
+    let mut port = open_radio(&cli.port, cli.speed, cli.quiet)?;
+    let port = &mut *port;
+    fn show<T: std::fmt::Display>(label: &str, value: Result<T, ()>) {
+        match value {
+            Ok(v) => println!("{:<10} {}", label, v),
+            Err(_) => println!("{:<10} ?", label),
+        }
+    }
+    show("Firmware", firmware().map(|v| format!("MAIN {}", v)).ok_or(()));
+    show("VFO-A", cat_send(port, &CMD_FA.read()).and_then(|rx| CMD_FA.decode(&rx).map_err(reported)).map(|f| f.to_u32()));
+    show("VFO-B", cat_send(port, &CMD_FB.read()).and_then(|rx| CMD_FB.decode(&rx).map_err(reported)).map(|f| f.to_u32()));
+    show("Mode", cat_send(port, &CMD_VM.read(side)).and_then(|rx| CMD_VM.decode(&rx).map_err(reported)).map(|m| format!("{:?}", m)));
+    show("Operating", cat_send(port, &CMD_VS.read()).and_then(|rx| CMD_VS.decode(&rx).map_err(reported)));
+    show("RX", cat_send(port, &CMD_FR.read()).and_then(|rx| CMD_FR.decode(&rx).map_err(reported)));
+    show("TX", cat_send(port, &CMD_FT.read()).and_then(|rx| CMD_FT.decode(&rx).map_err(reported)));
+    show("Split", cat_send(port, &CMD_ST.read()).and_then(|rx| CMD_ST.decode(&rx).map_err(reported)).map(on_off));
+    show("Status", cat_send(port, &CMD_RI.read()).and_then(|rx| CMD_RI.decode(&rx).map_err(reported)));
+    Ok(())
+}
+
+/// Reads what --mqtt publishes.
+fn read_telemetry(port: &mut dyn Transport) -> mqtt::Telemetry {
+    mqtt::Telemetry {
+        frequency: cat_send(port, &CMD_FA.read()).and_then(|rx| CMD_FA.decode(&rx).map_err(reported)).map(|f| f.to_u32()).ok(),
+        mode: cat_send(port, &CMD_MD.read(Side::Main)).and_then(|rx| CMD_MD.decode(&rx).map_err(reported)).ok(),
+        s_meter: cat_send(port, &CMD_RM.read(Meter::SMain)).and_then(|rx| CMD_RM.decode(Meter::SMain, &rx).map_err(reported)).ok(),
+        tx: cat_send(port, &CMD_TX.read()).and_then(|rx| CMD_TX.decode(&rx).map_err(reported)).ok(),
+    }
+}
+
+/// Publishes telemetry once --mqtt-interval has passed since the last time.
+struct MqttSchedule {
+    mqtt: mqtt::Mqtt,
+    interval: Duration,
+    last: Option<Instant>,
+    quiet: bool,
+}
+
+impl MqttSchedule {
+    /// None without --mqtt; fails if the broker can't be reached.
+    fn new(cli: &Cli) -> Result<Option<Self>, ()> {
+        let Some(addr) = &cli.mqtt else { return Ok(None) };
+        let interval = Duration::from_secs(cli.mqtt_interval.max(1));
+        let mqtt = mqtt::Mqtt::connect(addr, &cli.mqtt_topic, interval).map_err(|e| {
+            if !cli.quiet { println!("Failed to connect to MQTT broker '{}': {}", addr, e); }
+        })?;
+        Ok(Some(MqttSchedule { mqtt, interval, last: None, quiet: cli.quiet }))
+    }
+
+    fn tick(&mut self, read: &mut dyn FnMut() -> mqtt::Telemetry) {
+        if self.last.is_some_and(|t| t.elapsed() < self.interval) {
+            return;
+        }
+        self.last = Some(Instant::now());
+        if let Err(e) = self.mqtt.publish_telemetry(&read())
+            && !self.quiet
+        {
+            println!("MQTT publish failed: {}", e);
+        }
+    }
+}
+
+/// Follows the --side VFO and the status flags until Ctrl-C, printing a
+/// line whenever either changes, as the radio reports it (see monitor.rs).
+fn monitor(cli: &Cli) -> Result<(), ()> {
+    let side = cli_side(cli)?;
+    let mut port = open_radio(&cli.port, cli.speed, cli.quiet)?;
+    let port = &mut *port;
+    let events = match &cli.udp_events {
+        Some(addr) => Some(UdpEvents::open(addr).map_err(|e| {
+            if !cli.quiet { println!("Failed to open UDP socket for '{}': {}", addr, e); }
+        })?),
+        None => None,
+    };
+    let mut mqtt = MqttSchedule::new(cli)?;
+    let stop = stop_on_ctrlc()?;
+    let auto_info = CMD_AI.decode(&cat_send(port, &CMD_AI.read())?).ok();
+    if auto_info != Some(true) {
+        let _ = cat_send(port, &CMD_AI.set(true))?;
+    }
+    let result = monitor_events(cli, port, side, events.as_ref(), mqtt.as_mut(), &stop);
+    if auto_info != Some(true) {
+        let _ = cat_send(port, &CMD_AI.set(false));
+    }
+    result
+}
+
+/// The loop of --monitor: reads whatever the radio sends, and every
+/// monitor::POLL sends the queries for what it doesn't send by itself.
+fn monitor_events(
+    cli: &Cli,
+    port: &mut dyn Transport,
+    side: Side,
+    events: Option<&UdpEvents>,
+    mut mqtt: Option<&mut MqttSchedule>,
+    stop: &AtomicBool,
+) -> Result<(), ()> {
+    let meter = match side {
+        Side::Main => Meter::SMain,
+        Side::Sub => Meter::SSub,
+    };
+    let mut queries = vec![vfo_read(side), CMD_RI.read()];
+    if events.is_some() || mqtt.is_some() { queries.push(CMD_MD.read(side)); }
+    if events.is_some() { queries.push(CMD_MC.read(side)); }
+    if mqtt.is_some() { queries.push(CMD_RM.read(meter)); }
+    let queries = queries.concat();
+
+    let mut frames = monitor::Frames::default();
+    let mut chunk = vec![0; RX_BUFFER_SIZE];
+    let mut next_poll = Instant::now();
+    let (mut freq, mut flags, mut mode, mut channel, mut s_meter) = (None, None, None, None, None);
+    let mut shown: Option<(u32, StatusFlags)> = None;
+    while !stop.load(Ordering::SeqCst) {
+        if Instant::now() >= next_poll {
+            port.write_all(&queries).map_err(|e| {
+                if !cli.quiet { println!("Failed to write to the radio: {}", e); }
+            })?;
+            trace::frame(trace::Direction::Tx, &queries);
+            next_poll = Instant::now() + monitor::POLL;
+        }
+        // Blocks until the radio sends something or the read times out.
+        let n = match port.read(&mut chunk) {
+            Ok(n) => n,
+            Err(ref e) if e.kind() == io::ErrorKind::TimedOut => continue,
+            Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                eprintln!("Connection to the radio closed.");
+                return Err(());
+            }
+            Err(e) => {
+                eprintln!("{:?}", e);
+                return Err(());
+            }
+        };
+        trace::frame(trace::Direction::Rx, &chunk[..n]);
+        for frame in frames.push(&chunk[..n]) {
+            match monitor::update(side, &frame) {
+                Some(monitor::Update::Frequency(hz)) => {
+                    if let Some(events) = events && freq != Some(hz) {
+                        events.send(Event::Frequency(hz));
+                    }
+                    freq = Some(hz);
+                }
+                Some(monitor::Update::Status(status)) => flags = Some(status),
+                Some(monitor::Update::Mode(m)) => {
+                    if let Some(events) = events && mode != Some(m) {
+                        events.send(Event::Mode(m));
+                    }
+                    mode = Some(m);
+                }
+                Some(monitor::Update::Channel(ch)) => {
+                    if let Some(events) = events && channel != Some(ch) {
+                        events.send(Event::Channel(ch));
+                    }
+                    channel = Some(ch);
+                }
+                Some(monitor::Update::SMeter(raw)) => s_meter = Some(raw),
+                None => {}
+            }
+        }
+        if let (Some(hz), Some(status)) = (freq, flags)
+            && shown != Some((hz, status))
+        {
+            let time = jiff::Zoned::now().strftime("%H:%M:%S").to_string();
+            println!("{}  {} Hz  {}", time, hz, status);
+            shown = Some((hz, status));
+        }
+        if let Some(mqtt) = &mut mqtt {
+            let tx = flags.map(|f| f.contains(StatusFlags::TX));
+            mqtt.tick(&mut || mqtt::Telemetry { frequency: freq, mode, s_meter, tx });
+        }
+    }
+    Ok(())
+}
+
+/// Appends --side S-meter readings to `path` every --interval until
+/// Ctrl-C (see smeter.rs).
+fn smeter_log(cli: &Cli, path: &str) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let side = cli_side(cli)?;
+    let interval = match &cli.interval {
+        Some(text) => smeter::parse_interval(text).ok_or_else(|| {
+            if !quiet { println!("Expected an --interval like 500ms, 2s or 1m (at least 50ms), got '{}'.", text); }
+        })?,
+        None => Duration::from_secs(1),
+    };
+    let header = smeter::header(cli.log_frequency);
+    if let Ok(existing) = std::fs::read_to_string(path)
+        && let Some(first) = existing.lines().next()
+        && first != header
+    {
+        if !quiet { println!("'{}' has other columns ({}); log to a new file.", path, first); }
+        return Err(());
+    }
+    let is_new = std::fs::metadata(path).map(|m| m.len() == 0).unwrap_or(true);
+    let mut log = std::fs::OpenOptions::new().create(true).append(true).open(path).map_err(|e| {
+        if !quiet { println!("Failed to open S-meter log '{}': {}", path, e); }
+    })?;
+    let failed = |e: io::Error| {
+        if !quiet { println!("Failed to write S-meter log '{}': {}", path, e); }
+    };
+    if is_new {
+        writeln!(log, "{}", header).map_err(failed)?;
+    }
+    let mut port = open_radio(&cli.port, cli.speed, quiet)?;
+    let meter = match side {
+        Side::Main => Meter::SMain,
+        Side::Sub => Meter::SSub,
+    };
+
+    let stop = stop_on_ctrlc()?;
+    if !quiet { println!("Logging the S-meter to {} every {:?}. Press Ctrl-C to stop.", path, interval); }
+    let mut readings = 0u64;
+    let mut next = Instant::now();
+    while !stop.load(Ordering::SeqCst) {
+        let time = jiff::Timestamp::now().strftime("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
+        if let Ok(raw) = CMD_RM.decode(meter, &cat_send(&mut *port, &CMD_RM.read(meter))?) {
+            let frequency = cli
+                .log_frequency
+                .then(|| cat_send(&mut *port, &vfo_read(side)).ok().and_then(|rx| vfo_decode(side, &rx).ok()).map(|f| f.to_u32()));
+            writeln!(log, "{}", smeter::row(&time, raw, frequency)).map_err(failed)?;
+            readings += 1;
+        }
+        // On a fixed schedule, so slow replies don't make the log drift;
+        // readings that are already late are skipped, not bunched up.
+        next += interval;
+        let now = Instant::now();
+        while next <= now {
+            next += interval;
+        }
+        while !stop.load(Ordering::SeqCst) && Instant::now() < next {
+            std::thread::sleep(next.saturating_duration_since(Instant::now()).min(Duration::from_millis(100)));
+        }
+    }
+    log.sync_data().map_err(failed)?;
+    if !quiet { println!("Stopped; {} readings logged to {}.", readings, path); }
+    Ok(())
+}
+
+/// --bandscan: samples the S-meter across `range` (see bandscan.rs) and
+/// puts the VFO back where it was.
+fn bandscan(cli: &Cli, range: &str) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let side = cli_side(cli)?;
+    let (lo, hi) = sweep::parse_range(range).ok_or_else(|| {
+        if !quiet { println!("Expected a range in MHz like 146.4-146.6 for --bandscan, got '{}'.", range); }
+    })?;
+    if cli.bandscan_step == 0 {
+        if !quiet { println!("--bandscan-step must be at least 1 kHz."); }
+        return Err(());
+    }
+    let step = cli.bandscan_step.saturating_mul(1000);
+    if sweep::step_count(lo, hi, step) as usize > bandscan::MAX_STEPS {
+        if !quiet { println!("A --bandscan-step of {} kHz gives more than {} steps; use a larger step.", cli.bandscan_step, bandscan::MAX_STEPS); }
+        return Err(());
+    }
+    let freqs = sweep::steps(lo, hi, step);
+    let region = check_options(cli)?.region;
+    if let Some(freq) = freqs.iter().find(|f| !FrequencyHz::try_from(**f).is_ok_and(|f| region.can_receive(f))) {
+        if !quiet { println!("{} Hz is outside the radio's receive coverage.", freq); }
+        return Err(());
+    }
+    let meter = match side {
+        Side::Main => Meter::SMain,
+        Side::Sub => Meter::SSub,
+    };
+
+    let mut port = open_radio(&cli.port, cli.speed, quiet)?;
+    let port = &mut *port;
+    let old_freq = vfo_decode(side, &cat_send(port, &vfo_read(side))?).map_err(reported)?;
+    let stop = stop_on_ctrlc()?;
+    let mut steps: Vec<bandscan::Step> = freqs.iter().map(|&freq| bandscan::Step { freq, readings: Vec::new() }).collect();
+    let passes = cli.bandscan_passes.max(1);
+    if !quiet { println!("Scanning {} steps, {} pass(es). Press Ctrl-C to stop.", steps.len(), passes); }
+    let mut bar = Progress::new(cli, steps.len() * passes as usize);
+    let mut scanned = Ok(());
+    'passes: for _ in 0..passes {
+        for step in steps.iter_mut() {
+            if stop.load(Ordering::SeqCst) {
+                break 'passes;
+            }
+            let read = FrequencyHz::try_from(step.freq).and_then(|f| cat_send(port, &vfo_set(side, f))).and_then(|_| {
+                std::thread::sleep(Duration::from_millis(cli.bandscan_dwell));
+                cat_send(port, &CMD_RM.read(meter))
+            });
+            match read {
+                Ok(rx) => step.readings.extend(CMD_RM.decode(meter, &rx).ok()),
+                Err(()) => {
+                    scanned = Err(());
+                    break 'passes;
+                }
+            }
+            bar.inc();
+        }
+    }
+    bar.finish();
+    let _ = cat_send(port, &vfo_set(side, old_freq));
+
+    let level = cli.busy_level.unwrap_or_else(|| bandscan::auto_level(&steps));
+    if let Some(file) = &cli.file {
+        let mut text = format!("{}\n", bandscan::CSV_HEADER);
+        steps.iter().filter(|s| !s.readings.is_empty()).for_each(|s| text.push_str(&format!("{}\n", s.row(level))));
+        std::fs::write(file, text).map_err(|e| {
+            if !quiet { println!("Failed to write '{}': {}", file, e); }
+        })?;
+        if !quiet { println!("Readings saved to {}", file); }
+    }
+    let mhz = |freq: u32| f64::from(freq) / 1e6;
+    let busy = bandscan::busy(&steps, level);
+    println!("Busy (S-meter at or over {}):", level);
+    if busy.is_empty() {
+        println!("  none");
+    }
+    for step in busy {
+        println!("  {:>10.4} MHz  peak {:>3}  busy {:>3}%", mhz(step.freq), step.peak(), step.busy_percent(level));
+    }
+    println!("Clearest:");
+    let clear = bandscan::clearest(&steps, level, 5);
+    if clear.is_empty() {
+        println!("  none");
+    }
+    for step in clear {
+        println!("  {:>10.4} MHz  peak {:>3}", mhz(step.freq), step.peak());
+    }
+    scanned
+}
+
+fn list_ports() {
+    match serialport::available_ports() {
+        Ok(ports) if ports.is_empty() => println!("No serial ports found."),
+        Ok(ports) => ports.iter().for_each(|p| println!("{}", ports::describe(p))),
+        Err(e) => println!("Failed to list serial ports: {}", e),
+    }
+}
+
+/// Prints paired Bluetooth devices (via bluetoothctl) and the rfcomm ttys
+/// bound to them, with the command to bind the radio if it isn't yet.
+fn list_bluetooth() {
+    let devices = match std::process::Command::new("bluetoothctl").arg("devices").output() {
+        Ok(out) => transport::parse_bluetooth_devices(&String::from_utf8_lossy(&out.stdout)),
+        Err(_) => {
+            println!("bluetoothctl not found; pair the radio from your system's Bluetooth settings.");
+            Vec::new()
+        }
+    };
+    let bindings = transport::rfcomm_bindings();
+    for device in &devices {
+        let radio = device.name.to_uppercase().contains("FTX");
+        let tty = bindings.iter().find(|(_, addr)| addr.eq_ignore_ascii_case(&device.address));
+        match tty {
+            Some((tty, _)) => println!("{}  {}  {}", device.address, device.name, tty),
+            None if radio => {
+                println!("{}  {}  not bound: sudo rfcomm bind {} {}", device.address, device.name, bindings.len(), device.address)
+            }
+            None => println!("{}  {}", device.address, device.name),
+        }
+    }
+    for (tty, addr) in &bindings {
+        if !devices.iter().any(|d| d.address.eq_ignore_ascii_case(addr)) {
+            println!("{}  (unpaired)  {}", addr, tty);
+        }
+    }
+    if devices.is_empty() && bindings.is_empty() {
+        println!("No paired Bluetooth devices found.");
+    }
+}
+
+/// The radio behind --daemon's REST API.
+struct CatRadio {
+    port: queue::QueuedPort,
+    options: CheckOptions,
+    audit: Arc<std::sync::Mutex<Option<audit::Log>>>,
+    quiet: bool,
+}
+
+impl daemon::Radio for CatRadio {
+    fn status(&mut self) -> Result<serde_json::Value, ()> {
+        let port = &mut self.port;
+        let vfo_a = cat_send(port, &CMD_FA.read()).and_then(|rx| CMD_FA.decode(&rx).map_err(reported)).map(|f| f.to_u32());
+        let vfo_b = cat_send(port, &CMD_FB.read()).and_then(|rx| CMD_FB.decode(&rx).map_err(reported)).map(|f| f.to_u32());
+        let mode = cat_send(port, &CMD_MD.read(Side::Main)).and_then(|rx| CMD_MD.decode(&rx).map_err(reported)).map(|m| m.to_string());
+        let vm = cat_send(port, &CMD_VM.read(Side::Main)).and_then(|rx| CMD_VM.decode(&rx).map_err(reported)).map(|m| format!("{:?}", m));
+        let split = cat_send(port, &CMD_ST.read()).and_then(|rx| CMD_ST.decode(&rx).map_err(reported));
+        let flags = cat_send(port, &CMD_RI.read()).and_then(|rx| CMD_RI.decode(&rx).map_err(reported)).map(|f| f.to_string());
+        Ok(serde_json::json!({
+            "vfo_a": vfo_a.ok(),
+            "vfo_b": vfo_b.ok(),
+            "mode": mode.ok(),
+            "vfo_memory": vm.ok(),
+            "split": split.ok(),
+            "status": flags.ok(),
+            "firmware": firmware().map(|v| v.to_string()),
+        }))
+    }
+
+    fn channels(&mut self) -> Result<serde_json::Value, ()> {
+        // Other requests go first between channels.
+        self.port.priority = queue::Priority::Bulk;
+        let mut channels = Vec::new();
+        // Each channel is one run on the port, or another request's frames
+        // could land between selecting it and reading its tones.
+        let read = (1..=CHANNELS).try_for_each(|ch| {
+            read_channel(&mut *self.port.hold(), ch)?.into_iter().for_each(|rec| channels.push(rec));
+            Ok(())
+        });
+        self.port.priority = queue::Priority::Interactive;
+        read?;
+        serde_json::to_value(channels).map_err(|_| ())
+    }
+
+    fn channel(&mut self, ch: u16) -> Result<Option<serde_json::Value>, ()> {
+        match read_channel(&mut *self.port.hold(), ch)? {
+            Some(rec) => serde_json::to_value(rec).map(Some).map_err(|_| ()),
+            None => Ok(None),
+        }
+    }
+
+    fn put_channel(&mut self, ch: u16, mut channel: serde_json::Value) -> Result<(), daemon::PutError> {
+        check_firmware(MIN_CAT_FIRMWARE, "Writing memory channels").map_err(|e| daemon::PutError::Invalid(vec![e]))?;
+        if let Some(fields) = channel.as_object_mut() {
+            fields.insert("Channel Number".to_string(), format!("{:05}", ch).into());
+        }
+        let mut rec: CsvRecord =
+            serde_json::from_value(channel).map_err(|e| daemon::PutError::Invalid(vec![e.to_string()]))?;
+        normalize_record(&mut rec, self.options.fix_tags);
+        validate_record(&rec, self.options.allow_any_frequency, self.options.region).map_err(daemon::PutError::Invalid)?;
+        let mut audit = self.audit.lock().unwrap_or_else(|e| e.into_inner());
+        audited(&mut *self.port.hold(), audit.as_mut(), audit::Operation::Api, &rec, self.quiet, |port| write_channel(port, rec.clone()))
+            .map_err(|_| daemon::PutError::Radio)
+    }
+
+    fn telemetry(&mut self) -> mqtt::Telemetry {
+        read_telemetry(&mut self.port)
+    }
+
+    fn meters(&mut self) -> Vec<(&'static str, f64)> {
+        let port = &mut self.port;
+        let mut meters = Vec::new();
+        if let Ok(raw) = cat_send(port, &CMD_RM.read(Meter::SMain)).and_then(|rx| CMD_RM.decode(Meter::SMain, &rx).map_err(reported)) {
+            meters.push(("ftx1_s_meter_raw", f64::from(raw)));
+        }
+        let tx = cat_send(port, &CMD_TX.read()).and_then(|rx| CMD_TX.decode(&rx).map_err(reported));
+        if let Ok(tx) = tx {
+            meters.push(("ftx1_transmitting", f64::from(u8::from(tx))));
+        }
+        // The SWR meter only reads while transmitting.
+        if tx == Ok(true)
+            && let Ok(raw) = cat_send(port, &CMD_RM.read(Meter::Swr)).and_then(|rx| CMD_RM.decode(Meter::Swr, &rx).map_err(reported))
+        {
+            meters.push(("ftx1_swr", f64::from(swr_from_raw(raw))));
+        }
+        meters
+    }
+}
+
+/// Runs the REST daemon until Ctrl-C. The requests run on threads of
+/// their own; this one owns the port and exchanges their frames (see
+/// queue.rs).
+fn run_daemon(cli: &Cli, addr: &str) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let options = check_options(cli)?;
+    let audit = Arc::new(std::sync::Mutex::new(audit_log(cli)?));
+    // Identifies the radio (ID and VE) before anything is served, so
+    // put_channel knows the firmware it's writing to.
+    let mut port = open_radio(&cli.port, cli.speed, quiet)?;
+    let addr = host_port(addr, 8080);
+    let listener = std::net::TcpListener::bind(&addr).map_err(|e| {
+        if !quiet { println!("Failed to listen on '{}': {}", addr, e); }
+    })?;
+    let mut mqtt = MqttSchedule::new(cli)?;
+    let stop = stop_on_ctrlc()?;
+    if !quiet { println!("REST API on http://{}; Ctrl-C to stop.", addr); }
+    let mut idle = |radio: &mut dyn daemon::Radio| {
+        if let Some(mqtt) = &mut mqtt {
+            mqtt.tick(&mut || radio.telemetry());
+        }
+    };
+    let queue = queue::Queue::new();
+    let radios = || {
+        let port = queue::QueuedPort::new(Arc::clone(&queue), queue::Priority::Interactive);
+        Box::new(CatRadio { port, options, audit: Arc::clone(&audit), quiet }) as Box<dyn daemon::Radio>
+    };
+    std::thread::scope(|s| {
+        let server = s.spawn(|| {
+            let served = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| daemon::run(&listener, &stop, &radios, &mut idle, quiet)));
+            // Also after a panic, or serve() would wait forever.
+            queue.close();
+            served.unwrap_or_else(|_| Err(io::Error::other("the server thread panicked")))
+        });
+        queue.serve(&mut *port);
+        server.join().unwrap_or_else(|_| Err(io::Error::other("the server thread panicked")))
+    })
+    .map_err(|e| {
+        if !quiet { println!("Server error: {}", e); }
+    })
+}
+
+/// Runs the rigctld-protocol server until Ctrl-C.
+fn serve(cli: &Cli, addr: &str) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    // Without --allow-tx nothing keys, and the watchdog never acts.
+    let port = std::cell::RefCell::new(watched_radio(cli)?);
+    let addr = host_port(addr, 4532);
+    let listener = std::net::TcpListener::bind(&addr).map_err(|e| {
+        if !quiet { println!("Failed to listen on '{}': {}", addr, e); }
+    })?;
+    let stop = stop_on_ctrlc()?;
+    if !quiet { println!("Serving rigctld protocol on {}; Ctrl-C to stop.", addr); }
+    let mut cat = |frame: &[u8]| cat_send(&mut *port.borrow_mut(), frame);
+    // A client can key with "T 1" and then go quiet.
+    let mut tick = || {
+        let _ = port.borrow_mut().tick();
+    };
+    serve::run(&listener, &stop, &mut cat, &mut tick, cli.allow_tx, quiet).map_err(|e| {
+        if !quiet { println!("Server error: {}", e); }
+    })
+}
+
+/// Runs a scan until Ctrl-C or until the radio stops scanning on its own,
+/// polling the --side VFO to notice where it halts. A halt is a frequency that
+/// holds for two polls in a row; each one is reported once, and logged to
+/// --scan-log with its channel, peak S-meter and dwell once the scan moves
+/// on (see scanlog.rs). With --scan-resume a scan the radio stops on a
+/// signal is started again once the squelch closes.
+fn scan(cli: &Cli, dir: Direction) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let side = cli_side(cli)?;
+    let mut log = match &cli.scan_log {
+        Some(path) => {
+            if let Ok(existing) = std::fs::read_to_string(path)
+                && let Some(first) = existing.lines().next()
+                && first != scanlog::HEADER
+            {
+                if !quiet {
+                    match first {
+                        scanlog::OLD_HEADER => println!("'{}' is a scan log without channels; log to a new file (--scan-report still reads it).", path),
+                        _ => println!("'{}' has other columns ({}); log to a new file.", path, first),
+                    }
+                }
+                return Err(());
+            }
+            let is_new = std::fs::metadata(path).map(|m| m.len() == 0).unwrap_or(true);
+            let mut f = std::fs::OpenOptions::new().create(true).append(true).open(path).map_err(|e| {
+                if !quiet { println!("Failed to open scan log '{}': {}", path, e); }
+            })?;
+            if is_new {
+                writeln!(f, "{}", scanlog::HEADER).map_err(|_| ())?;
+            }
+            Some(f)
+        }
+        None => None,
+    };
+    let (meter, busy_flag) = match side {
+        Side::Main => (Meter::SMain, StatusFlags::MAIN_BUSY),
+        Side::Sub => (Meter::SSub, StatusFlags::SUB_BUSY),
+    };
+    let mut port = open_radio(&cli.port, cli.speed, quiet)?;
+    let memory = CMD_VM.decode(&cat_send(&mut *port, &CMD_VM.read(side))?) == Ok(VmMode::Memory);
+
+    let stop = stop_on_ctrlc()?;
+
+    let _ = cat_send(&mut *port, &CMD_SC.set(side, dir.into()))?;
+    if !quiet { println!("Scanning {:?}. Press Ctrl-C to stop.", dir); }
+
+    let mut last: Option<u32> = None;
+    let mut halt: Option<(Instant, scanlog::Halt)> = None;
+    let mut halts = Vec::new();
+    let mut finish = |halt: &mut Option<(Instant, scanlog::Halt)>, halts: &mut Vec<scanlog::Halt>| -> Result<(), ()> {
+        let Some((since, mut h)) = halt.take() else { return Ok(()) };
+        h.dwell = Some(since.elapsed());
+        if let Some(f) = log.as_mut() {
+            writeln!(f, "{}", h.row()).map_err(|_| ())?;
+        }
+        halts.push(h);
+        Ok(())
+    };
+    while !stop.load(Ordering::SeqCst) {
+        std::thread::sleep(Duration::from_millis(300));
+        let scanning = CMD_SC.decode(&cat_send(&mut *port, &CMD_SC.read(side))?) != Ok(ScanState::Off);
+        // Once the radio has stopped, a halt lasts while the squelch is open.
+        let held = !scanning
+            && cli.scan_resume
+            && CMD_RI.decode(&cat_send(&mut *port, &CMD_RI.read())?).is_ok_and(|flags| flags.contains(busy_flag));
+        let freq = vfo_decode(side, &cat_send(&mut *port, &vfo_read(side))?).ok().map(|f| f.to_u32());
+        if let Some((_, h)) = &halt
+            && (freq.is_some_and(|hz| hz != h.freq) || (!scanning && !held))
+        {
+            finish(&mut halt, &mut halts)?;
+        }
+        if !scanning && !held {
+            if !cli.scan_resume {
+                if !quiet { println!("Radio stopped scanning."); }
+                break;
+            }
+            let _ = cat_send(&mut *port, &CMD_SC.set(side, dir.into()))?;
+            last = None;
+            continue;
+        }
+        let Some(hz) = freq else { continue };
+        let peak = |port: &mut dyn Transport| cat_send(port, &CMD_RM.read(meter)).ok().and_then(|rx| CMD_RM.decode(meter, &rx).ok());
+        match &mut halt {
+            Some((_, h)) => h.peak = h.peak.max(peak(&mut *port)),
+            None if last == Some(hz) || held => {
+                let time = jiff::Zoned::now().strftime("%Y-%m-%d %H:%M:%S").to_string();
+                let channel = if memory {
+                    cat_send(&mut *port, &CMD_MC.read(side))
+                        .ok()
+                        .and_then(|rx| CMD_MC.decode(&rx).ok())
+                        .and_then(|mc| mc.channel.to_string().ok())
+                } else {
+                    None
+                };
+                match &channel {
+                    Some(ch) => if !quiet { println!("{}  halted on channel {} ({} Hz)", time, ch, hz); },
+                    None => if !quiet { println!("{}  halted on {} Hz", time, hz); },
+                }
+                halt = Some((Instant::now(), scanlog::Halt { time, freq: hz, channel, peak: peak(&mut *port), dwell: None }));
+            }
+            None => {}
+        }
+        last = Some(hz);
+    }
+    finish(&mut halt, &mut halts)?;
+
+    let _ = cat_send(&mut *port, &CMD_SC.set(side, ScanState::Off))?;
+    if !quiet {
+        println!("Scan stopped.");
+        if !halts.is_empty() {
+            print_scan_activity(&scanlog::report(&halts));
+        }
+    }
+    Ok(())
+}
+
+/// The channel activity table of --scan and --scan-report.
+fn print_scan_activity(activity: &[scanlog::Activity]) {
+    println!("{:<8} {:>12} {:>6} {:>9} {:>9} {:>5}  {:<19}  {:<19}", "Channel", "MHz", "Halts", "Total s", "Longest", "Peak", "First", "Last");
+    for a in activity {
+        println!(
+            "{:<8} {:>12.4} {:>6} {:>9.1} {:>9.1} {:>5}  {:<19}  {:<19}",
+            a.channel.as_deref().unwrap_or("-"),
+            f64::from(a.freq) / 1e6,
+            a.halts,
+            a.total.as_secs_f64(),
+            a.longest.as_secs_f64(),
+            a.peak.map(|p| p.to_string()).unwrap_or_else(|| "-".to_string()),
+            a.first,
+            a.last
+        );
+    }
+}
+
+/// --scan-report: the channel activity in a --scan-log file.
+fn scan_report(cli: &Cli, path: &str) -> Result<(), ()> {
+    let text = std::fs::read_to_string(path).map_err(|e| {
+        if !cli.quiet { println!("Error opening file '{}': {}", path, e); }
+    })?;
+    let halts: Vec<scanlog::Halt> = text.lines().filter_map(scanlog::Halt::parse).collect();
+    if halts.is_empty() {
+        println!("No scan halts in {}.", path);
+        return Ok(());
+    }
+    print_scan_activity(&scanlog::report(&halts));
+    Ok(())
+}
+
+/// Power switching can't go through open_radio: a radio that's off
+/// doesn't answer ID. Power-on sends the wake-up dummy first, then waits
+/// for the radio to boot far enough to identify itself.
+fn power(cli: &Cli, state: &str) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let status = state.eq_ignore_ascii_case("status");
+    let on = if status {
+        None
+    } else {
+        Some(parse_on_off(state).map_err(|_| {
+            if !quiet { println!("Expected on, off or status, got '{}'.", state); }
+        })?)
+    };
+    let mut port = open_port(&cli.port, cli.speed, quiet)?;
+    match on {
+        None => {
+            let on = CMD_PS.decode(&cat_send(&mut *port, &CMD_PS.read())?).unwrap_or(false);
+            println!("Power: {}", on_off(on));
+        }
+        Some(true) => {
+            let _ = cat_send(&mut *port, &CMD_PS.wake())?;
+            std::thread::sleep(Duration::from_millis(PS_WAKE_DELAY_MS));
+            let _ = cat_send(&mut *port, &CMD_PS.set(true))?;
+            if !quiet { println!("Waiting for the radio to start..."); }
+            let mut ready = false;
+            for _ in 0..20 {
+                std::thread::sleep(Duration::from_millis(500));
+                if CMD_ID.decode(&cat_send(&mut *port, &CMD_ID.read())?).is_ok() {
+                    ready = true;
+                    break;
+                }
+            }
+            if !ready {
+                if !quiet { println!("Radio did not respond after power-on."); }
+                return Err(());
+            }
+            if !quiet { println!("Power: ON"); }
+        }
+        Some(false) => {
+            read_validate_id(&mut *port, quiet).map_err(|e| {
+                if !quiet { println!("Error validating radio ID: {:?}", e); }
+            })?;
+            let _ = cat_send(&mut *port, &CMD_PS.set(false))?;
+            if !quiet { println!("Power: OFF"); }
+        }
+    }
+    Ok(())
+}
+
+fn vm_action(cli: &Cli, mode: &str) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let mode = mode.to_ascii_lowercase();
+    if !["vfo", "memory", "toggle", "status"].contains(&mode.as_str()) {
+        if !quiet { println!("Unknown mode '{}' (expected vfo, memory, toggle or status).", mode); }
+        return Err(());
+    }
+    let side = cli_side(cli)?;
+    let mut port = open_radio(&cli.port, cli.speed, quiet)?;
+    let frame = match mode.as_str() {
+        "vfo" => CMD_VM.set(side, VmMode::Vfo),
+        "memory" => CMD_VM.set(side, VmMode::Memory),
+        _ => {
+            let current = CMD_VM.decode(&cat_send(&mut *port, &CMD_VM.read(side))?).map_err(reported)?;
+            if mode == "status" {
+                println!("Mode: {:?}", current);
+                return Ok(());
+            }
+            CMD_VM.toggle(side, current)
+        }
+    };
+    let _ = cat_send(&mut *port, &frame)?;
+    Ok(())
+}
+
+/// Parses a signed kHz offset such as "+5" or "-1.5" into Hz.
+fn parse_khz_offset(value: &str) -> Result<i64, ()> {
+    let khz: f64 = value.trim().parse().map_err(|_| ())?;
+    if !khz.is_finite() {
+        return Err(());
+    }
+    Ok((khz * 1000.0).round() as i64)
+}
+
+/// The DX split helper: VFO-B = VFO-A + offset, then split on.
+fn split_offset(cli: &Cli, value: &str) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let offset = parse_khz_offset(value).map_err(|_| {
+        if !quiet { println!("Invalid split offset '{}' (expected kHz, e.g. +5 or -1.5).", value); }
+    })?;
+    let mut port = open_radio(&cli.port, cli.speed, quiet)?;
+    let rx = CMD_FA.decode(&cat_send(&mut *port, &CMD_FA.read())?).map_err(reported)?;
+    let tx = u32::try_from(i64::from(rx.to_u32()) + offset).map_err(|_| ()).and_then(FrequencyHz::try_from).map_err(|_| {
+        if !quiet { println!("Offset {} puts VFO-B out of range.", value); }
+    })?;
+    let _ = cat_send(&mut *port, &CMD_FB.set(tx))?;
+    let _ = cat_send(&mut *port, &CMD_ST.set(true))?;
+    if !quiet { println!("Split ON: RX {} Hz, TX {} Hz", rx.to_u32(), tx.to_u32()); }
+    Ok(())
+}
+
+/// A bare number only changes the TFT level, so the LED level is read
+/// back first and kept.
+fn dimmer(cli: &Cli, level: &str) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let status = level.eq_ignore_ascii_case("status");
+    let tft_only: Option<u8> = level.trim().parse().ok().filter(|t| *t <= Dimmer::MAX);
+    let full = Dimmer::try_from(level).ok();
+    if !status && full.is_none() && tft_only.is_none() {
+        if !quiet { println!("Invalid dimmer level '{}' (expected 0-{}, LED/TFT or status).", level, Dimmer::MAX); }
+        return Err(());
+    }
+    let mut port = open_radio(&cli.port, cli.speed, quiet)?;
+    let dimmer = match (full, tft_only) {
+        (Some(d), _) => d,
+        (None, Some(tft)) => {
+            let current = CMD_DA.decode(&cat_send(&mut *port, &CMD_DA.read())?).map_err(reported)?;
+            Dimmer::new(current.led(), tft)?
+        }
+        (None, None) => {
+            let current = CMD_DA.decode(&cat_send(&mut *port, &CMD_DA.read())?).map_err(reported)?;
+            println!("Dimmer (LED/TFT): {}", current);
+            return Ok(());
+        }
+    };
+    let _ = cat_send(&mut *port, &CMD_DA.set(dimmer))?;
+    if !quiet { println!("Dimmer (LED/TFT): {}", dimmer); }
+    Ok(())
+}
+
+/// Shared body for the on/off actions: "status" reads and prints the
+/// current state, anything else must be on/off and is sent as a set.
+fn switch_action(
+    cli: &Cli,
+    label: &str,
+    state: &str,
+    read: &[u8],
+    set: impl Fn(bool) -> Vec<u8>,
+    decode: impl Fn(&[u8]) -> Result<bool, DecodeError>,
+) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    if state.eq_ignore_ascii_case("status") {
+        let mut port = open_radio(&cli.port, cli.speed, quiet)?;
+        let on = decode(&cat_send(&mut *port, read)?).map_err(reported)?;
+        println!("{}: {}", label, on_off(on));
+        return Ok(());
+    }
+    let on = parse_on_off(state).map_err(|_| {
+        if !quiet { println!("Expected on, off or status, got '{}'.", state); }
+    })?;
+    let mut port = open_radio(&cli.port, cli.speed, quiet)?;
+    let _ = cat_send(&mut *port, &set(on))?;
+    if !quiet { println!("{}: {}", label, on_off(on)); }
+    Ok(())
+}
+
+fn apf_offset(cli: &Cli, value: &str) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let side = cli_side(cli)?;
+    let offset = if value.eq_ignore_ascii_case("status") {
+        None
+    } else {
+        let hz = value.trim().parse::<i16>().ok().and_then(|hz| ApfOffsetHz::try_from(hz).ok());
+        Some(hz.ok_or_else(|| {
+            if !quiet { println!("Expected an APF offset of -250…+250 Hz in 10 Hz steps, or status, got '{}'.", value); }
+        })?)
+    };
+    let mut port = open_radio(&cli.port, cli.speed, quiet)?;
+    if let Some(offset) = offset {
+        let _ = cat_send(&mut *port, &CMD_CO.set(side, ApfParam::Offset(offset)))?;
+    }
+    let offset = CMD_CO.decode_offset(&cat_send(&mut *port, &CMD_CO.read(side, ApfFunction::Offset))?).map_err(reported)?;
+    println!("APF offset: {} Hz", offset);
+    Ok(())
+}
+
+/// The --side option.
+fn cli_side(cli: &Cli) -> Result<Side, ()> {
+    Side::try_from(cli.side.as_str()).map_err(|_| {
+        if !cli.quiet { println!("Expected main or sub for --side, got '{}'.", cli.side); }
+    })
+}
+
+/// The frequency-read frame for a side's VFO: FA for main, FB for sub.
+fn vfo_read(side: Side) -> Vec<u8> {
+    match side {
+        Side::Main => CMD_FA.read(),
+        Side::Sub => CMD_FB.read(),
+    }
+}
+
+fn vfo_decode(side: Side, rx: &[u8]) -> Result<FrequencyHz, DecodeError> {
+    match side {
+        Side::Main => CMD_FA.decode(rx),
+        Side::Sub => CMD_FB.decode(rx),
+    }
+}
+
+fn vfo_set(side: Side, freq: FrequencyHz) -> Vec<u8> {
+    match side {
+        Side::Main => CMD_FA.set(freq),
+        Side::Sub => CMD_FB.set(freq),
+    }
+}
+
+/// The bands with an antenna choice: HF and 50 MHz.
+const ANTENNA_BANDS: [Band; 11] = [
+    Band::M160, Band::M80, Band::M60, Band::M40, Band::M30, Band::M20,
+    Band::M17, Band::M15, Band::M12, Band::M10, Band::M6,
+];
+
+/// --antenna: the current band's antenna, or per-band choices. Going
+/// through the bands switches the side's band (BS), so its VFO is put
+/// back where it was afterwards.
+fn antenna(cli: &Cli, value: &str) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let side = cli_side(cli)?;
+    let value = value.trim().to_ascii_lowercase();
+    if value == "status" || Antenna::try_from(value.as_str()).is_ok() {
+        let mut port = open_radio(&cli.port, cli.speed, quiet)?;
+        if let Ok(antenna) = Antenna::try_from(value.as_str()) {
+            let _ = cat_send(&mut *port, &CMD_AN.set(side, antenna))?;
+        }
+        println!("Antenna: {}", CMD_AN.decode(&cat_send(&mut *port, &CMD_AN.read(side))?).map_err(reported)?);
+        return Ok(());
+    }
+    let mut choices = Vec::new();
+    if value != "bands" {
+        for pair in value.split(',') {
+            let parsed = pair.split_once('=').and_then(|(band, antenna)| {
+                let band = Band::try_from(band).ok().filter(|b| ANTENNA_BANDS.contains(b))?;
+                Some((band, Antenna::try_from(antenna).ok()?))
+            });
+            let Some(choice) = parsed else {
+                if !quiet { println!("Expected 1, 2, status, bands or BAND=N (160m…10m, 6m), got '{}'.", pair); }
+                return Err(());
+            };
+            choices.push(choice);
+        }
+    }
+    let mut port = open_radio(&cli.port, cli.speed, quiet)?;
+    let home = vfo_decode(side, &cat_send(&mut *port, &vfo_read(side))?).ok();
+    let bands: Vec<Band> = if choices.is_empty() { ANTENNA_BANDS.to_vec() } else { choices.iter().map(|c| c.0).collect() };
+    for band in bands {
+        let _ = cat_send(&mut *port, &CMD_BS.set(side, band))?;
+        if let Some((_, antenna)) = choices.iter().find(|c| c.0 == band) {
+            let _ = cat_send(&mut *port, &CMD_AN.set(side, *antenna))?;
+        }
+        match CMD_AN.decode(&cat_send(&mut *port, &CMD_AN.read(side))?) {
+            Ok(antenna) => println!("{:<5} ANT {}", band.to_string(), antenna),
+            Err(_) => println!("{:<5} ?", band.to_string()),
+        }
+    }
+    if let Some(f) = home { let _ = cat_send(&mut *port, &vfo_set(side, f))?; }
+    Ok(())
+}
+
+/// Like switch_action, for settings that pick a side (a/b, main/sub).
+fn side_action(
+    cli: &Cli,
+    label: &str,
+    value: &str,
+    read: &[u8],
+    set: impl Fn(Side) -> Vec<u8>,
+    decode: impl Fn(&[u8]) -> Result<Side, DecodeError>,
+) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    if value.eq_ignore_ascii_case("status") {
+        let mut port = open_radio(&cli.port, cli.speed, quiet)?;
+        let side = decode(&cat_send(&mut *port, read)?).map_err(reported)?;
+        println!("{}: {}", label, side);
+        return Ok(());
+    }
+    let side = Side::try_from(value).map_err(|_| {
+        if !quiet { println!("Expected a, b, main, sub or status, got '{}'.", value); }
+    })?;
+    let mut port = open_radio(&cli.port, cli.speed, quiet)?;
+    let _ = cat_send(&mut *port, &set(side))?;
+    if !quiet { println!("{}: {}", label, side); }
+    Ok(())
+}
+
+fn find_menu_item(name: &str, quiet: bool) -> Result<&'static MenuItem, ()> {
+    MenuItem::find(name).ok_or_else(|| {
+        if !quiet { println!("Unknown menu item '{}'. Use --ex-list to see the known items.", name); }
+    })
+}
+
+fn ex_get(cli: &Cli, name: &str) -> Result<(), ()> {
+    let item = find_menu_item(name, cli.quiet)?;
+    let mut port = open_radio(&cli.port, cli.speed, cli.quiet)?;
+    let rx = cat_send(&mut *port, &CMD_EX.read(item))?;
+    let value = item.format_value(CMD_EX.decode(item, &rx).map_err(reported)?)?;
+    println!("{} = {}", item.name, value);
+    Ok(())
+}
+
+fn ex_set(cli: &Cli, assignment: &str) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let Some((name, value)) = assignment.split_once('=') else {
+        if !quiet { println!("Expected NAME=VALUE, got '{}'.", assignment); }
+        return Err(());
+    };
+    let item = find_menu_item(name.trim(), quiet)?;
+    let raw = item.parse_value(value).map_err(|_| {
+        if !quiet { println!("Value '{}' is not valid for {} (expected {}).", value, item.name, item.value); }
+    })?;
+    let mut port = open_radio(&cli.port, cli.speed, quiet)?;
+    let _ = cat_send(&mut *port, &CMD_EX.set(item, raw)?)?;
+    if !quiet { println!("{} set to {}", item.name, item.format_value(raw)?); }
+    Ok(())
+}
+
+fn ex_list(quiet: bool) {
+    if quiet { return; }
+    for item in MENU_ITEMS {
+        println!("{:<24} EX{:02}{:02}{:02}  {}", item.name, item.group, item.section, item.item, item.value);
+    }
+}
+
+// Menu dumps use the same Setting/Value layout as --read-settings, keyed by
+// the symbolic menu item name.
+fn menu_dump(cli: &Cli) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let file = cli.file.clone().unwrap_or_else(|| timestamped_filename("ftx1_menu"));
+    let mut port = open_radio(&cli.port, cli.speed, quiet)?;
+    if cli.sync_clock { set_clock(&mut *port, cli.utc, quiet)?; }
+    let mut wtr = csv::Writer::from_path(&file).map_err(|_| ())?;
+
+    if !quiet { println!("Reading menu items..."); }
+    let mut bar = Progress::new(cli, MENU_ITEMS.len());
+    for item in MENU_ITEMS {
+        bar.inc();
+        let rx = cat_send(&mut *port, &CMD_EX.read(item))?;
+        // decode has already checked the value formats.
+        match CMD_EX.decode(item, &rx) {
+            Ok(raw) => {
+                let value = item.format_value(raw)?;
+                wtr.serialize(SettingRecord { name: item.name.to_string(), value }).map_err(|_| ())?;
+            }
+            Err(e) => error!("Menu item {}: {}", item.name, e),
+        }
+    }
+    bar.finish();
+    wtr.flush().map_err(|_| ())?;
+    if !quiet { println!("Menu saved to CSV file: {}", file); }
+    Ok(())
+}
+
+fn menu_restore(cli: &Cli, file: &str) -> Result<(), ()> {
+    let quiet = cli.quiet;
+    let records = read_setting_records(file, quiet)?;
+
+    let mut commands: Vec<Vec<u8>> = Vec::new();
+    for rec in &records {
+        let item = find_menu_item(&rec.name, quiet)?;
+        let raw = item.parse_value(&rec.value).map_err(|_| {
+            if !quiet { println!("Value '{}' is not valid for {} (expected {}).", rec.value, item.name, item.value); }
+        })?;
+        // Changing the link mid-restore would cut off the rest of it.
+        if item.is_cat_link() {
+            if !quiet { println!("Skipping {}={}: it changes the CAT link; set it with --ex-set if needed.", item.name, rec.value); }
+            continue;
+        }
+        commands.push(CMD_EX.set(item, raw)?);
+    }
+
+    let mut port = open_radio(&cli.port, cli.speed, quiet)?;
+    if !quiet { println!("Restoring menu from CSV file: {} ({} items)... ", file, commands.len()); }
+    let mut bar = Progress::new(cli, commands.len());
+    for cmd in commands {
+        bar.inc();
+        let _ = cat_send(&mut *port, &cmd)?;
+    }
+    bar.finish();
+    if !quiet { println!("Menu written to radio."); }
+    Ok(())
+}
+
+fn set_clock(port: &mut dyn Transport, utc: bool, quiet: bool) -> Result<(), ()> {
+    let now = if utc {
+        jiff::Timestamp::now().to_zoned(jiff::tz::TimeZone::UTC)
+    } else {
+        jiff::Zoned::now()
+    };
+    let offset_minutes = (now.offset().seconds() / 60) as i16;
+    let _ = cat_send(port, &CMD_DT.set_date(now.year() as u16, now.month() as u8, now.day() as u8)?)?;
+    let _ = cat_send(port, &CMD_DT.set_time(now.hour() as u8, now.minute() as u8, now.second() as u8)?)?;
+    let _ = cat_send(port, &CMD_DT.set_utc_offset(offset_minutes)?)?;
+    if !quiet { println!("Radio clock set to {}", now.strftime("%Y-%m-%d %H:%M:%S %:z")); }
+    Ok(())
+}
@@ -0,0 +1,654 @@
+// The memory file: CsvRecord and its columns, and the checks --check-data
+// runs on it. Shared by the CLI and the library (the `files` feature and
+// the WASM build, see wasm.rs), so a file is judged by the same rules
+// wherever it's checked.
+//
+// Checking is two passes. check_record normalises and validates each
+// record on its own, so the CLI can run it in parallel; CrossCheck then
+// goes through the records in file order for what depends on the ones
+// before (duplicate channels, shared frequencies) and the warnings.
+
+#![allow(clippy::result_unit_err, reason = "errors are reported as Err(()), like the protocol layer's")]
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::bandplan::{BandPlan, PlanCheck};
+use crate::dialect;
+use crate::ftx1::*;
+use crate::groups::MemoryGroup;
+use crate::hamlib;
+use crate::import::ListEntry;
+
+/// The numbered memory channels, 1-999.
+pub const CHANNELS: u16 = 999;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CsvRecord {
+    #[serde(rename = "Channel Number")]
+    pub channel: String,
+    #[serde(
+        rename = "Frequency (Hz)",
+        alias = "Frequency (MHz)",
+        serialize_with = "dialect::serialize_hz",
+        deserialize_with = "dialect::deserialize_hz"
+    )]
+    pub freq: u32, // FrequencyHz,
+    #[serde(rename = "Memory Tag")]
+    pub tag: Option<String>,
+    #[serde(rename = "Mode")]
+    pub mode: String,
+    #[serde(rename = "Channel Type")]
+    pub ch_type: ChType,
+    #[serde(rename = "Squelch Type")]
+    pub tone: SqlType,
+    #[serde(rename = "Shift (Hz)")]
+    pub shift: Shift,
+    #[serde(rename = "Clarifier Offset (Hz)")]
+    pub clarifier_offset_hz: i16,
+    #[serde(rename = "Rx Clarifier Enabled")]
+    pub rx_clarifier_enabled: RxClarifierOnOff,
+    #[serde(rename = "Tx Clarifier Enabled")]
+    pub tx_clarifier_enabled: TxClarifierOnOff,
+    #[serde(rename = "CTCSS Tone")]
+    pub ctcss_tone: String,
+    #[serde(rename = "DCS Tone")]
+    pub dcs_tone: String,
+    /// Optional split-memory TX frequency. Empty cell or missing column = no
+    /// split (TX = RX). Set to a Hz value to enable per-channel split via MZ.
+    #[serde(
+        rename = "Split TX (Hz)",
+        alias = "Split TX (MHz)",
+        default,
+        serialize_with = "dialect::serialize_opt_hz",
+        deserialize_with = "dialect::deserialize_opt_hz"
+    )]
+    pub tx_frequency_hz: Option<u32>,
+    /// Band group the radio files the channel under (see groups.rs).
+    /// Filled in by --read-radio; informational, never written.
+    #[serde(rename = "Group", default)]
+    pub group: Option<String>,
+}
+
+/// CsvRecord's columns, in the order --read-radio writes them. The last
+/// two (split TX, group) may be left out.
+pub const CSV_COLUMNS: [&str; 14] = [
+    "Channel Number",
+    "Frequency (Hz)",
+    "Memory Tag",
+    "Mode",
+    "Channel Type",
+    "Squelch Type",
+    "Shift (Hz)",
+    "Clarifier Offset (Hz)",
+    "Rx Clarifier Enabled",
+    "Tx Clarifier Enabled",
+    "CTCSS Tone",
+    "DCS Tone",
+    "Split TX (Hz)",
+    "Group",
+];
+
+/// Checks a memory file's header row against CSV_COLUMNS, so a renamed or
+/// missing column is reported once by name instead of as a deserialize
+/// error on every row.
+pub fn check_headers(headers: &csv::StringRecord) -> Result<(), Vec<String>> {
+    check_columns(headers, &CSV_COLUMNS[..CSV_COLUMNS.len() - 2])
+}
+
+/// check_headers for a --patch file, which needs only the channel number.
+pub fn check_patch_headers(headers: &csv::StringRecord) -> Result<(), Vec<String>> {
+    check_columns(headers, &CSV_COLUMNS[..1])
+}
+
+fn check_columns(headers: &csv::StringRecord, required: &[&str]) -> Result<(), Vec<String>> {
+    let headers: Vec<&str> = headers.iter().map(dialect::canonical_column).collect();
+    let key = |h: &str| h.trim().to_ascii_lowercase();
+    let mut errors = Vec::new();
+    for h in headers.iter().filter(|h| !CSV_COLUMNS.contains(h)) {
+        match CSV_COLUMNS.iter().find(|c| key(c) == key(h)) {
+            Some(c) => errors.push(format!("Column '{}' should be named '{}'.", h, c)),
+            None => errors.push(format!("Unexpected column '{}'.", h)),
+        }
+    }
+    for c in required.iter().filter(|c| !headers.iter().any(|h| key(h) == key(c))) {
+        errors.push(format!("Missing column '{}'.", c));
+    }
+    for c in CSV_COLUMNS.iter().filter(|c| headers.iter().filter(|h| **h == **c).count() > 1) {
+        errors.push(format!("Column '{}' appears more than once.", c));
+    }
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
+
+impl CsvRecord {
+    /// What the radio stores in the slot, for comparing channels: no slot
+    /// number, the tag trimmed, without the informational group, and the
+    /// mode and tones spelled as a read gives them back ("100" is
+    /// "100.0", "023" is "23"). Fields that don't decode are kept as they
+    /// are.
+    pub fn contents(&self) -> CsvRecord {
+        let tag = self.tag.as_deref().unwrap_or("").trim_end().to_string();
+        let tone = |tone_type: ToneType, cell: &str| {
+            CmdCn::tone_code_from_string(tone_type.clone(), cell.trim())
+                .and_then(|code| CmdCn::tone_code_to_string(tone_type, code))
+                .unwrap_or_else(|_| cell.trim().to_string())
+        };
+        CsvRecord {
+            channel: String::new(),
+            tag: Some(tag),
+            mode: Mode::try_from(self.mode.clone()).map(|m| m.to_string()).unwrap_or_else(|_| self.mode.clone()),
+            ctcss_tone: tone(ToneType::Ctcss, &self.ctcss_tone),
+            dcs_tone: tone(ToneType::Dcs, &self.dcs_tone),
+            group: None,
+            ..self.clone()
+        }
+    }
+}
+
+/// Whether `a` and `b` put the same thing in a slot (see
+/// CsvRecord::contents). ARS picks the shift direction by itself, so a
+/// channel written with it reads back with + or - shift.
+pub fn same_contents(a: &CsvRecord, b: &CsvRecord) -> bool {
+    let (a, b) = (a.contents(), b.contents());
+    let ars = |x: &CsvRecord, y: &CsvRecord| x.shift == Shift::Ars && matches!(y.shift, Shift::PlusShift | Shift::MinusShift);
+    if ars(&a, &b) || ars(&b, &a) {
+        CsvRecord { shift: Shift::Ars, ..a } == CsvRecord { shift: Shift::Ars, ..b }
+    } else {
+        a == b
+    }
+}
+
+impl TryFrom<CsvRecord> for MemoryReadWrite {
+    type Error = ();
+
+    fn try_from(item: CsvRecord) -> Result<Self, Self::Error> {
+        let channel = MemoryChannel::try_from(item.channel)?;
+        let mem = MemoryReadWrite {
+            channel,
+            frequency_hz: FrequencyHz::try_from(item.freq)?,
+            clarifier_offset_hz: ClarifierOffsetHz::try_from(item.clarifier_offset_hz)?,
+            rx_clarifier_enabled: item.rx_clarifier_enabled,
+            tx_clarifier_enabled: item.tx_clarifier_enabled,
+            mode: Mode::try_from(item.mode)?,
+            ch_type: item.ch_type,
+            sql_type: item.tone,
+            shift: item.shift,
+        };
+        Ok(mem)
+    }
+}
+
+/// Validation policy for check_data: the CLI takes it from the command
+/// line, the WASM build from its caller.
+#[derive(Clone, Copy)]
+pub struct CheckOptions {
+    pub warnings_enabled: bool,
+    pub allow_any_frequency: bool,
+    pub region: Region,
+    pub fix_tags: bool,
+    pub band_plan: Option<BandPlan>,
+}
+
+/// A record after normalisation, with its errors and notes about fixes
+/// normalize_record made.
+pub type CheckedRecord = (CsvRecord, Vec<String>, Vec<String>);
+
+/// Normalises and validates one record.
+pub fn check_record(mut record: CsvRecord, options: &CheckOptions) -> CheckedRecord {
+    let original_tag = record.tag.clone();
+    normalize_record(&mut record, options.fix_tags);
+    let errors = validate_record(&record, options.allow_any_frequency, options.region).err().unwrap_or_default();
+    let mut notes = Vec::new();
+    if let (Some(before), Some(after)) = (&original_tag, &record.tag)
+        && before.trim_end() != after.trim_end()
+    {
+        notes.push(format!("Tag '{}' will be written as '{}'.", before, after.trim_end()));
+    }
+    (record, errors, notes)
+}
+
+/// The checks that look at a record against the ones before it, plus the
+/// warnings. Feed it the checked records in file order.
+#[derive(Default)]
+pub struct CrossCheck {
+    seen_channels: HashMap<String, usize>,
+    seen_frequencies: HashMap<u32, (String, Option<String>)>,
+    seen_settings: HashMap<(u32, String, String), String>,
+    /// Whether a channel number appeared twice.
+    pub duplicates_found: bool,
+}
+
+impl CrossCheck {
+    /// Adds record `number` (1-based)'s cross-record errors to `errors`
+    /// and returns its warnings, `notes` first.
+    pub fn check(&mut self, number: usize, record: &CsvRecord, errors: &mut Vec<String>, notes: Vec<String>, options: &CheckOptions) -> Vec<String> {
+        let CheckOptions { warnings_enabled, allow_any_frequency, region, band_plan, .. } = *options;
+        if let Some(first) = self.seen_channels.insert(record.channel.clone(), number) {
+            errors.push(format!("Channel '{}' appears more than once (also record {}).", record.channel, first));
+            self.duplicates_found = true;
+        }
+
+        let mut warnings: Vec<String> = Vec::new();
+        if !warnings_enabled {
+            return warnings;
+        }
+        warnings.extend(notes);
+        // Same frequency, mode and tone is almost certainly a merge
+        // leftover; a shared frequency alone may well be intended.
+        let settings_key = (record.freq, record.mode.clone(), effective_tone(record));
+        let exact_dup = self.seen_settings.get(&settings_key).cloned();
+        self.seen_settings.entry(settings_key).or_insert_with(|| record.channel.clone());
+        match (exact_dup, self.seen_frequencies.get(&record.freq)) {
+            (Some(first), _) => {
+                warnings.push(format!(
+                    "Channel '{}' duplicates channel '{}' (same frequency, mode and tone).",
+                    record.channel, first
+                ));
+            }
+            (None, Some((prev_ch, prev_tag))) => {
+                let prev_label = match prev_tag {
+                    Some(t) if !t.trim().is_empty() => format!("'{}' ({})", prev_ch, t.trim()),
+                    _ => format!("'{}'", prev_ch),
+                };
+                let cur_label = match &record.tag {
+                    Some(t) if !t.trim().is_empty() => format!(" ({})", t.trim()),
+                    _ => String::new(),
+                };
+                warnings.push(format!(
+                    "Frequency {} Hz{} is also used by channel {}.",
+                    record.freq, cur_label, prev_label
+                ));
+            }
+            (None, None) => {
+                self.seen_frequencies.insert(record.freq, (record.channel.clone(), record.tag.clone()));
+            }
+        }
+
+        // A MARS-CAP unit (--allow-any-frequency) transmits out of band
+        // too, so only stock radios get the receive-only warning.
+        let tx_freq = record.tx_frequency_hz.unwrap_or(record.freq);
+        match FrequencyHz::try_from(tx_freq) {
+            Ok(f) if !allow_any_frequency && region.can_receive(f) && !region.can_transmit(f) => {
+                warnings.push(format!(
+                    "TX frequency {} Hz is outside the {} model's transmit coverage; the channel will be receive-only.",
+                    tx_freq, region
+                ));
+            }
+            _ => {}
+        }
+
+        warnings.extend(consistency_warnings(record));
+
+        let group = MemoryGroup::of(record.freq);
+        if let Some(cell) = record.group.as_deref().filter(|g| !g.trim().is_empty())
+            && MemoryGroup::try_from(cell) != Ok(group)
+        {
+            warnings.push(format!(
+                "Group '{}' won't be applied: the radio files {} Hz under {} (M-GRP can only be set on the radio).",
+                cell.trim(), record.freq, group
+            ));
+        }
+
+        if let (Some(plan), Ok(mode)) = (band_plan, Mode::try_from(record.mode.clone())) {
+            let mhz = record.freq as f64 / 1_000_000.0;
+            match plan.check(record.freq, mode) {
+                PlanCheck::ModeNotPermitted => {
+                    warnings.push(format!("{:.3} MHz {} not permitted in {}.", mhz, record.mode, plan))
+                }
+                PlanCheck::OutsidePlan => warnings.push(format!("{:.3} MHz is outside the {} amateur bands.", mhz, plan)),
+                PlanCheck::Ok | PlanCheck::NotAmateur => {}
+            }
+        }
+        warnings
+    }
+}
+
+/// The tone and code every channel has until one is chosen.
+const FACTORY_CTCSS: &str = "88.5";
+const FACTORY_DCS: &str = "023";
+
+/// Fields of one record that contradict each other. The radio takes them
+/// as they are, but they're almost always a spreadsheet mistake.
+pub fn consistency_warnings(record: &CsvRecord) -> Vec<String> {
+    let mut warnings = Vec::new();
+    // A read fills in both tone columns, with the factory default for a
+    // tone that was never set; only other codes count as chosen.
+    let chosen = |tone_type: ToneType, cell: &str, default: &str| {
+        let code = |s: &str| CmdCn::tone_code_from_string(tone_type.clone(), s.trim());
+        code(cell).is_ok_and(|c| code(default) != Ok(c))
+    };
+    if record.tone == SqlType::CtcssOff {
+        if chosen(ToneType::Ctcss, &record.ctcss_tone, FACTORY_CTCSS) {
+            warnings.push(format!(
+                "CTCSS tone {} is set but Squelch Type is {}; the tone won't be used.",
+                record.ctcss_tone.trim(), record.tone
+            ));
+        }
+        if chosen(ToneType::Dcs, &record.dcs_tone, FACTORY_DCS) {
+            warnings.push(format!(
+                "DCS code {} is set but Squelch Type is {}; the code won't be used.",
+                record.dcs_tone.trim(), record.tone
+            ));
+        }
+    }
+    // The repeater offset itself is a menu setting; a channel's only
+    // offset of its own is a split TX frequency.
+    if matches!(record.shift, Shift::PlusShift | Shift::MinusShift) && record.tx_frequency_hz == Some(record.freq) {
+        warnings.push(format!("{} is set but the split TX frequency is the receive frequency (zero offset).", record.shift));
+    }
+    if record.clarifier_offset_hz == 0 {
+        if record.tx_clarifier_enabled == TxClarifierOnOff::TxClarifierOn {
+            warnings.push("TX clarifier is enabled with a zero offset.".to_string());
+        }
+        if record.rx_clarifier_enabled == RxClarifierOnOff::RxClarifierOn {
+            warnings.push("RX clarifier is enabled with a zero offset.".to_string());
+        }
+    }
+    warnings
+}
+
+// The tone a channel actually uses: the CTCSS/DCS code columns are always
+// filled in, but only the one the squelch type selects matters.
+pub fn effective_tone(record: &CsvRecord) -> String {
+    match record.tone {
+        SqlType::CtcssOff => String::new(),
+        SqlType::CtcssEnc | SqlType::CtcssEncDec => format!("{} {}", record.tone, record.ctcss_tone),
+        SqlType::Dcs => format!("{} {}", record.tone, record.dcs_tone),
+        other => other.to_string(),
+    }
+}
+
+// Normalise fields a spreadsheet (LibreOffice, Excel) is likely to have
+// mangled on a save round-trip. Only the leading-zero numeric memory channel
+// format (00001–00999) is affected: spreadsheets open it as Number and strip
+// the zeros. PMS (`P-01L`), 5MHz band (`50001`), and EMGCH already survive
+// because they contain non-digits or no leading zero.
+//
+// Tags get their trailing spaces normalised to the radio's 12-character
+// padding, so a tag read back compares equal to the one written. With
+// --fix-tags, tags the radio can't store are fixed here too (see fix_tag);
+// otherwise validate_record rejects them.
+pub fn normalize_record(record: &mut CsvRecord, fix_tags: bool) {
+    let ch = &record.channel;
+    if (1..5).contains(&ch.len()) && ch.chars().all(|c| c.is_ascii_digit()) {
+        record.channel = format!("{:0>5}", ch);
+    }
+    record.ctcss_tone = dialect::from_decimal_comma(&record.ctcss_tone);
+    if let Some(tag) = &mut record.tag {
+        let trimmed = tag.trim_end();
+        if fix_tags {
+            *tag = fix_tag(trimmed);
+        } else if trimmed.chars().count() <= TAG_LEN {
+            *tag = format!("{:<width$}", trimmed, width = TAG_LEN);
+        }
+    }
+}
+
+pub fn validate_record(record: &CsvRecord, allow_any_frequency: bool, region: Region) -> Result<(), Vec<String>> {
+    let mut errors = Vec::new();
+
+    // Validate channel
+    if record.channel.len() != 5 {
+        errors.push(format!("Channel '{}' has invalid length. Expected 5.", record.channel));
+    } else {
+        if MemoryChannel::try_from(record.channel.as_bytes()).is_err() {
+            errors.push(format!("Channel '{}' is not a valid memory channel.", record.channel));
+        }
+    }
+
+    // Validate frequency. The type-level check enforces the 9-char wire-format
+    // limit; the radio-coverage range check is gated by --allow-any-frequency
+    // so MARS-CAP units can program out-of-band channels.
+    match FrequencyHz::try_from(record.freq) {
+        Err(_) => errors.push(format!("Frequency '{}' is not valid.", record.freq)),
+        Ok(f) if !allow_any_frequency && !region.can_receive(f) => errors.push(format!(
+            "Frequency '{}' is not valid: outside the radio's documented coverage (30 kHz\u{2013}174 MHz, 400\u{2013}470 MHz). Pass --allow-any-frequency for MARS-CAP units.",
+            record.freq
+        )),
+        Ok(_) => {}
+    }
+
+    // Validate clarifier offset: ±9990 Hz in 10 Hz steps
+    let clar = record.clarifier_offset_hz;
+    if clar.abs() > ClarifierOffsetHz::MAX {
+        errors.push(format!("Clarifier offset '{}' is out of range (\u{b1}{} Hz).", clar, ClarifierOffsetHz::MAX));
+    } else if ClarifierOffsetHz::try_from(clar).is_err() {
+        errors.push(format!(
+            "Clarifier offset '{}' is not a multiple of {} Hz (nearest: {}).",
+            clar,
+            ClarifierOffsetHz::STEP,
+            ClarifierOffsetHz::rounded(clar).to_i16()
+        ));
+    }
+
+    // Validate tag (already padded by normalize_record)
+    if let Some(tag) = &record.tag {
+        let len = tag.trim_end().chars().count();
+        if len > TAG_LEN {
+            errors.push(format!(
+                "Tag '{}' is {} characters long; the radio stores at most {}. Pass --fix-tags to truncate.",
+                tag.trim_end(),
+                len,
+                TAG_LEN
+            ));
+        }
+        let bad: String = tag.chars().filter(|c| !is_tag_char(*c)).collect();
+        if !bad.is_empty() {
+            errors.push(format!(
+                "Tag '{}' contains characters the radio can't store ({:?}). Pass --fix-tags to drop them.",
+                tag.trim_end(),
+                bad
+            ));
+        }
+    }
+
+    // Validate mode via the canonical Mode::try_from rather than a duplicated
+    // allowlist, so check_data never drifts from what MemoryReadWrite accepts.
+    if Mode::try_from(record.mode.clone()).is_err() {
+        errors.push(format!("Mode '{}' is not a valid mode.", record.mode));
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// A record as a Hamlib channel; None for channels Hamlib can't number.
+pub fn hamlib_channel(r: &CsvRecord) -> Result<Option<hamlib::Channel>, ()> {
+    let Some(num) = r.channel.parse::<u16>().ok().filter(|n| (1..=CHANNELS).contains(n)) else {
+        return Ok(None);
+    };
+    let tone = hamlib::tone_tenths(&r.ctcss_tone);
+    let dcs = r.dcs_tone.trim().parse::<u16>().ok();
+    Ok(Some(hamlib::Channel {
+        num,
+        desc: r.tag.clone().unwrap_or_default(),
+        freq: r.freq,
+        mode: Mode::try_from(r.mode.clone())?,
+        tx_freq: r.tx_frequency_hz,
+        shift: r.shift,
+        ctcss: match r.tone {
+            SqlType::CtcssEnc => tone.map(|t| (t, false)),
+            SqlType::CtcssEncDec => tone.map(|t| (t, true)),
+            _ => None,
+        },
+        dcs: if r.tone == SqlType::Dcs { dcs.map(|d| (d, true)) } else { None },
+    }))
+}
+
+/// The record --import-list makes of a list entry: memory channel
+/// `channel` ("00001") with `shift` and split TX as given (the entry's own,
+/// or guessed from a band plan), default tones, no clarifier.
+pub fn list_record(entry: &ListEntry, channel: String, shift: Shift, tx_frequency_hz: Option<u32>) -> CsvRecord {
+    CsvRecord {
+        channel,
+        freq: entry.freq,
+        tag: (!entry.name.is_empty()).then(|| fix_tag(&entry.name)),
+        mode: entry.mode.to_string(),
+        ch_type: ChType::MemoryChannel,
+        tone: entry.squelch,
+        shift,
+        clarifier_offset_hz: 0,
+        rx_clarifier_enabled: RxClarifierOnOff::RxClarifierOff,
+        tx_clarifier_enabled: TxClarifierOnOff::TxClarifierOff,
+        ctcss_tone: match entry.squelch {
+            SqlType::CtcssEnc | SqlType::CtcssEncDec => entry.tone.clone().unwrap_or_default(),
+            _ => "88.5".to_string(),
+        },
+        dcs_tone: if entry.squelch == SqlType::Dcs { entry.tone.clone().unwrap_or_default() } else { "23".to_string() },
+        tx_frequency_hz,
+        group: Some(MemoryGroup::of(entry.freq).to_string()),
+    }
+}
+
+/// A --patch row laid over the channel as it is on the radio (`current`,
+/// None when it's empty): the columns the patch file has replace the
+/// radio's values, even with an empty cell; the rest are kept. An empty
+/// channel has nothing to keep, so the patch must give it every column.
+pub fn patch_record(current: Option<&CsvRecord>, headers: &csv::StringRecord, row: &csv::StringRecord) -> Result<CsvRecord, String> {
+    let columns: Vec<&str> = headers.iter().map(dialect::canonical_column).collect();
+    let mut cells = vec![String::new(); CSV_COLUMNS.len()];
+    match current {
+        Some(record) => {
+            let mut wtr = csv::WriterBuilder::new().has_headers(false).from_writer(Vec::new());
+            wtr.serialize(record).map_err(|e| e.to_string())?;
+            let bytes = wtr.into_inner().map_err(|e| e.to_string())?;
+            let base = csv::ReaderBuilder::new().has_headers(false).from_reader(bytes.as_slice()).records().next();
+            let base = base.ok_or("the radio's channel didn't convert")?.map_err(|e| e.to_string())?;
+            for (cell, value) in cells.iter_mut().zip(base.iter()) {
+                *cell = value.to_string();
+            }
+        }
+        None => {
+            let missing: Vec<&str> = CSV_COLUMNS[..CSV_COLUMNS.len() - 2].iter().copied().filter(|c| !columns.contains(c)).collect();
+            if !missing.is_empty() {
+                return Err(format!("the channel is empty on the radio, so the patch must give it {}", missing.join(", ")));
+            }
+        }
+    }
+    for (column, value) in columns.iter().zip(row.iter()) {
+        if let Some(i) = CSV_COLUMNS.iter().position(|c| c == column) {
+            cells[i] = value.to_string();
+        }
+    }
+    csv::StringRecord::from(cells)
+        .deserialize(Some(&csv::StringRecord::from(CSV_COLUMNS.to_vec())))
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+impl CsvRecord {
+    /// A simplex FM channel with no tones and no group, for tests to build
+    /// their channels from.
+    pub fn test_channel(channel: &str, freq: u32, tag: &str) -> CsvRecord {
+        CsvRecord {
+            channel: channel.to_string(),
+            freq,
+            tag: Some(tag.to_string()),
+            mode: "FM".to_string(),
+            ch_type: ChType::MemoryChannel,
+            tone: SqlType::CtcssOff,
+            shift: Shift::Simplex,
+            clarifier_offset_hz: 0,
+            rx_clarifier_enabled: RxClarifierOnOff::RxClarifierOff,
+            tx_clarifier_enabled: TxClarifierOnOff::TxClarifierOff,
+            ctcss_tone: "88.5".to_string(),
+            dcs_tone: "23".to_string(),
+            tx_frequency_hz: None,
+            group: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record() -> CsvRecord {
+        CsvRecord::test_channel("00012", 145_500_000, "CALL")
+    }
+
+    #[test]
+    fn test_same_contents() {
+        let written = record();
+        // A read gives the tones and mode back in its own spelling.
+        let back = CsvRecord { channel: "00012".to_string(), tag: Some("CALL        ".to_string()), ..record() };
+        assert!(same_contents(&written, &back));
+        let integer_ctcss = CsvRecord { tone: SqlType::CtcssEncDec, ctcss_tone: "100".to_string(), ..record() };
+        assert!(same_contents(&integer_ctcss, &CsvRecord { ctcss_tone: "100.0".to_string(), ..integer_ctcss.clone() }));
+        assert!(!same_contents(&integer_ctcss, &CsvRecord { ctcss_tone: "103.5".to_string(), ..integer_ctcss.clone() }));
+        let padded_dcs = CsvRecord { tone: SqlType::Dcs, dcs_tone: "023".to_string(), ..record() };
+        assert!(same_contents(&padded_dcs, &CsvRecord { dcs_tone: "23".to_string(), ..padded_dcs.clone() }));
+        assert!(!same_contents(&padded_dcs, &CsvRecord { dcs_tone: "25".to_string(), ..padded_dcs.clone() }));
+
+        // ARS reads back as whichever direction the radio chose.
+        let ars = CsvRecord { shift: Shift::Ars, ..record() };
+        assert!(same_contents(&ars, &CsvRecord { shift: Shift::MinusShift, ..record() }));
+        assert!(same_contents(&CsvRecord { shift: Shift::PlusShift, ..record() }, &ars));
+        assert!(!same_contents(&ars, &record()));
+        assert!(!same_contents(&CsvRecord { shift: Shift::PlusShift, ..record() }, &CsvRecord { shift: Shift::MinusShift, ..record() }));
+        assert!(!same_contents(&CsvRecord { freq: 145_525_000, ..ars.clone() }, &CsvRecord { shift: Shift::MinusShift, ..record() }));
+    }
+
+    #[test]
+    fn test_consistency_warnings() {
+        let clean = record();
+        assert!(consistency_warnings(&clean).is_empty());
+        let warnings = consistency_warnings(&CsvRecord { ctcss_tone: "100.0".to_string(), dcs_tone: "754".to_string(), ..record() });
+        assert_eq!(warnings.len(), 2, "{:?}", warnings);
+        assert!(warnings[0].starts_with("CTCSS tone 100.0 is set but Squelch Type is"), "{}", warnings[0]);
+        assert!(warnings[1].starts_with("DCS code 754 is set"), "{}", warnings[1]);
+        // With the tone in use there's nothing to say.
+        assert!(consistency_warnings(&CsvRecord { tone: SqlType::CtcssEncDec, ctcss_tone: "100.0".to_string(), ..record() }).is_empty());
+
+        let zero_split = CsvRecord { shift: Shift::PlusShift, tx_frequency_hz: Some(145_500_000), ..clean.clone() };
+        assert_eq!(consistency_warnings(&zero_split).len(), 1);
+        let split = CsvRecord { tx_frequency_hz: Some(146_100_000), ..zero_split };
+        assert!(consistency_warnings(&split).is_empty());
+
+        let clar = CsvRecord { tx_clarifier_enabled: TxClarifierOnOff::TxClarifierOn, ..clean.clone() };
+        assert_eq!(consistency_warnings(&clar), ["TX clarifier is enabled with a zero offset."]);
+        assert!(consistency_warnings(&CsvRecord { clarifier_offset_hz: 120, ..clar }).is_empty());
+    }
+
+    #[test]
+    fn test_check_patch_headers() {
+        let headers = csv::StringRecord::from(vec!["Channel Number", "CTCSS Tone", "Squelch Type"]);
+        assert!(check_patch_headers(&headers).is_ok());
+        assert!(check_headers(&headers).is_err());
+        let headers = csv::StringRecord::from(vec!["CTCSS Tone", "Tone"]);
+        assert_eq!(
+            check_patch_headers(&headers),
+            Err(vec!["Unexpected column 'Tone'.".to_string(), "Missing column 'Channel Number'.".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_patch_record() {
+        let headers = csv::StringRecord::from(vec!["Channel Number", "Squelch Type", "CTCSS Tone", "Memory Tag"]);
+        let row = csv::StringRecord::from(vec!["12", "CtcssEncDec", "123.0", ""]);
+        let patched = patch_record(Some(&record()), &headers, &row).unwrap();
+        let expected = CsvRecord {
+            channel: "12".to_string(),
+            tag: None,
+            tone: SqlType::CtcssEncDec,
+            ctcss_tone: "123.0".to_string(),
+            ..record()
+        };
+        assert_eq!(patched, expected);
+
+        let err = patch_record(None, &headers, &row).unwrap_err();
+        assert!(err.contains("Frequency (Hz), Mode, Channel Type"), "{}", err);
+
+        let mut full: Vec<&str> = CSV_COLUMNS.to_vec();
+        full.truncate(12);
+        let row = csv::StringRecord::from(vec![
+            "00013", "7074000", "FT8", "USB", "MemoryChannel", "CtcssOff", "Simplex", "0", "RxClarifierOff",
+            "TxClarifierOff", "88.5", "23",
+        ]);
+        let new = patch_record(None, &csv::StringRecord::from(full), &row).unwrap();
+        assert_eq!((new.channel.as_str(), new.freq, new.tx_frequency_hz), ("00013", 7_074_000, None));
+    }
+}
@@ -0,0 +1,215 @@
+// EX - MENU
+//
+// Typed access to the radio's setup menu. Each MenuItem names one menu
+// entry, its P1/P2/P3 address and the shape of its value; CmdEx builds
+// and decodes frames for it. Only items listed in MENU_ITEMS are reachable
+// by name — extend the table from the MENU chart in the CAT reference
+// manual as more items are needed.
+//
+// Wire format:
+//   Set : EX gg ss ii <value> ;
+//   Read: EX gg ss ii ;         → EX gg ss ii <value> ;
+//     gg ss ii — 2-digit group, section and item numbers
+//     value    — fixed-width per item (see MenuValue::width)
+
+use super::{Cmd, DecodeError};
+use core::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MenuValue {
+    /// Integer in min..=max, sent zero-padded to `width` digits. Signed
+    /// ranges carry a leading '+'/'-' that isn't counted in `width`.
+    Range { min: i32, max: i32, width: usize },
+    /// One of a fixed set of options; the wire value is the option index
+    /// as a single digit.
+    Choice(&'static [&'static str]),
+}
+
+impl MenuValue {
+    /// Number of value characters on the wire.
+    pub fn width(&self) -> usize {
+        match *self {
+            MenuValue::Range { min, width, .. } => width + usize::from(min < 0),
+            MenuValue::Choice(_) => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MenuItem {
+    pub name: &'static str,
+    pub group: u8,
+    pub section: u8,
+    pub item: u8,
+    pub value: MenuValue,
+}
+
+const OFF_ON: &[&str] = &["OFF", "ON"];
+
+#[rustfmt::skip]
+pub const MENU_ITEMS: &[MenuItem] = &[
+    // 01 RADIO SETTING
+    MenuItem { name: "agc-fast-delay-ms", group: 1, section: 1, item: 1, value: MenuValue::Range { min: 20, max: 4000, width: 4 } },
+    MenuItem { name: "agc-mid-delay-ms", group: 1, section: 1, item: 2, value: MenuValue::Range { min: 20, max: 4000, width: 4 } },
+    MenuItem { name: "agc-slow-delay-ms", group: 1, section: 1, item: 3, value: MenuValue::Range { min: 20, max: 4000, width: 4 } },
+    MenuItem { name: "beep-level", group: 1, section: 2, item: 1, value: MenuValue::Range { min: 0, max: 100, width: 3 } },
+    MenuItem { name: "tx-timeout-timer", group: 1, section: 3, item: 1, value: MenuValue::Choice(&["OFF", "3MIN", "5MIN", "10MIN", "15MIN", "20MIN", "30MIN"]) },
+    // 02 CW SETTING
+    MenuItem { name: "cw-keyer-type", group: 2, section: 1, item: 1, value: MenuValue::Choice(&["OFF", "BUG", "ELEKEY-A", "ELEKEY-B", "ELEKEY-Y", "ACS"]) },
+    MenuItem { name: "cw-keyer-dot-dash", group: 2, section: 1, item: 2, value: MenuValue::Choice(&["NOR", "REV"]) },
+    MenuItem { name: "cw-keyer-weight", group: 2, section: 1, item: 3, value: MenuValue::Range { min: 25, max: 45, width: 2 } },
+    MenuItem { name: "cw-break-in-type", group: 2, section: 2, item: 1, value: MenuValue::Choice(&["SEMI", "FULL"]) },
+    MenuItem { name: "cw-wave-shape", group: 2, section: 2, item: 2, value: MenuValue::Choice(&["1MS", "2MS", "4MS", "6MS"]) },
+    MenuItem { name: "cw-sidetone-level", group: 2, section: 2, item: 3, value: MenuValue::Range { min: 0, max: 100, width: 3 } },
+    MenuItem { name: "cw-auto-mode", group: 2, section: 2, item: 4, value: MenuValue::Choice(&["OFF", "50M", "ON"]) },
+    MenuItem { name: "cw-freq-display", group: 2, section: 2, item: 5, value: MenuValue::Choice(&["DIRECT", "PITCH"]) },
+    MenuItem { name: "cw-qsk-delay", group: 2, section: 2, item: 6, value: MenuValue::Choice(&["15MS", "20MS", "25MS", "30MS"]) },
+    // 03 OPERATION SETTING
+    MenuItem { name: "rpt-shift-28mhz-khz", group: 3, section: 1, item: 1, value: MenuValue::Range { min: 0, max: 1000, width: 4 } },
+    MenuItem { name: "rpt-shift-50mhz-khz", group: 3, section: 1, item: 2, value: MenuValue::Range { min: 0, max: 4000, width: 4 } },
+    MenuItem { name: "rpt-shift-144mhz-khz", group: 3, section: 1, item: 3, value: MenuValue::Range { min: 0, max: 99950, width: 5 } },
+    MenuItem { name: "rpt-shift-430mhz-khz", group: 3, section: 1, item: 4, value: MenuValue::Range { min: 0, max: 99950, width: 5 } },
+    MenuItem { name: "auto-power-off", group: 3, section: 2, item: 1, value: MenuValue::Choice(&["OFF", "1H", "2H", "4H", "6H", "8H", "10H", "12H"]) },
+    MenuItem { name: "cat-rate", group: 3, section: 3, item: 1, value: MenuValue::Choice(&["4800", "9600", "19200", "38400", "115200"]) },
+    MenuItem { name: "cat-timeout", group: 3, section: 3, item: 2, value: MenuValue::Choice(&["10MS", "100MS", "1000MS", "3000MS"]) },
+    MenuItem { name: "cat-rts", group: 3, section: 3, item: 3, value: MenuValue::Choice(OFF_ON) },
+    MenuItem { name: "main-clar-offset-hz", group: 3, section: 4, item: 1, value: MenuValue::Range { min: -9990, max: 9990, width: 4 } },
+    // 04 DISPLAY SETTING
+    MenuItem { name: "my-call-time", group: 4, section: 1, item: 1, value: MenuValue::Choice(&["OFF", "1SEC", "2SEC", "3SEC", "4SEC", "5SEC"]) },
+    MenuItem { name: "screen-saver", group: 4, section: 1, item: 2, value: MenuValue::Choice(&["OFF", "15MIN", "30MIN", "60MIN"]) },
+    MenuItem { name: "tft-contrast", group: 4, section: 1, item: 3, value: MenuValue::Range { min: 0, max: 20, width: 2 } },
+];
+
+impl MenuItem {
+    pub fn find(name: &str) -> Option<&'static MenuItem> {
+        MENU_ITEMS.iter().find(|m| m.name == name)
+    }
+
+    /// Renders a raw wire value the way a user would type it back in.
+    pub fn format_value(&self, raw: i32) -> Result<String, ()> {
+        match self.value {
+            MenuValue::Range { min, max, .. } if (min..=max).contains(&raw) => Ok(raw.to_string()),
+            MenuValue::Choice(options) => {
+                usize::try_from(raw).ok().and_then(|i| options.get(i)).map(|o| o.to_string()).ok_or(())
+            }
+            _ => Err(()),
+        }
+    }
+
+    /// Parses a user value (number for ranges, option name for choices,
+    /// case-insensitive) into the raw wire value.
+    pub fn parse_value(&self, s: &str) -> Result<i32, ()> {
+        let s = s.trim();
+        match self.value {
+            MenuValue::Range { min, max, .. } => {
+                let v: i32 = s.parse().map_err(|_| ())?;
+                if (min..=max).contains(&v) { Ok(v) } else { Err(()) }
+            }
+            MenuValue::Choice(options) => {
+                options.iter().position(|o| o.eq_ignore_ascii_case(s)).map(|i| i as i32).ok_or(())
+            }
+        }
+    }
+
+    /// True for the items that set the CAT link itself (baud rate and
+    /// timeout): writing one cuts off the session that wrote it.
+    pub fn is_cat_link(&self) -> bool {
+        matches!(self.name, "cat-rate" | "cat-timeout")
+    }
+
+    fn address(&self) -> String {
+        format!("{:02}{:02}{:02}", self.group, self.section, self.item)
+    }
+}
+
+impl fmt::Display for MenuValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MenuValue::Range { min, max, .. } => write!(f, "{}..{}", min, max),
+            MenuValue::Choice(options) => write!(f, "{}", options.join("|")),
+        }
+    }
+}
+
+pub struct CmdEx<'a> {
+    cmd: Cmd<'a>,
+}
+
+pub const CMD_EX: CmdEx<'static> = CmdEx { cmd: Cmd { code: &['E', 'X'], read_params: 0 } };
+
+impl CmdEx<'_> {
+    pub fn read(&self, item: &MenuItem) -> Vec<u8> {
+        Cmd::tx_buffer(&self.cmd, Some(item.address().chars().collect()))
+    }
+
+    pub fn set(&self, item: &MenuItem, raw: i32) -> Result<Vec<u8>, ()> {
+        item.format_value(raw)?;
+        let value = match item.value {
+            MenuValue::Range { min, width, .. } if min < 0 => format!("{:+0w$}", raw, w = width + 1),
+            MenuValue::Range { width, .. } => format!("{:0w$}", raw, w = width),
+            MenuValue::Choice(_) => raw.to_string(),
+        };
+        let mut buffer: Vec<char> = item.address().chars().collect();
+        buffer.extend(value.chars());
+        Ok(Cmd::tx_buffer(&self.cmd, Some(buffer)))
+    }
+
+    pub fn decode(&self, item: &MenuItem, buffer: &[u8]) -> Result<i32, DecodeError> {
+        let cmd = Cmd { code: self.cmd.code, read_params: 6 + item.value.width() };
+        Cmd::is_reply_ok(&cmd, buffer)?;
+        if buffer[2..8] != *item.address().as_bytes() {
+            return Err(cmd.error(buffer, Some("menu address")));
+        }
+        let value = cmd.field(buffer, "value", core::str::from_utf8(&buffer[8..buffer.len() - 1]).map_err(|_| ()))?;
+        let raw: i32 = cmd.field(buffer, "value", value.parse().map_err(|_| ()))?;
+        cmd.field(buffer, "value", item.format_value(raw))?;
+        Ok(raw)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_menu_item_names_are_unique() {
+        for (i, a) in MENU_ITEMS.iter().enumerate() {
+            assert!(MENU_ITEMS[i + 1..].iter().all(|b| b.name != a.name), "duplicate menu item {}", a.name);
+        }
+    }
+
+    #[test]
+    fn test_cat_link_items() {
+        assert!(MenuItem::find("cat-rate").unwrap().is_cat_link());
+        assert!(MenuItem::find("cat-timeout").unwrap().is_cat_link());
+        assert!(!MenuItem::find("cw-keyer-type").unwrap().is_cat_link());
+    }
+
+    #[test]
+    fn test_cmd_ex_range_wire_format() {
+        let item = MenuItem::find("cw-sidetone-level").unwrap();
+        assert_eq!(CMD_EX.read(item).as_slice(), b"EX020203;");
+        assert_eq!(CMD_EX.set(item, 50).unwrap().as_slice(), b"EX020203050;");
+        assert_eq!(CMD_EX.decode(item, b"EX020203075;"), Ok(75));
+        assert!(CMD_EX.set(item, 101).is_err());
+        // Reply for a different menu item is rejected.
+        assert!(CMD_EX.decode(item, b"EX020204075;").is_err());
+    }
+
+    #[test]
+    fn test_cmd_ex_signed_range_wire_format() {
+        let item = MenuItem::find("main-clar-offset-hz").unwrap();
+        assert_eq!(CMD_EX.set(item, -120).unwrap().as_slice(), b"EX030401-0120;");
+        assert_eq!(CMD_EX.decode(item, b"EX030401+0500;"), Ok(500));
+    }
+
+    #[test]
+    fn test_cmd_ex_choice_by_name() {
+        let item = MenuItem::find("cw-keyer-type").unwrap();
+        let raw = item.parse_value("elekey-b").unwrap();
+        assert_eq!(CMD_EX.set(item, raw).unwrap().as_slice(), b"EX0201013;");
+        assert_eq!(item.format_value(CMD_EX.decode(item, b"EX0201015;").unwrap()).unwrap(), "ACS");
+        assert!(item.parse_value("STRAIGHT").is_err());
+        assert!(CMD_EX.decode(item, b"EX0201019;").is_err());
+    }
+}
@@ -0,0 +1,95 @@
+// Memory-file format versions. Files are stamped with a comment line
+// ahead of the header row,
+//
+//   # ftx1-mm memory file format 2
+//
+// which every reader already skips as a comment, and files from older
+// releases are brought up to the current format as they're read, so the
+// rest of the program only ever sees that.
+//
+//   1  ftx1-mm 0.2.0 and older: no stamp; frequencies in Hz; Split TX
+//      (Hz) may be missing
+//   2  stamped; the Group column; frequencies in Hz or MHz, as the
+//      headers say (see dialect.rs)
+//
+// A format change adds a step to `migrate`. Files from a newer release
+// are refused rather than misread.
+
+/// The format this release writes.
+pub const FORMAT_VERSION: u32 = 2;
+
+const STAMP: &str = "# ftx1-mm memory file format ";
+
+/// The comment line written ahead of the header row.
+pub fn stamp() -> String {
+    format!("{}{}", STAMP, FORMAT_VERSION)
+}
+
+/// The version a stamp line gives, if it is one. A spreadsheet may have
+/// padded it with empty cells ("# ftx1-mm ... 2,,,,").
+fn parse_stamp(line: &str) -> Option<u32> {
+    let version = line.trim().strip_prefix(STAMP)?;
+    version.trim_end_matches([',', ';', '\t', ' ']).parse().ok()
+}
+
+/// The format of a memory file: the stamp ahead of its header row, or 1
+/// when there's none.
+pub fn file_version(text: &str) -> u32 {
+    text.lines()
+        .take_while(|l| l.trim().is_empty() || l.starts_with('#'))
+        .find_map(parse_stamp)
+        .unwrap_or(1)
+}
+
+/// Brings the text of a memory file up to FORMAT_VERSION.
+pub fn migrate(text: String) -> Result<String, String> {
+    let version = file_version(&text);
+    if version > FORMAT_VERSION {
+        return Err(format!(
+            "it's in memory file format {}, newer than the {} this ftx1-mm reads; update ftx1-mm",
+            version, FORMAT_VERSION
+        ));
+    }
+    let mut text = text;
+    if version < 2 {
+        text = from_v1(&text);
+    }
+    Ok(text)
+}
+
+// Format 1 -> 2: the columns are the same but for Group, which is
+// optional, so only the stamp changes.
+fn from_v1(text: &str) -> String {
+    format!("{}\n{}", stamp(), text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "Channel Number,Frequency (Hz),Memory Tag";
+
+    #[test]
+    fn test_file_version() {
+        assert_eq!(file_version(&format!("{}\n{}\n", stamp(), HEADER)), FORMAT_VERSION);
+        assert_eq!(file_version(&format!("# ftx1-mm memory file format 2,,,\n{}\n", HEADER)), 2);
+        assert_eq!(file_version(&format!("# exported on field day\n\n# ftx1-mm memory file format 7\n{}\n", HEADER)), 7);
+        assert_eq!(file_version(&format!("{}\n", HEADER)), 1);
+        // A stamp below the header is data, not a stamp.
+        assert_eq!(file_version(&format!("{}\n{}\n", HEADER, stamp())), 1);
+    }
+
+    #[test]
+    fn test_migrate() {
+        let v1 = format!("{}\n00001,145000000,HOME\n", HEADER);
+        let migrated = migrate(v1.clone()).unwrap();
+        assert_eq!(file_version(&migrated), FORMAT_VERSION);
+        assert!(migrated.ends_with(&v1));
+
+        let current = format!("{}\n{}", stamp(), v1);
+        assert_eq!(migrate(current.clone()), Ok(current));
+
+        let newer = format!("# ftx1-mm memory file format 3\n{}", v1);
+        assert!(migrate(newer).unwrap_err().contains("format 3, newer than the 2"));
+    }
+}
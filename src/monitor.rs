@@ -0,0 +1,102 @@
+// --monitor's event loop. The radio is put in auto-information mode (AI1),
+// in which it sends FA/FB, MD, MC, ... by itself as soon as they change,
+// and the monitor sits in a blocking read on the port: a change is printed
+// the moment its frame arrives, and an idle radio costs one read timeout
+// at a time rather than a poll loop. Replies to the monitor's own queries
+// come in the same way and go through the same path, so nothing waits on
+// a particular answer.
+//
+// Status flags (busy, TX, HI-SWR) and the S-meter aren't pushed, and the
+// transports that relay CAT (rigctld://, flrig://) don't pass unsolicited
+// frames, so the status and the --side VFO are also asked for every POLL,
+// as are the mode and channel with --udp-events and the S-meter with
+// --mqtt. The radio's AI setting is put back when the monitor stops.
+
+use std::time::Duration;
+
+use crate::ftx1::{MemoryChannel, Meter, Mode, Side, StatusFlags, CMD_FA, CMD_FB, CMD_MC, CMD_MD, CMD_RI, CMD_RM};
+
+pub const POLL: Duration = Duration::from_secs(1);
+
+/// Longest run of bytes without a ';' kept while waiting for the rest of
+/// a frame; past that it's line noise.
+const MAX_PENDING: usize = 1024;
+
+/// What a frame from the radio says about the --side receiver.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Update {
+    Frequency(u32),
+    Status(StatusFlags),
+    Mode(Mode),
+    Channel(MemoryChannel),
+    SMeter(u8),
+}
+
+/// The update in `frame`, if it's one the monitor follows and for `side`.
+pub fn update(side: Side, frame: &[u8]) -> Option<Update> {
+    let side_char = u8::try_from(char::from(side)).ok()?;
+    let meter = match side {
+        Side::Main => Meter::SMain,
+        Side::Sub => Meter::SSub,
+    };
+    match frame.get(..2)? {
+        b"FA" if side == Side::Main => CMD_FA.decode(frame).ok().map(|f| Update::Frequency(f.to_u32())),
+        b"FB" if side == Side::Sub => CMD_FB.decode(frame).ok().map(|f| Update::Frequency(f.to_u32())),
+        b"RI" => CMD_RI.decode(frame).ok().map(Update::Status),
+        b"MD" if frame.get(2) == Some(&side_char) => CMD_MD.decode(frame).ok().map(Update::Mode),
+        b"MC" => CMD_MC.decode(frame).ok().filter(|mc| mc.side == side).map(|mc| Update::Channel(mc.channel)),
+        b"RM" => CMD_RM.decode(meter, frame).ok().map(Update::SMeter),
+        _ => None,
+    }
+}
+
+/// Bytes from the port in, whole frames out.
+#[derive(Debug, Default)]
+pub struct Frames {
+    pending: Vec<u8>,
+}
+
+impl Frames {
+    /// The frames `bytes` completes, each with its ';'.
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<Vec<u8>> {
+        self.pending.extend_from_slice(bytes);
+        let Some(end) = self.pending.iter().rposition(|b| *b == b';') else {
+            if self.pending.len() > MAX_PENDING {
+                self.pending.clear();
+            }
+            return Vec::new();
+        };
+        let complete: Vec<u8> = self.pending.drain(..=end).collect();
+        complete.split_inclusive(|b| *b == b';').map(<[u8]>::to_vec).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frames() {
+        let mut frames = Frames::default();
+        assert!(frames.push(b"FA0140").is_empty());
+        assert_eq!(frames.push(b"74000;RI00"), [b"FA014074000;".to_vec()]);
+        assert_eq!(frames.push(b"00000;MD01;"), [b"RI0000000;".to_vec(), b"MD01;".to_vec()]);
+        // Noise with no end is dropped rather than kept forever.
+        assert!(frames.push(&[b'x'; MAX_PENDING + 1]).is_empty());
+        assert_eq!(frames.push(b"FA;"), [b"FA;".to_vec()]);
+    }
+
+    #[test]
+    fn test_update() {
+        assert_eq!(update(Side::Main, b"FA014074000;"), Some(Update::Frequency(14_074_000)));
+        assert_eq!(update(Side::Sub, b"FA014074000;"), None);
+        assert_eq!(update(Side::Sub, b"FB145500000;"), Some(Update::Frequency(145_500_000)));
+        assert_eq!(update(Side::Main, b"MD02;"), Some(Update::Mode(Mode::Usb)));
+        assert_eq!(update(Side::Sub, b"MD02;"), None);
+        assert!(matches!(update(Side::Main, b"RI0001000;"), Some(Update::Status(flags)) if flags.contains(StatusFlags::TX)));
+        assert!(matches!(update(Side::Main, b"MC000012;"), Some(Update::Channel(ch)) if ch.to_string() == Ok("00012".to_string())));
+        assert_eq!(update(Side::Sub, b"MC000012;"), None);
+        assert_eq!(update(Side::Main, b"?;"), None);
+        assert_eq!(update(Side::Main, b""), None);
+    }
+}
@@ -0,0 +1,165 @@
+// Memory reorganisation for --plan-moves: the steps that turn the channels
+// of a radio dump into a desired layout, with as few writes as possible.
+//
+// A layout channel already in its slot costs nothing. One whose settings
+// sit in another slot of the dump is a move: it's written to its new slot
+// and the old one is freed, unless the layout puts something else there.
+// One that differs from its slot only by the tag gets just the tag
+// written (MT) instead of the whole channel. Anything else is a full
+// write. Slots the layout leaves empty need erasing, which CAT can't do,
+// so those are listed for deleting on the radio.
+//
+// Every write comes from the layout, not from another slot on the radio,
+// so the steps can run in any order and no spare slot is needed to swap
+// two channels.
+
+use core::fmt;
+
+use crate::memfile::CsvRecord;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    /// The channel in `from` goes to `to`.
+    Move { from: String, to: String },
+    /// Only the tag of `channel` changes.
+    Retag { channel: String, tag: String },
+    /// `channel` is new, or changed beyond its tag.
+    Write { channel: String },
+    /// `channel` isn't in the layout.
+    Erase { channel: String },
+}
+
+impl Step {
+    /// The slot the step changes.
+    pub fn channel(&self) -> &str {
+        match self {
+            Step::Move { to: channel, .. } | Step::Retag { channel, .. } | Step::Write { channel } | Step::Erase { channel } => channel,
+        }
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Step::Move { from, to } => write!(f, "{}: move from {}", to, from),
+            Step::Retag { channel, tag } => write!(f, "{}: retag '{}'", channel, tag),
+            Step::Write { channel } => write!(f, "{}: write", channel),
+            Step::Erase { channel } => write!(f, "{}: erase", channel),
+        }
+    }
+}
+
+fn tag(r: &CsvRecord) -> &str {
+    r.tag.as_deref().unwrap_or("").trim_end()
+}
+
+fn contents(r: &CsvRecord) -> CsvRecord {
+    r.contents()
+}
+
+fn untagged(r: &CsvRecord) -> CsvRecord {
+    CsvRecord { tag: None, ..contents(r) }
+}
+
+/// The steps from `current` to `layout`, in layout order, erases last.
+/// Both are normalised records (see normalize_record).
+pub fn plan(current: &[CsvRecord], layout: &[CsvRecord]) -> Vec<Step> {
+    let in_slot = |channel: &str| current.iter().find(|r| r.channel == channel);
+    let in_layout = |channel: &str| layout.iter().any(|r| r.channel == channel);
+    let mut sources: Vec<&str> = Vec::new();
+    let mut steps = Vec::new();
+    for want in layout {
+        let have = in_slot(&want.channel);
+        if have.is_some_and(|r| contents(r) == contents(want)) {
+            continue;
+        }
+        if let Some(have) = have
+            && untagged(have) == untagged(want)
+        {
+            steps.push(Step::Retag { channel: want.channel.clone(), tag: tag(want).to_string() });
+            continue;
+        }
+        // A slot the layout empties is the better source: that's a move,
+        // where the other would be a copy.
+        let source = current
+            .iter()
+            .filter(|r| r.channel != want.channel && !sources.contains(&r.channel.as_str()))
+            .filter(|r| contents(r) == contents(want))
+            .min_by_key(|r| in_layout(&r.channel));
+        match source {
+            Some(from) => {
+                sources.push(&from.channel);
+                steps.push(Step::Move { from: from.channel.clone(), to: want.channel.clone() });
+            }
+            None => steps.push(Step::Write { channel: want.channel.clone() }),
+        }
+    }
+    for r in current.iter().filter(|r| !in_layout(&r.channel)) {
+        steps.push(Step::Erase { channel: r.channel.clone() });
+    }
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_unchanged() {
+        let current = [CsvRecord::test_channel("00001", 145_500_000, "CALL"), CsvRecord::test_channel("00002", 145_550_000, "")];
+        let mut layout = current.to_vec();
+        layout[0].group = Some("VHF".to_string());
+        layout[1].tag = None;
+        assert_eq!(plan(&current, &layout), []);
+    }
+
+    #[test]
+    fn test_plan_moves_retags_and_erases() {
+        let current = [
+            CsvRecord::test_channel("00001", 145_500_000, "CALL"),
+            CsvRecord::test_channel("00002", 145_550_000, "S1"),
+            CsvRecord::test_channel("00003", 433_500_000, "UHF CALL"),
+            CsvRecord::test_channel("00004", 7_074_000, "FT8"),
+        ];
+        let layout = [
+            // Swapped, so both are moves.
+            CsvRecord::test_channel("00001", 145_550_000, "S1"),
+            CsvRecord::test_channel("00002", 145_500_000, "CALL"),
+            // Same channel, new tag.
+            CsvRecord::test_channel("00003", 433_500_000, "UHF SIMPLEX"),
+            // 00004 moves up and is freed; a new channel follows it.
+            CsvRecord::test_channel("00010", 7_074_000, "FT8"),
+            CsvRecord::test_channel("00011", 14_074_000, "FT8 20M"),
+        ];
+        let steps = plan(&current, &layout);
+        assert_eq!(
+            steps,
+            [
+                Step::Move { from: "00002".to_string(), to: "00001".to_string() },
+                Step::Move { from: "00001".to_string(), to: "00002".to_string() },
+                Step::Retag { channel: "00003".to_string(), tag: "UHF SIMPLEX".to_string() },
+                Step::Move { from: "00004".to_string(), to: "00010".to_string() },
+                Step::Write { channel: "00011".to_string() },
+                Step::Erase { channel: "00004".to_string() },
+            ]
+        );
+        assert_eq!(steps[0].to_string(), "00001: move from 00002");
+        assert_eq!(steps[2].to_string(), "00003: retag 'UHF SIMPLEX'");
+        assert_eq!(steps[5].channel(), "00004");
+    }
+
+    #[test]
+    fn test_plan_copies_once() {
+        // Two layout slots want the same channel: one moves, one is written.
+        let current = [CsvRecord::test_channel("00005", 145_500_000, "CALL")];
+        let layout = [CsvRecord::test_channel("00001", 145_500_000, "CALL"), CsvRecord::test_channel("00002", 145_500_000, "CALL")];
+        assert_eq!(
+            plan(&current, &layout),
+            [
+                Step::Move { from: "00005".to_string(), to: "00001".to_string() },
+                Step::Write { channel: "00002".to_string() },
+                Step::Erase { channel: "00005".to_string() },
+            ]
+        );
+    }
+}
@@ -0,0 +1,192 @@
+// MQTT publishing for `--monitor` and `--daemon` (`--mqtt HOST[:PORT]`):
+// every --mqtt-interval seconds the radio's frequency, mode, S-meter and
+// TX state are published, retained, under the --mqtt-topic prefix:
+//
+//   ftx1/frequency  14074000
+//   ftx1/mode       USB
+//   ftx1/smeter     87          (raw needle position, 0-255)
+//   ftx1/tx         0 | 1
+//
+// so a Home Assistant MQTT sensor can show them. Only what a publisher
+// needs of MQTT 3.1.1 is here: CONNECT, QoS 0 PUBLISH and DISCONNECT.
+// A broker that goes away is reconnected on the next publish.
+
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+use crate::ftx1::Mode;
+use crate::transport::host_port;
+
+const MQTT_DEFAULT_PORT: u16 = 1883;
+
+/// One reading of the values published; a field the radio didn't
+/// answer for is left out.
+#[derive(Debug, Default, PartialEq)]
+pub struct Telemetry {
+    pub frequency: Option<u32>,
+    pub mode: Option<Mode>,
+    pub s_meter: Option<u8>,
+    pub tx: Option<bool>,
+}
+
+impl Telemetry {
+    /// (topic suffix, payload) pairs.
+    pub fn messages(&self) -> Vec<(&'static str, String)> {
+        let mut messages = Vec::new();
+        if let Some(hz) = self.frequency { messages.push(("frequency", hz.to_string())); }
+        if let Some(mode) = self.mode { messages.push(("mode", mode.to_string())); }
+        if let Some(s) = self.s_meter { messages.push(("smeter", s.to_string())); }
+        if let Some(tx) = self.tx { messages.push(("tx", u8::from(tx).to_string())); }
+        messages
+    }
+}
+
+pub struct Mqtt {
+    addr: String,
+    prefix: String,
+    keep_alive: u16,
+    stream: Option<TcpStream>,
+}
+
+impl Mqtt {
+    /// Connects right away, so a wrong broker address fails up front.
+    pub fn connect(addr: &str, prefix: &str, interval: Duration) -> io::Result<Self> {
+        // The broker drops clients silent for 1.5x keep-alive; every
+        // publish counts, so twice the interval is enough.
+        let keep_alive = (interval.as_secs() * 2).clamp(10, u64::from(u16::MAX)) as u16;
+        let mut mqtt = Mqtt { addr: host_port(addr, MQTT_DEFAULT_PORT), prefix: prefix.to_string(), keep_alive, stream: None };
+        mqtt.stream = Some(mqtt.open()?);
+        Ok(mqtt)
+    }
+
+    fn open(&self) -> io::Result<TcpStream> {
+        let mut stream = TcpStream::connect(&self.addr)?;
+        stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+        let client_id = format!("ftx1-mm-{}", std::process::id());
+        stream.write_all(&connect_packet(&client_id, self.keep_alive))?;
+        let mut connack = [0u8; 4];
+        stream.read_exact(&mut connack)?;
+        if connack[0] != 0x20 || connack[3] != 0 {
+            return Err(io::Error::other(format!("broker refused the connection (code {})", connack[3])));
+        }
+        Ok(stream)
+    }
+
+    pub fn publish_telemetry(&mut self, telemetry: &Telemetry) -> io::Result<()> {
+        let packets: Vec<u8> = telemetry
+            .messages()
+            .iter()
+            .flat_map(|(topic, payload)| publish_packet(&format!("{}/{}", self.prefix, topic), payload.as_bytes(), true))
+            .collect();
+        if self.stream.is_none() {
+            self.stream = Some(self.open()?);
+        }
+        let result = self.stream.as_mut().map_or(Ok(()), |s| s.write_all(&packets));
+        if result.is_err() {
+            self.stream = None;
+        }
+        result
+    }
+}
+
+impl Drop for Mqtt {
+    fn drop(&mut self) {
+        if let Some(stream) = &mut self.stream {
+            let _ = stream.write_all(&[0xe0, 0x00]);
+        }
+    }
+}
+
+/// MQTT's variable-length "remaining length" field.
+fn remaining_length(mut len: usize) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    loop {
+        let mut byte = (len % 128) as u8;
+        len /= 128;
+        if len > 0 {
+            byte |= 0x80;
+        }
+        bytes.push(byte);
+        if len == 0 {
+            return bytes;
+        }
+    }
+}
+
+fn string_field(s: &str) -> Vec<u8> {
+    let mut field = (s.len() as u16).to_be_bytes().to_vec();
+    field.extend_from_slice(s.as_bytes());
+    field
+}
+
+fn connect_packet(client_id: &str, keep_alive: u16) -> Vec<u8> {
+    let mut body = string_field("MQTT");
+    body.push(4); // protocol level 3.1.1
+    body.push(0x02); // clean session
+    body.extend_from_slice(&keep_alive.to_be_bytes());
+    body.extend(string_field(client_id));
+    let mut packet = vec![0x10];
+    packet.extend(remaining_length(body.len()));
+    packet.extend(body);
+    packet
+}
+
+fn publish_packet(topic: &str, payload: &[u8], retain: bool) -> Vec<u8> {
+    let mut body = string_field(topic);
+    body.extend_from_slice(payload);
+    let mut packet = vec![0x30 | u8::from(retain)];
+    packet.extend(remaining_length(body.len()));
+    packet.extend(body);
+    packet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    #[test]
+    fn test_remaining_length() {
+        assert_eq!(remaining_length(0), [0x00]);
+        assert_eq!(remaining_length(127), [0x7f]);
+        assert_eq!(remaining_length(128), [0x80, 0x01]);
+        assert_eq!(remaining_length(16_383), [0xff, 0x7f]);
+    }
+
+    #[test]
+    fn test_packets() {
+        assert_eq!(publish_packet("a/b", b"1", true), [0x31, 0x06, 0x00, 0x03, b'a', b'/', b'b', b'1']);
+        let connect = connect_packet("id", 60);
+        assert_eq!(&connect[..2], [0x10, 14]);
+        assert_eq!(&connect[2..12], [0x00, 0x04, b'M', b'Q', b'T', b'T', 4, 0x02, 0x00, 60]);
+    }
+
+    #[test]
+    fn test_publish_telemetry() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let broker = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut header = [0u8; 2];
+            stream.read_exact(&mut header).unwrap();
+            let mut connect = vec![0u8; header[1] as usize];
+            stream.read_exact(&mut connect).unwrap();
+            stream.write_all(&[0x20, 0x02, 0x00, 0x00]).unwrap();
+            let mut rest = Vec::new();
+            stream.read_to_end(&mut rest).unwrap();
+            rest
+        });
+
+        let mut mqtt = Mqtt::connect(&addr, "shack/ftx1", Duration::from_secs(5)).unwrap();
+        let telemetry = Telemetry { frequency: Some(7_074_000), tx: Some(false), ..Default::default() };
+        mqtt.publish_telemetry(&telemetry).unwrap();
+        drop(mqtt);
+
+        let mut expected = publish_packet("shack/ftx1/frequency", b"7074000", true);
+        expected.extend(publish_packet("shack/ftx1/tx", b"0", true));
+        expected.extend([0xe0, 0x00]);
+        assert_eq!(broker.join().unwrap(), expected);
+    }
+}
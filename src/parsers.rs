@@ -1,4 +1,19 @@
 /// Small parsing helpers for fixed-width ASCII numeric fields used by the FTX1 protocol.
+pub fn buf2_to_u8(buffer: &[u8]) -> Result<u8, ()> {
+    if buffer.len() != 2 {
+        return Err(());
+    }
+    let mut result: u8 = 0;
+    for (i, item) in buffer.iter().enumerate().take(2) {
+        if let Some(n) = (*item as char).to_digit(10) {
+            result += n as u8 * (10u8.pow(1 - i as u32));
+        } else {
+            return Err(());
+        }
+    }
+    Ok(result)
+}
+
 pub fn buf3_to_u8(buffer: &[u8]) -> Result<u8, ()> {
     if buffer.len() != 3 {
         return Err(());
@@ -90,6 +105,14 @@ pub fn buf5_to_i16(buffer: &[u8]) -> Result<i16, ()> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn buf2_parses_and_length() {
+        assert_eq!(buf2_to_u8(b"00").unwrap(), 0);
+        assert_eq!(buf2_to_u8(b"75").unwrap(), 75);
+        assert!(buf2_to_u8(b"7").is_err());
+        assert!(buf2_to_u8(b"7a").is_err());
+    }
+
     #[test]
     fn buf5_parses_positive_and_negative() {
         assert_eq!(buf5_to_i16(b"+00015").unwrap(), 15);
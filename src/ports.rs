@@ -0,0 +1,162 @@
+// Serial port names and discovery for the platforms the radio's USB
+// bridge shows up on. Windows names ports COM1, COM2, ...; the radio's
+// Silicon Labs CP210x bridge gives it two, of which the "Enhanced COM
+// Port" is CAT. `--list-ports` shows what's attached and which one that is,
+// and `--port auto` picks it.
+//
+// macOS creates every port twice: /dev/tty.* waits for carrier detect
+// (DCD) on open, which the radio never raises, so opening it hangs;
+// /dev/cu.* is the one to use. A tty.* name is swapped for its cu.* twin.
+
+use serialport::{SerialPortInfo, SerialPortType};
+
+/// --port value that picks the CAT port by its USB description.
+pub const AUTO_PORT: &str = "auto";
+
+/// --port when none is given. Mac device names depend on the driver, so
+/// there it's detected.
+pub const DEFAULT_PORT: &str = if cfg!(windows) {
+    "COM3"
+} else if cfg!(target_os = "macos") {
+    AUTO_PORT
+} else {
+    "/dev/ttyUSB0"
+};
+
+/// The /dev/cu.* twin of a macOS /dev/tty.* device; None for other names.
+pub fn callout_twin(name: &str) -> Option<String> {
+    name.strip_prefix("/dev/tty.").map(|rest| format!("/dev/cu.{}", rest))
+}
+
+/// The port `--port auto` opens: the CP210x CAT interface if there is one,
+/// otherwise the only USB-serial port. None when that's ambiguous or
+/// nothing is attached. macOS /dev/tty.* devices are never picked.
+pub fn pick_cat_port(ports: &[SerialPortInfo]) -> Option<String> {
+    let usb: Vec<(&str, Option<&str>)> = ports
+        .iter()
+        .filter(|p| callout_twin(&p.port_name).is_none())
+        .filter_map(|p| match &p.port_type {
+            SerialPortType::UsbPort(usb) => Some((p.port_name.as_str(), usb.product.as_deref())),
+            _ => None,
+        })
+        .collect();
+    let cat: Vec<&str> = usb.iter().filter(|(_, product)| product.is_some_and(is_cat_interface)).map(|(n, _)| *n).collect();
+    match (cat.as_slice(), usb.as_slice()) {
+        ([name], _) | ([], [(name, _)]) => Some(name.to_string()),
+        _ => None,
+    }
+}
+
+/// Accepts the spellings Windows users type for a COM port — "com12",
+/// "COM12:", "\\.\COM12" — and returns the plain "COM12" the serial
+/// driver expects (it adds the \\.\ prefix COM10 and up need). Anything
+/// else comes back unchanged.
+pub fn normalize_port_name(name: &str) -> String {
+    let trimmed = name.trim();
+    let bare = trimmed.strip_prefix(r"\\.\").unwrap_or(trimmed);
+    let bare = bare.strip_suffix(':').unwrap_or(bare);
+    match bare.get(..3) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("COM") && bare.len() > 3 && bare[3..].chars().all(|c| c.is_ascii_digit()) => {
+            format!("COM{}", &bare[3..])
+        }
+        _ => name.to_string(),
+    }
+}
+
+/// The USB-serial chip family for a vendor ID, for the port listing.
+fn chip_name(vid: u16) -> Option<&'static str> {
+    match vid {
+        0x0403 => Some("FTDI"),
+        0x10c4 => Some("Silicon Labs CP210x"),
+        0x067b => Some("Prolific"),
+        0x1a86 => Some("WCH CH340"),
+        _ => None,
+    }
+}
+
+/// True for the CP210x interface that carries CAT (the other one is the
+/// radio's TX/keying port).
+fn is_cat_interface(product: &str) -> bool {
+    product.contains("Enhanced")
+}
+
+/// One line for --list-ports: name, then what's behind it.
+pub fn describe(info: &SerialPortInfo) -> String {
+    match &info.port_type {
+        SerialPortType::UsbPort(usb) => {
+            let mut parts = vec![format!("USB {:04x}:{:04x}", usb.vid, usb.pid)];
+            parts.extend(chip_name(usb.vid).map(str::to_string));
+            parts.extend(usb.manufacturer.clone());
+            parts.extend(usb.product.clone());
+            let cat = usb.product.as_deref().is_some_and(is_cat_interface);
+            format!("{}  {}{}", info.port_name, parts.join(", "), if cat { "  <- CAT" } else { "" })
+        }
+        SerialPortType::BluetoothPort => format!("{}  Bluetooth", info.port_name),
+        SerialPortType::PciPort => format!("{}  PCI", info.port_name),
+        SerialPortType::Unknown => info.port_name.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serialport::UsbPortInfo;
+
+    #[test]
+    fn test_normalize_port_name() {
+        assert_eq!(normalize_port_name("com12"), "COM12");
+        assert_eq!(normalize_port_name("COM3:"), "COM3");
+        assert_eq!(normalize_port_name(r"\\.\COM12"), "COM12");
+        assert_eq!(normalize_port_name("/dev/ttyUSB0"), "/dev/ttyUSB0");
+        assert_eq!(normalize_port_name("COMPORT"), "COMPORT");
+    }
+
+    fn usb_port(name: &str, product: &str) -> SerialPortInfo {
+        let usb = UsbPortInfo {
+            vid: 0x10c4,
+            pid: 0xea70,
+            serial_number: None,
+            manufacturer: None,
+            product: Some(product.to_string()),
+        };
+        SerialPortInfo { port_name: name.to_string(), port_type: SerialPortType::UsbPort(usb) }
+    }
+
+    #[test]
+    fn test_callout_twin() {
+        assert_eq!(callout_twin("/dev/tty.SLAB_USBtoUART").as_deref(), Some("/dev/cu.SLAB_USBtoUART"));
+        assert_eq!(callout_twin("/dev/cu.SLAB_USBtoUART"), None);
+        assert_eq!(callout_twin("/dev/ttyUSB0"), None);
+    }
+
+    #[test]
+    fn test_pick_cat_port() {
+        let ports = [
+            usb_port("/dev/tty.SLAB_USBtoUART", "Enhanced COM Port"),
+            usb_port("/dev/cu.SLAB_USBtoUART", "Enhanced COM Port"),
+            usb_port("/dev/cu.SLAB_USBtoUART3", "Standard COM Port"),
+        ];
+        assert_eq!(pick_cat_port(&ports).as_deref(), Some("/dev/cu.SLAB_USBtoUART"));
+        assert_eq!(pick_cat_port(&ports[2..]).as_deref(), Some("/dev/cu.SLAB_USBtoUART3"));
+        let two_plain = [usb_port("/dev/ttyUSB0", "CP2105"), usb_port("/dev/ttyUSB1", "CP2105")];
+        assert_eq!(pick_cat_port(&two_plain), None);
+        assert_eq!(pick_cat_port(&[]), None);
+    }
+
+    #[test]
+    fn test_describe() {
+        let usb = UsbPortInfo {
+            vid: 0x10c4,
+            pid: 0xea70,
+            serial_number: None,
+            manufacturer: Some("Silicon Labs".to_string()),
+            product: Some("Silicon Labs Dual CP2105 USB to UART Bridge: Enhanced COM Port".to_string()),
+        };
+        let info = SerialPortInfo { port_name: "COM12".to_string(), port_type: SerialPortType::UsbPort(usb) };
+        assert_eq!(
+            describe(&info),
+            "COM12  USB 10c4:ea70, Silicon Labs CP210x, Silicon Labs, \
+             Silicon Labs Dual CP2105 USB to UART Bridge: Enhanced COM Port  <- CAT"
+        );
+    }
+}
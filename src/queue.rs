@@ -0,0 +1,460 @@
+// The CAT command queue. When several parts of the program want the radio
+// at once (the REST daemon serving one client a 999-channel read while
+// another asks for /status), they don't each talk to the port: they hand
+// frames to a Queue, and the one thread that owns the port exchanges them
+// one at a time with serve(). Each exchange is a frame out and its whole
+// reply back before the next frame goes, so replies can't get mixed up,
+// and the next frame is always the most urgent one waiting:
+//
+//   Safety       TX0 and PB00, whoever sends them
+//   Interactive  a request someone is waiting on
+//   Bulk         long reads, which give way between their frames
+//
+// Frames of the same priority go in the order they came. QueuedPort is
+// the handle the other threads use: it's a Transport, so cat_send and
+// everything built on it work through the queue unchanged.
+//
+// Reading or writing a memory channel is a run of frames that only makes
+// sense together (select the channel, then read its tones; set up the VFO,
+// then commit it to a slot), so a handle can hold the port for such a run:
+// while it does, only its own frames and Safety ones are exchanged, and
+// everyone else's wait.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::io::{self, Read, Write};
+use std::ops::{Deref, DerefMut};
+use std::sync::mpsc;
+use std::sync::{Arc, Condvar, Mutex};
+
+use crate::ftx1::{reply_complete, CMD_PB, CMD_TX};
+use crate::transport::Transport;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Bulk,
+    Interactive,
+    Safety,
+}
+
+/// The priority a frame is sent at from a handle of `priority`: unkeying
+/// always jumps the queue.
+pub fn effective(priority: Priority, frames: &[u8]) -> Priority {
+    if frames == CMD_TX.set(false) || frames == CMD_PB.stop() { Priority::Safety } else { priority }
+}
+
+struct Request {
+    priority: Priority,
+    seq: u64,
+    owner: u64,
+    frames: Vec<u8>,
+    reply: mpsc::Sender<io::Result<Vec<u8>>>,
+}
+
+impl PartialEq for Request {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Request {}
+
+impl PartialOrd for Request {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Request {
+    /// Highest priority first, then oldest first (BinaryHeap pops the
+    /// greatest).
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority.cmp(&other.priority).then(other.seq.cmp(&self.seq))
+    }
+}
+
+#[derive(Default)]
+struct State {
+    waiting: BinaryHeap<Request>,
+    seq: u64,
+    handles: u64,
+    /// The handle holding the port, if any.
+    holder: Option<u64>,
+    closed: bool,
+}
+
+impl State {
+    /// Whether `request` may go now: anything does unless a handle holds
+    /// the port, then only its frames and Safety ones.
+    fn may_go(&self, request: &Request) -> bool {
+        self.holder.is_none_or(|h| h == request.owner) || request.priority == Priority::Safety
+    }
+}
+
+#[derive(Default)]
+pub struct Queue {
+    state: Mutex<State>,
+    /// Signalled when there may be a request to exchange.
+    ready: Condvar,
+    /// Signalled when the port is let go.
+    free: Condvar,
+}
+
+impl Queue {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Queue::default())
+    }
+
+    /// Queues `frames`; the reply comes back on the receiver. Fails once
+    /// the queue is closed.
+    fn submit(&self, owner: u64, priority: Priority, frames: Vec<u8>) -> io::Result<mpsc::Receiver<io::Result<Vec<u8>>>> {
+        let (reply, answer) = mpsc::channel();
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.closed {
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, "the CAT queue is closed"));
+        }
+        state.seq += 1;
+        let seq = state.seq;
+        state.waiting.push(Request { priority: effective(priority, &frames), seq, owner, frames, reply });
+        self.ready.notify_one();
+        Ok(answer)
+    }
+
+    /// The next request to exchange; None once the queue is closed and
+    /// nothing is left waiting.
+    fn next(&self) -> Option<Request> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        loop {
+            if state.holder.is_none() {
+                if let Some(request) = state.waiting.pop() {
+                    return Some(request);
+                }
+            } else {
+                let mut waiting = std::mem::take(&mut state.waiting).into_vec();
+                let best = (0..waiting.len()).filter(|&i| state.may_go(&waiting[i])).max_by(|&a, &b| waiting[a].cmp(&waiting[b]));
+                let request = best.map(|i| waiting.swap_remove(i));
+                state.waiting = waiting.into();
+                if request.is_some() {
+                    return request;
+                }
+            }
+            if state.closed && state.waiting.is_empty() {
+                return None;
+            }
+            state = self.ready.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+    }
+
+    fn new_handle(&self) -> u64 {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.handles += 1;
+        state.handles
+    }
+
+    /// Waits until no other handle holds the port, then holds it for
+    /// `owner`.
+    fn hold(&self, owner: u64) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        while state.holder.is_some_and(|h| h != owner) && !state.closed {
+            state = self.free.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+        state.holder = Some(owner);
+    }
+
+    fn release(&self, owner: u64) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.holder == Some(owner) {
+            state.holder = None;
+            self.free.notify_one();
+            self.ready.notify_all();
+        }
+    }
+
+    /// No more requests are taken; serve() returns once the waiting ones
+    /// are done.
+    pub fn close(&self) {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).closed = true;
+        self.ready.notify_all();
+        self.free.notify_all();
+    }
+
+    /// Exchanges queued frames on `port` until the queue is closed.
+    pub fn serve(&self, port: &mut dyn Transport) {
+        while let Some(request) = self.next() {
+            let _ = request.reply.send(exchange(port, &request.frames));
+        }
+    }
+}
+
+/// Sends `frames` (one or more CAT frames) and reads until every query
+/// among them is answered or the port's read times out, as cat_send does.
+pub fn exchange(port: &mut dyn Transport, frames: &[u8]) -> io::Result<Vec<u8>> {
+    port.write_all(frames)?;
+    let count = frames.iter().filter(|b| **b == b';').count();
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 256];
+    loop {
+        match port.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(n) => {
+                buffer.extend_from_slice(&chunk[..n]);
+                let done = if count == 1 {
+                    reply_complete(frames, &buffer)
+                } else {
+                    buffer.iter().filter(|b| **b == b';').count() >= count
+                };
+                if done {
+                    break;
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::TimedOut => break,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(buffer)
+}
+
+/// A handle on the queue that reads and writes like the port. What's
+/// written is sent as one request when the reply is first read.
+pub struct QueuedPort {
+    queue: Arc<Queue>,
+    id: u64,
+    pub priority: Priority,
+    written: Vec<u8>,
+    reply: Vec<u8>,
+}
+
+impl QueuedPort {
+    pub fn new(queue: Arc<Queue>, priority: Priority) -> Self {
+        let id = queue.new_handle();
+        QueuedPort { queue, id, priority, written: Vec::new(), reply: Vec::new() }
+    }
+
+    /// Holds the port for this handle until the guard is dropped, so a
+    /// run of frames goes out without anyone else's in between.
+    pub fn hold(&mut self) -> Held<'_> {
+        self.queue.hold(self.id);
+        Held(self)
+    }
+
+    fn send(&mut self) -> io::Result<()> {
+        let frames = std::mem::take(&mut self.written);
+        let answer = self.queue.submit(self.id, self.priority, frames)?;
+        self.reply = answer.recv().map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "the CAT queue stopped"))??;
+        Ok(())
+    }
+}
+
+impl Write for QueuedPort {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // A reply nobody read is stale once the next frame goes.
+        self.reply.clear();
+        self.written.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Read for QueuedPort {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if !self.written.is_empty() {
+            self.send()?;
+        }
+        if self.reply.is_empty() {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        let n = buf.len().min(self.reply.len());
+        buf[..n].copy_from_slice(&self.reply[..n]);
+        self.reply.drain(..n);
+        Ok(n)
+    }
+}
+
+impl Drop for QueuedPort {
+    /// Frames written and never read after still go out.
+    fn drop(&mut self) {
+        if !self.written.is_empty() {
+            let _ = self.send();
+        }
+    }
+}
+
+/// A QueuedPort holding the port (see QueuedPort::hold).
+pub struct Held<'a>(&'a mut QueuedPort);
+
+impl Deref for Held<'_> {
+    type Target = QueuedPort;
+
+    fn deref(&self) -> &QueuedPort {
+        self.0
+    }
+}
+
+impl DerefMut for Held<'_> {
+    fn deref_mut(&mut self) -> &mut QueuedPort {
+        self.0
+    }
+}
+
+impl Drop for Held<'_> {
+    /// The last frames of the run go out before the port is let go.
+    fn drop(&mut self) {
+        if !self.0.written.is_empty() {
+            let _ = self.0.send();
+        }
+        self.0.queue.release(self.0.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    /// Answers each query frame with the frame's code and "1"; set
+    /// frames (with parameters) get nothing. Keeps the order frames came.
+    #[derive(Default)]
+    struct Echo {
+        sent: Arc<Mutex<Vec<String>>>,
+        pending: VecDeque<u8>,
+    }
+
+    impl Write for Echo {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            for frame in buf.split_inclusive(|b| *b == b';') {
+                self.sent.lock().unwrap().push(String::from_utf8_lossy(frame).into_owned());
+                if frame.len() == 3 {
+                    self.pending.extend(&frame[..2]);
+                    self.pending.extend(b"1;");
+                }
+            }
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Read for Echo {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.pending.is_empty() {
+                return Err(io::ErrorKind::TimedOut.into());
+            }
+            let n = buf.len().min(self.pending.len());
+            for (b, p) in buf.iter_mut().zip(self.pending.drain(..n)) {
+                *b = p;
+            }
+            Ok(n)
+        }
+    }
+
+    fn reply(answer: mpsc::Receiver<io::Result<Vec<u8>>>) -> Vec<u8> {
+        answer.recv().unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_priority_order() {
+        let queue = Queue::new();
+        let bulk1 = queue.submit(0, Priority::Bulk, b"MR;".to_vec()).unwrap();
+        let bulk2 = queue.submit(0, Priority::Bulk, b"MT;".to_vec()).unwrap();
+        let status = queue.submit(0, Priority::Interactive, b"FA;".to_vec()).unwrap();
+        let unkey = queue.submit(0, Priority::Bulk, b"TX0;".to_vec()).unwrap();
+        queue.close();
+        let mut echo = Echo::default();
+        let sent = echo.sent.clone();
+        queue.serve(&mut echo);
+        assert_eq!(*sent.lock().unwrap(), ["TX0;", "FA;", "MR;", "MT;"]);
+        assert_eq!(reply(status), b"FA1;");
+        assert_eq!(reply(bulk1), b"MR1;");
+        assert_eq!(reply(bulk2), b"MT1;");
+        assert_eq!(reply(unkey), b"");
+        assert!(queue.submit(0, Priority::Safety, b"TX0;".to_vec()).is_err());
+    }
+
+    #[test]
+    fn test_queued_port_with_threads() {
+        let queue = Queue::new();
+        let mut echo = Echo::default();
+        let sent = echo.sent.clone();
+        std::thread::scope(|s| {
+            let clients: Vec<_> = ["FA;", "FB;", "RI;"]
+                .into_iter()
+                .map(|frame| {
+                    let queue = queue.clone();
+                    s.spawn(move || {
+                        let mut port = QueuedPort::new(queue, Priority::Interactive);
+                        port.write_all(frame.as_bytes()).unwrap();
+                        let mut reply = Vec::new();
+                        let mut chunk = [0u8; 2];
+                        while let Ok(n) = port.read(&mut chunk) {
+                            reply.extend_from_slice(&chunk[..n]);
+                        }
+                        // A set command goes when the handle is dropped.
+                        port.write_all(b"AI1;").unwrap();
+                        reply
+                    })
+                })
+                .collect();
+            let closer = s.spawn({
+                let queue = queue.clone();
+                move || {
+                    let replies: Vec<Vec<u8>> = clients.into_iter().map(|c| c.join().unwrap()).collect();
+                    queue.close();
+                    replies
+                }
+            });
+            queue.serve(&mut echo);
+            assert_eq!(closer.join().unwrap(), [b"FA1;".to_vec(), b"FB1;".to_vec(), b"RI1;".to_vec()]);
+        });
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 6);
+        assert_eq!(sent.iter().filter(|f| *f == "AI1;").count(), 3);
+    }
+
+    #[test]
+    fn test_hold_keeps_others_out() {
+        let queue = Queue::new();
+        let mut echo = Echo::default();
+        let sent = echo.sent.clone();
+        let exchange = |port: &mut QueuedPort, frame: &str| {
+            port.write_all(frame.as_bytes()).unwrap();
+            let mut reply = [0u8; 16];
+            let n = port.read(&mut reply).unwrap_or(0);
+            reply[..n].to_vec()
+        };
+        std::thread::scope(|s| {
+            let server = s.spawn(|| queue.serve(&mut echo));
+            let mut a = QueuedPort::new(queue.clone(), Priority::Bulk);
+            let mut b = QueuedPort::new(queue.clone(), Priority::Interactive);
+            let mut held = a.hold();
+            assert_eq!(exchange(&mut held, "MC;"), b"MC1;");
+            let other = s.spawn(move || {
+                // Unkeying isn't held up; the rest waits for the run.
+                exchange(&mut b, "TX0;");
+                exchange(&mut b, "FA;")
+            });
+            while sent.lock().unwrap().len() < 2 {
+                std::thread::yield_now();
+            }
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            assert_eq!(exchange(&mut held, "CN;"), b"CN1;");
+            drop(held);
+            assert_eq!(other.join().unwrap(), b"FA1;");
+            queue.close();
+            server.join().unwrap();
+        });
+        assert_eq!(*sent.lock().unwrap(), ["MC;", "TX0;", "CN;", "FA;"]);
+    }
+
+    #[test]
+    fn test_effective() {
+        assert_eq!(effective(Priority::Bulk, b"TX0;"), Priority::Safety);
+        assert_eq!(effective(Priority::Interactive, b"PB00;"), Priority::Safety);
+        assert_eq!(effective(Priority::Bulk, b"TX1;"), Priority::Bulk);
+        assert_eq!(effective(Priority::Interactive, b"FA;"), Priority::Interactive);
+    }
+}
@@ -0,0 +1,139 @@
+// The scan log for `--scan --scan-log FILE`, and the channel activity
+// report `--scan-report FILE` makes from it. Each time a scan halts on a
+// signal, one line is appended once the scan moves on:
+//
+//   Time,FrequencyHz,Channel,SMeterPeak,DwellSeconds
+//   2026-03-01 18:04:05,146520000,00012,142,8.4
+//   2026-03-01 18:06:41,147000000,,96,2.1
+//
+// Channel is the memory channel the halt was on, empty for a VFO scan;
+// SMeterPeak is the highest raw S-meter reading (0-255) during the halt;
+// DwellSeconds is how long the scan stayed. Run after run can go to the
+// same file, so the report covers whatever span of time the log does:
+// per channel (or frequency) how often the scan stopped there, for how
+// long in all and at most, the strongest reading, and when first and last.
+// Logs from before the Channel column (Time,FrequencyHz) still report,
+// without the columns they lack.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+pub const HEADER: &str = "Time,FrequencyHz,Channel,SMeterPeak,DwellSeconds";
+/// The header of logs written before channels were logged.
+pub const OLD_HEADER: &str = "Time,FrequencyHz";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Halt {
+    pub time: String,
+    pub freq: u32,
+    pub channel: Option<String>,
+    pub peak: Option<u8>,
+    pub dwell: Option<Duration>,
+}
+
+impl Halt {
+    /// One log line, without the newline.
+    pub fn row(&self) -> String {
+        format!(
+            "{},{},{},{},{}",
+            self.time,
+            self.freq,
+            self.channel.as_deref().unwrap_or(""),
+            self.peak.map(|p| p.to_string()).unwrap_or_default(),
+            self.dwell.map(|d| format!("{:.1}", d.as_secs_f64())).unwrap_or_default()
+        )
+    }
+
+    /// A log line back; None for the header or a line that isn't one.
+    pub fn parse(line: &str) -> Option<Halt> {
+        let mut fields = line.trim_end().split(',');
+        let time = fields.next()?.to_string();
+        let freq = fields.next()?.parse().ok()?;
+        let mut next = || fields.next().filter(|f| !f.is_empty());
+        let channel = next().map(str::to_string);
+        let peak = next().and_then(|p| p.parse().ok());
+        let dwell = next().and_then(|d| d.parse::<f64>().ok()).filter(|d| d.is_finite() && *d >= 0.0).map(Duration::from_secs_f64);
+        Some(Halt { time, freq, channel, peak, dwell })
+    }
+}
+
+/// One channel's (or, for VFO scans, frequency's) line in the report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Activity {
+    pub channel: Option<String>,
+    pub freq: u32,
+    pub halts: usize,
+    pub total: Duration,
+    pub longest: Duration,
+    pub peak: Option<u8>,
+    pub first: String,
+    pub last: String,
+}
+
+/// Activity per channel, most time spent first.
+pub fn report(halts: &[Halt]) -> Vec<Activity> {
+    let mut by_key: BTreeMap<(Option<&str>, u32), Activity> = BTreeMap::new();
+    for halt in halts {
+        let dwell = halt.dwell.unwrap_or_default();
+        let entry = by_key.entry((halt.channel.as_deref(), halt.freq)).or_insert_with(|| Activity {
+            channel: halt.channel.clone(),
+            freq: halt.freq,
+            halts: 0,
+            total: Duration::ZERO,
+            longest: Duration::ZERO,
+            peak: None,
+            first: halt.time.clone(),
+            last: halt.time.clone(),
+        });
+        entry.halts += 1;
+        entry.total += dwell;
+        entry.longest = entry.longest.max(dwell);
+        entry.peak = entry.peak.max(halt.peak);
+        entry.first = entry.first.clone().min(halt.time.clone());
+        entry.last = entry.last.clone().max(halt.time.clone());
+    }
+    let mut activity: Vec<Activity> = by_key.into_values().collect();
+    activity.sort_by(|a, b| b.total.cmp(&a.total).then(b.halts.cmp(&a.halts)));
+    activity
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn halt(time: &str, freq: u32, channel: Option<&str>, peak: u8, dwell_ms: u64) -> Halt {
+        Halt { time: time.to_string(), freq, channel: channel.map(str::to_string), peak: Some(peak), dwell: Some(Duration::from_millis(dwell_ms)) }
+    }
+
+    #[test]
+    fn test_row_and_parse() {
+        let h = halt("2026-03-01 18:04:05", 146_520_000, Some("00012"), 142, 8_400);
+        assert_eq!(h.row(), "2026-03-01 18:04:05,146520000,00012,142,8.4");
+        assert_eq!(Halt::parse(&h.row()), Some(h));
+        let vfo = Halt { time: "t".to_string(), freq: 147_000_000, channel: None, peak: None, dwell: None };
+        assert_eq!(vfo.row(), "t,147000000,,,");
+        assert_eq!(Halt::parse(&vfo.row()), Some(vfo.clone()));
+        // A line from a log made before the Channel column.
+        assert_eq!(Halt::parse("t,147000000\r\n"), Some(vfo));
+        assert_eq!(Halt::parse(HEADER), None);
+        assert_eq!(Halt::parse(OLD_HEADER), None);
+        assert_eq!(Halt::parse(""), None);
+    }
+
+    #[test]
+    fn test_report() {
+        let halts = [
+            halt("2026-03-01 18:00:00", 146_520_000, Some("00012"), 100, 2_000),
+            halt("2026-03-01 18:05:00", 147_000_000, Some("00020"), 60, 9_000),
+            halt("2026-03-02 07:30:00", 146_520_000, Some("00012"), 140, 3_000),
+            halt("2026-03-02 08:00:00", 146_520_000, None, 90, 1_000),
+        ];
+        let report = report(&halts);
+        assert_eq!(report.len(), 3);
+        assert_eq!(report[0].channel.as_deref(), Some("00020"));
+        let ch12 = &report[1];
+        assert_eq!((ch12.halts, ch12.total, ch12.longest, ch12.peak), (2, Duration::from_secs(5), Duration::from_secs(3), Some(140)));
+        assert_eq!((ch12.first.as_str(), ch12.last.as_str()), ("2026-03-01 18:00:00", "2026-03-02 07:30:00"));
+        assert_eq!(report[2].channel, None);
+    }
+}
@@ -0,0 +1,316 @@
+// Hamlib NET rigctl server (`--serve`): lets WSJT-X, loggers and anything
+// else that speaks to rigctld control the radio through this tool. Each
+// client line is one rigctl command, in short ("f") or long ("\get_freq")
+// form; replies follow rigctld's default (non-extended) protocol: values
+// one per line, or "RPRT n" for set commands and errors.
+//
+// Supported: frequency (f/F), mode (m/M), VFO (v/V), PTT (t/T), split
+// (s/S, i/I), power status, and the \chk_vfo / \dump_state handshake
+// Hamlib's NET rigctl backend sends on connect. PTT is refused unless
+// --allow-tx was given, and --max-tx holds even while a keyed client is
+// silent. Clients are served one at a time.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use log::error;
+
+use crate::ftx1::*;
+use crate::hamlib::{hamlib_mode, MODES};
+
+/// One CAT exchange with the radio: sends a frame, returns the reply.
+pub type Exchange<'a> = dyn FnMut(&[u8]) -> Result<Vec<u8>, ()> + 'a;
+
+// Hamlib error codes (RIG_E*), negated as rigctld reports them.
+const RIG_OK: i32 = 0;
+const RIG_EINVAL: i32 = -1;
+const RIG_EIO: i32 = -6;
+const RIG_EPROTO: i32 = -8;
+const RIG_ERJCTED: i32 = -9;
+const RIG_ENAVAIL: i32 = -11;
+
+// Capabilities reported by \dump_state, in the protocol-0 layout: RX and
+// TX ranges (start end modes low_mW high_mW vfo ant), tuning steps,
+// filters, then limits and feature bit fields. Modes 0x1dff = AM CW USB
+// LSB RTTY FM WFM CWR RTTYR PKTLSB PKTUSB PKTFM; VFO 0x3 = VFOA|VFOB.
+const DUMP_STATE: &str = "0
+2
+1
+30000.000000 174000000.000000 0x1dff -1 -1 0x3 0x0
+400000000.000000 470000000.000000 0x1dff -1 -1 0x3 0x0
+0 0 0 0 0 0 0
+1800000.000000 54000000.000000 0x1dff 500 10000 0x3 0x0
+144000000.000000 148000000.000000 0x1dff 500 10000 0x3 0x0
+430000000.000000 450000000.000000 0x1dff 500 10000 0x3 0x0
+0 0 0 0 0 0 0
+0x1dff 10
+0x1dff 100
+0 0
+0x0c 2400
+0x82 500
+0x1030 3000
+0x21 12000
+0x40 200000
+0 0
+9990
+9990
+0
+0
+0
+0
+0
+0
+0
+0
+0
+0
+0
+";
+
+fn rprt(code: i32) -> String {
+    format!("RPRT {}\n", code)
+}
+
+fn short_form(verb: &str) -> &str {
+    match verb {
+        "\\get_freq" => "f",
+        "\\set_freq" => "F",
+        "\\get_mode" => "m",
+        "\\set_mode" => "M",
+        "\\get_vfo" => "v",
+        "\\set_vfo" => "V",
+        "\\get_ptt" => "t",
+        "\\set_ptt" => "T",
+        "\\get_split_vfo" => "s",
+        "\\set_split_vfo" => "S",
+        "\\get_split_freq" => "i",
+        "\\set_split_freq" => "I",
+        "\\quit" => "q",
+        other => other,
+    }
+}
+
+fn parse_hz(arg: Option<&str>) -> Option<FrequencyHz> {
+    let hz = arg?.parse::<f64>().ok()?;
+    if !(0.0..1e9).contains(&hz) {
+        return None;
+    }
+    FrequencyHz::try_from(hz.round() as u32).ok()
+}
+
+/// State of one client connection.
+pub struct Session {
+    pub allow_tx: bool,
+    /// The client keyed the transmitter and hasn't released it.
+    pub keyed: bool,
+}
+
+impl Session {
+    pub fn new(allow_tx: bool) -> Self {
+        Session { allow_tx, keyed: false }
+    }
+
+    /// Answers one command line; None when the client asked to quit.
+    pub fn handle(&mut self, line: &str, cat: &mut Exchange) -> Option<String> {
+        let mut words = line.split_whitespace();
+        let Some(verb) = words.next() else { return Some(String::new()) };
+        let arg = words.next();
+        let arg2 = words.next();
+
+        // Queries: one CAT read, reply formatted by `show`.
+        let query = |cat: &mut Exchange, frame: Vec<u8>, show: &dyn Fn(&[u8]) -> Result<String, DecodeError>| match cat(&frame) {
+            Ok(rx) => show(&rx).map_or_else(
+                |e| {
+                    error!("{}", e);
+                    rprt(RIG_EPROTO)
+                },
+                |v| v + "\n",
+            ),
+            Err(_) => rprt(RIG_EIO),
+        };
+        // Set commands: the radio doesn't answer, so only I/O can fail.
+        let set = |cat: &mut Exchange, frame: Vec<u8>| match cat(&frame) {
+            Ok(rx) if is_error_reply(&rx) => rprt(RIG_ERJCTED),
+            Ok(_) => rprt(RIG_OK),
+            Err(_) => rprt(RIG_EIO),
+        };
+
+        let reply = match short_form(verb) {
+            "q" | "Q" => return None,
+            "f" => query(cat, CMD_FA.read(), &|rx| CMD_FA.decode(rx).map(|f| f.to_u32().to_string())),
+            "F" => match parse_hz(arg) {
+                Some(f) => set(cat, CMD_FA.set(f)),
+                None => rprt(RIG_EINVAL),
+            },
+            "i" => query(cat, CMD_FB.read(), &|rx| CMD_FB.decode(rx).map(|f| f.to_u32().to_string())),
+            "I" => match parse_hz(arg) {
+                Some(f) => set(cat, CMD_FB.set(f)),
+                None => rprt(RIG_EINVAL),
+            },
+            "m" => query(cat, CMD_MD.read(Side::Main), &|rx| CMD_MD.decode(rx).map(|m| format!("{}\n0", hamlib_mode(m)))),
+            "M" if arg == Some("?") => MODES.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(" ") + "\n",
+            "M" => match MODES.iter().find(|(name, _)| Some(*name) == arg) {
+                Some((_, mode)) => set(cat, CMD_MD.set(Side::Main, *mode)),
+                None => rprt(RIG_EINVAL),
+            },
+            "v" => query(cat, CMD_FR.read(), &|rx| {
+                CMD_FR.decode(rx).map(|side| if side == Side::Main { "VFOA" } else { "VFOB" }.to_string())
+            }),
+            "V" => match arg {
+                Some("VFOA" | "Main") => set(cat, CMD_FR.set(Side::Main)),
+                Some("VFOB" | "Sub") => set(cat, CMD_FR.set(Side::Sub)),
+                Some("currVFO") => rprt(RIG_OK),
+                _ => rprt(RIG_EINVAL),
+            },
+            "t" => query(cat, CMD_TX.read(), &|rx| CMD_TX.decode(rx).map(|on| u8::from(on).to_string())),
+            "T" => match arg {
+                Some("0") => {
+                    self.keyed = false;
+                    set(cat, CMD_TX.set(false))
+                }
+                Some("1" | "2" | "3") if !self.allow_tx => rprt(RIG_ERJCTED),
+                Some("1" | "2" | "3") => {
+                    self.keyed = true;
+                    set(cat, CMD_TX.set(true))
+                }
+                _ => rprt(RIG_EINVAL),
+            },
+            "s" => query(cat, CMD_ST.read(), &|rx| CMD_ST.decode(rx).map(|on| format!("{}\nVFOB", u8::from(on)))),
+            "S" => match arg {
+                Some("0") => set(cat, CMD_ST.set(false)),
+                Some("1") if matches!(arg2, None | Some("VFOB" | "Sub")) => set(cat, CMD_ST.set(true)),
+                _ => rprt(RIG_EINVAL),
+            },
+            "\\get_powerstat" => query(cat, CMD_PS.read(), &|rx| CMD_PS.decode(rx).map(|on| u8::from(on).to_string())),
+            "\\chk_vfo" => "0\n".to_string(),
+            "\\dump_state" => DUMP_STATE.to_string(),
+            _ => rprt(RIG_ENAVAIL),
+        };
+        Some(reply)
+    }
+}
+
+/// Serves clients one after the other until `stop` is set. If a client
+/// leaves with the transmitter keyed, it's unkeyed. `tick` runs every
+/// 200 ms or so, client or not, for the TX watchdog.
+pub fn run(listener: &TcpListener, stop: &AtomicBool, cat: &mut Exchange, tick: &mut dyn FnMut(), allow_tx: bool, quiet: bool) -> io::Result<()> {
+    listener.set_nonblocking(true)?;
+    while !stop.load(Ordering::SeqCst) {
+        tick();
+        let (stream, peer) = match listener.accept() {
+            Ok(client) => client,
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                std::thread::sleep(Duration::from_millis(100));
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        if !quiet { println!("Client connected: {}", peer); }
+        let mut session = Session::new(allow_tx);
+        let result = serve_client(stream, stop, &mut session, cat, tick);
+        if session.keyed {
+            let _ = cat(&CMD_TX.set(false));
+        }
+        if !quiet {
+            match result {
+                Ok(()) => println!("Client disconnected: {}", peer),
+                Err(e) => println!("Client {} dropped: {}", peer, e),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn serve_client(stream: TcpStream, stop: &AtomicBool, session: &mut Session, cat: &mut Exchange, tick: &mut dyn FnMut()) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_millis(200)))?;
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+    let mut line = Vec::new();
+    while !stop.load(Ordering::SeqCst) {
+        tick();
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) => return Ok(()),
+            Ok(_) if line.ends_with(b"\n") => {}
+            Ok(_) => continue,
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => continue,
+            Err(e) => return Err(e),
+        }
+        let text = String::from_utf8_lossy(&line).into_owned();
+        line.clear();
+        match session.handle(&text, cat) {
+            Some(reply) => writer.write_all(reply.as_bytes())?,
+            None => return Ok(()),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A radio that answers queries from a fixed table and records frames.
+    fn radio(sent: &mut Vec<String>) -> impl FnMut(&[u8]) -> Result<Vec<u8>, ()> + '_ {
+        move |frame| {
+            let frame = String::from_utf8_lossy(frame).into_owned();
+            let reply: &[u8] = match frame.as_str() {
+                "FA;" => b"FA014074000;",
+                "MD0;" => b"MD02;",
+                "TX;" => b"TX0;",
+                "ST;" => b"ST1;",
+                _ => b"",
+            };
+            sent.push(frame);
+            Ok(reply.to_vec())
+        }
+    }
+
+    #[test]
+    fn test_queries() {
+        let mut sent = Vec::new();
+        let mut cat = radio(&mut sent);
+        let mut session = Session::new(false);
+        assert_eq!(session.handle("f\n", &mut cat).unwrap(), "14074000\n");
+        assert_eq!(session.handle("\\get_mode\n", &mut cat).unwrap(), "USB\n0\n");
+        assert_eq!(session.handle("t\n", &mut cat).unwrap(), "0\n");
+        assert_eq!(session.handle("s\n", &mut cat).unwrap(), "1\nVFOB\n");
+        assert_eq!(session.handle("\\chk_vfo\n", &mut cat).unwrap(), "0\n");
+        assert!(session.handle("\\dump_state\n", &mut cat).unwrap().starts_with("0\n2\n"));
+        assert_eq!(session.handle("\\get_level AF\n", &mut cat).unwrap(), "RPRT -11\n");
+        assert_eq!(session.handle("q\n", &mut cat), None);
+    }
+
+    #[test]
+    fn test_set_commands() {
+        let mut sent = Vec::new();
+        {
+            let mut cat = radio(&mut sent);
+            let mut session = Session::new(false);
+            assert_eq!(session.handle("F 14074000.000000\n", &mut cat).unwrap(), "RPRT 0\n");
+            assert_eq!(session.handle("M PKTUSB 3000\n", &mut cat).unwrap(), "RPRT 0\n");
+            assert_eq!(session.handle("M XYZ 0\n", &mut cat).unwrap(), "RPRT -1\n");
+            assert_eq!(session.handle("V VFOB\n", &mut cat).unwrap(), "RPRT 0\n");
+        }
+        assert_eq!(sent, ["FA014074000;", "MD0C;", "FR1;"]);
+    }
+
+    #[test]
+    fn test_ptt_needs_allow_tx() {
+        let mut sent = Vec::new();
+        {
+            let mut cat = radio(&mut sent);
+            let mut session = Session::new(false);
+            assert_eq!(session.handle("T 1\n", &mut cat).unwrap(), "RPRT -9\n");
+            assert!(!session.keyed);
+            let mut session = Session::new(true);
+            assert_eq!(session.handle("T 1\n", &mut cat).unwrap(), "RPRT 0\n");
+            assert!(session.keyed);
+            assert_eq!(session.handle("T 0\n", &mut cat).unwrap(), "RPRT 0\n");
+            assert!(!session.keyed);
+        }
+        assert_eq!(sent, ["TX1;", "TX0;"]);
+    }
+}
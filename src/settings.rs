@@ -0,0 +1,165 @@
+// Radio-wide settings that aren't part of any memory channel (CW pitch,
+// break-in, ...). Each entry knows how to query one setting, render the
+// reply as a human-readable value, and turn that value back into a set
+// command, so `--read-settings` / `--write-settings` are just loops over
+// SETTINGS. The backup file is a two-column CSV: Setting, Value.
+//
+// The radio keeps an antenna choice per band (AN), so each HF/50 MHz band
+// has its own entry, which switches the main side to that band (BS) on
+// the way; the callers put VFO-A back afterwards.
+
+use serde::{Deserialize, Serialize};
+
+use crate::ftx1::*;
+
+pub struct Setting {
+    pub name: &'static str,
+    pub read: fn() -> Vec<u8>,
+    pub decode: fn(&[u8]) -> Result<String, DecodeError>,
+    pub encode: fn(&str) -> Result<Vec<u8>, ()>,
+}
+
+macro_rules! keyer_memory_setting {
+    ($slot:literal, $name:literal) => {
+        Setting {
+            name: $name,
+            read: || CMD_KM.read($slot).unwrap(),
+            decode: |rx| CMD_KM.decode(rx).map(|r| r.text),
+            encode: |v| CMD_KM.set($slot, v),
+        }
+    };
+}
+
+macro_rules! antenna_setting {
+    ($band:expr, $name:literal) => {
+        Setting {
+            name: $name,
+            read: || [CMD_BS.set(Side::Main, $band), CMD_AN.read(Side::Main)].concat(),
+            decode: |rx| CMD_AN.decode(rx).map(|a| a.to_string()),
+            encode: |v| Ok([CMD_BS.set(Side::Main, $band), CMD_AN.set(Side::Main, Antenna::try_from(v)?)].concat()),
+        }
+    };
+}
+
+pub const SETTINGS: &[Setting] = &[
+    antenna_setting!(Band::M160, "antenna-160m"),
+    antenna_setting!(Band::M80, "antenna-80m"),
+    antenna_setting!(Band::M60, "antenna-60m"),
+    antenna_setting!(Band::M40, "antenna-40m"),
+    antenna_setting!(Band::M30, "antenna-30m"),
+    antenna_setting!(Band::M20, "antenna-20m"),
+    antenna_setting!(Band::M17, "antenna-17m"),
+    antenna_setting!(Band::M15, "antenna-15m"),
+    antenna_setting!(Band::M12, "antenna-12m"),
+    antenna_setting!(Band::M10, "antenna-10m"),
+    antenna_setting!(Band::M6, "antenna-6m"),
+    Setting {
+        name: "cw-key-pitch-hz",
+        read: || CMD_KP.read(),
+        decode: |rx| CMD_KP.decode(rx).map(|p| p.to_string()),
+        encode: |v| {
+            let hz: u16 = v.trim().parse().map_err(|_| ())?;
+            Ok(CMD_KP.set(KeyPitchHz::try_from(hz)?))
+        },
+    },
+    Setting {
+        name: "cw-break-in",
+        read: || CMD_BI.read(),
+        decode: |rx| CMD_BI.decode(rx).map(on_off),
+        encode: |v| Ok(CMD_BI.set(parse_on_off(v)?)),
+    },
+    Setting {
+        name: "cw-break-in-delay-ms",
+        read: || CMD_SD.read(),
+        decode: |rx| CMD_SD.decode(rx).map(|d| d.to_string()),
+        encode: |v| {
+            let ms: u16 = v.trim().parse().map_err(|_| ())?;
+            Ok(CMD_SD.set(BreakInDelayMs::try_from(ms)?))
+        },
+    },
+    Setting {
+        name: "cw-apf",
+        read: || CMD_CO.read(Side::Main, ApfFunction::OnOff),
+        decode: |rx| CMD_CO.decode_on_off(rx).map(on_off),
+        encode: |v| Ok(CMD_CO.set(Side::Main, ApfParam::OnOff(parse_on_off(v)?))),
+    },
+    Setting {
+        name: "cw-apf-offset-hz",
+        read: || CMD_CO.read(Side::Main, ApfFunction::Offset),
+        decode: |rx| CMD_CO.decode_offset(rx).map(|o| o.to_string()),
+        encode: |v| {
+            let hz: i16 = v.trim().parse().map_err(|_| ())?;
+            Ok(CMD_CO.set(Side::Main, ApfParam::Offset(ApfOffsetHz::try_from(hz)?)))
+        },
+    },
+    keyer_memory_setting!(1, "keyer-memory-1"),
+    keyer_memory_setting!(2, "keyer-memory-2"),
+    keyer_memory_setting!(3, "keyer-memory-3"),
+    keyer_memory_setting!(4, "keyer-memory-4"),
+    keyer_memory_setting!(5, "keyer-memory-5"),
+    Setting {
+        name: "dimmer",
+        read: || CMD_DA.read(),
+        decode: |rx| CMD_DA.decode(rx).map(|d| d.to_string()),
+        encode: |v| Ok(CMD_DA.set(Dimmer::try_from(v)?)),
+    },
+    Setting {
+        name: "fast-step",
+        read: || CMD_FS.read(Side::Main),
+        decode: |rx| CMD_FS.decode(rx).map(on_off),
+        encode: |v| Ok(CMD_FS.set(Side::Main, parse_on_off(v)?)),
+    },
+    Setting {
+        name: "scope-mode",
+        read: || CMD_SS.read(Side::Main, ScopeFunction::Mode),
+        decode: scope_param,
+        encode: |v| Ok(CMD_SS.set(Side::Main, ScopeParam::Mode(ScopeMode::try_from(v)?))),
+    },
+    Setting {
+        name: "scope-span-khz",
+        read: || CMD_SS.read(Side::Main, ScopeFunction::Span),
+        decode: scope_param,
+        encode: |v| {
+            let khz: u16 = v.trim().parse().map_err(|_| ())?;
+            Ok(CMD_SS.set(Side::Main, ScopeParam::Span(ScopeSpan::try_from(khz)?)))
+        },
+    },
+    Setting {
+        name: "scope-ref-level-db",
+        read: || CMD_SS.read(Side::Main, ScopeFunction::RefLevel),
+        decode: scope_param,
+        encode: |v| Ok(CMD_SS.set(Side::Main, ScopeParam::RefLevel(ScopeRefLevel::try_from(v)?))),
+    },
+];
+
+fn scope_param(rx: &[u8]) -> Result<String, DecodeError> {
+    match CMD_SS.decode(rx)? {
+        ScopeParam::RefLevel(l) => Ok(l.to_string()),
+        ScopeParam::Span(s) => Ok(s.to_string()),
+        ScopeParam::Mode(m) => Ok(m.to_string()),
+    }
+}
+
+pub fn on_off(on: bool) -> String {
+    if on { "ON".to_string() } else { "OFF".to_string() }
+}
+
+pub fn parse_on_off(v: &str) -> Result<bool, ()> {
+    match v.trim().to_ascii_uppercase().as_str() {
+        "ON" | "1" => Ok(true),
+        "OFF" | "0" => Ok(false),
+        _ => Err(()),
+    }
+}
+
+pub fn find(name: &str) -> Option<&'static Setting> {
+    SETTINGS.iter().find(|s| s.name == name)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SettingRecord {
+    #[serde(rename = "Setting")]
+    pub name: String,
+    #[serde(rename = "Value")]
+    pub value: String,
+}
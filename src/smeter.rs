@@ -0,0 +1,80 @@
+// S-meter logging for `--smeter-log FILE`: the --side receiver's S-meter
+// read every --interval (default 1s) until Ctrl-C, one CSV line a reading,
+// for propagation studies and A/B antenna comparisons:
+//
+//   Time,SMeterRaw,FrequencyHz
+//   2026-03-01T18:04:05.250Z,87,14074000
+//   2026-03-01T18:04:05.750Z,91,14074000
+//
+// Times are UTC to the millisecond, so logs from different stations line
+// up. The reading is the raw needle position (0-255) RM returns; the radio
+// doesn't say what that is in S-units or dBm, so it isn't converted.
+// FrequencyHz (the side's VFO) is only read and logged with
+// --log-frequency; without it each reading is one CAT command. A reading
+// the radio doesn't answer is left out rather than logged as zero.
+//
+// An existing FILE is appended to, so one log can span several runs, as
+// long as it has the same columns. Each line is flushed as it's written,
+// and Ctrl-C finishes the reading in progress before stopping.
+
+use std::time::Duration;
+
+/// Readings are never closer together than this; CAT answers in a few
+/// milliseconds, but the meter itself doesn't move faster.
+pub const MIN_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The CSV header line, without the newline.
+pub fn header(frequency: bool) -> &'static str {
+    if frequency { "Time,SMeterRaw,FrequencyHz" } else { "Time,SMeterRaw" }
+}
+
+/// One CSV line, without the newline. `frequency` is None when it isn't
+/// logged, Some(None) when it is but the radio didn't answer.
+pub fn row(time: &str, raw: u8, frequency: Option<Option<u32>>) -> String {
+    match frequency {
+        None => format!("{},{}", time, raw),
+        Some(hz) => format!("{},{},{}", time, raw, hz.map(|hz| hz.to_string()).unwrap_or_default()),
+    }
+}
+
+/// `--interval` values: a number and a unit, "500ms", "2s", "1m".
+pub fn parse_interval(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    let split = text.find(|c: char| !c.is_ascii_digit())?;
+    let count: u64 = text[..split].parse().ok()?;
+    let interval = match &text[split..] {
+        "ms" => Duration::from_millis(count),
+        "s" => Duration::from_secs(count),
+        "m" => Duration::from_secs(count.checked_mul(60)?),
+        _ => return None,
+    };
+    Some(interval).filter(|i| *i >= MIN_INTERVAL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_interval() {
+        assert_eq!(parse_interval("500ms"), Some(Duration::from_millis(500)));
+        assert_eq!(parse_interval(" 2s "), Some(Duration::from_secs(2)));
+        assert_eq!(parse_interval("1m"), Some(Duration::from_secs(60)));
+        assert_eq!(parse_interval("50ms"), Some(MIN_INTERVAL));
+        assert_eq!(parse_interval("10ms"), None);
+        assert_eq!(parse_interval("0s"), None);
+        assert_eq!(parse_interval("500"), None);
+        assert_eq!(parse_interval("2h"), None);
+        assert_eq!(parse_interval("ms"), None);
+        assert_eq!(parse_interval("-1s"), None);
+    }
+
+    #[test]
+    fn test_row() {
+        assert_eq!(row("2026-03-01T18:04:05.250Z", 87, None), "2026-03-01T18:04:05.250Z,87");
+        assert_eq!(row("t", 0, Some(Some(14_074_000))), "t,0,14074000");
+        assert_eq!(row("t", 255, Some(None)), "t,255,");
+        assert_eq!(header(false).split(',').count(), row("t", 1, None).split(',').count());
+        assert_eq!(header(true).split(',').count(), row("t", 1, Some(None)).split(',').count());
+    }
+}
@@ -0,0 +1,91 @@
+// Pre-write snapshots for `--undo`. Before --write-radio programs any
+// channel, the channels it's about to overwrite are read back from the
+// radio into a memory file in the snapshot directory
+// (ftx1_snapshots/snapshot_YYYYMMDD_HHMMSS.csv by default). `--undo` writes
+// the newest snapshot back and renames it to *.csv.undone, so a second
+// --undo steps back one write further.
+//
+// Slots that were empty before the write are listed in a comment line
+// below the header. CAT has no command to clear a memory channel, so undo
+// can only report those; they have to be deleted on the radio.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_DIR: &str = "ftx1_snapshots";
+pub const PREFIX: &str = "snapshot";
+
+const EMPTY: &str = "# Empty before the write:";
+
+/// The comment line listing the channels that were empty.
+pub fn empty_line(channels: &[u16]) -> String {
+    let numbers: Vec<String> = channels.iter().map(|ch| format!("{:05}", ch)).collect();
+    format!("{} {}", EMPTY, numbers.join(" "))
+}
+
+/// The channels an `empty_line` in a snapshot lists.
+pub fn empty_channels(text: &str) -> Vec<u16> {
+    text.lines()
+        .filter_map(|l| l.strip_prefix(EMPTY))
+        .flat_map(|rest| rest.split_whitespace().filter_map(|n| n.parse().ok()).collect::<Vec<u16>>())
+        .collect()
+}
+
+/// The newest snapshot in `dir` that hasn't been undone, if any. The
+/// timestamped names sort in the order they were taken.
+pub fn latest(dir: &Path) -> io::Result<Option<PathBuf>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let mut newest: Option<PathBuf> = None;
+    for entry in entries {
+        let path = entry?.path();
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if name.starts_with(PREFIX) && name.ends_with(".csv") && newest.as_ref().is_none_or(|n| path > *n) {
+            newest = Some(path);
+        }
+    }
+    Ok(newest)
+}
+
+/// Marks a snapshot as undone, so `latest` moves on to the one before.
+pub fn mark_undone(path: &Path) -> io::Result<PathBuf> {
+    let mut undone = path.as_os_str().to_owned();
+    undone.push(".undone");
+    let undone = PathBuf::from(undone);
+    fs::rename(path, &undone)?;
+    Ok(undone)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_line() {
+        let line = empty_line(&[5, 120]);
+        assert_eq!(line, "# Empty before the write: 00005 00120");
+        assert_eq!(empty_channels(&format!("# ftx1-mm memory file format 2\nChannel Number\n{}\n", line)), [5, 120]);
+        assert!(empty_channels("Channel Number\n00001,145000000\n").is_empty());
+    }
+
+    #[test]
+    fn test_latest_and_undo() {
+        let dir = std::env::temp_dir().join(format!("ftx1_snapshot_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(latest(&dir).unwrap(), None);
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["snapshot_20260101_120000.csv", "snapshot_20260102_080000.csv", "notes.csv"] {
+            fs::write(dir.join(name), "").unwrap();
+        }
+        let newest = latest(&dir).unwrap().unwrap();
+        assert!(newest.ends_with("snapshot_20260102_080000.csv"));
+        let undone = mark_undone(&newest).unwrap();
+        assert!(undone.ends_with("snapshot_20260102_080000.csv.undone"));
+        assert!(latest(&dir).unwrap().unwrap().ends_with("snapshot_20260101_120000.csv"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,171 @@
+// Per-command CAT timing, collected by cat_send for every frame and
+// printed by `--stats` once the action finishes. Numbers are grouped by
+// the two-letter command code, so a full read shows how the time splits
+// between MR, MT, CN, MZ, ... and whether any of them hit the timeout.
+// "No reply" counts frames that ended on the timeout without a complete
+// reply — expected for set commands, a sign of trouble for queries.
+// "Errors" counts frames the radio refused with "?;". "Retries" counts
+// frames sent again: a query re-asked after a late reply, an ID exchange
+// repeated to get back in step. Reconnects to a radio that went away
+// (see transport::reconnects) are retries too, of no single command, so
+// they only show in the total.
+//
+// The same numbers are served in Prometheus' text format by the daemon's
+// GET /metrics (see prometheus()).
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Default)]
+struct CmdStats {
+    count: u32,
+    no_reply: u32,
+    errors: u32,
+    retries: u32,
+    total: Duration,
+    min: Option<Duration>,
+    max: Duration,
+}
+
+struct Stats {
+    start: Option<Instant>,
+    commands: BTreeMap<String, CmdStats>,
+    bytes_sent: usize,
+    bytes_received: usize,
+}
+
+static STATS: Mutex<Stats> =
+    Mutex::new(Stats { start: None, commands: BTreeMap::new(), bytes_sent: 0, bytes_received: 0 });
+
+/// Records one exchange: the frame sent, how many bytes came back, how
+/// long it took and whether a complete reply arrived.
+pub fn record(sent: &[u8], received: usize, elapsed: Duration, replied: bool) {
+    let Ok(mut stats) = STATS.lock() else { return };
+    stats.start.get_or_insert_with(|| Instant::now().checked_sub(elapsed).unwrap_or_else(Instant::now));
+    stats.bytes_sent += sent.len();
+    stats.bytes_received += received;
+    let code = String::from_utf8_lossy(&sent[..sent.len().min(2)]).into_owned();
+    let cmd = stats.commands.entry(code).or_default();
+    cmd.count += 1;
+    cmd.no_reply += u32::from(!replied);
+    cmd.total += elapsed;
+    cmd.min = Some(cmd.min.map_or(elapsed, |m| m.min(elapsed)));
+    cmd.max = cmd.max.max(elapsed);
+}
+
+/// Counts a frame the radio answered with "?;".
+pub fn record_error(sent: &[u8]) {
+    let Ok(mut stats) = STATS.lock() else { return };
+    let code = String::from_utf8_lossy(&sent[..sent.len().min(2)]).into_owned();
+    stats.commands.entry(code).or_default().errors += 1;
+}
+
+/// Counts a frame sent again because the first try went wrong.
+pub fn record_retry(sent: &[u8]) {
+    let Ok(mut stats) = STATS.lock() else { return };
+    let code = String::from_utf8_lossy(&sent[..sent.len().min(2)]).into_owned();
+    stats.commands.entry(code).or_default().retries += 1;
+}
+
+pub fn print() {
+    let Ok(stats) = STATS.lock() else { return };
+    let Some(start) = stats.start else {
+        println!("No CAT traffic.");
+        return;
+    };
+    let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+    println!(
+        "{:<4} {:>6} {:>9} {:>9} {:>9} {:>9} {:>7} {:>7}",
+        "Cmd", "Count", "Avg ms", "Min ms", "Max ms", "No reply", "Errors", "Retries"
+    );
+    let mut frames = 0;
+    let mut retries = 0;
+    for (code, cmd) in &stats.commands {
+        frames += cmd.count;
+        retries += cmd.retries as usize;
+        println!(
+            "{:<4} {:>6} {:>9.1} {:>9.1} {:>9.1} {:>9} {:>7} {:>7}",
+            code,
+            cmd.count,
+            ms(cmd.total) / f64::from(cmd.count.max(1)),
+            ms(cmd.min.unwrap_or_default()),
+            ms(cmd.max),
+            cmd.no_reply,
+            cmd.errors,
+            cmd.retries
+        );
+    }
+    let reconnects = crate::transport::reconnects();
+    println!("{} retries, {} of them reconnects", retries + reconnects, reconnects);
+    let secs = start.elapsed().as_secs_f64();
+    println!(
+        "{} frames in {:.1} s ({:.1} frames/s), {} bytes sent, {} bytes received ({:.0} B/s)",
+        frames,
+        secs,
+        f64::from(frames) / secs,
+        stats.bytes_sent,
+        stats.bytes_received,
+        (stats.bytes_sent + stats.bytes_received) as f64 / secs
+    );
+}
+
+/// All counters in Prometheus' text exposition format.
+pub fn prometheus() -> String {
+    let Ok(stats) = STATS.lock() else { return String::new() };
+    let mut out = String::new();
+    let mut family = |name: &str, kind: &str, help: &str, value: &dyn Fn(&CmdStats) -> String| {
+        out += &format!("# HELP {} {}\n# TYPE {} {}\n", name, help, name, kind);
+        for (code, cmd) in &stats.commands {
+            out += &format!("{}{{cmd=\"{}\"}} {}\n", name, code, value(cmd));
+        }
+    };
+    family("ftx1_cat_commands_total", "counter", "CAT frames sent.", &|c| c.count.to_string());
+    family("ftx1_cat_no_reply_total", "counter", "Frames that got no complete reply.", &|c| c.no_reply.to_string());
+    family("ftx1_cat_errors_total", "counter", "Frames the radio refused with ?;.", &|c| c.errors.to_string());
+    family("ftx1_cat_retries_total", "counter", "Frames sent again after a late or out-of-step reply.", &|c| c.retries.to_string());
+    // A summary with no quantiles: the total time and the number of
+    // exchanges it covers, so a rate of one over the other is the latency.
+    out += "# HELP ftx1_cat_duration_seconds Time spent waiting on replies.\n# TYPE ftx1_cat_duration_seconds summary\n";
+    for (code, cmd) in &stats.commands {
+        out += &format!("ftx1_cat_duration_seconds_sum{{cmd=\"{}\"}} {}\n", code, cmd.total.as_secs_f64());
+        out += &format!("ftx1_cat_duration_seconds_count{{cmd=\"{}\"}} {}\n", code, cmd.count);
+    }
+    for (name, help, value) in [
+        ("ftx1_cat_bytes_sent_total", "Bytes sent to the radio.", stats.bytes_sent),
+        ("ftx1_cat_bytes_received_total", "Bytes received from the radio.", stats.bytes_received),
+        ("ftx1_reconnects_total", "Times a radio that went away was reconnected.", crate::transport::reconnects()),
+    ] {
+        out += &format!("# HELP {} {}\n# TYPE {} counter\n{} {}\n", name, help, name, name, value);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prometheus() {
+        record(b"ZZ;", 6, Duration::from_millis(20), true);
+        record_error(b"ZZ;");
+        record_retry(b"ZZ;");
+        let text = prometheus();
+        assert!(text.contains("# TYPE ftx1_cat_commands_total counter\n"), "{}", text);
+        assert!(text.contains("ftx1_cat_commands_total{cmd=\"ZZ\"} 1\n"), "{}", text);
+        assert!(text.contains("ftx1_cat_errors_total{cmd=\"ZZ\"} 1\n"), "{}", text);
+        assert!(text.contains("ftx1_cat_retries_total{cmd=\"ZZ\"} 1\n"), "{}", text);
+        assert!(text.contains("# TYPE ftx1_cat_duration_seconds summary\n"), "{}", text);
+        assert!(text.contains("ftx1_cat_duration_seconds_sum{cmd=\"ZZ\"} 0.02\n"), "{}", text);
+        assert!(text.contains("ftx1_cat_duration_seconds_count{cmd=\"ZZ\"} 1\n"), "{}", text);
+        assert!(text.contains("ftx1_cat_bytes_sent_total "), "{}", text);
+    }
+
+    #[test]
+    fn test_retries_per_command() {
+        record(b"YY;", 0, Duration::from_millis(20), false);
+        record_retry(b"YY;");
+        record_retry(b"YY;");
+        assert_eq!(STATS.lock().unwrap().commands["YY"].retries, 2);
+    }
+}
@@ -0,0 +1,130 @@
+// Memory-map statistics for `--summary`: how many of the 999 channels are
+// used, how they spread over bands and modes, which tones they use and
+// which stretch of spectrum they cover. Channels outside every amateur,
+// AIR or MW band count as GEN, like the radio's own band keys.
+//
+// Counts keep the order bands come in on the radio (160m first) and, for
+// modes and tones, most used first; the JSON is the same struct.
+
+use std::cmp::Reverse;
+
+use serde::Serialize;
+
+use crate::filter::band_edges;
+use crate::ftx1::{Band, BANDS};
+
+/// What `summarize` needs to know about one used channel.
+pub struct Entry<'a> {
+    pub freq: u32,
+    pub mode: &'a str,
+    /// The tone in use ("CTCSS_ENC 88.5", "DCS 023"), empty for none.
+    pub tone: &'a str,
+    pub split: bool,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Count {
+    pub name: String,
+    pub channels: usize,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Coverage {
+    pub lowest_hz: u32,
+    pub highest_hz: u32,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Summary {
+    pub used: usize,
+    pub empty: usize,
+    pub split: usize,
+    pub bands: Vec<Count>,
+    pub modes: Vec<Count>,
+    pub tones: Vec<Count>,
+    /// None when no channel is used.
+    pub coverage: Option<Coverage>,
+}
+
+/// The band a frequency falls in; GEN when it's in none of them.
+pub fn band_of(freq: u32) -> Band {
+    BANDS
+        .into_iter()
+        .find(|b| band_edges(*b).is_some_and(|(lo, hi)| (lo..=hi).contains(&freq)))
+        .unwrap_or(Band::Gen)
+}
+
+fn tally(counts: &mut Vec<Count>, name: &str) {
+    match counts.iter_mut().find(|c| c.name == name) {
+        Some(c) => c.channels += 1,
+        None => counts.push(Count { name: name.to_string(), channels: 1 }),
+    }
+}
+
+/// Summarises the used channels of a memory map with `capacity` slots.
+pub fn summarize(entries: &[Entry], capacity: usize) -> Summary {
+    let mut bands: Vec<Count> = Vec::new();
+    let mut modes = Vec::new();
+    let mut tones = Vec::new();
+    for e in entries {
+        tally(&mut bands, &band_of(e.freq).to_string());
+        tally(&mut modes, e.mode.trim());
+        tally(&mut tones, if e.tone.is_empty() { "none" } else { e.tone });
+    }
+    let band_order = |c: &Count| BANDS.iter().position(|b| b.to_string() == c.name);
+    bands.sort_by_key(band_order);
+    // Stable sorts: ties stay in first-seen order.
+    modes.sort_by_key(|c| Reverse(c.channels));
+    tones.sort_by_key(|c| Reverse(c.channels));
+    let coverage = entries.iter().map(|e| e.freq).min().zip(entries.iter().map(|e| e.freq).max());
+    Summary {
+        used: entries.len(),
+        empty: capacity.saturating_sub(entries.len()),
+        split: entries.iter().filter(|e| e.split).count(),
+        bands,
+        modes,
+        tones,
+        coverage: coverage.map(|(lowest_hz, highest_hz)| Coverage { lowest_hz, highest_hz }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry<'a>(freq: u32, mode: &'a str, tone: &'a str) -> Entry<'a> {
+        Entry { freq, mode, tone, split: false }
+    }
+
+    #[test]
+    fn test_band_of() {
+        assert_eq!(band_of(7_074_000), Band::M40);
+        assert_eq!(band_of(145_500_000), Band::M2);
+        assert_eq!(band_of(118_100_000), Band::Air);
+        assert_eq!(band_of(162_550_000), Band::Gen);
+    }
+
+    #[test]
+    fn test_summarize() {
+        let entries = [
+            entry(433_500_000, "FM", ""),
+            entry(145_500_000, "FM", "CTCSS_ENC 88.5"),
+            entry(145_600_000, "FM", "CTCSS_ENC 88.5"),
+            entry(7_074_000, "USB", ""),
+            Entry { split: true, ..entry(162_550_000, "FM-N", "") },
+        ];
+        let s = summarize(&entries, 999);
+        assert_eq!((s.used, s.empty, s.split), (5, 994, 1));
+        let names = |c: &[Count]| c.iter().map(|c| (c.name.clone(), c.channels)).collect::<Vec<_>>();
+        assert_eq!(
+            names(&s.bands),
+            [("40m".into(), 1), ("GEN".into(), 1), ("2m".into(), 2), ("70cm".into(), 1)]
+        );
+        assert_eq!(names(&s.modes), [("FM".into(), 3), ("USB".into(), 1), ("FM-N".into(), 1)]);
+        assert_eq!(names(&s.tones), [("none".into(), 3), ("CTCSS_ENC 88.5".into(), 2)]);
+        assert_eq!(s.coverage, Some(Coverage { lowest_hz: 7_074_000, highest_hz: 433_500_000 }));
+
+        let empty = summarize(&[], 999);
+        assert_eq!((empty.used, empty.empty, empty.coverage), (0, 999, None));
+    }
+}
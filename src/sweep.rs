@@ -0,0 +1,147 @@
+// SWR sweeps for `--swr-sweep LO-HI`: the transmitter is keyed briefly at
+// low power on each --sweep-step across the segment (MHz, "14.0-14.3")
+// and the SWR meter (RM6) read while it's keyed, to see where an antenna
+// is resonant without an analyzer. The result goes to a CSV file,
+//
+//   FrequencyHz,SWRRaw,SWR
+//   14000000,52,1.56
+//   14050000,44,1.46
+//
+// ready for a spreadsheet or gnuplot, and to the terminal as a bar chart.
+// SWR is the meter needle converted with swr_from_raw; past 3:1 the
+// meter's scale is too compressed for the figure to be more than a hint.
+//
+// Keying needs --allow-tx. Before anything is keyed every step is checked
+// to be inside the region's TX coverage, the power is set to --sweep-power
+// (at most MAX_WATTS) and read back, and the radio must report no fault.
+// The carrier is FM, which needs no audio. Frequency, mode and power are
+// put back afterwards, also on Ctrl-C or a fault.
+
+use crate::ftx1::swr_from_raw;
+
+/// Default and highest --sweep-power: enough for the meter, little enough
+/// not to matter into a bad antenna.
+pub const DEFAULT_WATTS: u8 = 5;
+pub const MAX_WATTS: u8 = 10;
+/// More steps than this is a mistyped --sweep-step, not a sweep.
+pub const MAX_STEPS: usize = 200;
+
+pub const CSV_HEADER: &str = "FrequencyHz,SWRRaw,SWR";
+
+/// A range like "14.0-14.3", in MHz, as (low, high) Hz.
+pub fn parse_range(text: &str) -> Option<(u32, u32)> {
+    let (lo, hi) = text.split_once('-')?;
+    let hz = |mhz: &str| mhz.trim().parse::<f64>().ok().filter(|f| f.is_finite() && *f > 0.0 && *f < 1000.0).map(|f| (f * 1e6).round() as u32);
+    let (lo, hi) = (hz(lo)?, hz(hi)?);
+    (lo < hi).then_some((lo, hi))
+}
+
+/// How many frequencies steps(lo, hi, step) gives, without building them;
+/// `step` must not be 0.
+pub fn step_count(lo: u32, hi: u32, step: u32) -> u32 {
+    (hi - lo).div_ceil(step) + 1
+}
+
+/// The frequencies to key on: every `step` Hz from `lo`, and `hi` itself.
+pub fn steps(lo: u32, hi: u32, step: u32) -> Vec<u32> {
+    let mut freqs: Vec<u32> = (lo..hi).step_by(step.max(1) as usize).collect();
+    freqs.push(hi);
+    freqs
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub freq: u32,
+    pub raw: u8,
+}
+
+impl Point {
+    pub fn swr(&self) -> f32 {
+        swr_from_raw(self.raw)
+    }
+
+    /// One CSV line, without the newline.
+    pub fn row(&self) -> String {
+        let swr = self.swr();
+        if swr.is_finite() { format!("{},{},{:.2}", self.freq, self.raw, swr) } else { format!("{},{},", self.freq, self.raw) }
+    }
+
+    /// A terminal line: frequency, SWR and a bar, 1:1 at the left edge
+    /// and 3:1 at the right.
+    pub fn chart_line(&self) -> String {
+        let swr = self.swr();
+        let width = ((swr.min(3.0) - 1.0) * 20.0).round() as usize;
+        let figure = if swr > 3.0 { ">3".to_string() } else { format!("{:.2}", swr) };
+        format!("{:>10.4} MHz  {:>5}  {}", f64::from(self.freq) / 1e6, figure, "#".repeat(width.max(1)))
+    }
+}
+
+/// The point with the lowest SWR, the first of equals.
+pub fn best(points: &[Point]) -> Option<Point> {
+    points.iter().copied().reduce(|best, p| if p.raw < best.raw { p } else { best })
+}
+
+/// The widest run of neighbouring points at or under `limit` (e.g. 2.0),
+/// as (first, last) frequency.
+pub fn bandwidth(points: &[Point], limit: f32) -> Option<(u32, u32)> {
+    let mut widest: Option<(u32, u32)> = None;
+    let mut start: Option<u32> = None;
+    for (i, p) in points.iter().enumerate() {
+        if p.swr() <= limit {
+            let first = *start.get_or_insert(p.freq);
+            let last_in_run = points.get(i + 1).is_none_or(|n| n.swr() > limit);
+            if last_in_run && widest.is_none_or(|(lo, hi)| p.freq - first > hi - lo) {
+                widest = Some((first, p.freq));
+            }
+        } else {
+            start = None;
+        }
+    }
+    widest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("14.0-14.35"), Some((14_000_000, 14_350_000)));
+        assert_eq!(parse_range(" 7 - 7.2 "), Some((7_000_000, 7_200_000)));
+        assert_eq!(parse_range("14.35-14.0"), None);
+        assert_eq!(parse_range("14.0"), None);
+        assert_eq!(parse_range("a-b"), None);
+        assert_eq!(parse_range("0-1"), None);
+    }
+
+    #[test]
+    fn test_steps() {
+        assert_eq!(steps(14_000_000, 14_100_000, 50_000), [14_000_000, 14_050_000, 14_100_000]);
+        assert_eq!(steps(14_000_000, 14_120_000, 50_000), [14_000_000, 14_050_000, 14_100_000, 14_120_000]);
+        assert_eq!(steps(14_000_000, 14_000_100, 1_000), [14_000_000, 14_000_100]);
+        for (lo, hi, step) in [(14_000_000, 14_100_000, 50_000), (14_000_000, 14_120_000, 50_000), (1_000_000, 1_000_010, 3)] {
+            assert_eq!(step_count(lo, hi, step) as usize, steps(lo, hi, step).len());
+        }
+    }
+
+    #[test]
+    fn test_point() {
+        assert_eq!(Point { freq: 14_000_000, raw: 48 }.row(), "14000000,48,1.50");
+        assert_eq!(Point { freq: 14_000_000, raw: 255 }.row(), "14000000,255,");
+        assert_eq!(Point { freq: 14_000_000, raw: 0 }.chart_line(), "   14.0000 MHz   1.00  #");
+        assert_eq!(Point { freq: 14_000_000, raw: 80 }.chart_line(), "   14.0000 MHz   2.00  ####################");
+        assert!(Point { freq: 14_000_000, raw: 200 }.chart_line().contains("   >3  "));
+    }
+
+    #[test]
+    fn test_best_and_bandwidth() {
+        let points: Vec<Point> = [(7_000_000, 100), (7_050_000, 60), (7_100_000, 20), (7_150_000, 70), (7_200_000, 90), (7_250_000, 10)]
+            .into_iter()
+            .map(|(freq, raw)| Point { freq, raw })
+            .collect();
+        assert_eq!(best(&points), Some(Point { freq: 7_250_000, raw: 10 }));
+        assert_eq!(bandwidth(&points, 2.0), Some((7_050_000, 7_150_000)));
+        assert_eq!(bandwidth(&points, 1.0), None);
+        assert_eq!(best(&[]), None);
+    }
+}
@@ -0,0 +1,320 @@
+// Two-way sync for --sync: a memory file (--file) and the radio, both
+// edited since they last matched, brought back together. The state file
+// next to the memory file (<file>.sync) holds the numbered channels as the
+// radio had them at the end of the last sync; comparing each side with it
+// tells which side changed a channel:
+//
+//   radio == file                 nothing to do
+//   radio == state, file changed  the file's edit goes to the radio
+//   file == state, radio changed  the radio's edit goes into the file
+//   both changed, differently     a conflict, for the user to settle
+//
+// A channel deleted from the file can't be cleared over CAT, so it's
+// reported for deleting on the radio; until it is, the state keeps it and
+// the next sync reports it again. A conflict left unsettled keeps its old
+// state, so it comes up again next time instead of being taken as synced.
+// Without a state file (the first sync) every difference is a conflict,
+// except channels only one side has, which are copied to the other.
+//
+// Only the numbered channels 1-999 are synced; the file's other rows are
+// kept as they are. Channels compare by what the radio stores (see
+// same_contents), so a group, tag padding or "100" for "100.0" alone isn't
+// an edit. A channel written to the radio goes into the state as it reads
+// back, which is how it will compare next time.
+
+use std::collections::BTreeMap;
+
+use crate::memfile::{same_contents, CsvRecord, CHANNELS};
+
+pub const SUFFIX: &str = ".sync";
+
+/// The state file kept for memory file `file`.
+pub fn state_file(file: &str) -> String {
+    format!("{}{}", file, SUFFIX)
+}
+
+/// Which side wins a conflict (--prefer).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Side {
+    Radio,
+    File,
+}
+
+impl TryFrom<&str> for Side {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_ascii_lowercase().as_str() {
+            "radio" | "r" => Ok(Side::Radio),
+            "file" | "f" => Ok(Side::File),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    /// Edited in the file: write it to the radio.
+    ToRadio(CsvRecord),
+    /// Deleted from the file; the radio keeps it until it's deleted there.
+    EraseOnRadio,
+    /// Edited on the radio: take it into the file.
+    ToFile(CsvRecord),
+    /// Deleted on the radio: drop it from the file.
+    DropFromFile,
+    /// Edited on both sides since the last sync.
+    Conflict { radio: Option<CsvRecord>, file: Option<CsvRecord> },
+}
+
+impl Change {
+    /// A conflict settled in favour of `side`; other changes are returned
+    /// as they are.
+    pub fn resolve(self, side: Side) -> Change {
+        match (self, side) {
+            (Change::Conflict { radio: Some(r), .. }, Side::Radio) => Change::ToFile(r),
+            (Change::Conflict { radio: None, .. }, Side::Radio) => Change::DropFromFile,
+            (Change::Conflict { file: Some(f), .. }, Side::File) => Change::ToRadio(f),
+            (Change::Conflict { file: None, .. }, Side::File) => Change::EraseOnRadio,
+            (change, _) => change,
+        }
+    }
+}
+
+/// One side of a conflict, for asking about it.
+pub fn describe(record: Option<&CsvRecord>) -> String {
+    match record {
+        Some(r) => format!("{} Hz {} '{}'", r.freq, r.mode, r.tag.as_deref().unwrap_or("").trim_end()),
+        None => "(empty)".to_string(),
+    }
+}
+
+/// The numbered channels among `records`, by channel.
+fn numbered(records: &[CsvRecord]) -> BTreeMap<&str, &CsvRecord> {
+    records
+        .iter()
+        .filter(|r| r.channel.parse::<u16>().is_ok_and(|n| (1..=CHANNELS).contains(&n)))
+        .map(|r| (r.channel.as_str(), r))
+        .collect()
+}
+
+fn same(a: Option<&&CsvRecord>, b: Option<&&CsvRecord>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => same_contents(a, b),
+        (a, b) => a.is_none() && b.is_none(),
+    }
+}
+
+/// What each channel needs, in channel order. `state` is None before the
+/// first sync. All three are normalised records (see normalize_record).
+pub fn reconcile(state: Option<&[CsvRecord]>, radio: &[CsvRecord], file: &[CsvRecord]) -> Vec<(String, Change)> {
+    let base = numbered(state.unwrap_or_default());
+    let radio = numbered(radio);
+    let file = numbered(file);
+    let mut channels: Vec<&str> = radio.keys().chain(file.keys()).copied().collect();
+    channels.sort();
+    channels.dedup();
+    let mut changes = Vec::new();
+    for channel in channels {
+        let (b, r, f) = (base.get(channel), radio.get(channel), file.get(channel));
+        if same(r, f) {
+            continue;
+        }
+        // Before the first sync a channel only one side has is new on that
+        // side, but one both have could have been edited on either.
+        let first_sync_edit = state.is_none() && r.is_some() && f.is_some();
+        let change = if !first_sync_edit && same(b, r) {
+            match f {
+                Some(f) => Change::ToRadio((*f).clone()),
+                None => Change::EraseOnRadio,
+            }
+        } else if !first_sync_edit && same(b, f) {
+            match r {
+                Some(r) => Change::ToFile((*r).clone()),
+                None => Change::DropFromFile,
+            }
+        } else {
+            Change::Conflict { radio: r.map(|r| (*r).clone()), file: f.map(|f| (*f).clone()) }
+        };
+        changes.push((channel.to_string(), change));
+    }
+    changes
+}
+
+/// The memory file after `changes`: radio edits in, channels deleted on
+/// the radio out, everything else as it was. A group the file gave a
+/// channel stays with it.
+pub fn merged_file(file: &[CsvRecord], changes: &[(String, Change)]) -> Vec<CsvRecord> {
+    let mut merged = file.to_vec();
+    for (channel, change) in changes {
+        let at = merged.iter().position(|r| &r.channel == channel);
+        match (change, at) {
+            (Change::ToFile(r), Some(i)) => {
+                let group = merged[i].group.take();
+                merged[i] = CsvRecord { group, ..r.clone() };
+            }
+            (Change::ToFile(r), None) => {
+                let i = merged.iter().position(|m| m.channel.as_str() > channel.as_str()).unwrap_or(merged.len());
+                merged.insert(i, r.clone());
+            }
+            (Change::DropFromFile, Some(i)) => {
+                merged.remove(i);
+            }
+            _ => {}
+        }
+    }
+    merged
+}
+
+/// The new state: the radio's channels after `changes`, except that an
+/// unsettled conflict keeps its old state. `written` are the channels
+/// written to the radio as they read back afterwards; one that couldn't be
+/// read back goes in as it was written.
+pub fn next_state(state: Option<&[CsvRecord]>, radio: &[CsvRecord], changes: &[(String, Change)], written: &[CsvRecord]) -> Vec<CsvRecord> {
+    let base = numbered(state.unwrap_or_default());
+    let written = numbered(written);
+    let mut next: BTreeMap<String, CsvRecord> = numbered(radio).into_iter().map(|(c, r)| (c.to_string(), r.clone())).collect();
+    for (channel, change) in changes {
+        match change {
+            Change::ToRadio(f) => {
+                let stored = written.get(channel.as_str()).copied().unwrap_or(f);
+                next.insert(channel.clone(), stored.clone());
+            }
+            Change::Conflict { .. } => match base.get(channel.as_str()) {
+                Some(b) => {
+                    next.insert(channel.clone(), (*b).clone());
+                }
+                None => {
+                    next.remove(channel);
+                }
+            },
+            _ => {}
+        }
+    }
+    next.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ftx1::{Shift, SqlType};
+
+    #[test]
+    fn test_side() {
+        assert_eq!(Side::try_from("radio"), Ok(Side::Radio));
+        assert_eq!(Side::try_from("File"), Ok(Side::File));
+        assert_eq!(Side::try_from("f"), Ok(Side::File));
+        assert!(Side::try_from("both").is_err());
+        assert_eq!(state_file("mem.csv"), "mem.csv.sync");
+        assert_eq!(describe(Some(&CsvRecord::test_channel("00001", 145_500_000, "CALL"))), "145500000 Hz FM 'CALL'");
+        assert_eq!(describe(None), "(empty)");
+    }
+
+    #[test]
+    fn test_reconcile() {
+        let state = [
+            CsvRecord::test_channel("00001", 145_500_000, "CALL"),
+            CsvRecord::test_channel("00002", 145_550_000, "S1"),
+            CsvRecord::test_channel("00003", 433_500_000, "UHF"),
+            CsvRecord::test_channel("00004", 7_074_000, "FT8"),
+            CsvRecord::test_channel("00005", 14_074_000, "FT8 20M"),
+            CsvRecord::test_channel("00006", 10_136_000, "FT8 30M"),
+        ];
+        let mut radio = state.to_vec();
+        let mut file = state.to_vec();
+        // Retagged on the radio.
+        radio[0] = CsvRecord::test_channel("00001", 145_500_000, "CALL FM");
+        // Retuned in the file, which also groups it: not an edit by itself.
+        file[1] = CsvRecord::test_channel("00002", 145_575_000, "S1");
+        file[2].group = Some("UHF".to_string());
+        // Edited on both.
+        radio[3] = CsvRecord::test_channel("00004", 7_074_000, "FT8 40M");
+        file[3] = CsvRecord::test_channel("00004", 7_047_500, "FT4 40M");
+        // Deleted on the radio, and from the file.
+        radio.remove(4);
+        file.remove(5);
+        // New in the file, and the same new channel on both.
+        file.push(CsvRecord::test_channel("00007", 18_100_000, "FT8 17M"));
+        radio.push(CsvRecord::test_channel("00008", 21_074_000, "FT8 15M"));
+        file.push(CsvRecord::test_channel("00008", 21_074_000, "FT8 15M"));
+        // Not a numbered channel.
+        file.push(CsvRecord::test_channel("P1L", 145_000_000, ""));
+
+        let changes = reconcile(Some(&state), &radio, &file);
+        assert_eq!(
+            changes,
+            [
+                ("00001".to_string(), Change::ToFile(radio[0].clone())),
+                ("00002".to_string(), Change::ToRadio(file[1].clone())),
+                ("00004".to_string(), Change::Conflict { radio: Some(radio[3].clone()), file: Some(file[3].clone()) }),
+                ("00005".to_string(), Change::DropFromFile),
+                ("00006".to_string(), Change::EraseOnRadio),
+                ("00007".to_string(), Change::ToRadio(file[5].clone())),
+            ]
+        );
+
+        let merged = merged_file(&file, &changes);
+        let channels: Vec<&str> = merged.iter().map(|r| r.channel.as_str()).collect();
+        assert_eq!(channels, ["00001", "00002", "00003", "00004", "00007", "00008", "P1L"]);
+        assert_eq!(merged[0].tag.as_deref(), Some("CALL FM"));
+        assert_eq!(merged[2].group.as_deref(), Some("UHF"));
+        assert_eq!(merged[3], file[3]);
+
+        // 00002 reads back in the radio's spelling; 00007 couldn't be read.
+        let back = CsvRecord { ctcss_tone: "88.5".to_string(), dcs_tone: "23".to_string(), ..file[1].clone() };
+        let next = next_state(Some(&state), &radio, &changes, std::slice::from_ref(&back));
+        let channels: Vec<&str> = next.iter().map(|r| r.channel.as_str()).collect();
+        assert_eq!(channels, ["00001", "00002", "00003", "00004", "00006", "00007", "00008"]);
+        assert_eq!(next[1], back);
+        assert_eq!(next[5], file[5]);
+        // The conflict stays unsynced; the erase stays until it's done.
+        assert_eq!(next[3], state[3]);
+        assert_eq!(next[4], state[5]);
+    }
+
+    #[test]
+    fn test_read_back_spelling_is_no_edit() {
+        // Pushed with "100" CTCSS and ARS; the radio gives back "100.0"
+        // and the direction it picked, and the state holds that.
+        let pushed = CsvRecord {
+            tone: SqlType::CtcssEncDec,
+            ctcss_tone: "100".to_string(),
+            shift: Shift::Ars,
+            ..CsvRecord::test_channel("00001", 145_500_000, "RPT")
+        };
+        let back = CsvRecord { ctcss_tone: "100.0".to_string(), shift: Shift::MinusShift, ..pushed.clone() };
+        let state = next_state(None, std::slice::from_ref(&back), &[("00001".to_string(), Change::ToRadio(pushed.clone()))], std::slice::from_ref(&back));
+        assert_eq!(state, std::slice::from_ref(&back));
+        assert!(reconcile(Some(&state), &[back], &[pushed]).is_empty());
+    }
+
+    #[test]
+    fn test_resolve() {
+        let r = CsvRecord::test_channel("00004", 7_074_000, "FT8");
+        let f = CsvRecord::test_channel("00004", 7_047_500, "FT4");
+        let conflict = |radio: Option<&CsvRecord>, file: Option<&CsvRecord>| Change::Conflict { radio: radio.cloned(), file: file.cloned() };
+        assert_eq!(conflict(Some(&r), Some(&f)).resolve(Side::Radio), Change::ToFile(r.clone()));
+        assert_eq!(conflict(Some(&r), Some(&f)).resolve(Side::File), Change::ToRadio(f.clone()));
+        assert_eq!(conflict(None, Some(&f)).resolve(Side::Radio), Change::DropFromFile);
+        assert_eq!(conflict(Some(&r), None).resolve(Side::File), Change::EraseOnRadio);
+        assert_eq!(Change::DropFromFile.resolve(Side::File), Change::DropFromFile);
+    }
+
+    #[test]
+    fn test_first_sync() {
+        // No state: one-sided channels are copied, differences conflict.
+        let radio = [CsvRecord::test_channel("00001", 145_500_000, "CALL"), CsvRecord::test_channel("00002", 145_550_000, "S1")];
+        let file = [CsvRecord::test_channel("00002", 145_575_000, "S1"), CsvRecord::test_channel("00003", 433_500_000, "UHF")];
+        assert_eq!(
+            reconcile(None, &radio, &file),
+            [
+                ("00001".to_string(), Change::ToFile(radio[0].clone())),
+                ("00002".to_string(), Change::Conflict { radio: Some(radio[1].clone()), file: Some(file[0].clone()) }),
+                ("00003".to_string(), Change::ToRadio(file[1].clone())),
+            ]
+        );
+        let changes = reconcile(None, &radio, &file);
+        let next = next_state(None, &radio, &changes, &[]);
+        let channels: Vec<&str> = next.iter().map(|r| r.channel.as_str()).collect();
+        assert_eq!(channels, ["00001", "00003"]);
+    }
+}
@@ -0,0 +1,163 @@
+// Tag templates for `--auto-tag`: channels whose Memory Tag is empty get
+// one built from the channel's own fields, e.g. "{freq_mhz} {mode}" gives
+// "146.520 FM". Fields:
+//
+//   {freq_mhz}  146.520      {freq_khz}  146520      {freq_hz}  146520000
+//   {mode}      FM           {channel}   00012       {group}    M-VHF
+//   {callsign}  W1AW (from an --import-list row that has one, else empty)
+//
+// A field may carry a format after a colon, as in Rust: "{freq_mhz:.4}"
+// gives 146.5200, "{callsign:.4}" keeps the first four characters, and a
+// width pads, "{channel:>6}" or "{mode:<4}". "{{" and "}}" are literal
+// braces. The result is trimmed and goes through fix_tag like any other
+// tag, so it's cut to the radio's 12 characters.
+
+use crate::groups::MemoryGroup;
+
+pub const DEFAULT_TEMPLATE: &str = "{freq_mhz} {mode}";
+
+/// The fields of one channel a template can use.
+pub struct TagFields<'a> {
+    pub freq: u32,
+    pub mode: &'a str,
+    pub channel: &'a str,
+    pub callsign: &'a str,
+}
+
+/// A field's value: numbers take a precision as decimals, text as a
+/// length limit.
+enum Value {
+    Number(f64, String),
+    Text(String),
+}
+
+impl TagFields<'_> {
+    fn get(&self, name: &str) -> Option<Value> {
+        let freq = f64::from(self.freq);
+        Some(match name {
+            "freq_mhz" => Value::Number(freq / 1e6, format!("{:.3}", freq / 1e6)),
+            "freq_khz" => Value::Number(freq / 1e3, (freq / 1e3).to_string()),
+            "freq_hz" => Value::Number(freq, self.freq.to_string()),
+            "mode" => Value::Text(self.mode.to_string()),
+            "channel" => Value::Text(self.channel.to_string()),
+            "group" => Value::Text(MemoryGroup::of(self.freq).to_string()),
+            "callsign" => Value::Text(self.callsign.to_string()),
+            _ => return None,
+        })
+    }
+}
+
+/// A field's format, "[<|>][width][.precision]".
+#[derive(Debug, Default, PartialEq)]
+struct Spec {
+    align: Option<char>,
+    width: usize,
+    precision: Option<usize>,
+}
+
+impl Spec {
+    fn parse(spec: &str) -> Option<Spec> {
+        let (align, rest) = match spec.chars().next() {
+            Some(c @ ('<' | '>')) => (Some(c), &spec[1..]),
+            _ => (None, spec),
+        };
+        let (width, precision) = match rest.split_once('.') {
+            Some((width, precision)) => (width, Some(precision.parse().ok()?)),
+            None => (rest, None),
+        };
+        let width = if width.is_empty() { 0 } else { width.parse().ok()? };
+        Some(Spec { align, width, precision })
+    }
+
+    fn apply(&self, value: Value) -> String {
+        let (text, right) = match (value, self.precision) {
+            (Value::Number(n, _), Some(p)) => (format!("{:.*}", p, n), true),
+            (Value::Number(_, text), None) => (text, true),
+            (Value::Text(text), Some(p)) => (text.chars().take(p).collect(), false),
+            (Value::Text(text), None) => (text, false),
+        };
+        match self.align.unwrap_or(if right { '>' } else { '<' }) {
+            '>' => format!("{:>width$}", text, width = self.width),
+            _ => format!("{:<width$}", text, width = self.width),
+        }
+    }
+}
+
+/// Fills in `template`; fails on an unknown field or an unmatched brace.
+pub fn render(template: &str, fields: &TagFields) -> Result<String, String> {
+    let mut out = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                out.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                out.push('}');
+            }
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => return Err("unmatched '{'".to_string()),
+                    }
+                }
+                let (field, spec) = name.split_once(':').unwrap_or((&name, ""));
+                let value = fields.get(field.trim()).ok_or_else(|| format!("unknown field '{{{}}}'", field.trim()))?;
+                let spec = Spec::parse(spec).ok_or_else(|| format!("bad format '{}' in '{{{}}}'", spec, name))?;
+                out += &spec.apply(value);
+            }
+            '}' => return Err("unmatched '}'".to_string()),
+            c => out.push(c),
+        }
+    }
+    Ok(out.trim().to_string())
+}
+
+/// Checks a template up front, so a typo fails before any channel is touched.
+pub fn check(template: &str) -> Result<(), String> {
+    render(template, &TagFields { freq: 0, mode: "", channel: "", callsign: "" }).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let fields = TagFields { freq: 146_520_000, mode: "FM", channel: "00012", callsign: "" };
+        assert_eq!(render(DEFAULT_TEMPLATE, &fields).as_deref(), Ok("146.520 FM"));
+        assert_eq!(render("{channel}:{freq_khz}", &fields).as_deref(), Ok("00012:146520"));
+        assert_eq!(render("{group} {{x}}", &fields).as_deref(), Ok("M-VHF {x}"));
+        let fields = TagFields { freq: 7_074_500, mode: "USB", channel: "00001", callsign: "" };
+        assert_eq!(render("{freq_khz}", &fields).as_deref(), Ok("7074.5"));
+    }
+
+    #[test]
+    fn test_formats() {
+        let fields = TagFields { freq: 146_940_000, mode: "FM", channel: "00012", callsign: "EW1ABZ" };
+        assert_eq!(render("{callsign} {freq_mhz:.3}", &fields).as_deref(), Ok("EW1ABZ 146.940"));
+        assert_eq!(render("{freq_mhz:.1}|{freq_khz:.0}", &fields).as_deref(), Ok("146.9|146940"));
+        assert_eq!(render("{callsign:.3}{mode:>4}", &fields).as_deref(), Ok("EW1  FM"));
+        assert_eq!(render("{mode:<4}|{freq_hz:10}", &fields).as_deref(), Ok("FM  | 146940000"));
+        // No callsign: the leading space goes.
+        let fields = TagFields { callsign: "", ..fields };
+        assert_eq!(render("{callsign} {freq_mhz:.3}", &fields).as_deref(), Ok("146.940"));
+        assert_eq!(Spec::parse(">8.2"), Some(Spec { align: Some('>'), width: 8, precision: Some(2) }));
+    }
+
+    #[test]
+    fn test_bad_templates() {
+        assert_eq!(check("{call}"), Err("unknown field '{call}'".to_string()));
+        assert_eq!(check("{freq_mhz:.x}"), Err("bad format '.x' in '{freq_mhz:.x}'".to_string()));
+        assert_eq!(check("{mode:^4}"), Err("bad format '^4' in '{mode:^4}'".to_string()));
+        assert_eq!(check("{callsign} {freq_mhz:.3}"), Ok(()));
+        assert_eq!(check("{freq_mhz"), Err("unmatched '{'".to_string()));
+        assert_eq!(check("a}b"), Err("unmatched '}'".to_string()));
+        assert_eq!(check("{mode} {freq_hz}"), Ok(()));
+    }
+}
@@ -0,0 +1,83 @@
+// Frame trace (`--trace-frames FILE`): cat_send appends every frame it
+// writes and every reply it reads to FILE as a hex dump with direction
+// and time, the thing to attach to a protocol bug report:
+//
+//   2026-01-02T03:04:05.123Z TX 8 bytes
+//     0000  4d 52 30 30 30 30 31 3b                           MR00001;
+//   2026-01-02T03:04:05.170Z RX 0 bytes (timeout)
+//
+// Unlike RUST_LOG=trace this shows the exact bytes, including the ones
+// that aren't printable, and doesn't mix in the rest of the log.
+
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, Write};
+use std::sync::Mutex;
+
+static TRACE: Mutex<Option<File>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Tx,
+    Rx,
+}
+
+/// Starts tracing to `path`, replacing what's there.
+pub fn open(path: &str) -> io::Result<()> {
+    let file = File::create(path)?;
+    if let Ok(mut trace) = TRACE.lock() {
+        *trace = Some(file);
+    }
+    Ok(())
+}
+
+/// Logs one frame; does nothing unless --trace-frames is on.
+pub fn frame(direction: Direction, bytes: &[u8]) {
+    let Ok(mut trace) = TRACE.lock() else { return };
+    let Some(file) = trace.as_mut() else { return };
+    let time = jiff::Timestamp::now().strftime("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
+    // Written unbuffered, so a crash still leaves the frames leading up to it.
+    let _ = file.write_all(format_frame(&time, direction, bytes).as_bytes());
+}
+
+fn format_frame(time: &str, direction: Direction, bytes: &[u8]) -> String {
+    let label = match direction {
+        Direction::Tx => "TX",
+        Direction::Rx => "RX",
+    };
+    let mut text = format!("{} {} {} bytes", time, label, bytes.len());
+    // Only a read ends empty: nothing arrived before the timeout.
+    text += if bytes.is_empty() { " (timeout)\n" } else { "\n" };
+    for (i, line) in bytes.chunks(16).enumerate() {
+        let hex: Vec<String> = line.iter().map(|b| format!("{:02x}", b)).collect();
+        let ascii: String = line.iter().map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' }).collect();
+        let _ = writeln!(text, "  {:04x}  {:<47}  {}", i * 16, hex.join(" "), ascii);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_frame() {
+        let time = "2026-01-02T03:04:05.123Z";
+        assert_eq!(
+            format_frame(time, Direction::Tx, b"FA;"),
+            format!("{} TX 3 bytes\n  0000  46 41 3b{}  FA;\n", time, " ".repeat(39))
+        );
+        assert_eq!(format_frame(time, Direction::Rx, b""), format!("{} RX 0 bytes (timeout)\n", time));
+    }
+
+    #[test]
+    fn test_format_frame_wraps_and_escapes() {
+        let text = format_frame("t", Direction::Rx, b"FA014074000;\r\n\x00MD01;");
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "t RX 20 bytes");
+        assert!(lines[1].starts_with("  0000  46 41 30"), "{}", lines[1]);
+        assert!(lines[1].ends_with("  FA014074000;...M"), "{}", lines[1]);
+        assert!(lines[2].starts_with("  0010  44 30 31 3b"), "{}", lines[2]);
+        assert!(lines[2].ends_with("  D01;"), "{}", lines[2]);
+    }
+}
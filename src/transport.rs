@@ -0,0 +1,679 @@
+// Ways to reach the radio's CAT interface. Everything above cat_send only
+// sees a byte stream (io::Read + io::Write) that behaves like the serial
+// port: frames go out, replies come back, and a read with nothing pending
+// fails with TimedOut. Which transport is used is picked by --port:
+//
+//   /dev/ttyUSB0, COM3      — the serial port itself
+//   rigctld://host[:port]   — a running Hamlib rigctld that owns the port
+//   flrig://host[:port]     — a running flrig that owns the port
+//   tcp://host:port         — raw CAT over TCP (ser2net, ESP32 bridges)
+//   /dev/rfcomm0            — Bluetooth CAT (FTX-1 Field) bound with rfcomm
+//
+// rigctld passes CAT frames through with its raw `w` command, flrig with
+// its `rig.cat_string` XML-RPC method, so the radio can stay shared with
+// a logger that already talks to either.
+
+use std::collections::VecDeque;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use log::warn;
+use serialport::{ClearBuffer, SerialPort};
+
+pub trait Transport: io::Read + io::Write {}
+
+impl<T: io::Read + io::Write + ?Sized> Transport for T {}
+
+/// How long a read waits for the radio before giving up, on the serial
+/// port and on raw TCP alike. Windows USB-serial drivers hold received
+/// bytes for their latency timer (16 ms FTDI, longer for CP210x under
+/// load) before handing them over, so replies there get more headroom.
+pub const READ_TIMEOUT: Duration = Duration::from_millis(if cfg!(windows) { 300 } else { 200 });
+
+pub const TCP_SCHEME: &str = "tcp://";
+
+// Bluetooth adds latency to every exchange, and the radio drops the first
+// bytes while the RFCOMM link is still coming up after the tty is opened.
+const RFCOMM_READ_TIMEOUT: Duration = Duration::from_millis(600);
+const RFCOMM_SETTLE: Duration = Duration::from_millis(1500);
+
+pub const RIGCTLD_SCHEME: &str = "rigctld://";
+const RIGCTLD_DEFAULT_PORT: u16 = 4532;
+// Long enough for rigctld to give up on a set command, which the radio
+// never answers.
+const RIGCTLD_TIMEOUT: Duration = Duration::from_secs(2);
+
+pub const FLRIG_SCHEME: &str = "flrig://";
+const FLRIG_DEFAULT_PORT: u16 = 12345;
+const FLRIG_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Reads and discards whatever is waiting: the late reply to a command
+/// of an aborted run, or frames the radio sends by itself in AI mode.
+/// Stops at the first read that times out, or after `limit` if the radio
+/// keeps talking. Returns what was thrown away.
+pub fn drain(port: &mut dyn Transport, limit: Duration) -> Vec<u8> {
+    let started = Instant::now();
+    let mut stale = Vec::new();
+    let mut chunk = [0u8; 256];
+    while started.elapsed() < limit {
+        match port.read(&mut chunk) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => stale.extend_from_slice(&chunk[..n]),
+        }
+    }
+    stale
+}
+
+/// Whether `stale` holds whole frames (not just noise or "?;"), which
+/// means the radio is sending without being asked.
+pub fn has_frames(stale: &[u8]) -> bool {
+    stale.split_inclusive(|b| *b == b';').any(|f| {
+        // Noise in front of a frame is skipped, as find_reply does.
+        let f = &f[f.iter().position(u8::is_ascii_uppercase).unwrap_or(f.len())..];
+        f.ends_with(b";") && f.len() > 3 && f[..2].iter().all(u8::is_ascii_uppercase)
+    })
+}
+
+/// Splits "host[:port]" into a socket address, filling in `default_port`.
+pub fn host_port(addr: &str, default_port: u16) -> String {
+    match addr.rsplit_once(':') {
+        Some((_, port)) if port.parse::<u16>().is_ok() => addr.to_string(),
+        _ => format!("{}:{}", addr, default_port),
+    }
+}
+
+/// CAT frames straight over a TCP socket, as a serial bridge forwards them.
+pub struct RawTcp(TcpStream);
+
+impl RawTcp {
+    pub fn connect(addr: &str) -> io::Result<Self> {
+        let stream = TcpStream::connect(addr)?;
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+        stream.set_nodelay(true)?;
+        Ok(RawTcp(stream))
+    }
+}
+
+impl io::Write for RawTcp {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl io::Read for RawTcp {
+    // Sockets report an expired read timeout as WouldBlock on Unix; the
+    // serial port's TimedOut is what cat_send waits for.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.0.read(buf) {
+            Ok(0) => Err(io::ErrorKind::UnexpectedEof.into()),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => Err(io::ErrorKind::TimedOut.into()),
+            other => other,
+        }
+    }
+}
+
+/// True for ttys bound to a Bluetooth RFCOMM channel.
+pub fn is_rfcomm(port_name: &str) -> bool {
+    port_name.rsplit('/').next().is_some_and(|name| name.starts_with("rfcomm"))
+}
+
+/// A Bluetooth RFCOMM tty. Opening it brings the link up, so each open is
+/// followed by a settling pause; if the link drops mid-session the tty is
+/// reopened once before the error is passed on.
+pub struct Rfcomm {
+    path: String,
+    speed: u32,
+    port: Box<dyn SerialPort>,
+}
+
+impl Rfcomm {
+    pub fn open(path: &str, speed: u32) -> io::Result<Self> {
+        Ok(Rfcomm { path: path.to_string(), speed, port: Self::open_port(path, speed)? })
+    }
+
+    fn open_port(path: &str, speed: u32) -> io::Result<Box<dyn SerialPort>> {
+        let port = serialport::new(path, speed).timeout(RFCOMM_READ_TIMEOUT).open()?;
+        std::thread::sleep(RFCOMM_SETTLE);
+        port.clear(ClearBuffer::All)?;
+        Ok(port)
+    }
+
+    fn reconnect(&mut self, cause: &io::Error) -> io::Result<()> {
+        warn!("Bluetooth link on {} dropped ({}); reconnecting", self.path, cause);
+        self.port = Self::open_port(&self.path, self.speed)?;
+        Ok(())
+    }
+}
+
+/// Errors that mean the link is gone rather than the radio being slow.
+fn is_link_drop(e: &io::Error) -> bool {
+    !matches!(e.kind(), io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted)
+}
+
+impl io::Write for Rfcomm {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.port.write(buf) {
+            Err(e) if is_link_drop(&e) => {
+                self.reconnect(&e)?;
+                self.port.write(buf)
+            }
+            other => other,
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.port.flush()
+    }
+}
+
+impl io::Read for Rfcomm {
+    // A reply in flight when the link dropped is lost; after reconnecting
+    // the read reports a timeout, as if the radio hadn't answered.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let e = match self.port.read(buf) {
+            Ok(0) => io::ErrorKind::UnexpectedEof.into(),
+            Err(e) if is_link_drop(&e) => e,
+            other => return other,
+        };
+        self.reconnect(&e)?;
+        Err(io::ErrorKind::TimedOut.into())
+    }
+}
+
+/// How long a radio that went away (USB unplugged, Bluetooth or network
+/// link lost) is waited for, and how often its port is tried meanwhile.
+pub const RECONNECT_WINDOW: Duration = Duration::from_secs(30);
+const RECONNECT_RETRY: Duration = Duration::from_secs(1);
+
+static RECONNECTS: AtomicUsize = AtomicUsize::new(0);
+
+/// How many times a lost radio has been reconnected in this process. A
+/// loop over channels compares it before and after a channel to tell
+/// that the channel was cut off and has to be done again.
+pub fn reconnects() -> usize {
+    RECONNECTS.load(Ordering::Relaxed)
+}
+
+/// Opens the port again with the settings it was first opened with and
+/// checks it's still the radio on the other end.
+pub type Reopen = Box<dyn Fn() -> Option<Box<dyn Transport>>>;
+
+/// Any transport that reconnects when the link to the radio drops. The
+/// old port is closed first (a serial port can't be opened twice), then
+/// reopened every RECONNECT_RETRY until the radio answers again or
+/// RECONNECT_WINDOW runs out, when the error is passed on.
+pub struct Reconnecting {
+    port: Option<Box<dyn Transport>>,
+    reopen: Reopen,
+    window: Duration,
+    retry: Duration,
+}
+
+impl Reconnecting {
+    pub fn new(port: Box<dyn Transport>, reopen: Reopen) -> Self {
+        Reconnecting { port: Some(port), reopen, window: RECONNECT_WINDOW, retry: RECONNECT_RETRY }
+    }
+
+    fn reconnect(&mut self, cause: io::Error) -> io::Result<()> {
+        self.port = None;
+        eprintln!("Lost the radio ({}); trying to reconnect for {}s...", cause, self.window.as_secs());
+        let started = Instant::now();
+        while started.elapsed() < self.window {
+            std::thread::sleep(self.retry);
+            if let Some(port) = (self.reopen)() {
+                self.port = Some(port);
+                RECONNECTS.fetch_add(1, Ordering::Relaxed);
+                eprintln!("Reconnected to the radio.");
+                return Ok(());
+            }
+        }
+        eprintln!("The radio didn't come back.");
+        Err(cause)
+    }
+
+    fn port(&mut self) -> io::Result<&mut Box<dyn Transport>> {
+        if self.port.is_none() {
+            self.reconnect(io::ErrorKind::NotConnected.into())?;
+        }
+        Ok(self.port.as_mut().expect("reconnected"))
+    }
+}
+
+impl io::Write for Reconnecting {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.port()?.write(buf) {
+            Err(e) if is_link_drop(&e) => {
+                self.reconnect(e)?;
+                self.port()?.write(buf)
+            }
+            other => other,
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.port()?.flush() {
+            Err(e) if is_link_drop(&e) => self.reconnect(e),
+            other => other,
+        }
+    }
+}
+
+impl io::Read for Reconnecting {
+    // As with Rfcomm, the reply that was in flight is lost and the read
+    // reports a timeout once the radio is back.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let e = match self.port()?.read(buf) {
+            Ok(0) if !buf.is_empty() => io::ErrorKind::UnexpectedEof.into(),
+            Err(e) if is_link_drop(&e) => e,
+            other => return other,
+        };
+        self.reconnect(e)?;
+        Err(io::ErrorKind::TimedOut.into())
+    }
+}
+
+/// A paired Bluetooth device, as listed by `bluetoothctl devices`.
+#[derive(Debug, PartialEq)]
+pub struct BluetoothDevice {
+    pub address: String,
+    pub name: String,
+}
+
+/// Parses `bluetoothctl devices` output ("Device AA:BB:CC:DD:EE:FF Name").
+pub fn parse_bluetooth_devices(output: &str) -> Vec<BluetoothDevice> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let rest = line.trim().strip_prefix("Device ")?;
+            let (address, name) = rest.split_once(' ').unwrap_or((rest, ""));
+            Some(BluetoothDevice { address: address.to_string(), name: name.trim().to_string() })
+        })
+        .collect()
+}
+
+/// RFCOMM ttys currently bound, with the device address each points to
+/// (from sysfs, so Linux only).
+pub fn rfcomm_bindings() -> Vec<(String, String)> {
+    let Ok(entries) = std::fs::read_dir("/sys/class/tty") else { return Vec::new() };
+    let mut bound: Vec<(String, String)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !name.starts_with("rfcomm") {
+                return None;
+            }
+            let address = std::fs::read_to_string(entry.path().join("address")).unwrap_or_default();
+            Some((format!("/dev/{}", name), address.trim().to_uppercase()))
+        })
+        .collect();
+    bound.sort();
+    bound
+}
+
+pub struct Rigctld {
+    reader: BufReader<TcpStream>,
+    stream: TcpStream,
+    // Bytes written but not yet sent: frames go out one by one.
+    pending: Vec<u8>,
+    // CAT replies waiting to be read.
+    replies: VecDeque<u8>,
+}
+
+impl Rigctld {
+    pub fn connect(addr: &str) -> io::Result<Self> {
+        let stream = TcpStream::connect(host_port(addr, RIGCTLD_DEFAULT_PORT))?;
+        stream.set_read_timeout(Some(RIGCTLD_TIMEOUT))?;
+        stream.set_nodelay(true)?;
+        let reader = BufReader::new(stream.try_clone()?);
+        Ok(Rigctld { reader, stream, pending: Vec::new(), replies: VecDeque::new() })
+    }
+
+    /// Sends one CAT frame through rigctld and queues the radio's reply.
+    /// rigctld answers with the reply on one line, or "RPRT n" when the
+    /// radio sent nothing back.
+    fn exchange(&mut self, frame: &[u8]) -> io::Result<()> {
+        self.stream.write_all(b"w ")?;
+        self.stream.write_all(frame)?;
+        self.stream.write_all(b"\n")?;
+        let mut line = Vec::new();
+        match self.reader.read_until(b'\n', &mut line) {
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(_) => {}
+            Err(e) if matches!(e.kind(), io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock) => return Ok(()),
+            Err(e) => return Err(e),
+        }
+        let reply = line.trim_ascii();
+        if !reply.starts_with(b"RPRT") {
+            self.replies.extend(reply);
+        }
+        Ok(())
+    }
+}
+
+impl io::Write for Rigctld {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        while let Some(end) = self.pending.iter().position(|b| *b == b';') {
+            let frame: Vec<u8> = self.pending.drain(..=end).collect();
+            self.exchange(&frame)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+impl io::Read for Rigctld {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        read_replies(&mut self.replies, buf)
+    }
+}
+
+/// Hands out queued replies like a serial read; TimedOut when none are left.
+fn read_replies(replies: &mut VecDeque<u8>, buf: &mut [u8]) -> io::Result<usize> {
+    if replies.is_empty() {
+        return Err(io::ErrorKind::TimedOut.into());
+    }
+    let n = buf.len().min(replies.len());
+    for (b, r) in buf.iter_mut().zip(replies.drain(..n)) {
+        *b = r;
+    }
+    Ok(n)
+}
+
+pub struct Flrig {
+    addr: String,
+    pending: Vec<u8>,
+    replies: VecDeque<u8>,
+}
+
+impl Flrig {
+    /// Checks that flrig is reachable; each frame then goes out as its own
+    /// XML-RPC request.
+    pub fn connect(addr: &str) -> io::Result<Self> {
+        let addr = host_port(addr, FLRIG_DEFAULT_PORT);
+        TcpStream::connect(&addr)?;
+        Ok(Flrig { addr, pending: Vec::new(), replies: VecDeque::new() })
+    }
+
+    /// Sends one CAT frame with rig.cat_string and queues whatever the
+    /// radio answered (nothing, for set commands).
+    fn exchange(&mut self, frame: &[u8]) -> io::Result<()> {
+        let body = format!(
+            "<?xml version=\"1.0\"?><methodCall><methodName>rig.cat_string</methodName>\
+             <params><param><value><string>{}</string></value></param></params></methodCall>",
+            xml_escape(&String::from_utf8_lossy(frame))
+        );
+        let mut stream = TcpStream::connect(&self.addr)?;
+        stream.set_read_timeout(Some(FLRIG_TIMEOUT))?;
+        let request = format!(
+            "POST /RPC2 HTTP/1.0\r\nHost: {}\r\nContent-Type: text/xml\r\nContent-Length: {}\r\n\r\n{}",
+            self.addr,
+            body.len(),
+            body
+        );
+        stream.write_all(request.as_bytes())?;
+        let mut response = String::new();
+        io::Read::read_to_string(&mut stream, &mut response)?;
+        if response.contains("<fault>") {
+            return Err(io::Error::other("flrig returned a fault"));
+        }
+        self.replies.extend(xml_value(&response).as_bytes());
+        Ok(())
+    }
+}
+
+impl io::Write for Flrig {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        while let Some(end) = self.pending.iter().position(|b| *b == b';') {
+            let frame: Vec<u8> = self.pending.drain(..=end).collect();
+            self.exchange(&frame)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl io::Read for Flrig {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        read_replies(&mut self.replies, buf)
+    }
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// The text of the first <value> in an XML-RPC response, with or without
+/// a <string> wrapper.
+fn xml_value(response: &str) -> String {
+    let Some(start) = response.find("<value>") else { return String::new() };
+    let rest = &response[start + "<value>".len()..];
+    let rest = rest.strip_prefix("<string>").unwrap_or(rest);
+    let end = rest.find('<').unwrap_or(rest.len());
+    rest[..end].replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::net::TcpListener;
+    use std::thread;
+
+    #[test]
+    fn test_drain() {
+        let mut port = io::Cursor::new(b"FA014074000;MD02;".to_vec());
+        assert_eq!(drain(&mut port, Duration::from_secs(1)), b"FA014074000;MD02;");
+        assert!(drain(&mut port, Duration::from_secs(1)).is_empty());
+        assert!(has_frames(b"FA014074000;"));
+        assert!(has_frames(b"\x00\xffMD02;"));
+        assert!(!has_frames(b"?;"));
+        assert!(!has_frames(b"\x00\xff"));
+        assert!(!has_frames(b"014074000;"));
+    }
+
+    /// A port that answers every write with "ID0840;" until `alive` runs
+    /// out, then fails like an unplugged USB adapter.
+    struct Flaky {
+        alive: usize,
+        pending: Vec<u8>,
+    }
+
+    impl Write for Flaky {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.alive == 0 {
+                return Err(io::Error::other("No such device"));
+            }
+            self.alive -= 1;
+            self.pending.extend_from_slice(b"ID0840;");
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Read for Flaky {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.pending.is_empty() {
+                return Err(io::ErrorKind::TimedOut.into());
+            }
+            let n = buf.len().min(self.pending.len());
+            buf[..n].copy_from_slice(&self.pending[..n]);
+            self.pending.drain(..n);
+            Ok(n)
+        }
+    }
+
+    fn reconnecting(opens: std::rc::Rc<std::cell::Cell<usize>>, comes_back: bool) -> Reconnecting {
+        let reopen: Reopen = Box::new(move || {
+            opens.set(opens.get() + 1);
+            comes_back.then(|| Box::new(Flaky { alive: 10, pending: Vec::new() }) as Box<dyn Transport>)
+        });
+        let mut port = Reconnecting::new(Box::new(Flaky { alive: 1, pending: Vec::new() }), reopen);
+        port.window = Duration::from_millis(50);
+        port.retry = Duration::from_millis(10);
+        port
+    }
+
+    #[test]
+    fn test_reconnecting() {
+        let opens = std::rc::Rc::new(std::cell::Cell::new(0));
+        let mut port = reconnecting(opens.clone(), true);
+        let mut reply = [0u8; 16];
+        port.write_all(b"ID;").unwrap();
+        assert_eq!(port.read(&mut reply).unwrap(), 7);
+        let before = reconnects();
+        // The next write hits the dead port; it goes out again once the
+        // radio is back.
+        port.write_all(b"ID;").unwrap();
+        assert_eq!(opens.get(), 1);
+        assert!(reconnects() > before);
+        let n = port.read(&mut reply).unwrap();
+        assert_eq!(&reply[..n], b"ID0840;");
+    }
+
+    #[test]
+    fn test_reconnecting_gives_up() {
+        let opens = std::rc::Rc::new(std::cell::Cell::new(0));
+        let mut port = reconnecting(opens.clone(), false);
+        port.write_all(b"ID;").unwrap();
+        let e = port.write_all(b"ID;").unwrap_err();
+        assert_eq!(e.to_string(), "No such device");
+        assert!(opens.get() >= 2);
+        // The port stays closed; the next use tries again.
+        assert!(port.write_all(b"ID;").is_err());
+    }
+
+    #[test]
+    fn test_host_port() {
+        assert_eq!(host_port("localhost", 4532), "localhost:4532");
+        assert_eq!(host_port("10.0.0.2:4600", 4532), "10.0.0.2:4600");
+    }
+
+    #[test]
+    fn test_rigctld_passes_frames_through() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut stream = stream;
+            let mut seen = Vec::new();
+            for _ in 0..2 {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let reply: &[u8] = if line == "w FA;\n" { b"FA014074000;\n" } else { b"RPRT -5\n" };
+                stream.write_all(reply).unwrap();
+                seen.push(line);
+            }
+            seen
+        });
+
+        let mut rig = Rigctld::connect(&addr).unwrap();
+        rig.write_all(b"FA;KS020;").unwrap();
+        let mut buf = [0u8; 32];
+        let n = rig.read(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"FA014074000;");
+        assert_eq!(rig.read(&mut buf).unwrap_err().kind(), io::ErrorKind::TimedOut);
+        assert_eq!(server.join().unwrap(), ["w FA;\n", "w KS020;\n"]);
+    }
+
+    #[test]
+    fn test_is_rfcomm() {
+        assert!(is_rfcomm("/dev/rfcomm0"));
+        assert!(is_rfcomm("rfcomm1"));
+        assert!(!is_rfcomm("/dev/ttyUSB0"));
+        assert!(!is_rfcomm("COM3"));
+    }
+
+    #[test]
+    fn test_parse_bluetooth_devices() {
+        let output = "Device 00:11:22:33:44:55 FTX-1 Field\nDevice AA:BB:CC:DD:EE:FF Headset\n[bluetooth]# \n";
+        assert_eq!(
+            parse_bluetooth_devices(output),
+            [
+                BluetoothDevice { address: "00:11:22:33:44:55".to_string(), name: "FTX-1 Field".to_string() },
+                BluetoothDevice { address: "AA:BB:CC:DD:EE:FF".to_string(), name: "Headset".to_string() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_raw_tcp_times_out_like_serial() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut frame = [0u8; 3];
+            stream.read_exact(&mut frame).unwrap();
+            stream.write_all(b"FA014074000;").unwrap();
+            thread::sleep(READ_TIMEOUT * 3);
+            frame
+        });
+
+        let mut rig = RawTcp::connect(&addr).unwrap();
+        rig.write_all(b"FA;").unwrap();
+        let mut buf = [0u8; 32];
+        let mut reply = Vec::new();
+        while !reply.ends_with(b";") {
+            let n = rig.read(&mut buf).unwrap();
+            reply.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(reply, b"FA014074000;");
+        assert_eq!(rig.read(&mut buf).unwrap_err().kind(), io::ErrorKind::TimedOut);
+        assert_eq!(&server.join().unwrap(), b"FA;");
+    }
+
+    #[test]
+    fn test_xml_value() {
+        let response = "HTTP/1.1 200 OK\r\n\r\n<methodResponse><params><param>\
+                        <value><string>MT001A&amp;B;</string></value></param></params></methodResponse>";
+        assert_eq!(xml_value(response), "MT001A&B;");
+        assert_eq!(xml_value("<params><param><value>FA014074000;</value></param></params>"), "FA014074000;");
+        assert_eq!(xml_value("<params><param><value></value></param></params>"), "");
+    }
+
+    #[test]
+    fn test_flrig_passes_frames_through() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let server = thread::spawn(move || {
+            let mut seen = Vec::new();
+            // The connection check, then one request per frame.
+            for _ in 0..3 {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = vec![0u8; 1024];
+                let n = stream.read(&mut request).unwrap();
+                let request = String::from_utf8_lossy(&request[..n]).into_owned();
+                let value = if request.contains("<string>FA;</string>") { "FA014074000;" } else { "" };
+                let body = format!("<methodResponse><params><param><value>{}</value></param></params></methodResponse>", value);
+                let _ = write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}", body.len(), body);
+                seen.push(xml_value(&request));
+            }
+            seen
+        });
+
+        let mut rig = Flrig::connect(&addr).unwrap();
+        rig.write_all(b"FA;KS020;").unwrap();
+        let mut buf = [0u8; 32];
+        let n = rig.read(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"FA014074000;");
+        assert_eq!(rig.read(&mut buf).unwrap_err().kind(), io::ErrorKind::TimedOut);
+        assert_eq!(server.join().unwrap(), ["", "FA;", "KS020;"]);
+    }
+}
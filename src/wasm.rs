@@ -0,0 +1,220 @@
+// WebAssembly exports of the memory-file checks and converters, for a
+// browser-based checker: the same memfile.rs, fix.rs, import.rs and
+// hamlib.rs the CLI runs, with text in and text out instead of files and
+// no serial I/O. Built with the `wasm` feature:
+//
+//   wasm-pack build --target web --no-default-features --features wasm
+//
+// Every function takes the file's text and returns the result as a string
+// (checkMemoryFile: a JSON report), or throws the message the CLI would
+// print when the file can't be read at all.
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::bandplan::BandPlan;
+use crate::dialect;
+use crate::fix;
+use crate::ftx1::*;
+use crate::import;
+use crate::memfile::{self, CheckOptions, CsvRecord, CHANNELS, CSV_COLUMNS};
+use crate::migrate;
+
+/// One record's findings in the checkMemoryFile report.
+#[derive(Debug, Serialize)]
+struct RecordReport {
+    /// 1-based, as the CLI numbers them.
+    record: usize,
+    channel: Option<String>,
+    errors: Vec<String>,
+    warnings: Vec<String>,
+}
+
+/// checkMemoryFile's result: records with errors or warnings, and counts.
+#[derive(Debug, Serialize)]
+struct CheckReport {
+    records: Vec<RecordReport>,
+    valid: usize,
+    invalid: usize,
+    warnings: usize,
+}
+
+/// "" for no band plan, else a --band-plan name.
+fn band_plan(name: &str) -> Result<Option<BandPlan>, String> {
+    if name.is_empty() {
+        return Ok(None);
+    }
+    BandPlan::try_from(name).map(Some).map_err(|_| format!("Unknown band plan '{}' (expected r1, r2, r3, us, eu or ja).", name))
+}
+
+fn reader(text: String) -> Result<csv::Reader<std::io::Cursor<Vec<u8>>>, String> {
+    let mut rdr = dialect::text_reader(text).map_err(|e| format!("Can't read the file: {}", e))?;
+    let headers = rdr.headers().cloned().unwrap_or_default();
+    memfile::check_headers(&headers).map_err(|errors| {
+        format!(
+            "The file doesn't have the expected columns: {} Expected: {}",
+            errors.join(" "),
+            CSV_COLUMNS.join(", ")
+        )
+    })?;
+    Ok(rdr)
+}
+
+fn written(wtr: csv::Writer<Vec<u8>>) -> Result<String, String> {
+    let bytes = wtr.into_inner().map_err(|e| e.to_string())?;
+    String::from_utf8(bytes).map_err(|e| e.to_string())
+}
+
+/// What --check-data says about a memory file, as JSON. `region` is usa
+/// or exp; the flags are --allow-any-frequency, --fix-tags and the
+/// opposite of --no-warnings.
+#[wasm_bindgen(js_name = checkMemoryFile)]
+pub fn check_memory_file(
+    text: String,
+    region: &str,
+    band_plan_name: &str,
+    allow_any_frequency: bool,
+    fix_tags: bool,
+    warnings: bool,
+) -> Result<String, String> {
+    let options = CheckOptions {
+        warnings_enabled: warnings,
+        allow_any_frequency,
+        region: Region::try_from(region).map_err(|_| format!("Unknown region '{}' (expected usa or exp).", region))?,
+        fix_tags,
+        band_plan: band_plan(band_plan_name)?,
+    };
+    let mut rdr = reader(text)?;
+    let mut cross = memfile::CrossCheck::default();
+    let mut report = CheckReport { records: Vec::new(), valid: 0, invalid: 0, warnings: 0 };
+    for (i, result) in rdr.deserialize::<CsvRecord>().enumerate() {
+        let (channel, errors, warnings) = match result {
+            Ok(record) => {
+                let (record, mut errors, notes) = memfile::check_record(record, &options);
+                let warnings = cross.check(i + 1, &record, &mut errors, notes, &options);
+                (Some(record.channel), errors, warnings)
+            }
+            Err(e) => (None, vec![format!("Error deserializing record {}: {}", i + 1, e)], Vec::new()),
+        };
+        if errors.is_empty() {
+            report.valid += 1;
+        } else {
+            report.invalid += 1;
+        }
+        report.warnings += warnings.len();
+        if !errors.is_empty() || !warnings.is_empty() {
+            report.records.push(RecordReport { record: i + 1, channel, errors, warnings });
+        }
+    }
+    serde_json::to_string(&report).map_err(|e| e.to_string())
+}
+
+/// The memory file with --check-data --fix's fixes applied.
+#[wasm_bindgen(js_name = fixMemoryFile)]
+pub fn fix_memory_file(text: String) -> Result<String, String> {
+    let mut out = Vec::new();
+    fix::fix_file(text.as_bytes(), &mut out, dialect::sniff_delimiter(&text), |_, _, _, _| {})
+        .map_err(|e| format!("Can't read the file: {}", e))?;
+    String::from_utf8(out).map_err(|e| e.to_string())
+}
+
+/// A memory file from an older release brought up to the current format.
+#[wasm_bindgen(js_name = migrateMemoryFile)]
+pub fn migrate_memory_file(text: String) -> Result<String, String> {
+    migrate::migrate(text).map_err(|e| format!("Can't read the file: {}", e))
+}
+
+/// A frequency list (see import.rs) as a memory file, like --import-list.
+/// `band_plan_name` ("" for none) turns on --auto-shift with that plan.
+/// Lines that can't be read are left out.
+#[wasm_bindgen(js_name = importList)]
+pub fn import_list(text: &str, band_plan_name: &str) -> Result<String, String> {
+    let plan = band_plan(band_plan_name)?;
+    let (entries, _skipped) = import::parse_list(text);
+    if entries.len() > CHANNELS as usize {
+        return Err(format!("The list has {} channels; the radio holds {}.", entries.len(), CHANNELS));
+    }
+    let mut out = Vec::new();
+    out.extend(format!("{}\n", migrate::stamp()).into_bytes());
+    let mut wtr = dialect::writer(out, b',');
+    wtr.write_record(CSV_COLUMNS.iter().map(|c| dialect::column_header(c))).map_err(|e| e.to_string())?;
+    for (i, entry) in entries.iter().enumerate() {
+        let (shift, tx) = match plan {
+            Some(plan) if entry.shift == Shift::Simplex => import::guess_shift(entry.freq, plan).unwrap_or((entry.shift, None)),
+            _ => (entry.shift, None),
+        };
+        let record = memfile::list_record(entry, format!("{:05}", i + 1), shift, tx);
+        wtr.serialize(&record).map_err(|e| e.to_string())?;
+    }
+    written(wtr)
+}
+
+/// A memory file as the CSV rigctl's load_channels reads, like
+/// --export-hamlib.
+#[wasm_bindgen(js_name = exportHamlib)]
+pub fn export_hamlib(text: String) -> Result<String, String> {
+    let mut rdr = reader(text)?;
+    let mut wtr = csv::Writer::from_writer(Vec::new());
+    wtr.write_record(crate::hamlib::CSV_HEADER).map_err(|e| e.to_string())?;
+    for (i, result) in rdr.deserialize::<CsvRecord>().enumerate() {
+        let mut record = result.map_err(|e| format!("Error deserializing record {}: {}", i + 1, e))?;
+        memfile::normalize_record(&mut record, false);
+        let channel = memfile::hamlib_channel(&record).map_err(|_| format!("Record {} has an invalid mode.", i + 1))?;
+        if let Some(channel) = channel {
+            wtr.write_record(channel.to_row()).map_err(|e| e.to_string())?;
+        }
+    }
+    written(wtr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = "# ftx1-mm memory file format 2
+Channel Number,Frequency (Hz),Memory Tag,Mode,Channel Type,Squelch Type,Shift (Hz),Clarifier Offset (Hz),Rx Clarifier Enabled,Tx Clarifier Enabled,CTCSS Tone,DCS Tone,Split TX (Hz),Group
+00001,145500000,CALL,FM,MemoryChannel,CtcssOff,Simplex,0,RxClarifierOff,TxClarifierOff,88.5,23,,VHF
+00002,145500000,,FM,MemoryChannel,CtcssOff,Simplex,125,RxClarifierOff,TxClarifierOff,88.5,23,,VHF
+00002,7074000,FT8,usb,MemoryChannel,CtcssOff,Simplex,0,RxClarifierOff,TxClarifierOff,88.5,23,,HF
+";
+
+    #[test]
+    fn test_check_memory_file() {
+        let report: serde_json::Value =
+            serde_json::from_str(&check_memory_file(FILE.to_string(), "usa", "", false, false, true).unwrap()).unwrap();
+        assert_eq!(report["valid"], 1);
+        assert_eq!(report["invalid"], 2);
+        let records = report["records"].as_array().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["record"], 2);
+        assert!(records[0]["errors"][0].as_str().unwrap().contains("Clarifier offset '125'"));
+        assert!(records[0]["warnings"][0].as_str().unwrap().contains("duplicates channel '00001'"));
+        assert!(records[1]["errors"].as_array().unwrap().iter().any(|e| e.as_str().unwrap().contains("appears more than once")));
+        assert!(check_memory_file(FILE.to_string(), "mars", "", false, false, true).is_err());
+        assert!(check_memory_file("Channel,Freq\n1,2\n".to_string(), "usa", "", false, false, true).is_err());
+    }
+
+    #[test]
+    fn test_fix_memory_file() {
+        let fixed = fix_memory_file(FILE.to_string()).unwrap();
+        assert!(fixed.contains("00002,145500000,,FM,MemoryChannel,CtcssOff,Simplex,130,"));
+        assert!(fixed.contains("00002,7074000,FT8,USB,"));
+        let report: serde_json::Value =
+            serde_json::from_str(&check_memory_file(fixed, "usa", "", false, false, false).unwrap()).unwrap();
+        assert_eq!(report["invalid"], 1);
+    }
+
+    #[test]
+    fn test_import_and_export() {
+        let file = import_list("146.940 - 100.0 PL Club\n7.074 USB FT8\nnonsense\n", "").unwrap();
+        assert!(file.starts_with("# ftx1-mm memory file format 2\nChannel Number,"));
+        let report: serde_json::Value =
+            serde_json::from_str(&check_memory_file(file.clone(), "usa", "", false, false, true).unwrap()).unwrap();
+        assert_eq!((report["valid"].as_u64(), report["invalid"].as_u64()), (Some(2), Some(0)));
+        let hamlib = export_hamlib(file).unwrap();
+        let rows: Vec<&str> = hamlib.lines().collect();
+        assert_eq!(rows.len(), 3);
+        assert!(rows[1].starts_with("1,Club,146940000,FM"), "{}", rows[1]);
+        assert!(rows[2].starts_with("2,FT8,7074000,USB"), "{}", rows[2]);
+    }
+}
@@ -0,0 +1,515 @@
+// The TX watchdog. Every action that can key the radio (--tune,
+// --swr-sweep, --cw-send, --voice, --serve with --allow-tx) talks to it
+// through a Watchdog wrapped around the port, which sees every CAT frame
+// going out and every reply coming back:
+//
+//   - TX1/TX2, KY (keyer text), PB0n (voice playback) and AC002 (tuning)
+//     start the keyed time; TX0 and PB00 end it, and so does a TX or RI
+//     reply saying the radio isn't transmitting.
+//   - Keyed for longer than --max-tx, the next frame first asks the radio
+//     (RI) whether it still transmits. If it does, or doesn't answer, TX0
+//     and PB00 are sent, and every frame that would key again is refused
+//     for the rest of the run.
+//   - A port that fails while keyed is opened again, once, to send TX0.
+//   - A panic sends TX0 and PB00 as the watchdog is dropped.
+//
+// Whatever it does is logged at error level, so it shows on stderr
+// without RUST_LOG. The watchdog acts when a frame is exchanged, or when
+// tick() is called: the actions that key poll the radio while keyed
+// anyway, and --serve ticks it while waiting on its client. The actions'
+// own cleanup (TX0 and PB00 however they end) goes through UnkeyGuard,
+// disarmed when the operator has keyed the radio themselves.
+// --panic-stop is the manual counterpart: TX0 and PB00 sent straight
+// away.
+
+use std::io::{self, Read, Write};
+use std::ops::{Deref, DerefMut};
+use std::time::{Duration, Instant};
+
+use log::error;
+
+use crate::ftx1::{StatusFlags, TunerState, CMD_AC, CMD_PB, CMD_RI, CMD_TX, VOICE_MEMORIES};
+use crate::trace;
+use crate::transport::Transport;
+
+/// Default --max-tx: longer than any tune cycle or voice memory, shorter
+/// than a stuck carrier should ever last.
+pub const DEFAULT_MAX_TX: Duration = Duration::from_secs(180);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Keying {
+    Key,
+    Unkey,
+}
+
+/// Whether a frame going to the radio keys or unkeys it.
+pub fn keying(frame: &[u8]) -> Option<Keying> {
+    if frame == CMD_TX.set(false) || frame == CMD_PB.stop() {
+        Some(Keying::Unkey)
+    } else if frame == CMD_TX.set(true)
+        || frame == b"TX2;"
+        || frame == CMD_AC.set(TunerState::Tuning)
+        || (frame.starts_with(b"KY0") && frame.len() > 4)
+        || (1..=VOICE_MEMORIES).any(|n| CMD_PB.play(n).is_ok_and(|play| frame == play))
+    {
+        Some(Keying::Key)
+    } else {
+        None
+    }
+}
+
+/// Whether a reply from the radio says it's transmitting; None for
+/// replies that don't tell.
+pub fn transmitting(reply: &[u8]) -> Option<bool> {
+    if reply.starts_with(b"TX") {
+        CMD_TX.decode(reply).ok()
+    } else if reply.starts_with(b"RI") {
+        CMD_RI.decode(reply).ok().map(|flags| flags.contains(StatusFlags::TX))
+    } else {
+        None
+    }
+}
+
+/// How long the radio has been keyed, from the frames seen.
+#[derive(Debug, Clone)]
+pub struct TxTimer {
+    pub max: Duration,
+    since: Option<Instant>,
+}
+
+impl TxTimer {
+    pub fn new(max: Duration) -> Self {
+        TxTimer { max, since: None }
+    }
+
+    pub fn is_keyed(&self) -> bool {
+        self.since.is_some()
+    }
+
+    pub fn clear(&mut self) {
+        self.since = None;
+    }
+
+    /// A frame went to the radio at `now`. Keying again while keyed keeps
+    /// the first start: back-to-back keyer frames are one transmission.
+    pub fn sent(&mut self, frame: &[u8], now: Instant) {
+        match keying(frame) {
+            Some(Keying::Key) => { self.since.get_or_insert(now); }
+            Some(Keying::Unkey) => self.since = None,
+            None => {}
+        }
+    }
+
+    /// A reply came back from the radio.
+    pub fn received(&mut self, reply: &[u8]) {
+        if transmitting(reply) == Some(false) {
+            self.since = None;
+        }
+    }
+
+    /// The keyed time at `now`, if it's over the maximum.
+    pub fn over(&self, now: Instant) -> Option<Duration> {
+        self.since.map(|since| now.saturating_duration_since(since)).filter(|keyed| *keyed > self.max)
+    }
+}
+
+/// Opens the radio's port again, for TX0 after the first one failed.
+pub type Reopen = Box<dyn Fn() -> Option<Box<dyn Transport>>>;
+
+/// The port, watched; see the top of this file.
+pub struct Watchdog {
+    port: Box<dyn Transport>,
+    timer: TxTimer,
+    reopen: Reopen,
+    /// Set once the watchdog has unkeyed the radio itself.
+    tripped: bool,
+    /// Reply bytes not yet ending in ';'.
+    pending: Vec<u8>,
+}
+
+impl Watchdog {
+    pub fn new(port: Box<dyn Transport>, max: Duration, reopen: Reopen) -> Self {
+        Watchdog { port, timer: TxTimer::new(max), reopen, tripped: false, pending: Vec::new() }
+    }
+
+    /// Unkeys the radio on the port the watchdog holds.
+    fn unkey(&mut self, reason: &str) {
+        let sent = unkey(&mut *self.port);
+        self.timer.clear();
+        match sent {
+            Ok(()) => error!("TX watchdog: {}; sent TX0.", reason),
+            Err(e) => error!("TX watchdog: {}; sending TX0 failed: {}", reason, e),
+        }
+    }
+
+    /// Asks the radio whether it's transmitting; None if it doesn't say.
+    fn still_transmitting(&mut self) -> Option<bool> {
+        let query = CMD_RI.read();
+        self.port.write_all(&query).ok()?;
+        trace::frame(trace::Direction::Tx, &query);
+        let mut reply = Vec::new();
+        let mut chunk = [0u8; 64];
+        while !reply.ends_with(b";") {
+            match self.port.read(&mut chunk) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => reply.extend_from_slice(&chunk[..n]),
+            }
+        }
+        trace::frame(trace::Direction::Rx, &reply);
+        transmitting(&reply)
+    }
+
+    /// Before `buf` goes out: refuses keying after a trip, and trips if
+    /// the radio has been keyed too long.
+    fn check(&mut self, buf: &[u8]) -> io::Result<()> {
+        let keys = frames(buf).any(|f| keying(f) == Some(Keying::Key));
+        if self.tripped && keys {
+            return Err(io::Error::other("the TX watchdog unkeyed the radio; not keying again"));
+        }
+        if frames(buf).all(|f| keying(f) == Some(Keying::Unkey)) {
+            return Ok(());
+        }
+        self.expire()
+    }
+
+    /// Trips if the radio has been keyed too long, with no frame going
+    /// out: for callers that can sit idle while keyed, like a --serve
+    /// client after "T 1". Call it every so often.
+    pub fn tick(&mut self) -> io::Result<()> {
+        self.expire()
+    }
+
+    fn expire(&mut self) -> io::Result<()> {
+        let Some(keyed) = self.timer.over(Instant::now()) else { return Ok(()) };
+        if self.still_transmitting() == Some(false) {
+            self.timer.clear();
+            return Ok(());
+        }
+        self.tripped = true;
+        self.unkey(&format!("keyed for {} s, over --max-tx {} s", keyed.as_secs(), self.timer.max.as_secs()));
+        Err(io::Error::other("the TX watchdog unkeyed the radio"))
+    }
+
+    /// The port failed: if the radio may be keyed, opens it again to
+    /// send TX0.
+    fn port_lost(&mut self, e: &io::Error) {
+        if !self.timer.is_keyed() {
+            return;
+        }
+        self.timer.clear();
+        match (self.reopen)() {
+            Some(mut port) => match unkey(&mut *port) {
+                Ok(()) => error!("TX watchdog: the port failed while keyed ({}); reopened it and sent TX0.", e),
+                Err(e2) => error!("TX watchdog: the port failed while keyed ({}); sending TX0 on it again failed: {}", e, e2),
+            },
+            None => error!("TX watchdog: the port failed while keyed ({}) and can't be opened again; check the radio is unkeyed!", e),
+        }
+    }
+}
+
+impl Write for Watchdog {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.check(buf)?;
+        match self.port.write(buf) {
+            Ok(n) => {
+                let now = Instant::now();
+                frames(&buf[..n]).for_each(|f| self.timer.sent(f, now));
+                Ok(n)
+            }
+            Err(e) => {
+                self.port_lost(&e);
+                Err(e)
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.port.flush()
+    }
+}
+
+impl Read for Watchdog {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.port.read(buf) {
+            Ok(n) => {
+                self.pending.extend_from_slice(&buf[..n]);
+                if let Some(end) = self.pending.iter().rposition(|b| *b == b';') {
+                    let complete: Vec<u8> = self.pending.drain(..=end).collect();
+                    frames(&complete).for_each(|f| self.timer.received(f));
+                }
+                Ok(n)
+            }
+            Err(e) if matches!(e.kind(), io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted) => Err(e),
+            Err(e) => {
+                self.port_lost(&e);
+                Err(e)
+            }
+        }
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            self.unkey("the program panicked");
+        }
+    }
+}
+
+/// Holds a keying action's port and unkeys the radio when dropped, so an
+/// error part way through can't leave it keyed.
+pub struct UnkeyGuard<'a> {
+    port: &'a mut dyn Transport,
+    armed: bool,
+}
+
+impl<'a> UnkeyGuard<'a> {
+    pub fn new(port: &'a mut dyn Transport) -> Self {
+        UnkeyGuard { port, armed: true }
+    }
+
+    /// Leaves the radio alone when dropped: the operator keyed it, and
+    /// TX0 would cut them off.
+    pub fn disarm(&mut self) {
+        self.armed = false;
+    }
+}
+
+impl<'a> Deref for UnkeyGuard<'a> {
+    type Target = dyn Transport + 'a;
+
+    fn deref(&self) -> &Self::Target {
+        self.port
+    }
+}
+
+impl DerefMut for UnkeyGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.port
+    }
+}
+
+impl Drop for UnkeyGuard<'_> {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        if let Err(e) = unkey(self.port) {
+            error!("Failed to unkey the radio: {}", e);
+        }
+    }
+}
+
+/// TX0 and PB00: stops a carrier, a voice memory playing, and (the radio
+/// drops the keyer's buffer when unkeyed) CW being sent.
+pub fn unkey(port: &mut dyn Transport) -> io::Result<()> {
+    let frames = [CMD_TX.set(false), CMD_PB.stop()].concat();
+    trace::frame(trace::Direction::Tx, &frames);
+    port.write_all(&frames)?;
+    port.flush()
+}
+
+/// The ';'-terminated frames in `buf`, each with its ';'.
+fn frames(buf: &[u8]) -> impl Iterator<Item = &[u8]> {
+    buf.split_inclusive(|b| *b == b';').filter(|f| f.ends_with(b";"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+    use std::collections::VecDeque;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_keying() {
+        assert_eq!(keying(b"TX1;"), Some(Keying::Key));
+        assert_eq!(keying(b"TX2;"), Some(Keying::Key));
+        assert_eq!(keying(b"KY0CQ CQ;"), Some(Keying::Key));
+        assert_eq!(keying(b"PB03;"), Some(Keying::Key));
+        assert_eq!(keying(b"AC002;"), Some(Keying::Key));
+        assert_eq!(keying(b"TX0;"), Some(Keying::Unkey));
+        assert_eq!(keying(b"PB00;"), Some(Keying::Unkey));
+        for other in [&b"TX;"[..], b"PB0;", b"KY;", b"AC001;", b"FA;", b"PB06;"] {
+            assert_eq!(keying(other), None, "{}", String::from_utf8_lossy(other));
+        }
+    }
+
+    #[test]
+    fn test_transmitting() {
+        assert_eq!(transmitting(b"TX1;"), Some(true));
+        assert_eq!(transmitting(b"TX0;"), Some(false));
+        assert_eq!(transmitting(b"RI0001000;"), Some(true));
+        assert_eq!(transmitting(b"RI0000010;"), Some(false));
+        assert_eq!(transmitting(b"FA014074000;"), None);
+        assert_eq!(transmitting(b"?;"), None);
+    }
+
+    #[test]
+    fn test_timer() {
+        let start = Instant::now();
+        let mut timer = TxTimer::new(Duration::from_secs(10));
+        timer.sent(b"KY0CQ;", start);
+        // A second segment doesn't restart the clock.
+        timer.sent(b"KY0DE;", start + Duration::from_secs(5));
+        assert_eq!(timer.over(start + Duration::from_secs(10)), None);
+        assert_eq!(timer.over(start + Duration::from_secs(11)), Some(Duration::from_secs(11)));
+        timer.received(b"RI0001000;");
+        assert!(timer.is_keyed());
+        timer.received(b"RI0000000;");
+        assert!(!timer.is_keyed());
+        timer.sent(b"TX1;", start);
+        timer.sent(b"TX0;", start);
+        assert_eq!(timer.over(start + Duration::from_secs(60)), None);
+    }
+
+    /// Replies queued up front; everything written is kept in `written`.
+    struct Fake {
+        replies: VecDeque<Vec<u8>>,
+        written: Rc<RefCell<Vec<u8>>>,
+        fail: bool,
+    }
+
+    impl Read for Fake {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.fail {
+                return Err(io::Error::from(io::ErrorKind::BrokenPipe));
+            }
+            let reply = self.replies.pop_front().ok_or(io::ErrorKind::TimedOut)?;
+            buf[..reply.len()].copy_from_slice(&reply);
+            Ok(reply.len())
+        }
+    }
+
+    impl Write for Fake {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.written.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn watched(replies: &[&[u8]], max: Duration) -> (Watchdog, Rc<RefCell<Vec<u8>>>) {
+        let written = Rc::new(RefCell::new(Vec::new()));
+        let fake = Fake { replies: replies.iter().map(|r| r.to_vec()).collect(), written: written.clone(), fail: false };
+        (Watchdog::new(Box::new(fake), max, Box::new(|| None)), written)
+    }
+
+    fn text(written: &Rc<RefCell<Vec<u8>>>) -> String {
+        String::from_utf8_lossy(&written.borrow()).into_owned()
+    }
+
+    #[test]
+    fn test_trips_when_keyed_too_long() {
+        let (mut dog, written) = watched(&[b"RI0001000;"], Duration::ZERO);
+        dog.write_all(b"TX1;").unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(dog.write_all(b"RM6;").is_err());
+        assert_eq!(text(&written), "TX1;RI;TX0;PB00;");
+        // Keying again is refused; unkeying and reading still go through.
+        assert!(dog.write_all(b"TX1;").is_err());
+        dog.write_all(b"TX0;FA;").unwrap();
+        assert_eq!(text(&written), "TX1;RI;TX0;PB00;TX0;FA;");
+    }
+
+    #[test]
+    fn test_tick_trips_while_idle() {
+        let (mut dog, written) = watched(&[b"RI0001000;"], Duration::ZERO);
+        dog.tick().unwrap();
+        dog.write_all(b"TX1;").unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        // Nothing else is sent, and the radio is still unkeyed.
+        assert!(dog.tick().is_err());
+        assert_eq!(text(&written), "TX1;RI;TX0;PB00;");
+        assert!(dog.write_all(b"TX1;").is_err());
+        dog.tick().unwrap();
+    }
+
+    #[test]
+    fn test_guard_unkeys() {
+        let (mut dog, written) = watched(&[], Duration::from_secs(60));
+        let mut failed = || -> Result<(), ()> {
+            let mut port = UnkeyGuard::new(&mut dog);
+            port.write_all(b"PB01;").map_err(|_| ())?;
+            Err(())
+        };
+        assert!(failed().is_err());
+        assert_eq!(text(&written), "PB01;TX0;PB00;");
+        // Disarmed, it sends nothing.
+        let (mut dog, written) = watched(&[], Duration::from_secs(60));
+        UnkeyGuard::new(&mut dog).disarm();
+        assert_eq!(text(&written), "");
+    }
+
+    #[test]
+    fn test_no_trip_once_unkeyed() {
+        let (mut dog, written) = watched(&[b"RI0000000;"], Duration::ZERO);
+        dog.write_all(b"PB01;").unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        // The memory ended on its own; RI says so and nothing is sent.
+        dog.write_all(b"FA;").unwrap();
+        assert_eq!(text(&written), "PB01;RI;FA;");
+        dog.write_all(b"PB01;").unwrap();
+    }
+
+    #[test]
+    fn test_reply_clears_the_timer() {
+        let (mut dog, written) = watched(&[b"TX0;"], Duration::ZERO);
+        dog.write_all(b"TX1;TX;").unwrap();
+        assert_eq!(dog.read(&mut [0u8; 16]).unwrap(), 4);
+        std::thread::sleep(Duration::from_millis(5));
+        dog.write_all(b"FA;").unwrap();
+        assert_eq!(text(&written), "TX1;TX;FA;");
+    }
+
+    #[test]
+    fn test_panic_unkeys() {
+        let (dog, written) = watched(&[], Duration::from_secs(60));
+        let dog = std::panic::AssertUnwindSafe(dog);
+        let panicked = std::panic::catch_unwind(move || {
+            let _dog = dog;
+            panic!("keyed action failed");
+        });
+        assert!(panicked.is_err());
+        assert_eq!(text(&written), "TX0;PB00;");
+        // Dropped without a panic, it leaves the radio alone.
+        let (dog, written) = watched(&[], Duration::from_secs(60));
+        drop(dog);
+        assert_eq!(text(&written), "");
+    }
+
+    #[test]
+    fn test_lost_port_reopens_to_unkey() {
+        let reopened = Rc::new(Cell::new(0));
+        let count = reopened.clone();
+        let fake = Fake { replies: VecDeque::new(), written: Rc::default(), fail: true };
+        let reopen: Reopen = Box::new(move || {
+            count.set(count.get() + 1);
+            None
+        });
+        let mut dog = Watchdog::new(Box::new(fake), Duration::from_secs(60), reopen);
+        dog.write_all(b"TX1;").unwrap();
+        assert!(dog.read(&mut [0u8; 16]).is_err());
+        assert_eq!(reopened.get(), 1);
+        // Only while keyed.
+        assert!(dog.read(&mut [0u8; 16]).is_err());
+        assert_eq!(reopened.get(), 1);
+    }
+
+    #[test]
+    fn test_drop_while_keyed_unkeys_on_the_new_port() {
+        let fake = Fake { replies: VecDeque::new(), written: Rc::default(), fail: true };
+        let written = Rc::new(RefCell::new(Vec::new()));
+        let new_written = written.clone();
+        let reopen: Reopen = Box::new(move || {
+            Some(Box::new(Fake { replies: VecDeque::new(), written: new_written.clone(), fail: false }))
+        });
+        let mut dog = Watchdog::new(Box::new(fake), Duration::from_secs(60), reopen);
+        dog.write_all(b"TX1;").unwrap();
+        assert!(dog.read(&mut [0u8; 16]).is_err());
+        assert_eq!(text(&written), "TX0;PB00;");
+    }
+}
@@ -1,3 +1,6 @@
+// The binary needs the default `cli` feature.
+#![cfg(feature = "cli")]
+
 use std::path::PathBuf;
 use std::process::{Command, Output};
 
@@ -46,11 +49,56 @@ fn temp_csv(name: &str) -> PathBuf {
     std::env::temp_dir().join(format!("ftx1_test_{name}.csv"))
 }
 
+/// One channel row for csv_with_rows; tests set only the fields they vary.
+struct Row<'a> {
+    channel: &'a str,
+    freq: u32,
+    tag: &'a str,
+    mode: &'a str,
+    squelch: &'a str,
+    shift: &'a str,
+    clarifier: i32,
+    rx_clarifier: &'a str,
+    ctcss: &'a str,
+}
+
+impl Default for Row<'_> {
+    fn default() -> Self {
+        Row {
+            channel: "00001",
+            freq: 145_000_000,
+            tag: "",
+            mode: "FM",
+            squelch: "CtcssOff",
+            shift: "Simplex",
+            clarifier: 0,
+            rx_clarifier: "RxClarifierOff",
+            ctcss: "88.5",
+        }
+    }
+}
+
+/// Writes `rows` under valid.csv's header to temp_csv(name).
+fn csv_with_rows(name: &str, rows: &[Row]) -> PathBuf {
+    let valid = std::fs::read_to_string(fixture("valid.csv")).unwrap();
+    let mut csv = format!("{}\n", valid.lines().next().unwrap());
+    for r in rows {
+        csv += &format!(
+            "{},{},{},{},MemoryChannel,{},{},{},{},TxClarifierOff,{},023\n",
+            r.channel, r.freq, r.tag, r.mode, r.squelch, r.shift, r.clarifier, r.rx_clarifier, r.ctcss
+        );
+    }
+    let path = temp_csv(name);
+    std::fs::write(&path, csv).unwrap();
+    path
+}
+
 /// Parse a CSV file into sorted, whitespace-normalised data rows (header excluded).
 fn normalise_csv(path: &PathBuf) -> Vec<String> {
     let content = std::fs::read_to_string(path).unwrap();
     let mut rows: Vec<String> = content
         .lines()
+        .filter(|line| !line.starts_with('#'))
         .skip(1)
         .map(|line| {
             line.split(',')
@@ -121,6 +169,272 @@ fn check_data_multiple_errors() {
     assert!(stdout.contains("not a valid mode"));
 }
 
+#[test]
+fn check_data_large_file_reports_errors_in_order() {
+    let valid = std::fs::read_to_string(fixture("valid.csv")).unwrap();
+    let mut csv = format!("{}\n", valid.lines().next().unwrap());
+    for ch in 1..=999 {
+        let mode = if ch % 250 == 0 { "BOGUS" } else { "FM" };
+        let freq = 144_000_000 + ch * 1000;
+        csv.push_str(&format!(
+            "{ch:05},{freq},,{mode},MemoryChannel,CtcssOff,Simplex,0,RxClarifierOff,TxClarifierOff,88.5,023\n"
+        ));
+    }
+    let path = temp_csv("large_check");
+    std::fs::write(&path, csv).unwrap();
+    let out = bin().args(["--check-data", "--file", path.to_str().unwrap()]).output().unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let records: Vec<&str> = stdout.lines().filter(|l| l.starts_with("Record ")).collect();
+    assert_eq!(records, ["Record 250 is invalid:", "Record 500 is invalid:", "Record 750 is invalid:"]);
+    assert!(stdout.contains("Total records processed: 999"));
+}
+
+#[test]
+fn check_data_rejects_clarifier_off_step() {
+    let path = csv_with_rows("clar_step", &[Row { mode: "USB", clarifier: 1234, rx_clarifier: "RxClarifierOn", ..Row::default() }]);
+    let out = bin().args(["--check-data", "--file", path.to_str().unwrap()]).output().unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("not a multiple of 10 Hz (nearest: 1230)"), "expected step error: {stdout}");
+}
+
+fn long_tag_csv(name: &str) -> PathBuf {
+    csv_with_rows(name, &[Row { tag: "REPEATER NORTH", ..Row::default() }])
+}
+
+#[test]
+fn check_data_rejects_long_tag() {
+    let path = long_tag_csv("long_tag");
+    let out = bin().args(["--check-data", "--file", path.to_str().unwrap()]).output().unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("is 14 characters long"), "expected tag length error: {stdout}");
+    assert!(stdout.contains("--fix-tags"), "expected pointer to the flag: {stdout}");
+}
+
+#[test]
+fn check_data_fix_tags_truncates() {
+    let path = long_tag_csv("long_tag_fixed");
+    let out = bin().args(["--check-data", "--fix-tags", "--file", path.to_str().unwrap()]).output().unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert_success(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("will be written as 'REPEATER NOR'"), "expected fix note: {stdout}");
+}
+
+#[test]
+fn check_data_five_mhz_channels() {
+    let row = |channel| Row { channel, freq: 5_357_000, mode: "USB", ..Row::default() };
+    let path = csv_with_rows("five_mhz", &[row("50001"), row("50021")]);
+    let out = bin().args(["--check-data", "--file", path.to_str().unwrap()]).output().unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(!stdout.contains("'50001' is not a valid"), "50001 should be accepted: {stdout}");
+    assert!(stdout.contains("'50021' is not a valid memory channel"), "50021 should be rejected: {stdout}");
+}
+
+#[test]
+fn export_hamlib_writes_channels() {
+    let path = temp_csv("hamlib_export");
+    let out = bin()
+        .args(["--export-hamlib", path.to_str().unwrap(), "--file", fixture("valid.csv").to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert_success(&out);
+    let exported = std::fs::read_to_string(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    let mut lines = exported.lines();
+    assert_eq!(lines.next(), Some("num,channel_desc,freq,mode,split,tx_freq,rptr_shift,ctcss_tone,ctcss_sql,dcs_code,dcs_sql"));
+    assert!(exported.contains("\n2,REPEATER,433500000,FM,off,0,+,885,0,0,0\n"), "unexpected export: {exported}");
+}
+
+#[test]
+fn import_list_builds_checkable_file() {
+    let list = std::env::temp_dir().join("ftx1_test_import_list.txt");
+    std::fs::write(&list, "146.520 FM Natl Simplex\n146.940 - 100.0 PL Club rptr\nnothing here\n").unwrap();
+    let path = temp_csv("import_list");
+    let out = bin()
+        .args(["--import-list", list.to_str().unwrap(), "--file", path.to_str().unwrap()])
+        .output()
+        .unwrap();
+    let _ = std::fs::remove_file(&list);
+    assert_success(&out);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("Line 3: no frequency; skipped."));
+    let check = bin().args(["--check-data", "--file", path.to_str().unwrap()]).output().unwrap();
+    let rows = normalise_csv(&path);
+    let _ = std::fs::remove_file(&path);
+    assert_success(&check);
+    assert_eq!(rows.len(), 2);
+    assert!(rows[1].starts_with("00002,146940000,Club rptr,FM,MemoryChannel,CtcssEnc,MinusShift,"), "{rows:?}");
+}
+
+#[test]
+fn import_list_auto_shift_guesses_repeaters() {
+    let list = std::env::temp_dir().join("ftx1_test_auto_shift.txt");
+    std::fs::write(&list, "146.520 FM Natl Simplex\n146.940 Club rptr\n439.000 Eu rptr\n").unwrap();
+    let path = temp_csv("auto_shift");
+    let out = bin()
+        .args(["--import-list", list.to_str().unwrap(), "--auto-shift", "--band-plan", "r1"])
+        .args(["--file", path.to_str().unwrap()])
+        .output()
+        .unwrap();
+    let _ = std::fs::remove_file(&list);
+    let rows = normalise_csv(&path);
+    let _ = std::fs::remove_file(&path);
+    assert_success(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Channel 00003 (439000000 Hz): guessed split TX 431400000 Hz"), "{stdout}");
+    assert!(stdout.contains("Guessed the repeater shift of 1 channel(s)"), "{stdout}");
+    assert!(rows[1].starts_with("00002,146940000,Club rptr,FM,MemoryChannel,CtcssOff,Simplex,"), "{rows:?}");
+    assert!(rows[2].contains(",431400000,"), "{rows:?}");
+}
+
+#[test]
+fn import_list_auto_tag_fills_empty_tags() {
+    let list = std::env::temp_dir().join("ftx1_test_auto_tag.txt");
+    std::fs::write(&list, "146.520\n7.074 USB\n433.500 FM Club\n").unwrap();
+    let path = temp_csv("auto_tag");
+    let out = bin()
+        .args(["--import-list", list.to_str().unwrap(), "--auto-tag", "--file", path.to_str().unwrap()])
+        .output()
+        .unwrap();
+    let rows = normalise_csv(&path);
+    let _ = std::fs::remove_file(&path);
+    assert_success(&out);
+    assert!(rows[0].starts_with("00001,146520000,146.520 FM,"), "{rows:?}");
+    assert!(rows[1].starts_with("00002,7074000,7.074 USB,"), "{rows:?}");
+    assert!(rows[2].starts_with("00003,433500000,Club,"), "{rows:?}");
+
+    let out = bin()
+        .args(["--import-list", list.to_str().unwrap(), "--auto-tag", "{call}", "--file", path.to_str().unwrap()])
+        .output()
+        .unwrap();
+    let _ = std::fs::remove_file(&list);
+    assert_failure(&out);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("unknown field '{call}'"));
+}
+
+#[test]
+fn import_list_auto_tag_uses_callsign_column() {
+    let list = std::env::temp_dir().join("ftx1_test_auto_tag_callsign.csv");
+    std::fs::write(&list, "Frequency,Callsign,Tone\n146.940,w1aw,100.0\n147.000,,\n").unwrap();
+    let path = temp_csv("auto_tag_callsign");
+    let out = bin()
+        .args(["--import-list", list.to_str().unwrap(), "--auto-tag", "{callsign} {freq_mhz:.2}", "--file", path.to_str().unwrap()])
+        .output()
+        .unwrap();
+    let rows = normalise_csv(&path);
+    let _ = std::fs::remove_file(&list);
+    let _ = std::fs::remove_file(&path);
+    assert_success(&out);
+    assert!(rows[0].starts_with("00001,146940000,W1AW 146.94,"), "{rows:?}");
+    assert!(rows[1].starts_with("00002,147000000,147.00,"), "{rows:?}");
+}
+
+#[test]
+fn semicolon_decimal_comma_files_round_trip() {
+    let list = std::env::temp_dir().join("ftx1_test_eu_locale.txt");
+    std::fs::write(&list, "145.650 - 88.5 PL Rptr\n").unwrap();
+    let path = temp_csv("eu_locale");
+    let out = bin()
+        .args(["--import-list", list.to_str().unwrap(), "--delimiter", ";", "--decimal-comma"])
+        .args(["--file", path.to_str().unwrap()])
+        .output()
+        .unwrap();
+    let _ = std::fs::remove_file(&list);
+    assert_success(&out);
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(text.starts_with("# ftx1-mm memory file format 2\nChannel Number;Frequency (Hz);"), "{text}");
+    assert!(text.contains(";88,5;"), "{text}");
+    let check = bin().args(["--check-data", "--file", path.to_str().unwrap()]).output().unwrap();
+    let hamlib = temp_csv("eu_locale_hamlib");
+    let export = bin()
+        .args(["--export-hamlib", hamlib.to_str().unwrap(), "--file", path.to_str().unwrap()])
+        .output()
+        .unwrap();
+    let exported = std::fs::read_to_string(&hamlib).unwrap_or_default();
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_file(&hamlib);
+    assert_success(&check);
+    assert_success(&export);
+    assert!(exported.contains(",885,"), "{exported}");
+}
+
+#[test]
+fn mhz_frequency_columns_round_trip_exactly() {
+    let list = std::env::temp_dir().join("ftx1_test_freq_unit.txt");
+    std::fs::write(&list, "145.525 FM Calling\n7074123 USB Odd\n").unwrap();
+    let path = temp_csv("freq_unit");
+    let out = bin()
+        .args(["--import-list", list.to_str().unwrap(), "--freq-unit", "mhz", "--file", path.to_str().unwrap()])
+        .output()
+        .unwrap();
+    let _ = std::fs::remove_file(&list);
+    assert_success(&out);
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(text.starts_with("# ftx1-mm memory file format 2\nChannel Number,Frequency (MHz),"), "{text}");
+    assert!(text.contains(",Split TX (MHz),"), "{text}");
+    assert!(text.contains("00001,145.52500,") && text.contains("00002,7.074123,"), "{text}");
+    let check = bin().args(["--check-data", "--file", path.to_str().unwrap()]).output().unwrap();
+    let hamlib = temp_csv("freq_unit_hamlib");
+    let export = bin()
+        .args(["--export-hamlib", hamlib.to_str().unwrap(), "--file", path.to_str().unwrap()])
+        .output()
+        .unwrap();
+    let exported = std::fs::read_to_string(&hamlib).unwrap_or_default();
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_file(&hamlib);
+    assert_success(&check);
+    assert_success(&export);
+    assert!(exported.contains(",145525000,") && exported.contains(",7074123,"), "{exported}");
+}
+
+#[test]
+fn check_data_fix_writes_corrected_copy() {
+    let valid = std::fs::read_to_string(fixture("valid.csv")).unwrap();
+    let mut lines = valid.lines();
+    let header = lines.next().unwrap();
+    let row = lines.next().unwrap().replacen("00001,", "1,", 1).replace(",FM,", ",fm,").replace(",Simplex,0,", ",Simplex,125,");
+    let path = temp_csv("fix_in");
+    let fixed = temp_csv("fix_out");
+    std::fs::write(&path, format!("{header}\n# --- 2 m ---\n{row}\n")).unwrap();
+    let out = bin()
+        .args(["--check-data", "--fix", fixed.to_str().unwrap(), "--file", path.to_str().unwrap()])
+        .output()
+        .unwrap();
+    let text = std::fs::read_to_string(&fixed).unwrap_or_default();
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_file(&fixed);
+    assert_success(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Record 1: Channel Number '1' -> '00001'."), "{stdout}");
+    assert!(stdout.contains("Record 1: Mode 'fm' -> 'FM'."), "{stdout}");
+    assert!(stdout.contains("Record 1: Clarifier Offset (Hz) '125' -> '130'."), "{stdout}");
+    assert!(stdout.contains("Made 3 fix(es)"), "{stdout}");
+    assert!(text.contains("# --- 2 m ---\n00001,145000000,HOME,FM,MemoryChannel,CtcssOff,Simplex,130,"), "{text}");
+}
+
+#[test]
+fn check_data_reports_header_problems() {
+    let valid = std::fs::read_to_string(fixture("valid.csv")).unwrap();
+    let header = valid.lines().next().unwrap().replace("Mode", "mode").replace(",DCS Tone", ",Notes");
+    let path = temp_csv("bad_header");
+    std::fs::write(&path, format!("{header}\n")).unwrap();
+    let out = bin().args(["--check-data", "--file", path.to_str().unwrap()]).output().unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Column 'mode' should be named 'Mode'"), "expected misnamed column: {stdout}");
+    assert!(stdout.contains("Unexpected column 'Notes'"), "expected extra column: {stdout}");
+    assert!(stdout.contains("Missing column 'DCS Tone'"), "expected missing column: {stdout}");
+    assert!(stdout.contains("Expected: Channel Number, Frequency (Hz),"), "expected column list: {stdout}");
+}
+
 #[test]
 fn check_data_empty_file() {
     let out = bin()
@@ -150,7 +464,39 @@ fn check_data_duplicate_frequency_warns_but_passes() {
     let stdout = String::from_utf8_lossy(&out.stdout);
     assert!(stdout.contains("has warnings"), "expected warning banner in stdout: {stdout}");
     assert!(stdout.contains("is also used by channel '00001'"), "expected dup-frequency warning text: {stdout}");
-    assert!(stdout.contains("Warnings: 1"), "expected warning count in summary: {stdout}");
+    // Row 1 also has a CTCSS tone set with the squelch off.
+    assert!(stdout.contains("CTCSS tone 100.0 is set but Squelch Type is CTCSS_OFF"), "expected unused-tone warning: {stdout}");
+    assert!(stdout.contains("Warnings: 2"), "expected warning count in summary: {stdout}");
+}
+
+#[test]
+fn check_data_flags_duplicate_channels_and_settings() {
+    let row = |channel, ctcss| Row { channel, freq: 146_940_000, squelch: "CtcssEncDec", shift: "MinusShift", ctcss, ..Row::default() };
+    let path = csv_with_rows("duplicates", &[row("00001", "103.5"), row("00005", "103.5"), row("00001", "88.5")]);
+    let out = bin().args(["--check-data", "--file", path.to_str().unwrap()]).output().unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(
+        stdout.contains("Channel '00005' duplicates channel '00001' (same frequency, mode and tone)"),
+        "expected exact-duplicate warning: {stdout}"
+    );
+    assert!(stdout.contains("Channel '00001' appears more than once (also record 1)"), "expected dup channel: {stdout}");
+    assert!(stdout.contains("is also used by channel '00001'"), "different tone is only a shared frequency: {stdout}");
+}
+
+#[test]
+fn check_data_warns_about_contradicting_fields() {
+    let out = bin()
+        .args(["--check-data", "--file", fixture("inconsistent_fields.csv").to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert_success(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("CTCSS tone 100.0 is set but Squelch Type is"), "expected unused-tone warning: {stdout}");
+    assert!(stdout.contains("(zero offset)"), "expected zero-offset warning: {stdout}");
+    assert!(stdout.contains("TX clarifier is enabled with a zero offset."), "expected clarifier warning: {stdout}");
+    assert!(stdout.contains("Warnings: 3"), "expected one warning per row: {stdout}");
 }
 
 #[test]
@@ -161,8 +507,11 @@ fn check_data_accepts_libreoffice_mangled_channel_numbers() {
         .unwrap();
     assert_success(&out);
     let stdout = String::from_utf8_lossy(&out.stdout);
-    assert!(stdout.contains("Data looks good!"), "expected lenient acceptance: {stdout}");
+    assert!(stdout.contains("Data is valid"), "expected lenient acceptance: {stdout}");
     assert!(stdout.contains("Valid records: 2"), "expected both rows valid: {stdout}");
+    // The only complaint is row 1's tone, set with the squelch off.
+    assert!(stdout.contains("CTCSS tone 100 is set but Squelch Type is CTCSS_OFF"), "expected unused-tone warning: {stdout}");
+    assert!(stdout.contains("Warnings: 1"), "{stdout}");
 }
 
 #[test]
@@ -201,7 +550,11 @@ fn check_data_allows_out_of_band_frequency_with_flag() {
         .unwrap();
     assert_success(&out);
     let stdout = String::from_utf8_lossy(&out.stdout);
-    assert!(stdout.contains("Data looks good!"), "expected clean verdict: {stdout}");
+    assert!(stdout.contains("Invalid records: 0"), "expected the frequency to be allowed: {stdout}");
+    assert!(!stdout.contains("coverage"), "{stdout}");
+    // The only complaint is the tone, set with the squelch off.
+    assert!(stdout.contains("CTCSS tone 100.0 is set but Squelch Type is CTCSS_OFF"), "expected unused-tone warning: {stdout}");
+    assert!(stdout.contains("Warnings: 1"), "{stdout}");
 }
 
 #[test]
@@ -222,6 +575,45 @@ fn check_data_no_warnings_flag_suppresses_dup_frequency() {
     assert!(stdout.contains("Data looks good!"), "expected clean verdict: {stdout}");
 }
 
+#[test]
+fn check_data_warns_receive_only_for_region() {
+    let out = bin()
+        .args(["--check-data", "--region", "exp", "--file", fixture("duplicate_frequency.csv").to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert_success(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("outside the EXP model's transmit coverage"), "expected TX warning: {stdout}");
+    assert!(stdout.contains("Warnings: 5"), "expected 3 TX + 1 dup + 1 unused-tone warnings: {stdout}");
+}
+
+#[test]
+fn check_data_band_plan_warns() {
+    let path = csv_with_rows(
+        "band_plan",
+        &[
+            Row { freq: 7_150_000, ..Row::default() },
+            Row { channel: "00002", freq: 7_100_000, mode: "LSB", ..Row::default() },
+        ],
+    );
+    let out = bin().args(["--check-data", "--band-plan", "r1", "--file", path.to_str().unwrap()]).output().unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert_success(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("7.150 MHz FM not permitted in R1"), "expected band-plan warning: {stdout}");
+    assert!(stdout.contains("Warnings: 1"), "only the FM channel should warn: {stdout}");
+}
+
+#[test]
+fn check_data_rejects_unknown_region() {
+    let out = bin()
+        .args(["--check-data", "--region", "mars", "--file", fixture("valid.csv").to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert_failure(&out);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("Unknown region 'mars'"));
+}
+
 // ---------------------------------------------------------------------------
 // Group 2: CLI argument handling (no radio required)
 // ---------------------------------------------------------------------------
@@ -248,124 +640,912 @@ fn mutually_exclusive_actions() {
     assert_failure(&out);
 }
 
-// ---------------------------------------------------------------------------
-// Group 2b: --print (no radio required)
-// ---------------------------------------------------------------------------
-
 #[test]
-fn print_valid_file() {
-    let out = bin()
-        .args(["--print", "--file", fixture("valid.csv").to_str().unwrap()])
-        .output()
-        .unwrap();
-    assert_success(&out);
+fn cw_send_requires_allow_tx() {
+    let out = bin().args(["--cw-send", "CQ TEST", "--port", "/dev/nonexistent"]).output().unwrap();
+    assert_failure(&out);
     let stdout = String::from_utf8_lossy(&out.stdout);
-    assert!(stdout.contains("145.000 MHz"));
-    assert!(stdout.contains("HOME"));
-    assert!(stdout.contains("433.500 MHz"));
-    assert!(stdout.contains("REPEATER"));
+    assert!(stdout.contains("--allow-tx"), "expected pointer to the flag: {stdout}");
+    assert!(!stdout.contains("Failed to open port"), "port should not be opened: {stdout}");
 }
 
 #[test]
-fn print_missing_file() {
-    let out = bin()
-        .args(["--print", "--file", "nonexistent.csv"])
-        .output()
-        .unwrap();
+fn cw_beacon_checks_its_interlocks_before_opening_port() {
+    let beacon = |args: &[&str]| bin().args(["--cw-send", "VVV DE EW1ABZ", "--port", "/dev/nonexistent"]).args(args).output().unwrap();
+    let out = beacon(&["--beacon-interval", "60s"]);
+    assert_failure(&out);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("--allow-tx"));
+
+    for (args, expected) in [
+        (&["--beacon-interval", "5s"][..], "at least 10s"),
+        (&["--beacon-interval", "often"], "at least 10s"),
+        (&["--beacon-interval", "60s", "--beacon-count", "0"], "at least 1"),
+    ] {
+        let out = beacon(&[args, &["--allow-tx"]].concat());
+        assert_failure(&out);
+        let stdout = String::from_utf8_lossy(&out.stdout);
+        assert!(stdout.contains(expected), "{:?}: {}", args, stdout);
+        assert!(!stdout.contains("Failed to open port"), "port should not be opened: {stdout}");
+    }
+
+    // A count is only for beacons.
+    let out = beacon(&["--beacon-count", "10", "--allow-tx"]);
     assert_failure(&out);
+    assert!(String::from_utf8_lossy(&out.stderr).contains("--beacon-interval"));
 }
 
-// ---------------------------------------------------------------------------
-// Group 3: --read-radio (real radio required)
-// ---------------------------------------------------------------------------
+#[test]
+fn voice_play_requires_allow_tx() {
+    let out = bin().args(["--voice", "play", "1", "--port", "/dev/nonexistent"]).output().unwrap();
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("--allow-tx"), "expected pointer to the flag: {stdout}");
+    assert!(!stdout.contains("Failed to open port"), "port should not be opened: {stdout}");
+}
 
 #[test]
-#[ignore = "requires physical radio on RADIO_PORT"]
-fn read_radio_produces_csv() {
-    let out_file = temp_csv("read_produces");
-    let out = bin()
-        .args(["--read-radio", "--port", &radio_port(), "--file", out_file.to_str().unwrap()])
-        .output()
-        .unwrap();
-    assert_success(&out);
-    let content = std::fs::read_to_string(&out_file).unwrap();
-    assert!(content.contains("Channel Number"), "CSV header missing");
-    assert!(content.lines().count() > 1, "CSV has no data rows");
-    let _ = std::fs::remove_file(&out_file);
+fn voice_cq_checks_before_opening_port() {
+    let cq = |extra: &[&str]| bin().args(["--voice", "cq"]).args(extra).args(["--port", "/dev/nonexistent"]).output().unwrap();
+    for (args, expected) in [
+        (&["1"][..], "--allow-tx"),
+        (&["1", "--cq-interval", "soon", "--allow-tx"][..], "--cq-interval"),
+        (&["9", "--allow-tx"][..], "1"),
+    ] {
+        let out = cq(args);
+        assert_failure(&out);
+        let stdout = String::from_utf8_lossy(&out.stdout);
+        assert!(stdout.contains(expected), "{args:?}: {stdout}");
+        assert!(!stdout.contains("Failed to open port"), "port should not be opened: {stdout}");
+    }
 }
 
 #[test]
-#[ignore = "requires physical radio on RADIO_PORT"]
-fn read_radio_default_filename() {
-    // Run in a temp dir so the generated file is easy to find and clean up
-    let tmp = std::env::temp_dir();
-    let out = bin()
-        .args(["--read-radio", "--port", &radio_port()])
-        .current_dir(&tmp)
-        .output()
-        .unwrap();
-    assert_success(&out);
-    // stdout says "Memory data saved to CSV file: ftx1_YYYYMMDD_HHMMSS.csv"
+fn max_tx_checked_before_opening_port() {
+    let out = bin().args(["--tune", "--allow-tx", "--max-tx", "forever", "--port", "/dev/nonexistent"]).output().unwrap();
+    assert_failure(&out);
     let stdout = String::from_utf8_lossy(&out.stdout);
-    let fname = stdout
-        .lines()
-        .find(|l| l.contains("Memory data saved"))
-        .and_then(|l| l.split(": ").nth(1))
-        .expect("expected 'Memory data saved to CSV file:' line in output");
-    let generated = tmp.join(fname.trim());
-    assert!(generated.exists(), "generated file not found: {:?}", generated);
-    let content = std::fs::read_to_string(&generated).unwrap();
-    assert!(content.contains("Channel Number"), "CSV header missing");
-    let _ = std::fs::remove_file(&generated);
+    assert!(stdout.contains("--max-tx"), "expected pointer to the flag: {stdout}");
+    assert!(!stdout.contains("Failed to open port"), "port should not be opened: {stdout}");
 }
 
 #[test]
-#[ignore = "requires physical radio on RADIO_PORT"]
-fn read_radio_csv_passes_check_data() {
-    let out_file = temp_csv("read_check");
-    let read = bin()
-        .args(["--read-radio", "--port", &radio_port(), "--file", out_file.to_str().unwrap()])
-        .output()
-        .unwrap();
-    assert_success(&read);
-
-    let check = bin()
-        .args(["--check-data", "--file", out_file.to_str().unwrap()])
-        .output()
-        .unwrap();
-    assert_success(&check);
-    let _ = std::fs::remove_file(&out_file);
+fn resume_needs_file() {
+    let out = bin().args(["--read-radio", "--resume", "--port", "/dev/nonexistent"]).output().unwrap();
+    assert_failure(&out);
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("--file"), "expected --file to be required: {stderr}");
 }
 
 #[test]
-#[ignore = "requires physical radio on RADIO_PORT"]
-fn read_radio_wrong_port() {
+fn resume_read_needs_the_same_options() {
+    let file = temp_csv("resume_read_options");
+    let checkpoint = format!("{}.checkpoint", file.display());
+    std::fs::copy(fixture("valid.csv"), &file).unwrap();
+    std::fs::write(&checkpoint, "# ftx1-mm read checkpoint\noptions delimiter=comma freq-unit=hz decimal-comma=no group=all\nlast 00003\n").unwrap();
     let out = bin()
-        .args(["--read-radio", "--port", "/dev/nonexistent"])
+        .args(["--read-radio", "--resume", "--file", file.to_str().unwrap(), "--delimiter", ";", "--port", "/dev/nonexistent"])
         .output()
         .unwrap();
+    let _ = std::fs::remove_file(&file);
+    let _ = std::fs::remove_file(&checkpoint);
     assert_failure(&out);
     let stdout = String::from_utf8_lossy(&out.stdout);
-    assert!(stdout.contains("Failed to open port"));
+    assert!(stdout.contains("was made with other options"), "{stdout}");
+    assert!(stdout.contains("now:  delimiter=semicolon freq-unit=hz"), "{stdout}");
+    assert!(!stdout.contains("Failed to open port"), "{stdout}");
 }
 
-// ---------------------------------------------------------------------------
-// Group 4: --write-radio (real radio required)
-// ---------------------------------------------------------------------------
+#[test]
+fn panic_stop_needs_no_allow_tx() {
+    let out = bin().args(["--panic-stop", "--port", "/dev/nonexistent"]).output().unwrap();
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Failed to open port"), "expected an attempt to open the port: {stdout}");
+    assert!(!stdout.contains("--allow-tx"), "{stdout}");
+}
 
 #[test]
-#[ignore = "requires physical radio on RADIO_PORT"]
-fn write_radio_roundtrip() {
-    require_destructive();
-    let before = temp_csv("roundtrip_before");
-    let after = temp_csv("roundtrip_after");
-    let port = radio_port();
+fn tune_requires_allow_tx() {
+    let out = bin().args(["--tune", "--port", "/dev/nonexistent"]).output().unwrap();
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("--allow-tx"), "expected pointer to the flag: {stdout}");
+    assert!(!stdout.contains("Failed to open port"), "port should not be opened: {stdout}");
+}
 
-    let out = bin()
-        .args(["--read-radio", "--port", &port, "--file", before.to_str().unwrap()])
-        .output()
-        .unwrap();
-    assert_success(&out);
+#[test]
+fn swr_sweep_requires_allow_tx() {
+    let out = bin().args(["--swr-sweep", "14.0-14.35", "--port", "/dev/nonexistent"]).output().unwrap();
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("--allow-tx"), "expected pointer to the flag: {stdout}");
+    assert!(!stdout.contains("Failed to open port"), "port should not be opened: {stdout}");
+}
+
+#[test]
+fn swr_sweep_checks_range_and_power_before_keying() {
+    let sweep = |args: &[&str]| bin().arg("--swr-sweep").args(args).args(["--allow-tx", "--port", "/dev/nonexistent"]).output().unwrap();
+    for (args, expected) in [
+        (&["14.35-14.0"][..], "Expected a range in MHz"),
+        (&["14.0-14.35", "--sweep-power", "50"], "--sweep-power must be 1-10 W"),
+        (&["14.0-14.35", "--sweep-step", "1"], "more than 200 steps"),
+        (&["1-999", "--sweep-step", "0"], "--sweep-step must be at least 1 kHz"),
+        // 14.35-14.4 MHz is outside the amateur band the radio transmits on.
+        (&["14.3-14.4"], "outside the USA version's TX coverage"),
+    ] {
+        let out = sweep(args);
+        assert_failure(&out);
+        let stdout = String::from_utf8_lossy(&out.stdout);
+        assert!(stdout.contains(expected), "{:?}: {}", args, stdout);
+        assert!(!stdout.contains("Failed to open port"), "port should not be opened: {stdout}");
+    }
+}
+
+#[test]
+fn bandscan_checks_its_range_before_opening_port() {
+    for (args, expected) in [
+        (&["146.6-146.4"][..], "Expected a range in MHz"),
+        (&["144-148", "--bandscan-step", "1"], "more than 2000 steps"),
+        (&["1-999", "--bandscan-step", "0"], "--bandscan-step must be at least 1 kHz"),
+        (&["990-999"], "outside the radio's receive coverage"),
+    ] {
+        let out = bin().arg("--bandscan").args(args).args(["--port", "/dev/nonexistent"]).output().unwrap();
+        assert_failure(&out);
+        let stdout = String::from_utf8_lossy(&out.stdout);
+        assert!(stdout.contains(expected), "{:?}: {}", args, stdout);
+        assert!(!stdout.contains("Failed to open port"), "port should not be opened: {stdout}");
+    }
+}
+
+#[test]
+fn scan_report_totals_halts_per_channel() {
+    let out = bin().args(["--scan-report", fixture("scan_log.csv").to_str().unwrap()]).output().unwrap();
+    assert_success(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 3, "{}", stdout);
+    // Most time spent first.
+    assert!(lines[1].starts_with("00020"), "{}", stdout);
+    let ch12: Vec<&str> = lines[2].split_whitespace().collect();
+    assert_eq!(&ch12[..6], ["00012", "146.5200", "2", "5.5", "3.5", "140"], "{}", stdout);
+    assert!(lines[2].contains("2026-03-01 18:00:00") && lines[2].contains("2026-03-02 07:30:00"), "{}", stdout);
+}
+
+#[test]
+fn scan_log_refuses_a_log_without_channels() {
+    let log = std::env::temp_dir().join("ftx1_test_old_scan_log.csv");
+    std::fs::write(&log, "Time,FrequencyHz\n2026-03-01 18:00:00,146520000\n").unwrap();
+    let out = bin().args(["--scan", "up", "--scan-log", log.to_str().unwrap(), "--port", "/dev/nonexistent"]).output().unwrap();
+    let report = bin().args(["--scan-report", log.to_str().unwrap()]).output().unwrap();
+    let _ = std::fs::remove_file(&log);
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("scan log without channels"), "{}", stdout);
+    assert!(!stdout.contains("Failed to open port"), "port should not be opened: {stdout}");
+    // ...which --scan-report still reads.
+    assert_success(&report);
+    assert!(String::from_utf8_lossy(&report.stdout).contains("146.5200"));
+}
+
+#[test]
+fn ex_list_shows_menu_items() {
+    let out = bin().arg("--ex-list").output().unwrap();
+    assert_success(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("cw-sidetone-level"), "expected menu item in list: {stdout}");
+    assert!(stdout.contains("EX020203"), "expected menu address in list: {stdout}");
+}
+
+#[test]
+fn ex_set_rejects_out_of_range_value() {
+    let out = bin()
+        .args(["--ex-set", "cw-sidetone-level=150", "--port", "/dev/nonexistent"])
+        .output()
+        .unwrap();
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("expected 0..100"), "expected range hint: {stdout}");
+    assert!(!stdout.contains("Failed to open port"), "port should not be opened: {stdout}");
+}
+
+#[test]
+fn menu_restore_rejects_invalid_choice() {
+    let out = bin()
+        .args([
+            "--menu-restore",
+            "--port",
+            "/dev/nonexistent",
+            "--file",
+            fixture("menu_invalid_choice.csv").to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("'STRAIGHT' is not valid for cw-keyer-type"), "expected choice error: {stdout}");
+    assert!(!stdout.contains("Failed to open port"), "port should not be opened: {stdout}");
+}
+
+#[test]
+fn menu_restore_skips_cat_link_items() {
+    let path = temp_csv("menu_cat_link");
+    std::fs::write(&path, "Setting,Value\ncat-rate,4800\ncw-keyer-type,ACS\n").unwrap();
+    let out = bin()
+        .args(["--menu-restore", "--port", "/dev/nonexistent", "--file", path.to_str().unwrap()])
+        .output()
+        .unwrap();
+    let _ = std::fs::remove_file(&path);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Skipping cat-rate=4800"), "expected skip note: {stdout}");
+}
+
+#[test]
+fn band_rejects_unknown_name() {
+    let out = bin().args(["--band", "11m", "--port", "/dev/nonexistent"]).output().unwrap();
+    assert_failure(&out);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("Unknown band '11m'"));
+}
+
+#[test]
+fn band_step_rejects_unknown_direction() {
+    let out = bin().args(["--band-step", "sideways", "--port", "/dev/nonexistent"]).output().unwrap();
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Unknown direction 'sideways'"));
+    assert!(!stdout.contains("Failed to open port"));
+}
+
+#[test]
+fn lock_rejects_invalid_state() {
+    let out = bin().args(["--lock", "maybe", "--port", "/dev/nonexistent"]).output().unwrap();
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Expected on, off or status, got 'maybe'"));
+    assert!(!stdout.contains("Failed to open port"));
+}
+
+#[test]
+fn rigctld_connection_failure_is_reported() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let out = bin().args(["--info", "--port", &format!("rigctld://127.0.0.1:{port}")]).output().unwrap();
+    assert_failure(&out);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("Failed to connect to rigctld"));
+}
+
+#[test]
+fn tcp_connection_failure_is_reported() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let out = bin().args(["--info", "--port", &format!("tcp://127.0.0.1:{port}")]).output().unwrap();
+    assert_failure(&out);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("Failed to connect to '127.0.0.1:"));
+}
+
+#[test]
+fn daemon_needs_the_radio() {
+    let out = bin().args(["--daemon", "--port", "/dev/nonexistent"]).output().unwrap();
+    assert_failure(&out);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("Failed to open port"));
+}
+
+#[test]
+fn serve_needs_the_radio() {
+    let out = bin().args(["--serve", "--port", "/dev/nonexistent"]).output().unwrap();
+    assert_failure(&out);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("Failed to open port"));
+}
+
+#[test]
+fn split_rejects_invalid_offset() {
+    let out = bin().args(["--split", "+5k", "--port", "/dev/nonexistent"]).output().unwrap();
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Invalid split offset '+5k'"));
+    assert!(!stdout.contains("Failed to open port"));
+}
+
+// ---------------------------------------------------------------------------
+// Group 2b: --print (no radio required)
+// ---------------------------------------------------------------------------
+
+#[test]
+fn print_valid_file() {
+    let out = bin()
+        .args(["--print", "--file", fixture("valid.csv").to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert_success(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("145.000 MHz"));
+    assert!(stdout.contains("HOME"));
+    assert!(stdout.contains("433.500 MHz"));
+    assert!(stdout.contains("REPEATER"));
+}
+
+#[test]
+fn print_filters_by_group() {
+    let out = bin()
+        .args(["--print", "--group", "uhf", "--file", fixture("valid.csv").to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert_success(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("REPEATER") && stdout.contains("M-UHF"), "{stdout}");
+    assert!(!stdout.contains("HOME"), "{stdout}");
+}
+
+#[test]
+fn check_data_warns_about_group_it_cannot_set() {
+    let path = temp_csv("group_mismatch");
+    let valid = std::fs::read_to_string(fixture("valid.csv")).unwrap();
+    let mut lines = valid.lines();
+    let mut csv = format!("{},Split TX (Hz),Group\n", lines.next().unwrap());
+    csv += &format!("{},,M-GRP\n", lines.next().unwrap());
+    std::fs::write(&path, csv).unwrap();
+    let out = bin().args(["--check-data", "--file", path.to_str().unwrap()]).output().unwrap();
+    assert_success(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Group 'M-GRP' won't be applied"), "{stdout}");
+}
+
+#[test]
+fn filter_selects_matching_channels() {
+    let file = fixture("valid.csv");
+    let out = bin()
+        .args(["--filter", "band=70cm mode=fm tag~peat", "--format", "json", "--file", file.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert_success(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("REPEATER") && !stdout.contains("HOME"), "{stdout}");
+
+    let out = bin()
+        .args(["--filter", "band=2m", "--format", "csv", "--file", file.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert_success(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines[0], "# ftx1-mm memory file format 2", "{stdout}");
+    assert!(lines[1].starts_with("Channel Number,Frequency (Hz),"), "{stdout}");
+    assert_eq!(lines.len(), 3, "{stdout}");
+    assert!(lines[2].starts_with("00001,145000000,HOME"), "{stdout}");
+
+    let out = bin().args(["--filter", "band=3m", "--file", file.to_str().unwrap()]).output().unwrap();
+    assert_failure(&out);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("bad band '3m'"));
+}
+
+#[test]
+fn summary_counts_channels() {
+    let file = fixture("valid.csv");
+    let out = bin().args(["--summary", "--format", "json", "--file", file.to_str().unwrap()]).output().unwrap();
+    assert_success(&out);
+    let json: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(json["used"], 2);
+    assert_eq!(json["empty"], 997);
+    assert_eq!(json["bands"][0]["name"], "2m");
+    assert_eq!(json["coverage"]["highest_hz"], 433_500_000);
+
+    let out = bin().args(["--summary", "--plain", "--file", file.to_str().unwrap()]).output().unwrap();
+    assert_success(&out);
+    assert!(String::from_utf8_lossy(&out.stdout).starts_with("2 of 999 channels used, 997 empty, 0 split."));
+
+    let out = bin().args(["--summary", "--format", "csv", "--file", file.to_str().unwrap()]).output().unwrap();
+    assert_failure(&out);
+}
+
+#[test]
+fn plan_moves_lists_steps_without_radio() {
+    let current = fixture("valid.csv");
+    let layout = temp_csv("plan_moves_layout");
+    std::fs::write(
+        &layout,
+        "Channel Number,Frequency (Hz),Memory Tag,Mode,Channel Type,Squelch Type,Shift (Hz),Clarifier Offset (Hz),Rx Clarifier Enabled,Tx Clarifier Enabled,CTCSS Tone,DCS Tone\n\
+         00001,145000000,HOME 2M,FM,MemoryChannel,CtcssOff,Simplex,0,RxClarifierOff,TxClarifierOff,88.5,023\n\
+         00005,433500000,REPEATER,FM,MemoryChannel,CtcssEnc,PlusShift,0,RxClarifierOff,TxClarifierOff,88.5,023\n",
+    )
+    .unwrap();
+    let out = bin()
+        .args(["--plan-moves", layout.to_str().unwrap(), "--file", current.to_str().unwrap()])
+        .output()
+        .unwrap();
+    let _ = std::fs::remove_file(&layout);
+    assert_success(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("00001: retag 'HOME 2M'"), "{}", stdout);
+    assert!(stdout.contains("00005: move from 00002"), "{}", stdout);
+    assert!(stdout.contains("00002: erase"), "{}", stdout);
+    assert!(stdout.contains("1 move(s), 1 retag(s), 0 write(s), 1 erase(s); 0 channel(s) already in place."), "{}", stdout);
+    assert!(stdout.contains("Pass --apply"), "{}", stdout);
+}
+
+#[test]
+fn apply_requires_plan_moves() {
+    let out = bin().args(["--check-data", "--apply", "--file", "x.csv"]).output().unwrap();
+    assert_failure(&out);
+}
+
+#[test]
+fn memory_files_from_newer_releases_are_refused() {
+    let valid = std::fs::read_to_string(fixture("valid.csv")).unwrap();
+    let path = temp_csv("newer_format");
+    std::fs::write(&path, format!("# ftx1-mm memory file format 99\n{}", valid)).unwrap();
+    let out = bin().args(["--print", "--file", path.to_str().unwrap()]).output().unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert_failure(&out);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("memory file format 99, newer than"));
+}
+
+#[test]
+fn undo_without_snapshot_fails_before_opening_port() {
+    let dir = std::env::temp_dir().join("ftx1_test_no_snapshots");
+    let _ = std::fs::remove_dir_all(&dir);
+    let out = bin()
+        .args(["--undo", "--snapshot-dir", dir.to_str().unwrap(), "--port", "/dev/nonexistent"])
+        .output()
+        .unwrap();
+    assert_failure(&out);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("No snapshot to undo in"));
+}
+
+#[test]
+fn write_patch_checks_columns_before_opening_port() {
+    let path = temp_csv("patch_bad_column");
+    std::fs::write(&path, "Channel Number,CTCSS Tone,Tone\n00012,123.0,x\n").unwrap();
+    let out = bin()
+        .args(["--write-radio", "--patch", "--file", path.to_str().unwrap(), "--port", "/dev/nonexistent"])
+        .output()
+        .unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("doesn't work as a patch"), "{}", stdout);
+    assert!(stdout.contains("Unexpected column 'Tone'."), "{}", stdout);
+}
+
+#[test]
+fn write_patch_accepts_partial_file() {
+    // A partial file passes the patch check and gets as far as the port.
+    let path = temp_csv("patch_partial");
+    std::fs::write(&path, "Channel Number,CTCSS Tone\n00012,123.0\n").unwrap();
+    let out = bin()
+        .args(["--write-radio", "--patch", "--file", path.to_str().unwrap(), "--port", "/dev/nonexistent"])
+        .output()
+        .unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(!stdout.contains("patch"), "{}", stdout);
+    assert!(!stdout.contains("Missing column"), "{}", stdout);
+}
+
+#[test]
+fn patch_requires_write_radio() {
+    let out = bin().args(["--check-data", "--patch", "--file", "x.csv"]).output().unwrap();
+    assert_failure(&out);
+}
+
+#[test]
+fn backup_fails_when_radio_is_unreachable() {
+    let dir = std::env::temp_dir().join("ftx1_test_backup_unreachable");
+    let _ = std::fs::remove_dir_all(&dir);
+    let out = bin()
+        .args(["--backup", dir.to_str().unwrap(), "--keep", "3", "--port", "/dev/nonexistent"])
+        .output()
+        .unwrap();
+    assert_failure(&out);
+    // Nothing half-written is left behind.
+    assert!(std::fs::read_dir(&dir).map_or(true, |mut d| d.next().is_none()));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn backup_rejects_bad_period_before_opening_port() {
+    let out = bin().args(["--backup", "--every", "daily", "--port", "/dev/nonexistent"]).output().unwrap();
+    assert_failure(&out);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("got 'daily'"));
+    let out = bin().args(["--backup", "--keep", "0", "--port", "/dev/nonexistent"]).output().unwrap();
+    assert_failure(&out);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("--keep needs at least 1."));
+}
+
+#[test]
+fn restore_refuses_unverified_backups() {
+    let root = std::env::temp_dir().join("ftx1_test_restore");
+    let _ = std::fs::remove_dir_all(&root);
+    let dir = root.join("ftx1_backup_20260301_030000");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::copy(fixture("valid.csv"), dir.join("memory.csv")).unwrap();
+    std::fs::write(dir.join("settings.csv"), "Setting,Value\n").unwrap();
+    let restore = |path: &std::path::Path| bin().args(["--restore", path.to_str().unwrap(), "--port", "/dev/nonexistent"]).output().unwrap();
+
+    // No manifest: not a backup --restore can vouch for.
+    let out = restore(&dir);
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Only backups made by --backup can be restored."), "{}", stdout);
+
+    // A manifest that's been edited, found through the backup directory.
+    std::fs::write(dir.join("manifest.txt"), "# ftx1-mm backup manifest\n# checksum: 00\n").unwrap();
+    let out = restore(&root);
+    let _ = std::fs::remove_dir_all(&root);
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Refusing to restore"), "{}", stdout);
+    assert!(stdout.contains("its own checksum"), "{}", stdout);
+    assert!(!stdout.contains("Failed to open port"), "{}", stdout);
+}
+
+#[test]
+fn decrypt_recovers_encrypted_backup_file() {
+    let dir = std::env::temp_dir();
+    let passphrase = dir.join("ftx1_test_decrypt_passphrase.txt");
+    let out_file = dir.join("ftx1_test_decrypted.csv");
+    let _ = std::fs::remove_file(&out_file);
+    let input = fixture("encrypted_backup").join("memory.csv.enc");
+    let decrypt = |secret: &str| {
+        std::fs::write(&passphrase, secret).unwrap();
+        bin()
+            .args(["--decrypt", input.to_str().unwrap(), "--file", out_file.to_str().unwrap()])
+            .args(["--passphrase-file", passphrase.to_str().unwrap()])
+            .output()
+            .unwrap()
+    };
+
+    let out = decrypt("club secreT\n");
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("wrong passphrase, or the file is damaged"), "{}", stdout);
+    assert!(!out_file.exists());
+
+    // Only the first line is the passphrase, CRLF or not.
+    let out = decrypt("club secret\r\nnotes\n");
+    let decrypted = std::fs::read(&out_file);
+    let _ = std::fs::remove_file(&out_file);
+    let _ = std::fs::remove_file(&passphrase);
+    assert_success(&out);
+    assert_eq!(decrypted.unwrap(), std::fs::read(fixture("valid.csv")).unwrap());
+}
+
+#[test]
+fn decrypt_needs_a_passphrase() {
+    let input = fixture("encrypted_backup").join("memory.csv.enc");
+    let out = bin().args(["--decrypt", input.to_str().unwrap(), "--file", "unused.csv"]).output().unwrap();
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("--passphrase-file is required"), "{}", stdout);
+
+    let empty = std::env::temp_dir().join("ftx1_test_empty_passphrase.txt");
+    std::fs::write(&empty, "\n").unwrap();
+    let out = bin()
+        .args(["--decrypt", input.to_str().unwrap(), "--file", "unused.csv", "--passphrase-file", empty.to_str().unwrap()])
+        .output()
+        .unwrap();
+    let _ = std::fs::remove_file(&empty);
+    assert_failure(&out);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("is empty"));
+}
+
+#[test]
+fn restore_of_encrypted_backup_checks_passphrase_before_opening_port() {
+    let backup = fixture("encrypted_backup");
+    let restore = |extra: &[&str]| bin().args(["--restore", backup.to_str().unwrap(), "--port", "/dev/nonexistent"]).args(extra).output().unwrap();
+
+    let out = restore(&[]);
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("checks out"), "{}", stdout);
+    assert!(stdout.contains("is encrypted; give its passphrase with --passphrase-file"), "{}", stdout);
+    assert!(!stdout.contains("Failed to open port"), "{}", stdout);
+
+    let passphrase = std::env::temp_dir().join("ftx1_test_restore_passphrase.txt");
+    std::fs::write(&passphrase, "not it\n").unwrap();
+    let out = restore(&["--passphrase-file", passphrase.to_str().unwrap()]);
+    let _ = std::fs::remove_file(&passphrase);
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Can't decrypt"), "{}", stdout);
+    assert!(!stdout.contains("Failed to open port"), "{}", stdout);
+}
+
+#[test]
+fn smeter_log_checks_its_arguments_before_opening_port() {
+    let out = bin().args(["--interval", "500ms"]).output().unwrap();
+    assert_failure(&out);
+
+    let log = std::env::temp_dir().join("ftx1_test_smeter.csv");
+    let _ = std::fs::remove_file(&log);
+    let smeter_log = |extra: &[&str]| {
+        bin().args(["--smeter-log", log.to_str().unwrap(), "--port", "/dev/nonexistent"]).args(extra).output().unwrap()
+    };
+    let out = smeter_log(&["--interval", "10ms"]);
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Expected an --interval"), "{}", stdout);
+    assert!(!log.exists());
+
+    // An existing log with other columns isn't appended to.
+    std::fs::write(&log, "Time,SMeterRaw\n2026-03-01T18:04:05.250Z,87\n").unwrap();
+    let out = smeter_log(&["--log-frequency"]);
+    let kept = std::fs::read_to_string(&log).unwrap();
+    let _ = std::fs::remove_file(&log);
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("has other columns"), "{}", stdout);
+    assert!(!stdout.contains("Failed to open port"), "{}", stdout);
+    assert_eq!(kept, "Time,SMeterRaw\n2026-03-01T18:04:05.250Z,87\n");
+}
+
+#[test]
+fn keep_requires_backup() {
+    let out = bin().args(["--read-radio", "--keep", "3"]).output().unwrap();
+    assert_failure(&out);
+}
+
+#[test]
+fn radio_profile_fills_in_options() {
+    let config = std::env::temp_dir().join("ftx1_test_profiles.toml");
+    std::fs::write(
+        &config,
+        format!(
+            "[radio.base]\nport = \"/dev/nonexistent-base\"\nspeed = 9600\n\n[radio.field]\nmodel = \"exp\"\nfile = \"{}\"\n",
+            fixture("valid.csv").display().to_string().replace('\\', "\\\\")
+        ),
+    )
+    .unwrap();
+    let config = config.to_str().unwrap();
+    // The profile's file, checked as the profile's model.
+    let out = bin().args(["--check-data", "--radio", "field", "--config", config]).output().unwrap();
+    assert_success(&out);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("Data looks good!"));
+    // The profile's port, unless --port overrides it.
+    let out = bin().args(["--read-radio", "--radio", "base", "--config", config, "--file", "x.csv"]).output().unwrap();
+    assert_failure(&out);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("'/dev/nonexistent-base'"));
+    let out = bin()
+        .args(["--read-radio", "--radio", "base", "--config", config, "--port", "/dev/nonexistent", "--file", "x.csv"])
+        .output()
+        .unwrap();
+    assert_failure(&out);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("'/dev/nonexistent'"));
+    let out = bin().args(["--check-data", "--radio", "mobile", "--config", config]).output().unwrap();
+    assert_failure(&out);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("No [radio.mobile]"));
+    assert!(String::from_utf8_lossy(&out.stdout).contains("(radios: base, field)"));
+    let _ = std::fs::remove_file(config);
+}
+
+#[test]
+fn bad_config_file_is_reported() {
+    let config = std::env::temp_dir().join("ftx1_test_bad_profiles.toml");
+    std::fs::write(&config, "[radio.base]\nbaud = 9600\n").unwrap();
+    let out = bin().args(["--check-data", "--radio", "base", "--config", config.to_str().unwrap()]).output().unwrap();
+    let _ = std::fs::remove_file(&config);
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("line 2") && stdout.contains("unknown field `baud`"), "{stdout}");
+    let out = bin().args(["--check-data", "--config", "x.toml", "--file", "x.csv"]).output().unwrap();
+    assert_failure(&out);
+}
+
+#[test]
+fn unwritable_audit_log_fails_before_opening_port() {
+    let log = std::env::temp_dir().join("ftx1_test_no_such_dir").join("audit.log");
+    let out = bin()
+        .args(["--write-radio", "--file", fixture("valid.csv").to_str().unwrap(), "--port", "/dev/nonexistent"])
+        .args(["--audit-log", log.to_str().unwrap(), "--reason", "test"])
+        .output()
+        .unwrap();
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Failed to open audit log"), "{}", stdout);
+    assert!(!stdout.contains("Failed to open port"), "{}", stdout);
+}
+
+#[test]
+fn write_resume_needs_a_checkpoint() {
+    let file = temp_csv("write_resume");
+    std::fs::copy(fixture("valid.csv"), &file).unwrap();
+    let out = bin()
+        .args(["--write-radio", "--resume", "--file", file.to_str().unwrap(), "--port", "/dev/nonexistent"])
+        .output()
+        .unwrap();
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("No interrupted write"), "{}", stdout);
+    assert!(!stdout.contains("Failed to open port"), "{}", stdout);
+    let _ = std::fs::remove_file(&file);
+}
+
+#[test]
+fn sync_checks_its_arguments_before_opening_port() {
+    let out = bin().args(["--sync", "--port", "/dev/nonexistent"]).output().unwrap();
+    assert_failure(&out);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("--file is required for --sync"));
+    let file = fixture("valid.csv");
+    let out = bin()
+        .args(["--sync", "--prefer", "both", "--file", file.to_str().unwrap(), "--port", "/dev/nonexistent"])
+        .output()
+        .unwrap();
+    assert_failure(&out);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("Expected radio or file for --prefer, got 'both'."));
+}
+
+#[test]
+fn sync_leaves_no_state_when_radio_is_unreachable() {
+    let file = temp_csv("sync_unreachable");
+    std::fs::copy(fixture("valid.csv"), &file).unwrap();
+    let state = format!("{}.sync", file.display());
+    let out = bin()
+        .args(["--sync", "--file", file.to_str().unwrap(), "--port", "/dev/nonexistent"])
+        .output()
+        .unwrap();
+    assert_failure(&out);
+    assert!(!std::path::Path::new(&state).exists());
+    let _ = std::fs::remove_file(&file);
+}
+
+#[test]
+fn bad_side_is_rejected_before_opening_port() {
+    let out = bin().args(["--band", "2m", "--side", "left", "--port", "/dev/nonexistent"]).output().unwrap();
+    assert_failure(&out);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("Expected main or sub for --side, got 'left'."));
+}
+
+#[test]
+fn bad_antenna_choice_is_rejected_before_opening_port() {
+    let out = bin().args(["--antenna", "20m=2,2m=1", "--port", "/dev/nonexistent"]).output().unwrap();
+    assert_failure(&out);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("got '2m=1'"));
+}
+
+#[test]
+fn bad_apf_offset_is_rejected_before_opening_port() {
+    let out = bin().args(["--apf-offset", "-35", "--port", "/dev/nonexistent"]).output().unwrap();
+    assert_failure(&out);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("got '-35'"));
+}
+
+#[test]
+fn print_missing_file() {
+    let out = bin()
+        .args(["--print", "--file", "nonexistent.csv"])
+        .output()
+        .unwrap();
+    assert_failure(&out);
+}
+
+// ---------------------------------------------------------------------------
+// Group 2c: --write-settings file validation (no radio required)
+// ---------------------------------------------------------------------------
+
+// Settings files are fully encoded before the port is opened, so bad rows
+// fail fast without touching the radio.
+#[test]
+fn write_settings_rejects_invalid_value() {
+    let out = bin()
+        .args([
+            "--write-settings",
+            "--port",
+            "/dev/nonexistent",
+            "--file",
+            fixture("settings_invalid_value.csv").to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("has invalid value '605'"), "expected value error: {stdout}");
+    assert!(!stdout.contains("Failed to open port"), "port should not be opened: {stdout}");
+}
+
+#[test]
+fn write_settings_rejects_unknown_setting() {
+    let out = bin()
+        .args([
+            "--write-settings",
+            "--port",
+            "/dev/nonexistent",
+            "--file",
+            fixture("settings_unknown.csv").to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert_failure(&out);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("Unknown setting 'no-such-setting'"));
+}
+
+// ---------------------------------------------------------------------------
+// Group 3: --read-radio (real radio required)
+// ---------------------------------------------------------------------------
+
+#[test]
+#[ignore = "requires physical radio on RADIO_PORT"]
+fn read_radio_produces_csv() {
+    let out_file = temp_csv("read_produces");
+    let out = bin()
+        .args(["--read-radio", "--port", &radio_port(), "--file", out_file.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert_success(&out);
+    let content = std::fs::read_to_string(&out_file).unwrap();
+    assert!(content.contains("Channel Number"), "CSV header missing");
+    assert!(content.lines().count() > 1, "CSV has no data rows");
+    let _ = std::fs::remove_file(&out_file);
+}
+
+#[test]
+#[ignore = "requires physical radio on RADIO_PORT"]
+fn read_radio_default_filename() {
+    // Run in a temp dir so the generated file is easy to find and clean up
+    let tmp = std::env::temp_dir();
+    let out = bin()
+        .args(["--read-radio", "--port", &radio_port()])
+        .current_dir(&tmp)
+        .output()
+        .unwrap();
+    assert_success(&out);
+    // stdout says "Memory data saved to CSV file: ftx1_YYYYMMDD_HHMMSS.csv"
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let fname = stdout
+        .lines()
+        .find(|l| l.contains("Memory data saved"))
+        .and_then(|l| l.split(": ").nth(1))
+        .expect("expected 'Memory data saved to CSV file:' line in output");
+    let generated = tmp.join(fname.trim());
+    assert!(generated.exists(), "generated file not found: {:?}", generated);
+    let content = std::fs::read_to_string(&generated).unwrap();
+    assert!(content.contains("Channel Number"), "CSV header missing");
+    let _ = std::fs::remove_file(&generated);
+}
+
+#[test]
+#[ignore = "requires physical radio on RADIO_PORT"]
+fn read_radio_csv_passes_check_data() {
+    let out_file = temp_csv("read_check");
+    let read = bin()
+        .args(["--read-radio", "--port", &radio_port(), "--file", out_file.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert_success(&read);
+
+    let check = bin()
+        .args(["--check-data", "--file", out_file.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert_success(&check);
+    let _ = std::fs::remove_file(&out_file);
+}
+
+#[test]
+#[ignore = "requires physical radio on RADIO_PORT"]
+fn read_radio_wrong_port() {
+    let out = bin()
+        .args(["--read-radio", "--port", "/dev/nonexistent"])
+        .output()
+        .unwrap();
+    assert_failure(&out);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Failed to open port"));
+}
+
+// ---------------------------------------------------------------------------
+// Group 4: --write-radio (real radio required)
+// ---------------------------------------------------------------------------
+
+#[test]
+#[ignore = "requires physical radio on RADIO_PORT"]
+fn write_radio_roundtrip() {
+    require_destructive();
+    let before = temp_csv("roundtrip_before");
+    let after = temp_csv("roundtrip_after");
+    let port = radio_port();
+
+    let out = bin()
+        .args(["--read-radio", "--port", &port, "--file", before.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert_success(&out);
 
     let out = bin()
         .args(["--write-radio", "--port", &port, "--file", before.to_str().unwrap()])
@@ -385,6 +1565,85 @@ fn write_radio_roundtrip() {
     let _ = std::fs::remove_file(&after);
 }
 
+// A patch giving only a channel number writes the channel back as it is.
+#[test]
+#[ignore = "requires physical radio on RADIO_PORT"]
+fn write_patch_keeps_unlisted_columns() {
+    require_destructive();
+    let before = temp_csv("patch_before");
+    let patch = temp_csv("patch_file");
+    let after = temp_csv("patch_after");
+    let port = radio_port();
+
+    let out = bin()
+        .args(["--read-radio", "--port", &port, "--file", before.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert_success(&out);
+    let lines = normalise_csv(&before);
+    let channel = lines.iter().find_map(|l| l.split(',').next().filter(|c| c.parse::<u16>().is_ok())).unwrap();
+    std::fs::write(&patch, format!("Channel Number\n{}\n", channel)).unwrap();
+
+    let out = bin()
+        .args(["--write-radio", "--patch", "--port", &port, "--file", patch.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert_success(&out);
+
+    let out = bin()
+        .args(["--read-radio", "--port", &port, "--file", after.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert_success(&out);
+
+    assert_eq!(lines, normalise_csv(&after), "CSV mismatch after patch");
+
+    let _ = std::fs::remove_file(&before);
+    let _ = std::fs::remove_file(&patch);
+    let _ = std::fs::remove_file(&after);
+}
+
+// Writing a channel back as it is logs it with the same old and new value.
+#[test]
+#[ignore = "requires physical radio on RADIO_PORT"]
+fn write_logs_to_audit_log() {
+    require_destructive();
+    let before = temp_csv("audit_before");
+    let patch = temp_csv("audit_patch");
+    let log = std::env::temp_dir().join("ftx1_test_audit.log");
+    let _ = std::fs::remove_file(&log);
+    let port = radio_port();
+
+    let out = bin()
+        .args(["--read-radio", "--port", &port, "--file", before.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert_success(&out);
+    let lines = normalise_csv(&before);
+    let channel = lines.iter().find_map(|l| l.split(',').next().filter(|c| c.parse::<u16>().is_ok())).unwrap();
+    std::fs::write(&patch, format!("Channel Number\n{}\n", channel)).unwrap();
+
+    let out = bin()
+        .args(["--write-radio", "--patch", "--no-snapshot", "--port", &port, "--file", patch.to_str().unwrap()])
+        .args(["--audit-log", log.to_str().unwrap(), "--operator", "tester", "--reason", "integration test"])
+        .output()
+        .unwrap();
+    assert_success(&out);
+
+    let text = std::fs::read_to_string(&log).unwrap();
+    let entries: Vec<serde_json::Value> = text.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["operator"], "tester");
+    assert_eq!(entries[0]["operation"], "write");
+    assert_eq!(entries[0]["reason"], "integration test");
+    assert_eq!(entries[0]["channel"], format!("{:0>5}", channel));
+    assert_eq!(entries[0]["old"]["Frequency (Hz)"], entries[0]["new"]["Frequency (Hz)"]);
+
+    let _ = std::fs::remove_file(&before);
+    let _ = std::fs::remove_file(&patch);
+    let _ = std::fs::remove_file(&log);
+}
+
 // Targeted test: writes channels 19 + 20 (one with split memory, one without),
 // reads them back, and asserts the Split TX (Hz) column round-trips. Restores
 // the prior state by re-writing the full snapshot. Channels 19 and 20 will be